@@ -0,0 +1,72 @@
+// font-kit/examples/describe-family.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks reading the names and properties of every face in a family, first from freshly
+//! loaded fonts and then again once the fonts have cached them.
+
+extern crate font_kit;
+
+use font_kit::family_name::FamilyName;
+use font_kit::font::Font;
+use font_kit::source::{Source, SystemSource};
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: u32 = 100;
+
+fn time_descriptions(fonts: &[Font], iterations: u32) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for font in fonts {
+            font.properties();
+            font.family_name();
+            font.full_name();
+            font.postscript_name();
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let mut args = env::args().skip(1);
+    let family_name = match args.next() {
+        Some(family_name) => FamilyName::Title(family_name),
+        None => FamilyName::SansSerif,
+    };
+    let iterations = args
+        .next()
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+
+    let source = SystemSource::new();
+    let family = source.select_family_by_generic_name(&family_name).unwrap();
+    let load_fonts = || -> Vec<Font> {
+        family
+            .fonts()
+            .iter()
+            .map(|handle| handle.load().unwrap())
+            .collect()
+    };
+
+    // Each iteration loads the fonts again, so that nothing is cached.
+    let mut uncached = Duration::default();
+    for _ in 0..iterations {
+        let fonts = load_fonts();
+        uncached += time_descriptions(&fonts, 1);
+    }
+    let fonts = load_fonts();
+    time_descriptions(&fonts, 1);
+    let cached = time_descriptions(&fonts, iterations);
+
+    let count = iterations * fonts.len() as u32;
+    println!("{} faces described", count);
+    println!("uncached: {:?} ({:?}/face)", uncached, uncached / count);
+    println!("cached:   {:?} ({:?}/face)", cached, cached / count);
+}
@@ -0,0 +1,75 @@
+// font-kit/examples/font-info.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks describing every font in a directory with `FontInfo`, which reads only a few
+//! tables, against loading each one as a `Font`.
+//!
+//! Pass the directory to scan as the first argument. Only the first font of each collection is
+//! described.
+
+extern crate font_kit;
+
+use font_kit::font::Font;
+use font_kit::font_info::FontInfo;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::Instant;
+
+fn collect_files(directory: &Path, paths: &mut Vec<PathBuf>) {
+    let entries = match fs::read_dir(directory) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, paths);
+        } else {
+            paths.push(path);
+        }
+    }
+}
+
+fn main() {
+    let directory = match env::args().nth(1) {
+        Some(directory) => PathBuf::from(directory),
+        None => {
+            eprintln!("usage: font-info DIRECTORY");
+            process::exit(1);
+        }
+    };
+    let mut paths = vec![];
+    collect_files(&directory, &mut paths);
+
+    // Read every file once so that both passes start with a warm disk cache.
+    for path in &paths {
+        drop(fs::read(path));
+    }
+
+    let start = Instant::now();
+    let described = paths
+        .iter()
+        .filter(|path| FontInfo::from_path(path, 0).is_ok())
+        .count();
+    let info_time = start.elapsed();
+
+    let start = Instant::now();
+    let loaded = paths
+        .iter()
+        .filter(|path| Font::from_path(path, 0).is_ok())
+        .count();
+    let load_time = start.elapsed();
+
+    println!("{} files", paths.len());
+    println!("FontInfo::from_path: {:?} ({} fonts)", info_time, described);
+    println!("Font::from_path:     {:?} ({} fonts)", load_time, loaded);
+}
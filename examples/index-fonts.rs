@@ -0,0 +1,63 @@
+// font-kit/examples/index-fonts.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks describing every font in a directory one at a time against
+//! `Source::all_fonts_parallel()`, which should scale with the number of cores.
+//!
+//! Pass the directory to scan as the first argument. Requires the `rayon` feature.
+
+extern crate font_kit;
+
+use font_kit::source::Source;
+use font_kit::sources::fs::FsSource;
+use std::env;
+use std::process;
+use std::thread;
+use std::time::Instant;
+
+fn main() {
+    let directory = match env::args().nth(1) {
+        Some(directory) => directory,
+        None => {
+            eprintln!("usage: index-fonts DIRECTORY");
+            process::exit(1);
+        }
+    };
+    let source = FsSource::in_path(directory);
+    let handles = source.all_fonts().unwrap();
+
+    // Load every font once so that both passes start with a warm disk cache.
+    for handle in &handles {
+        drop(handle.load());
+    }
+
+    let start = Instant::now();
+    let described = handles
+        .iter()
+        .filter(|handle| source.describe_handle(handle).is_ok())
+        .count();
+    let sequential_time = start.elapsed();
+
+    let start = Instant::now();
+    let described_in_parallel = source.all_fonts_parallel().unwrap().len();
+    let parallel_time = start.elapsed();
+
+    let thread_count = thread::available_parallelism().map_or(1, |count| count.get());
+    println!("{} fonts, {} threads", handles.len(), thread_count);
+    println!("sequential: {:?} ({} fonts)", sequential_time, described);
+    println!(
+        "parallel:   {:?} ({} fonts)",
+        parallel_time, described_in_parallel
+    );
+    println!(
+        "speedup:    {:.2}x",
+        sequential_time.as_secs_f64() / parallel_time.as_secs_f64()
+    );
+}
@@ -0,0 +1,66 @@
+// font-kit/examples/load-overhead.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks loading a font repeatedly on one thread, which reuses that thread's FreeType
+//! library, against loading it once on each of many new threads, each of which has to initialize
+//! a library of its own.
+//!
+//! Pass the font file to load as the first argument and, optionally, the number of loads as the
+//! second. With loaders other than FreeType, the difference is only the cost of spawning threads.
+
+extern crate font_kit;
+
+use font_kit::font::Font;
+use std::env;
+use std::fs;
+use std::process;
+use std::sync::Arc;
+use std::thread;
+use std::time::Instant;
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: load-overhead FONT-FILE [LOAD-COUNT]");
+            process::exit(1);
+        }
+    };
+    let load_count: u32 = env::args()
+        .nth(2)
+        .and_then(|count| count.parse().ok())
+        .unwrap_or(1000);
+    let font_data = Arc::new(fs::read(path).unwrap());
+
+    let start = Instant::now();
+    for _ in 0..load_count {
+        drop(Font::from_bytes(font_data.clone(), 0).unwrap());
+    }
+    let reused_time = start.elapsed();
+
+    let start = Instant::now();
+    for _ in 0..load_count {
+        let font_data = font_data.clone();
+        thread::spawn(move || drop(Font::from_bytes(font_data, 0).unwrap()))
+            .join()
+            .unwrap();
+    }
+    let fresh_time = start.elapsed();
+
+    println!("{} loads", load_count);
+    println!(
+        "one thread:          {:?} per load",
+        reused_time / load_count
+    );
+    println!(
+        "one thread per load: {:?} per load",
+        fresh_time / load_count
+    );
+}
@@ -0,0 +1,54 @@
+// font-kit/examples/match-cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks repeated identical font matching queries with and without a `CachingSource`.
+
+extern crate font_kit;
+
+use font_kit::family_name::FamilyName;
+use font_kit::properties::Properties;
+use font_kit::source::{Source, SystemSource};
+use font_kit::sources::caching::CachingSource;
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: u32 = 100;
+
+fn time_queries(source: &dyn Source, families: &[FamilyName], iterations: u32) -> Duration {
+    let properties = Properties::new();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        source.select_best_match(families, &properties).unwrap();
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let iterations = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let families = [FamilyName::Title("Arial".to_owned()), FamilyName::SansSerif];
+
+    let uncached = time_queries(&SystemSource::new(), &families, iterations);
+    let cached = time_queries(
+        &CachingSource::new(SystemSource::new()),
+        &families,
+        iterations,
+    );
+
+    println!("{} queries", iterations);
+    println!(
+        "uncached: {:?} ({:?}/query)",
+        uncached,
+        uncached / iterations
+    );
+    println!("cached:   {:?} ({:?}/query)", cached, cached / iterations);
+}
@@ -0,0 +1,79 @@
+// font-kit/examples/outline-cache.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks repeated extraction of the same glyph outlines with and without a `CachingFont`.
+
+extern crate font_kit;
+
+use font_kit::caching_font::CachingFont;
+use font_kit::family_name::FamilyName;
+use font_kit::hinting::HintingOptions;
+use font_kit::outline::OutlineBuilder;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: u32 = 100;
+const TEXT: &str = "The quick brown fox jumps over the lazy dog.";
+
+fn time_outlines<F>(outline: F, glyph_ids: &[u32], iterations: u32) -> Duration
+where
+    F: Fn(u32, &mut OutlineBuilder),
+{
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for &glyph_id in glyph_ids {
+            let mut builder = OutlineBuilder::new();
+            outline(glyph_id, &mut builder);
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let iterations = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_ids: Vec<u32> = TEXT
+        .chars()
+        .filter_map(|c| font.glyph_for_char(c))
+        .collect();
+
+    let uncached = time_outlines(
+        |glyph_id, builder| {
+            font.outline(glyph_id, HintingOptions::None, builder)
+                .unwrap()
+        },
+        &glyph_ids,
+        iterations,
+    );
+    let caching_font = CachingFont::new(font.clone());
+    let cached = time_outlines(
+        |glyph_id, builder| {
+            caching_font
+                .outline(glyph_id, HintingOptions::None, builder)
+                .unwrap()
+        },
+        &glyph_ids,
+        iterations,
+    );
+
+    let count = iterations * glyph_ids.len() as u32;
+    println!("{} outlines", count);
+    println!("uncached: {:?} ({:?}/outline)", uncached, uncached / count);
+    println!("cached:   {:?} ({:?}/outline)", cached, cached / count);
+}
@@ -0,0 +1,118 @@
+// font-kit/examples/rasterize-context.rs
+//
+// Copyright © 2020 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Benchmarks rasterizing the same run of glyphs over and over with and without a reusable
+//! `RasterizeContext`.
+//!
+//! With the FreeType loader, `rasterize_glyph()` has FreeType allocate and free a bitmap for every
+//! glyph, while `rasterize_glyph_with_context()` renders into the context's buffer, which is
+//! allocated once and then reused. Those allocations happen inside FreeType's C allocator, so they
+//! can only be observed here as time per glyph. How much they cost depends on the allocator and on
+//! the glyph size, which can be passed as the second argument.
+
+extern crate font_kit;
+extern crate pathfinder_geometry;
+
+use font_kit::canvas::{Canvas, Format, RasterizationOptions, RasterizeContext};
+use font_kit::family_name::FamilyName;
+use font_kit::hinting::HintingOptions;
+use font_kit::properties::Properties;
+use font_kit::source::SystemSource;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+use std::env;
+use std::time::{Duration, Instant};
+
+const DEFAULT_ITERATIONS: u32 = 1000;
+const DEFAULT_POINT_SIZE: f32 = 32.0;
+const TEXT: &str = "The quick brown fox jumps over the lazy dog.";
+
+fn time_rasterization<F>(
+    mut rasterize: F,
+    canvas_size: i32,
+    glyph_ids: &[u32],
+    iterations: u32,
+) -> Duration
+where
+    F: FnMut(u32, &mut Canvas),
+{
+    let mut canvas = Canvas::new(Vector2I::splat(canvas_size), Format::A8);
+    let start = Instant::now();
+    for _ in 0..iterations {
+        for &glyph_id in glyph_ids {
+            canvas.clear();
+            rasterize(glyph_id, &mut canvas);
+        }
+    }
+    start.elapsed()
+}
+
+fn main() {
+    let iterations = env::args()
+        .nth(1)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_ITERATIONS);
+    let point_size = env::args()
+        .nth(2)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_POINT_SIZE);
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_ids: Vec<u32> = TEXT
+        .chars()
+        .filter_map(|c| font.glyph_for_char(c))
+        .collect();
+    let canvas_size = (point_size * 2.0).ceil() as i32;
+    let transform = Transform2F::from_translation(Vector2F::new(0.25, 1.5) * point_size);
+
+    let one_off = time_rasterization(
+        |glyph_id, canvas| {
+            font.rasterize_glyph(
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap()
+        },
+        canvas_size,
+        &glyph_ids,
+        iterations,
+    );
+    let mut context = RasterizeContext::new();
+    let reused = time_rasterization(
+        |glyph_id, canvas| {
+            font.rasterize_glyph_with_context(
+                &mut context,
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap()
+        },
+        canvas_size,
+        &glyph_ids,
+        iterations,
+    );
+
+    let count = iterations * glyph_ids.len() as u32;
+    println!("{} glyphs", count);
+    println!("one-off: {:?} ({:?}/glyph)", one_off, one_off / count);
+    println!("context: {:?} ({:?}/glyph)", reused, reused / count);
+    println!("context buffer: {} bytes", context.capacity());
+}
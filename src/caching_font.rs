@@ -0,0 +1,176 @@
+// font-kit/src/caching_font.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A font that wraps a loaded font and memoizes its glyph outlines.
+//!
+//! Applications that extract the same outlines over and over (for example, to tessellate them
+//! for a GPU renderer every frame) can wrap their font in a `CachingFont` so that only the first
+//! request for each glyph pays the cost of parsing and, if requested, hinting it.
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::GlyphLoadingError;
+use crate::font::Font;
+use crate::hinting::HintingOptions;
+use crate::loader::Loader;
+use crate::outline::{Outline, OutlineBuilder, OutlineSink};
+
+/// The number of outlines that `CachingFont::new()` remembers.
+pub const DEFAULT_CACHE_CAPACITY: usize = 1024;
+
+/// A font that wraps another font and memoizes the results of `outline()`.
+///
+/// Outlines are keyed by glyph ID and hinting options only. `Loader::outline()` always draws the
+/// instance that the wrapped font was loaded at, so a `CachingFont` is fixed to that one instance
+/// of a variable font. To extract outlines at several axis positions, load a font for each
+/// position and wrap each one in its own `CachingFont`.
+///
+/// Each entry holds one `Outline`, which costs about 9 bytes per point plus a small fixed overhead
+/// per contour, so the default capacity of 1024 glyphs typically stays well under a megabyte. Once the cache is full, the oldest entry is
+/// evicted. Errors are not cached.
+///
+/// The cache is protected by a mutex, so this font is `Send` and `Sync` whenever the wrapped font
+/// is.
+#[allow(missing_debug_implementations)]
+pub struct CachingFont<F = Font>
+where
+    F: Loader,
+{
+    font: F,
+    cache: Mutex<OutlineCache>,
+}
+
+struct OutlineCache {
+    entries: HashMap<OutlineKey, Outline>,
+    insertion_order: VecDeque<OutlineKey>,
+    capacity: usize,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct OutlineKey {
+    glyph_id: u32,
+    hinting_mode: u8,
+    hinting_size: u32,
+}
+
+impl OutlineKey {
+    fn new(glyph_id: u32, hinting: HintingOptions) -> OutlineKey {
+        let hinting_mode = match hinting {
+            HintingOptions::None => 0,
+            HintingOptions::Vertical(_) => 1,
+            HintingOptions::VerticalSubpixel(_) => 2,
+            HintingOptions::Full(_) => 3,
+        };
+        OutlineKey {
+            glyph_id,
+            hinting_mode,
+            hinting_size: hinting.grid_fitting_size().unwrap_or(0.0).to_bits(),
+        }
+    }
+}
+
+impl<F> CachingFont<F>
+where
+    F: Loader,
+{
+    /// Wraps `font`, remembering up to `DEFAULT_CACHE_CAPACITY` outlines.
+    #[inline]
+    pub fn new(font: F) -> CachingFont<F> {
+        CachingFont::with_capacity(font, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Wraps `font`, remembering up to `capacity` outlines.
+    ///
+    /// A capacity of zero disables caching.
+    pub fn with_capacity(font: F, capacity: usize) -> CachingFont<F> {
+        CachingFont {
+            font,
+            cache: Mutex::new(OutlineCache {
+                entries: HashMap::new(),
+                insertion_order: VecDeque::new(),
+                capacity,
+            }),
+        }
+    }
+
+    /// Returns the wrapped font.
+    #[inline]
+    pub fn font(&self) -> &F {
+        &self.font
+    }
+
+    /// Unwraps this font, discarding the cache.
+    #[inline]
+    pub fn into_font(self) -> F {
+        self.font
+    }
+
+    /// Discards all cached outlines, releasing their memory.
+    pub fn clear_outline_cache(&self) {
+        let mut cache = self.lock_cache();
+        cache.entries.clear();
+        cache.insertion_order.clear();
+    }
+
+    /// Returns the number of cached outlines.
+    pub fn cached_outline_count(&self) -> usize {
+        self.lock_cache().entries.len()
+    }
+
+    /// Sends the vector path for a glyph to a sink, consulting the cache first.
+    ///
+    /// See `Loader::outline()` for the meaning of the arguments.
+    pub fn outline<S>(
+        &self,
+        glyph_id: u32,
+        hinting_mode: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        let key = OutlineKey::new(glyph_id, hinting_mode);
+        if let Some(outline) = self.lock_cache().entries.get(&key) {
+            outline.copy_to(sink);
+            return Ok(());
+        }
+
+        // Don't hold the lock while loading, which may be slow.
+        let mut builder = OutlineBuilder::new();
+        self.font.outline(glyph_id, hinting_mode, &mut builder)?;
+        let outline = builder.into_outline();
+        outline.copy_to(sink);
+        self.lock_cache().insert(key, outline);
+        Ok(())
+    }
+
+    fn lock_cache(&self) -> MutexGuard<'_, OutlineCache> {
+        // The cache is always left consistent, so a poisoned lock is safe to reuse.
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl OutlineCache {
+    fn insert(&mut self, key: OutlineKey, outline: Outline) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key, outline).is_some() {
+            return;
+        }
+        self.insertion_order.push_back(key);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest_key) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest_key);
+            }
+        }
+    }
+}
@@ -12,23 +12,27 @@
 
 use lazy_static::lazy_static;
 use pathfinder_geometry::rect::RectI;
-use pathfinder_geometry::vector::Vector2I;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::cmp;
 use std::fmt;
+#[cfg(feature = "png")]
+use std::io::{self, Write};
 
+use crate::color::Color;
+use crate::error::CanvasError;
+use crate::hinting::HintingOptions;
 use crate::utils;
 
 lazy_static! {
     static ref BITMAP_1BPP_TO_8BPP_LUT: [[u8; 8]; 256] = {
         let mut lut = [[0; 8]; 256];
-        for byte in 0..0x100 {
-            let mut value = [0; 8];
-            for bit in 0..8 {
+        for (byte, value) in lut.iter_mut().enumerate() {
+            for (bit, pixel) in value.iter_mut().enumerate() {
                 if (byte & (0x80 >> bit)) != 0 {
-                    value[bit] = 0xff;
+                    *pixel = 0xff;
                 }
             }
-            lut[byte] = value
         }
         lut
     };
@@ -47,18 +51,49 @@ pub struct Canvas {
 }
 
 impl Canvas {
+    /// The largest width or height that `try_new()` accepts, in pixels.
+    pub const MAX_DIMENSION: i32 = 1 << 16;
+
     /// Creates a new blank canvas with the given pixel size and format.
     ///
     /// Stride is automatically calculated from width.
     ///
     /// The canvas is initialized with transparent black (all values 0).
+    ///
+    /// Panics if the width or height is negative, or if the pixels don't fit in memory; use
+    /// `try_new()` to handle sizes computed from untrusted bounds.
     #[inline]
     pub fn new(size: Vector2I, format: Format) -> Canvas {
-        Canvas::with_stride(
+        Canvas::with_stride(size, format.bytes_per_row(size.x() as usize), format)
+    }
+
+    /// Creates a new blank canvas with the given pixel size and format, or returns an error if the
+    /// size isn't usable.
+    ///
+    /// The width and height must each be positive and at most `Canvas::MAX_DIMENSION`. Otherwise
+    /// this behaves like `new()`.
+    pub fn try_new(size: Vector2I, format: Format) -> Result<Canvas, CanvasError> {
+        if size.x() <= 0 || size.y() <= 0 {
+            return Err(CanvasError::EmptySize);
+        }
+        if size.x() > Canvas::MAX_DIMENSION || size.y() > Canvas::MAX_DIMENSION {
+            return Err(CanvasError::TooLarge);
+        }
+        let stride = format.bytes_per_row(size.x() as usize);
+        let length = stride
+            .checked_mul(size.y() as usize)
+            .ok_or(CanvasError::TooLarge)?;
+        let mut pixels = vec![];
+        pixels
+            .try_reserve_exact(length)
+            .map_err(|_| CanvasError::TooLarge)?;
+        pixels.resize(length, 0);
+        Ok(Canvas {
+            pixels,
             size,
-            size.x() as usize * format.bytes_per_pixel() as usize,
+            stride,
             format,
-        )
+        })
     }
 
     /// Creates a new blank canvas with the given pixel size, stride (number of bytes between
@@ -74,6 +109,253 @@ impl Canvas {
         }
     }
 
+    /// Returns the bytes of row `y`, without any padding at the end of the row.
+    ///
+    /// Panics if `y` is outside the canvas.
+    pub fn row(&self, y: usize) -> &[u8] {
+        let (start, end) = self.row_range(y);
+        &self.pixels[start..end]
+    }
+
+    /// Returns the bytes of row `y` for writing, without any padding at the end of the row.
+    ///
+    /// Panics if `y` is outside the canvas.
+    pub fn row_mut(&mut self, y: usize) -> &mut [u8] {
+        let (start, end) = self.row_range(y);
+        &mut self.pixels[start..end]
+    }
+
+    /// Returns the value of the pixel at (`x`, `y`), decoded according to the canvas format.
+    ///
+    /// Panics if the pixel is outside the canvas.
+    pub fn pixel(&self, x: usize, y: usize) -> PixelValue {
+        assert!(
+            x < self.size.x().max(0) as usize,
+            "x coordinate {} is outside a canvas {} pixels wide",
+            x,
+            self.size.x()
+        );
+        // `A1` pixels are read bit by bit instead.
+        let bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let bytes = &self.row(y)[x * bytes_per_pixel..(x + 1) * bytes_per_pixel];
+        match self.format {
+            Format::Rgba32 => PixelValue::Rgba32([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            Format::Rgb24 => PixelValue::Rgb24([bytes[0], bytes[1], bytes[2]]),
+            Format::A8 => PixelValue::A8(bytes[0]),
+            Format::A16 => PixelValue::A16(u16::from_le_bytes([bytes[0], bytes[1]])),
+            Format::AF32 => {
+                PixelValue::AF32(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            }
+            Format::Sdf => PixelValue::Sdf(bytes[0]),
+            Format::A1 => PixelValue::A1(self.a1_pixel(x, y)),
+        }
+    }
+
+    // Reads and writes single pixels of an `A1` canvas.
+    fn a1_pixel(&self, x: usize, y: usize) -> bool {
+        self.pixels[y * self.stride + x / 8] & (0x80 >> (x % 8)) != 0
+    }
+
+    fn set_a1_pixel(&mut self, x: usize, y: usize, on: bool) {
+        let mask = 0x80 >> (x % 8);
+        let byte = &mut self.pixels[y * self.stride + x / 8];
+        if on {
+            *byte |= mask
+        } else {
+            *byte &= !mask
+        }
+    }
+
+    fn row_range(&self, y: usize) -> (usize, usize) {
+        assert!(
+            y < self.size.y().max(0) as usize,
+            "y coordinate {} is outside a canvas {} pixels high",
+            y,
+            self.size.y()
+        );
+        let start = y * self.stride;
+        (
+            start,
+            start + self.format.bytes_per_row(self.size.x() as usize),
+        )
+    }
+
+    /// Sets every pixel in the canvas to transparent black (all values 0), whatever its format.
+    #[inline]
+    pub fn clear(&mut self) {
+        self.pixels.fill(0)
+    }
+
+    /// Fills a rectangle of the canvas with a single pixel value.
+    ///
+    /// `value` holds the bytes of one pixel in the canvas format, so its length must equal
+    /// `format.bytes_per_pixel()`; for example, `[0xff]` for `A8` or `[r, g, b, a]` for `Rgba32`.
+    /// `A1` pixels take up less than a byte, so `A1` canvases take a single byte instead, which
+    /// turns the pixels off if it is 0 and on otherwise. The rectangle is clipped to the bounds
+    /// of the canvas.
+    pub fn fill_rect(&mut self, rect: RectI, value: &[u8]) {
+        assert_eq!(
+            value.len(),
+            self.format.bytes_per_row(1),
+            "pixel value doesn't match the canvas format"
+        );
+
+        let rect = match rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return,
+        };
+        if self.format == Format::A1 {
+            for y in rect.min_y()..rect.max_y() {
+                for x in rect.min_x()..rect.max_x() {
+                    self.set_a1_pixel(x as usize, y as usize, value[0] != 0);
+                }
+            }
+            return;
+        }
+        let bytes_per_pixel = self.format.aligned_bytes_per_pixel();
+        for y in rect.min_y()..rect.max_y() {
+            let row_start = y as usize * self.stride + rect.min_x() as usize * bytes_per_pixel;
+            let row_end = row_start + rect.width() as usize * bytes_per_pixel;
+            for pixel in self.pixels[row_start..row_end].chunks_mut(bytes_per_pixel) {
+                pixel.copy_from_slice(value);
+            }
+        }
+    }
+
+    /// Returns a new canvas holding a copy of the pixels of `rect`, in the same format.
+    ///
+    /// The rectangle is clipped to the bounds of the canvas, so the result may be smaller than
+    /// `rect`, or even empty. The new canvas has no padding at the end of each row.
+    pub fn crop(&self, rect: RectI) -> Canvas {
+        let rect = rect
+            .intersection(RectI::new(Vector2I::default(), self.size))
+            .filter(|rect| rect.width() > 0 && rect.height() > 0)
+            .unwrap_or_default();
+        let mut canvas = Canvas::new(rect.size(), self.format);
+        if canvas.stride == 0 {
+            return canvas;
+        }
+        if self.format == Format::A1 {
+            for y in 0..rect.height() as usize {
+                for x in 0..rect.width() as usize {
+                    let on = self.a1_pixel(rect.min_x() as usize + x, rect.min_y() as usize + y);
+                    canvas.set_a1_pixel(x, y, on);
+                }
+            }
+            return canvas;
+        }
+
+        let bytes_per_pixel = self.format.aligned_bytes_per_pixel();
+        for (y, dst_row) in
+            (rect.min_y()..rect.max_y()).zip(canvas.pixels.chunks_mut(canvas.stride))
+        {
+            let src_start = y as usize * self.stride + rect.min_x() as usize * bytes_per_pixel;
+            dst_row.copy_from_slice(&self.pixels[src_start..src_start + dst_row.len()]);
+        }
+        canvas
+    }
+
+    /// Returns the smallest rectangle that contains every pixel that isn't transparent black
+    /// (all values 0).
+    ///
+    /// This is the region that glyphs rasterized to the canvas actually inked, which is usually
+    /// smaller than their raster bounds. Returns an empty rectangle at the origin if the canvas
+    /// is blank.
+    pub fn ink_bounds(&self) -> RectI {
+        let row_length = self.format.bytes_per_row(self.size.x().max(0) as usize);
+        if row_length == 0 {
+            return RectI::default();
+        }
+
+        let (mut min_x, mut max_x) = (usize::MAX, 0);
+        let (mut min_y, mut max_y) = (usize::MAX, 0);
+        let rows = self.pixels.chunks(self.stride).take(self.size.y() as usize);
+        for (y, row) in rows.enumerate() {
+            let row = &row[..row_length];
+            if let Some(first) = row.iter().position(|&byte| byte != 0) {
+                let last = row.iter().rposition(|&byte| byte != 0).unwrap();
+                let (first_x, last_x) = match self.format {
+                    // The unused bits at the end of `A1` rows are always 0.
+                    Format::A1 => (
+                        first * 8 + row[first].leading_zeros() as usize,
+                        last * 8 + 7 - row[last].trailing_zeros() as usize,
+                    ),
+                    _ => {
+                        let bytes_per_pixel = self.format.aligned_bytes_per_pixel();
+                        (first / bytes_per_pixel, last / bytes_per_pixel)
+                    }
+                };
+                min_x = min_x.min(first_x);
+                max_x = max_x.max(last_x + 1);
+                min_y = min_y.min(y);
+                max_y = y + 1;
+            }
+        }
+        if min_y == usize::MAX {
+            return RectI::default();
+        }
+        RectI::from_points(
+            Vector2I::new(min_x as i32, min_y as i32),
+            Vector2I::new(max_x as i32, max_y as i32),
+        )
+    }
+
+    /// Writes the canvas to `writer` as a PNG image.
+    ///
+    /// `A8` canvases become 8-bit grayscale images, `A1` canvases 1-bit grayscale images, `Rgb24`
+    /// canvases RGB images, and `Rgba32` canvases RGBA images. `A16` and `AF32` canvases become
+    /// 16-bit grayscale images, with `AF32` coverage clamped to [0, 1]. Pixels are written as they
+    /// are; in particular, glyphs rasterized to `Rgba32` have premultiplied alpha unless
+    /// `rasterize_glyph_with_alpha()` was asked for straight alpha, as PNG expects. Any padding at
+    /// the end of each row is skipped.
+    ///
+    /// This method is only available with the `png` Cargo feature.
+    #[cfg(feature = "png")]
+    pub fn encode_png(&self, writer: impl Write) -> io::Result<()> {
+        let (bit_depth, color_type) = match self.format {
+            Format::A8 | Format::Sdf => (png::BitDepth::Eight, png::ColorType::Grayscale),
+            Format::A1 => (png::BitDepth::One, png::ColorType::Grayscale),
+            Format::A16 | Format::AF32 => (png::BitDepth::Sixteen, png::ColorType::Grayscale),
+            Format::Rgb24 => (png::BitDepth::Eight, png::ColorType::Rgb),
+            Format::Rgba32 => (png::BitDepth::Eight, png::ColorType::Rgba),
+        };
+
+        let row_length = self.format.bytes_per_row(self.size.x() as usize);
+        let rows: Vec<Vec<u8>> = (0..self.size.y() as usize)
+            .map(|y| {
+                let row = &self.pixels[y * self.stride..y * self.stride + row_length];
+                match self.format {
+                    // `A1` rows are already packed as PNG expects.
+                    Format::A8 | Format::A1 | Format::Rgb24 | Format::Rgba32 | Format::Sdf => {
+                        row.to_vec()
+                    }
+                    // PNG samples are big-endian.
+                    Format::A16 => row
+                        .chunks(2)
+                        .flat_map(|sample| [sample[1], sample[0]])
+                        .collect(),
+                    Format::AF32 => row
+                        .chunks(4)
+                        .flat_map(|sample| {
+                            let coverage =
+                                f32::from_le_bytes([sample[0], sample[1], sample[2], sample[3]]);
+                            ((coverage.clamp(0.0, 1.0) * 65535.0).round() as u16).to_be_bytes()
+                        })
+                        .collect(),
+                }
+            })
+            .collect();
+
+        let mut encoder = png::Encoder::new(writer, self.size.x() as u32, self.size.y() as u32);
+        encoder.set_depth(bit_depth);
+        encoder.set_color(color_type);
+        let mut writer = encoder.write_header().map_err(png_error_to_io_error)?;
+        writer
+            .write_image_data(&rows.concat())
+            .map_err(png_error_to_io_error)?;
+        writer.finish().map_err(png_error_to_io_error)
+    }
+
     #[allow(dead_code)]
     pub(crate) fn blit_from_canvas(&mut self, src: &Canvas) {
         self.blit_from(
@@ -102,12 +384,19 @@ impl Canvas {
         };
 
         match (self.format, src_format) {
-            (Format::A8, Format::A8)
+            // `Sdf` pixels are single bytes that rise through 128 at the outline, so they're
+            // blitted as `A8` coverage is. Coverage copied to an `Sdf` canvas reads as a field
+            // with a spread of half a pixel, and a field copied out reads as coverage that is half
+            // on at the outline.
+            (_, Format::Sdf) => {
+                self.blit_from(dst_point, src_bytes, src_size, src_stride, Format::A8)
+            }
+            (Format::A8 | Format::Sdf, Format::A8)
             | (Format::Rgb24, Format::Rgb24)
             | (Format::Rgba32, Format::Rgba32) => {
                 self.blit_from_with::<BlitMemcpy>(dst_rect, src_bytes, src_stride, src_format)
             }
-            (Format::A8, Format::Rgb24) => {
+            (Format::A8 | Format::Sdf, Format::Rgb24) => {
                 self.blit_from_with::<BlitRgb24ToA8>(dst_rect, src_bytes, src_stride, src_format)
             }
             (Format::Rgb24, Format::A8) => {
@@ -117,7 +406,72 @@ impl Canvas {
                 .blit_from_with::<BlitRgba32ToRgb24>(dst_rect, src_bytes, src_stride, src_format),
             (Format::Rgba32, Format::Rgb24) => self
                 .blit_from_with::<BlitRgb24ToRgba32>(dst_rect, src_bytes, src_stride, src_format),
-            (Format::Rgba32, Format::A8) | (Format::A8, Format::Rgba32) => unimplemented!(),
+            (Format::Rgba32, Format::A8) => {
+                self.blit_from_with::<BlitA8ToRgba32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A8 | Format::Sdf, Format::Rgba32) => {
+                self.blit_from_with::<BlitRgba32ToA8>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::A16) | (Format::AF32, Format::AF32) => {
+                self.blit_from_with::<BlitMemcpy>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::A8) => {
+                self.blit_from_with::<BlitA8ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::Rgb24) => {
+                self.blit_from_with::<BlitRgb24ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::Rgba32) => {
+                self.blit_from_with::<BlitRgba32ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::AF32, Format::A8) => {
+                self.blit_from_with::<BlitA8ToAF32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::AF32, Format::Rgb24) => {
+                self.blit_from_with::<BlitRgb24ToAF32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::AF32, Format::Rgba32) => {
+                self.blit_from_with::<BlitRgba32ToAF32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A8 | Format::Sdf, Format::A16) => {
+                self.blit_from_with::<BlitA16ToA8>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A8 | Format::Sdf, Format::AF32) => {
+                self.blit_from_with::<BlitAF32ToA8>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A16, Format::AF32) => {
+                self.blit_from_with::<BlitAF32ToA16>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::AF32, Format::A16) => {
+                self.blit_from_with::<BlitA16ToAF32>(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (Format::A1, _) => {
+                self.blit_to_a1_with_threshold(dst_rect, src_bytes, src_stride, src_format)
+            }
+            (_, Format::A1) => {
+                self.blit_from_bitmap_1bpp(dst_point, src_bytes, src_size, src_stride)
+            }
+            (Format::Rgb24, Format::A16)
+            | (Format::Rgb24, Format::AF32)
+            | (Format::Rgba32, Format::A16)
+            | (Format::Rgba32, Format::AF32) => {
+                // Narrow to 8-bit coverage first, then convert.
+                let mut a8_canvas = Canvas::new(src_size, Format::A8);
+                a8_canvas.blit_from(
+                    Vector2I::default(),
+                    src_bytes,
+                    src_size,
+                    src_stride,
+                    src_format,
+                );
+                self.blit_from(
+                    dst_point,
+                    &a8_canvas.pixels,
+                    src_size,
+                    a8_canvas.stride,
+                    Format::A8,
+                );
+            }
         }
     }
 
@@ -129,8 +483,22 @@ impl Canvas {
         src_size: Vector2I,
         src_stride: usize,
     ) {
+        if self.format == Format::A1 {
+            self.blit_from(dst_point, src_bytes, src_size, src_stride, Format::A1);
+            return;
+        }
         if self.format != Format::A8 {
-            unimplemented!()
+            // Expand to 8-bit coverage first, then convert.
+            let mut a8_canvas = Canvas::new(src_size, Format::A8);
+            a8_canvas.blit_from_bitmap_1bpp(Vector2I::default(), src_bytes, src_size, src_stride);
+            self.blit_from(
+                dst_point,
+                &a8_canvas.pixels,
+                src_size,
+                a8_canvas.stride,
+                Format::A8,
+            );
+            return;
         }
 
         let dst_rect = RectI::new(dst_point, src_size);
@@ -142,7 +510,7 @@ impl Canvas {
 
         let size = dst_rect.size();
 
-        let dest_bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        let dest_bytes_per_pixel = self.format.aligned_bytes_per_pixel();
         let dest_row_stride = size.x() as usize * dest_bytes_per_pixel;
         let src_row_stride = utils::div_round_up(size.x() as usize, 8);
 
@@ -166,15 +534,157 @@ impl Canvas {
         }
     }
 
-    fn blit_from_with<B: Blit>(
+    /// Tints the `A8` or `Rgb24` coverage in `coverage` with `color` and composites it over this
+    /// `Rgba32` canvas, placing its top left corner at `dst_point`.
+    ///
+    /// `Rgb24` coverage tints each channel separately, with the largest of the three as alpha.
+    /// `Rgba32` sources, such as color glyphs, hold premultiplied colors of their own and are
+    /// composited untinted.
+    pub(crate) fn composite_coverage(
+        &mut self,
+        dst_point: Vector2I,
+        coverage: &Canvas,
+        color: Color,
+    ) {
+        debug_assert_eq!(self.format, Format::Rgba32);
+        let src_rect = RectI::new(dst_point, coverage.size);
+        let dst_rect = match src_rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(dst_rect) => dst_rect,
+            None => return,
+        };
+
+        let color_alpha = color.a as u32;
+        let tint = |coverage: u8, channel: u8| {
+            ((coverage as u32 * channel as u32 * color_alpha + 65025 / 2) / 65025) as u8
+        };
+        let src_bytes_per_pixel = coverage.format.aligned_bytes_per_pixel();
+        for y in dst_rect.min_y()..dst_rect.max_y() {
+            let src_row_start = (y - dst_point.y()) as usize * coverage.stride;
+            let dst_row_start = y as usize * self.stride;
+            for x in dst_rect.min_x()..dst_rect.max_x() {
+                let src_start = src_row_start + (x - dst_point.x()) as usize * src_bytes_per_pixel;
+                let src_pixel = &coverage.pixels[src_start..(src_start + src_bytes_per_pixel)];
+                let src = match coverage.format {
+                    Format::Rgba32 => [src_pixel[0], src_pixel[1], src_pixel[2], src_pixel[3]],
+                    Format::Rgb24 => [
+                        tint(src_pixel[0], color.r),
+                        tint(src_pixel[1], color.g),
+                        tint(src_pixel[2], color.b),
+                        tint(src_pixel[0].max(src_pixel[1]).max(src_pixel[2]), 255),
+                    ],
+                    _ => [
+                        tint(src_pixel[0], color.r),
+                        tint(src_pixel[0], color.g),
+                        tint(src_pixel[0], color.b),
+                        tint(src_pixel[0], 255),
+                    ],
+                };
+                if src[3] == 0 {
+                    continue;
+                }
+
+                let dst_start = dst_row_start + x as usize * 4;
+                let dst_pixel = &mut self.pixels[dst_start..(dst_start + 4)];
+                let inverse_alpha = 255 - src[3] as u32;
+                for (dst, src) in dst_pixel.iter_mut().zip(src.iter()) {
+                    *dst = (*src as u32 + (*dst as u32 * inverse_alpha + 127) / 255) as u8;
+                }
+            }
+        }
+    }
+
+    /// Combines `src`, which must have the same format as this canvas, into this canvas with its
+    /// top left corner at `dst_point`, keeping the larger of the two values of each byte.
+    ///
+    /// Unlike a plain copy, this keeps overlapping glyphs from erasing each other's coverage.
+    pub(crate) fn blend_max_from(&mut self, dst_point: Vector2I, src: &Canvas) {
+        debug_assert_eq!(self.format, src.format);
+        let src_rect = RectI::new(dst_point, src.size);
+        let dst_rect = match src_rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(dst_rect) => dst_rect,
+            None => return,
+        };
+
+        let bytes_per_pixel = self.format.aligned_bytes_per_pixel();
+        let row_length = dst_rect.width() as usize * bytes_per_pixel;
+        for y in dst_rect.min_y()..dst_rect.max_y() {
+            let dst_start = y as usize * self.stride + dst_rect.min_x() as usize * bytes_per_pixel;
+            let src_start = (y - dst_point.y()) as usize * src.stride
+                + (dst_rect.min_x() - dst_point.x()) as usize * bytes_per_pixel;
+            let dst_row = &mut self.pixels[dst_start..(dst_start + row_length)];
+            let src_row = &src.pixels[src_start..(src_start + row_length)];
+            for (dst, &src) in dst_row.iter_mut().zip(src_row.iter()) {
+                *dst = (*dst).max(src);
+            }
+        }
+    }
+
+    /// Converts the premultiplied `Rgba32` pixels within `rect` to straight (unassociated) alpha.
+    ///
+    /// Does nothing for other formats.
+    pub(crate) fn unpremultiply_alpha(&mut self, rect: RectI) {
+        if self.format != Format::Rgba32 {
+            return;
+        }
+        let rect = match rect.intersection(RectI::new(Vector2I::default(), self.size)) {
+            Some(rect) => rect,
+            None => return,
+        };
+
+        for y in rect.min_y()..rect.max_y() {
+            let row_start = y as usize * self.stride + rect.min_x() as usize * 4;
+            let row_end = row_start + rect.width() as usize * 4;
+            for pixel in self.pixels[row_start..row_end].chunks_mut(4) {
+                let alpha = pixel[3] as u32;
+                if alpha == 0 || alpha == 255 {
+                    continue;
+                }
+                for component in &mut pixel[0..3] {
+                    *component = cmp::min(255, (*component as u32 * 255 + alpha / 2) / alpha) as u8;
+                }
+            }
+        }
+    }
+
+    // Copies coverage into this `A1` canvas, turning on the pixels that are at least half covered.
+    fn blit_to_a1_with_threshold(
         &mut self,
         rect: RectI,
         src_bytes: &[u8],
         src_stride: usize,
         src_format: Format,
     ) {
+        // `A1` sources are read bit by bit instead.
         let src_bytes_per_pixel = src_format.bytes_per_pixel() as usize;
-        let dest_bytes_per_pixel = self.format.bytes_per_pixel() as usize;
+        for y in 0..rect.height() as usize {
+            let src_row = &src_bytes[y * src_stride..];
+            for x in 0..rect.width() as usize {
+                let pixel = &src_row[x * src_bytes_per_pixel..(x + 1) * src_bytes_per_pixel];
+                let on = match src_format {
+                    Format::A1 => src_row[x / 8] & (0x80 >> (x % 8)) != 0,
+                    // Distance fields are at least 128 inside the outline.
+                    Format::A8 | Format::Sdf => pixel[0] >= 0x80,
+                    Format::Rgb24 => pixel[0].max(pixel[1]).max(pixel[2]) >= 0x80,
+                    Format::Rgba32 => pixel[3] >= 0x80,
+                    Format::A16 => u16::from_le_bytes([pixel[0], pixel[1]]) >= 0x8000,
+                    Format::AF32 => {
+                        f32::from_le_bytes([pixel[0], pixel[1], pixel[2], pixel[3]]) >= 0.5
+                    }
+                };
+                self.set_a1_pixel(rect.min_x() as usize + x, rect.min_y() as usize + y, on);
+            }
+        }
+    }
+
+    fn blit_from_with<B: Blit>(
+        &mut self,
+        rect: RectI,
+        src_bytes: &[u8],
+        src_stride: usize,
+        src_format: Format,
+    ) {
+        let src_bytes_per_pixel = src_format.aligned_bytes_per_pixel();
+        let dest_bytes_per_pixel = self.format.aligned_bytes_per_pixel();
 
         for y in 0..rect.height() {
             let (dest_row_start, src_row_start) = (
@@ -202,15 +712,50 @@ impl fmt::Debug for Canvas {
     }
 }
 
+/// How far, in pixels, the field of an `Sdf` canvas extends from the outline by default.
+pub const DEFAULT_SDF_SPREAD: f32 = 4.0;
+
 /// The image format for the canvas.
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Format {
     /// Premultiplied R8G8B8A8, little-endian.
+    ///
+    /// Glyphs are rasterized into this format with premultiplied alpha unless straight alpha is
+    /// requested with `rasterize_glyph_with_alpha()`.
     Rgba32,
     /// R8G8B8, little-endian.
     Rgb24,
     /// A8.
     A8,
+    /// A16, little-endian. Coverage ranges from 0 to 65535.
+    ///
+    /// All loaders currently rasterize with 8-bit precision, so glyph coverage is upscaled
+    /// exactly (by a factor of 257) rather than computed at full 16-bit precision.
+    A16,
+    /// A single 32-bit little-endian float per pixel. Coverage ranges from 0.0 to 1.0.
+    ///
+    /// All loaders currently rasterize with 8-bit precision, so glyph coverage takes one of 256
+    /// evenly spaced values (an 8-bit value divided by 255).
+    AF32,
+    /// An 8-bit signed distance field, generated from the glyph outline rather than rasterized.
+    ///
+    /// Values of 128 lie on the outline and rise toward 255 inside the glyph, falling toward 0
+    /// outside it; they reach the ends of the range at `DEFAULT_SDF_SPREAD` pixels from the
+    /// outline, or at the spread given to `rasterize_glyph_sdf()`. Sampling the field with
+    /// bilinear filtering and thresholding at 0.5 renders the glyph crisply at any scale.
+    Sdf,
+    /// A1: 1-bit coverage, with each pixel entirely on or off, packed eight pixels to a byte.
+    ///
+    /// The leftmost of each eight pixels is in the most significant bit, as in FreeType's
+    /// monochrome bitmaps and 1-bit PNG images. Every row starts on a byte boundary, so a row of
+    /// `width` pixels takes up `(width + 7) / 8` bytes, and the unused low bits of its last byte
+    /// stay 0.
+    ///
+    /// For true monochrome output, like FreeType's `FT_RENDER_MODE_MONO`, rasterize with
+    /// `RasterizationOptions::Bilevel`, preferably with full hinting, which snaps stems to whole
+    /// pixels. Antialiased coverage drawn to this format is thresholded instead, turning on the
+    /// pixels that are at least half covered.
+    A1,
 }
 
 impl Format {
@@ -220,7 +765,10 @@ impl Format {
         match self {
             Format::Rgba32 => 32,
             Format::Rgb24 => 24,
-            Format::A8 => 8,
+            Format::A8 | Format::Sdf => 8,
+            Format::A16 => 16,
+            Format::AF32 => 32,
+            Format::A1 => 1,
         }
     }
 
@@ -230,7 +778,7 @@ impl Format {
         match self {
             Format::Rgba32 => 4,
             Format::Rgb24 => 3,
-            Format::A8 => 1,
+            Format::A8 | Format::A16 | Format::AF32 | Format::Sdf | Format::A1 => 1,
         }
     }
 
@@ -241,10 +789,48 @@ impl Format {
     }
 
     /// Returns the number of bytes per pixel that this image format corresponds to.
+    ///
+    /// This is 0 for `A1`, whose pixels are packed eight to a byte; use `bytes_per_row()` to size
+    /// rows of any format.
     #[inline]
     pub fn bytes_per_pixel(self) -> u8 {
         self.bits_per_pixel() / 8
     }
+
+    // The number of bytes per pixel of a byte-aligned format. `A1` canvases take separate paths.
+    #[inline]
+    fn aligned_bytes_per_pixel(self) -> usize {
+        debug_assert_ne!(self, Format::A1);
+        self.bits_per_pixel() as usize / 8
+    }
+
+    /// Returns the number of bytes that a row of `width` pixels takes up in this image format,
+    /// which is the smallest stride a canvas of that width can have.
+    #[inline]
+    pub fn bytes_per_row(self, width: usize) -> usize {
+        utils::div_round_up(width * self.bits_per_pixel() as usize, 8)
+    }
+}
+
+/// The value of a single pixel of a canvas, as returned by `Canvas::pixel()`.
+///
+/// There is one variant per canvas format, holding the pixel's components in that format.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PixelValue {
+    /// The red, green, blue, and alpha components of an `Rgba32` pixel.
+    Rgba32([u8; 4]),
+    /// The red, green, and blue components of an `Rgb24` pixel.
+    Rgb24([u8; 3]),
+    /// The coverage of an `A8` pixel.
+    A8(u8),
+    /// The coverage of an `A16` pixel.
+    A16(u16),
+    /// The coverage of an `AF32` pixel.
+    AF32(f32),
+    /// The value of an `Sdf` pixel.
+    Sdf(u8),
+    /// Whether an `A1` pixel is on.
+    A1(bool),
 }
 
 /// The antialiasing strategy that should be used when rasterizing glyphs.
@@ -256,6 +842,203 @@ pub enum RasterizationOptions {
     GrayscaleAa,
     /// Subpixel RGB antialiasing, for LCD screens.
     SubpixelAa,
+    /// Grayscale antialiasing done by font-kit itself instead of the platform, so that a glyph
+    /// rasterizes to the same bytes with every loader and every version of FreeType, Core Text,
+    /// or DirectWrite. This is meant for golden-image tests.
+    ///
+    /// These settings are pinned, whatever else is requested:
+    ///
+    /// * Hinting options are ignored; the unhinted outline is drawn.
+    ///
+    /// * Embedded bitmaps and color glyphs aren't used, so glyphs with no outline leave the
+    ///   canvas unchanged.
+    ///
+    /// * Curves are flattened into lines no longer than a pixel.
+    ///
+    /// * A pixel's coverage is the area of the pixel inside the outline, found by summing the
+    ///   signed areas of the contours and clamping to full, and is stored linearly, with no gamma
+    ///   correction or filtering.
+    ///
+    /// * The pixels within the glyph's typographic bounds, rounded out to whole pixels, are
+    ///   replaced; the rest of the canvas is untouched.
+    StableGrayscaleAa,
+}
+
+/// Whether color glyphs, from `COLR`, `sbix`, or `CBDT` tables, are drawn in their own colors or
+/// as monochrome outlines.
+///
+/// `Font::rasterize_glyph_colored()` takes this from `GlyphKey::color`; it is the only
+/// rasterization function that draws color. `Font::rasterize_glyph()` and the other functions
+/// that produce coverage always draw outlines, as with `Never`, unless the FreeType loader is
+/// given `FT_LOAD_COLOR` with `set_load_flags()`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum ColorPreference {
+    /// Draw glyphs in color if the font has color data for them that the loader can draw.
+    #[default]
+    Auto,
+    /// Draw glyphs in color, failing for glyphs without color data that the loader can draw.
+    Force,
+    /// Draw only the outlines of glyphs, even if the font has color data for them, as a
+    /// terminal would.
+    Never,
+}
+
+/// Everything that determines how a glyph is rasterized, short of the font itself.
+///
+/// The fields correspond to the arguments of `rasterize_glyph()`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GlyphKey {
+    /// The ID of the glyph.
+    pub glyph_id: u32,
+    /// The size of the font, in pixels per em.
+    pub point_size: f32,
+    /// The transform applied to the glyph.
+    pub transform: Transform2F,
+    /// How the glyph outline is grid-fitted.
+    pub hinting_options: HintingOptions,
+    /// How the glyph is antialiased.
+    pub rasterization_options: RasterizationOptions,
+    /// Whether color glyphs are drawn in their own colors; see
+    /// `Font::rasterize_glyph_colored()`.
+    pub color: ColorPreference,
+}
+
+impl GlyphKey {
+    /// Creates a key for the glyph `glyph_id` at `point_size`, with no transform or hinting,
+    /// grayscale antialiasing, and color glyphs drawn in color.
+    #[inline]
+    pub fn new(glyph_id: u32, point_size: f32) -> GlyphKey {
+        GlyphKey {
+            glyph_id,
+            point_size,
+            transform: Transform2F::default(),
+            hinting_options: HintingOptions::None,
+            rasterization_options: RasterizationOptions::GrayscaleAa,
+            color: ColorPreference::Auto,
+        }
+    }
+}
+
+/// Composes the transform that `rasterize_glyph()` takes from a font size, a resolution, and a
+/// position on the canvas.
+///
+/// The conventions are those of `rasterize_glyph()`: transforms act in canvas pixels, with y
+/// pointing down and the glyph origin on the baseline at (0, 0).
+#[derive(Clone, Copy, Debug)]
+pub struct RasterTransform;
+
+impl RasterTransform {
+    /// Returns the transform that draws a glyph of `point_size` points at `dpi` dots per inch with
+    /// its origin at `origin`, to be passed to `rasterize_glyph()` along with `point_size` itself.
+    ///
+    /// The glyph is first transformed by `extra`, if given, about its origin; a skew or rotation
+    /// there is independent of the size, and a translation is in ems, so that a baseline shift of
+    /// `-0.33` raises a superscript by a third of the font size at any size. The result is then
+    /// scaled by `dpi / 72.0` and finally moved to `origin`, in canvas pixels. `origin` is not
+    /// rounded; see `subpixel_positions()` for how precisely each loader honors a fractional one.
+    ///
+    /// Without a rotation or skew, the FreeType loader grid-fits at the scaled size; the other
+    /// loaders grid-fit at `point_size`, so pass a `dpi` of 72 and a size in pixels when hinting
+    /// matters there.
+    pub fn build(
+        point_size: f32,
+        dpi: f32,
+        origin: Vector2F,
+        extra: Option<Transform2F>,
+    ) -> Transform2F {
+        let extra = extra.unwrap_or_default();
+        let extra = Transform2F {
+            matrix: extra.matrix,
+            vector: extra.vector * point_size,
+        };
+        Transform2F::from_translation(origin) * Transform2F::from_scale(dpi / 72.0) * extra
+    }
+}
+
+/// A summary of the coverage values of a rasterized glyph.
+///
+/// The statistics cover the glyph's ink rectangle: the smallest rectangle of pixels that contains
+/// every pixel with nonzero coverage. A glyph with no ink, such as a space, has all fields zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct CoverageStats {
+    /// The lowest coverage in the ink rectangle.
+    pub min: u8,
+    /// The highest coverage in the ink rectangle.
+    pub max: u8,
+    /// The average coverage in the ink rectangle, from 0.0 to 255.0.
+    pub mean: f32,
+    /// The number of pixels with nonzero coverage.
+    pub filled_pixel_count: u32,
+    /// The number of pixels in the ink rectangle.
+    pub pixel_count: u32,
+}
+
+impl CoverageStats {
+    /// Summarizes the coverage values of an `A8` canvas.
+    pub(crate) fn from_a8_canvas(canvas: &Canvas) -> CoverageStats {
+        debug_assert_eq!(canvas.format, Format::A8);
+        let ink_bounds = canvas.ink_bounds();
+        if ink_bounds.width() == 0 {
+            return CoverageStats::default();
+        }
+
+        let mut stats = CoverageStats {
+            min: u8::MAX,
+            ..CoverageStats::default()
+        };
+        let mut total = 0u64;
+        let (min_x, max_x) = (ink_bounds.min_x() as usize, ink_bounds.max_x() as usize);
+        let rows = canvas.pixels.chunks(canvas.stride);
+        let rows = rows
+            .skip(ink_bounds.min_y() as usize)
+            .take(ink_bounds.height() as usize);
+        for row in rows {
+            for &coverage in &row[min_x..max_x] {
+                stats.min = stats.min.min(coverage);
+                stats.max = stats.max.max(coverage);
+                stats.filled_pixel_count += (coverage != 0) as u32;
+                total += coverage as u64;
+            }
+        }
+        stats.pixel_count = (ink_bounds.width() * ink_bounds.height()) as u32;
+        stats.mean = total as f32 / stats.pixel_count as f32;
+        stats
+    }
+}
+
+/// Reusable scratch state for rasterizing many glyphs in a row.
+///
+/// Pass the same context to successive `rasterize_glyph_with_context()` calls so that the
+/// intermediate coverage buffer is allocated once and then reused. The buffer grows to fit the
+/// largest glyph rasterized so far and is never shrunk; drop the context to release it.
+///
+/// A context isn't tied to a particular font or canvas, but it can't be shared between threads
+/// at the same time, so keep one per thread.
+#[derive(Clone, Default)]
+pub struct RasterizeContext {
+    pub(crate) buffer: Vec<u8>,
+}
+
+impl RasterizeContext {
+    /// Creates a new context with an empty scratch buffer.
+    #[inline]
+    pub fn new() -> RasterizeContext {
+        RasterizeContext::default()
+    }
+
+    /// Returns the number of bytes currently reserved for the scratch buffer.
+    #[inline]
+    pub fn capacity(&self) -> usize {
+        self.buffer.capacity()
+    }
+}
+
+impl fmt::Debug for RasterizeContext {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RasterizeContext")
+            .field("capacity", &self.buffer.capacity())
+            .finish()
+    }
 }
 
 trait Blit {
@@ -296,6 +1079,29 @@ impl Blit for BlitA8ToRgb24 {
     }
 }
 
+struct BlitA8ToRgba32;
+
+impl Blit for BlitA8ToRgba32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        // Premultiplied white.
+        for (dest, src) in dest.chunks_mut(4).zip(src.iter()) {
+            dest.copy_from_slice(&[*src; 4])
+        }
+    }
+}
+
+struct BlitRgba32ToA8;
+
+impl Blit for BlitRgba32ToA8 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.iter_mut().zip(src.chunks(4)) {
+            *dest = src[3]
+        }
+    }
+}
+
 struct BlitRgba32ToRgb24;
 
 impl Blit for BlitRgba32ToRgb24 {
@@ -320,3 +1126,126 @@ impl Blit for BlitRgb24ToRgba32 {
         }
     }
 }
+
+struct BlitA8ToA16;
+
+impl Blit for BlitA8ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.iter()) {
+            dest.copy_from_slice(&(*src as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct BlitRgb24ToA16;
+
+impl Blit for BlitRgb24ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(3)) {
+            dest.copy_from_slice(&(src[1] as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct BlitRgba32ToA16;
+
+impl Blit for BlitRgba32ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(4)) {
+            dest.copy_from_slice(&(src[3] as u16 * 257).to_le_bytes())
+        }
+    }
+}
+
+struct BlitA16ToA8;
+
+impl Blit for BlitA16ToA8 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.iter_mut().zip(src.chunks(2)) {
+            *dest = ((u16::from_le_bytes([src[0], src[1]]) as u32 + 128) / 257) as u8
+        }
+    }
+}
+
+struct BlitAF32ToA8;
+
+impl Blit for BlitAF32ToA8 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.iter_mut().zip(src.chunks(4)) {
+            let coverage = f32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+            *dest = (coverage.clamp(0.0, 1.0) * 255.0).round() as u8
+        }
+    }
+}
+
+struct BlitAF32ToA16;
+
+impl Blit for BlitAF32ToA16 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(2).zip(src.chunks(4)) {
+            let coverage = f32::from_le_bytes([src[0], src[1], src[2], src[3]]);
+            dest.copy_from_slice(
+                &((coverage.clamp(0.0, 1.0) * 65535.0).round() as u16).to_le_bytes(),
+            )
+        }
+    }
+}
+
+struct BlitA16ToAF32;
+
+impl Blit for BlitA16ToAF32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(2)) {
+            let coverage = u16::from_le_bytes([src[0], src[1]]) as f32 / 65535.0;
+            dest.copy_from_slice(&coverage.to_le_bytes())
+        }
+    }
+}
+
+struct BlitA8ToAF32;
+
+impl Blit for BlitA8ToAF32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.iter()) {
+            dest.copy_from_slice(&(*src as f32 / 255.0).to_le_bytes())
+        }
+    }
+}
+
+struct BlitRgb24ToAF32;
+
+impl Blit for BlitRgb24ToAF32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(3)) {
+            dest.copy_from_slice(&(src[1] as f32 / 255.0).to_le_bytes())
+        }
+    }
+}
+
+struct BlitRgba32ToAF32;
+
+impl Blit for BlitRgba32ToAF32 {
+    #[inline]
+    fn blit(dest: &mut [u8], src: &[u8]) {
+        for (dest, src) in dest.chunks_mut(4).zip(src.chunks(4)) {
+            dest.copy_from_slice(&(src[3] as f32 / 255.0).to_le_bytes())
+        }
+    }
+}
+
+#[cfg(feature = "png")]
+fn png_error_to_io_error(error: png::EncodingError) -> io::Error {
+    match error {
+        png::EncodingError::IoError(error) => error,
+        error => io::Error::new(io::ErrorKind::InvalidInput, error),
+    }
+}
@@ -0,0 +1,180 @@
+// font-kit/src/classification.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Metadata that helps classify fonts by design and by the scripts they cover, and that says how
+//! they may be embedded in documents.
+//!
+//! For OpenType fonts, these come from the `OS/2` table.
+
+use std::ops::RangeInclusive;
+
+use crate::metrics::ScriptTag;
+
+bitflags! {
+    /// The legacy code pages that a font claims to be functional for, from the
+    /// `ulCodePageRange1` and `ulCodePageRange2` fields of the `OS/2` table.
+    ///
+    /// Bit `n` of `ulCodePageRange1` is bit `n` here, and bit `n` of `ulCodePageRange2` is bit
+    /// `32 + n`. Bits without a named constant are preserved.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct CodePageRanges: u64 {
+        /// Code page 1252, Latin 1.
+        const LATIN_1 = 1 << 0;
+        /// Code page 1250, Latin 2: Eastern Europe.
+        const LATIN_2 = 1 << 1;
+        /// Code page 1251, Cyrillic.
+        const CYRILLIC = 1 << 2;
+        /// Code page 1253, Greek.
+        const GREEK = 1 << 3;
+        /// Code page 1254, Turkish.
+        const TURKISH = 1 << 4;
+        /// Code page 1255, Hebrew.
+        const HEBREW = 1 << 5;
+        /// Code page 1256, Arabic.
+        const ARABIC = 1 << 6;
+        /// Code page 1257, Windows Baltic.
+        const BALTIC = 1 << 7;
+        /// Code page 1258, Vietnamese.
+        const VIETNAMESE = 1 << 8;
+        /// Code page 874, Thai.
+        const THAI = 1 << 16;
+        /// Code page 932, JIS/Japan.
+        const JAPANESE = 1 << 17;
+        /// Code page 936, Chinese: Simplified characters (PRC and Singapore).
+        const CHINESE_SIMPLIFIED = 1 << 18;
+        /// Code page 949, Korean Wansung.
+        const KOREAN_WANSUNG = 1 << 19;
+        /// Code page 950, Chinese: Traditional characters (Taiwan and Hong Kong SAR).
+        const CHINESE_TRADITIONAL = 1 << 20;
+        /// Code page 1361, Korean Johab.
+        const KOREAN_JOHAB = 1 << 21;
+        /// The Macintosh character set (US Roman).
+        const MACINTOSH = 1 << 29;
+        /// The OEM character set.
+        const OEM = 1 << 30;
+        /// The symbol character set.
+        const SYMBOL = 1 << 31;
+    }
+}
+
+/// The PANOSE classification digit for the family kind (the first of the ten digits).
+pub const PANOSE_FAMILY_KIND: usize = 0;
+/// The PANOSE classification digit for the serif style (the second of the ten digits), for fonts
+/// whose family kind is `PANOSE_FAMILY_KIND_LATIN_TEXT`.
+pub const PANOSE_SERIF_STYLE: usize = 1;
+
+/// The PANOSE family kind for Latin text faces.
+pub const PANOSE_FAMILY_KIND_LATIN_TEXT: u8 = 2;
+
+/// Returns true if the PANOSE serif style digit of a Latin text face denotes a sans-serif design
+/// (normal, obtuse, perpendicular, flared, or rounded sans).
+#[inline]
+pub fn panose_serif_style_is_sans_serif(serif_style: u8) -> bool {
+    (11..=15).contains(&serif_style)
+}
+
+/// Returns the core repertoire of a script: the characters that a font needs to be useful for
+/// the script's common languages.
+///
+/// The repertoires are the basic letters (and, for Devanagari and Thai, the marks and digits) of
+/// each script, as assigned in Unicode 15.1, without historic, phonetic, or compatibility
+/// extensions. `Loader::script_coverage()` measures fonts against them. Scripts without a
+/// repertoire, including `ScriptTag::DEFAULT`, return an empty slice.
+pub fn script_repertoire(script: ScriptTag) -> &'static [RangeInclusive<char>] {
+    match script {
+        ScriptTag::LATIN => &[
+            'A'..='Z',
+            'a'..='z',
+            '\u{c0}'..='\u{d6}',
+            '\u{d8}'..='\u{f6}',
+            '\u{f8}'..='\u{17f}',
+        ],
+        ScriptTag::GREEK => &[
+            '\u{386}'..='\u{386}',
+            '\u{388}'..='\u{38a}',
+            '\u{38c}'..='\u{38c}',
+            '\u{38e}'..='\u{3a1}',
+            '\u{3a3}'..='\u{3ce}',
+        ],
+        ScriptTag::CYRILLIC => &['\u{400}'..='\u{45f}'],
+        ScriptTag::ARABIC => &['\u{621}'..='\u{64a}'],
+        ScriptTag::HEBREW => &['\u{5d0}'..='\u{5ea}'],
+        ScriptTag::DEVANAGARI => &[
+            '\u{901}'..='\u{939}',
+            '\u{93c}'..='\u{94d}',
+            '\u{950}'..='\u{950}',
+            '\u{966}'..='\u{96f}',
+        ],
+        ScriptTag::THAI => &['\u{e01}'..='\u{e3a}', '\u{e40}'..='\u{e4e}'],
+        ScriptTag::HAN => &['\u{4e00}'..='\u{9fff}'],
+        ScriptTag::KANA => &['\u{3041}'..='\u{3096}', '\u{30a1}'..='\u{30fa}'],
+        ScriptTag::HANGUL => &['\u{ac00}'..='\u{d7a3}'],
+        _ => &[],
+    }
+}
+
+/// What a font's license allows documents that embed the font to do with it, from the `fsType`
+/// field of the `OS/2` table.
+///
+/// Applications that embed fonts in documents, such as PDF generators, are expected to honor
+/// these restrictions.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EmbeddingRestrictions {
+    /// How an embedded copy of the font may be used.
+    pub permission: EmbeddingPermission,
+    /// If true, the font must be embedded whole rather than subsetted.
+    pub no_subsetting: bool,
+    /// If true, only the font's embedded bitmaps may be embedded, not its outlines.
+    pub bitmap_only: bool,
+}
+
+/// How an embedded copy of a font may be used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum EmbeddingPermission {
+    /// The font may be embedded and permanently installed on the remote system.
+    #[default]
+    Installable,
+    /// The font must not be embedded without permission from its legal owner.
+    Restricted,
+    /// The font may be embedded in documents that are only viewed and printed.
+    PreviewAndPrint,
+    /// The font may be embedded in documents that are viewed, printed, and edited, but not
+    /// installed.
+    Editable,
+}
+
+const FS_TYPE_RESTRICTED: u16 = 0x0002;
+const FS_TYPE_PREVIEW_AND_PRINT: u16 = 0x0004;
+const FS_TYPE_EDITABLE: u16 = 0x0008;
+const FS_TYPE_NO_SUBSETTING: u16 = 0x0100;
+const FS_TYPE_BITMAP_ONLY: u16 = 0x0200;
+
+impl EmbeddingRestrictions {
+    /// Decodes the `fsType` field of an `OS/2` table.
+    ///
+    /// Fonts made before version 3 of the `OS/2` table may set several permission bits; as the
+    /// OpenType specification directs, the least restrictive one wins.
+    pub fn from_fs_type(fs_type: u16) -> EmbeddingRestrictions {
+        let permission = if fs_type & FS_TYPE_EDITABLE != 0 {
+            EmbeddingPermission::Editable
+        } else if fs_type & FS_TYPE_PREVIEW_AND_PRINT != 0 {
+            EmbeddingPermission::PreviewAndPrint
+        } else if fs_type & FS_TYPE_RESTRICTED != 0 {
+            EmbeddingPermission::Restricted
+        } else {
+            EmbeddingPermission::Installable
+        };
+        EmbeddingRestrictions {
+            permission,
+            no_subsetting: fs_type & FS_TYPE_NO_SUBSETTING != 0,
+            bitmap_only: fs_type & FS_TYPE_BITMAP_ONLY != 0,
+        }
+    }
+}
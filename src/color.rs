@@ -0,0 +1,194 @@
+// font-kit/src/color.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Color glyphs described by the OpenType `COLR` and `CPAL` tables.
+//!
+//! A color glyph is a graph of paint operations: glyph outlines used as clip masks, filled with
+//! solid colors or gradients, transformed, and composited together. `COLR` version 0 fonts can only
+//! stack solid-colored layers; version 1 fonts can use the full graph. Both are represented by the
+//! same `Paint` type.
+//!
+//! All coordinates are in font units, with the y axis pointing up.
+
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::Vector2F;
+
+/// An 8-bit-per-channel color with straight (non-premultiplied) alpha.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+pub struct Color {
+    /// The red channel.
+    pub r: u8,
+    /// The green channel.
+    pub g: u8,
+    /// The blue channel.
+    pub b: u8,
+    /// The alpha channel.
+    pub a: u8,
+}
+
+/// The color of a solid fill or a gradient stop.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PaintColor {
+    /// A color from the palette, with the paint's alpha already multiplied into its alpha channel.
+    Color(Color),
+    /// The text foreground color chosen by the application, multiplied by `alpha`, which ranges
+    /// from 0.0 to 1.0.
+    Foreground {
+        /// The opacity to apply to the foreground color.
+        alpha: f32,
+    },
+}
+
+/// What a gradient does outside the range of its color stops.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Extend {
+    /// The colors of the first and last stops extend forever.
+    Pad,
+    /// The gradient repeats.
+    Repeat,
+    /// The gradient repeats, reversing direction every other time.
+    Reflect,
+}
+
+/// One stop of a gradient.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct ColorStop {
+    /// The position of the stop along the gradient, where 0.0 is the start and 1.0 is the end.
+    ///
+    /// Fonts may place stops outside that range.
+    pub offset: f32,
+    /// The color at this stop.
+    pub color: PaintColor,
+}
+
+/// The colors of a gradient.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ColorLine {
+    /// What the gradient does outside the range of its stops.
+    pub extend: Extend,
+    /// The color stops, in the order the font lists them.
+    pub stops: Vec<ColorStop>,
+}
+
+/// How a `Paint::Composite` combines its source with its backdrop.
+///
+/// These are the Porter-Duff compositing operators and the blend modes of the W3C Compositing and
+/// Blending specification.
+#[allow(missing_docs)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CompositeMode {
+    Clear,
+    Source,
+    Destination,
+    SourceOver,
+    DestinationOver,
+    SourceIn,
+    DestinationIn,
+    SourceOut,
+    DestinationOut,
+    SourceAtop,
+    DestinationAtop,
+    Xor,
+    Plus,
+    Screen,
+    Overlay,
+    Darken,
+    Lighten,
+    ColorDodge,
+    ColorBurn,
+    HardLight,
+    SoftLight,
+    Difference,
+    Exclusion,
+    Multiply,
+    Hue,
+    Saturation,
+    Color,
+    Luminosity,
+}
+
+/// A node in the paint graph of a color glyph.
+///
+/// Each node paints into an unbounded layer; `Paint::Glyph` is what confines paint to a shape.
+/// References to other color glyphs are resolved in place, and variable paints are returned with
+/// their default values.
+#[derive(Clone, PartialEq, Debug)]
+pub enum Paint {
+    /// Paints each layer in turn, from bottom to top, compositing with source-over.
+    Layers(Vec<Paint>),
+    /// Fills with a single color.
+    Solid(PaintColor),
+    /// Fills with a linear gradient.
+    ///
+    /// The gradient runs from `p0` to `p1`, and its color bands are parallel to the line from `p0`
+    /// to `p2`.
+    LinearGradient {
+        /// The colors of the gradient.
+        color_line: ColorLine,
+        /// The start point.
+        p0: Vector2F,
+        /// The end point.
+        p1: Vector2F,
+        /// The rotation point.
+        p2: Vector2F,
+    },
+    /// Fills with a gradient that interpolates between two circles.
+    RadialGradient {
+        /// The colors of the gradient.
+        color_line: ColorLine,
+        /// The center of the start circle.
+        c0: Vector2F,
+        /// The radius of the start circle.
+        r0: f32,
+        /// The center of the end circle.
+        c1: Vector2F,
+        /// The radius of the end circle.
+        r1: f32,
+    },
+    /// Fills with a gradient that sweeps around a center point.
+    SweepGradient {
+        /// The colors of the gradient.
+        color_line: ColorLine,
+        /// The center of the sweep.
+        center: Vector2F,
+        /// The angle of the start of the gradient, in radians counterclockwise from the positive x
+        /// axis.
+        start_angle: f32,
+        /// The angle of the end of the gradient, in radians counterclockwise from the positive x
+        /// axis.
+        end_angle: f32,
+    },
+    /// Paints `paint`, clipped to the outline of the glyph `glyph_id`.
+    Glyph {
+        /// The glyph whose outline clips the paint.
+        glyph_id: u32,
+        /// The paint to clip.
+        paint: Box<Paint>,
+    },
+    /// Paints `paint` with `transform` applied to it.
+    ///
+    /// The translate, scale, rotate, and skew paints of `COLR` version 1 are all represented as
+    /// transforms.
+    Transform {
+        /// The transform, which maps the paint's coordinate space to that of its parent.
+        transform: Transform2F,
+        /// The paint to transform.
+        paint: Box<Paint>,
+    },
+    /// Composites `source` over `backdrop` with `mode`.
+    Composite {
+        /// The paint to composite.
+        source: Box<Paint>,
+        /// How to combine the two paints.
+        mode: CompositeMode,
+        /// The paint to composite onto.
+        backdrop: Box<Paint>,
+    },
+}
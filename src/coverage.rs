@@ -0,0 +1,120 @@
+// font-kit/src/coverage.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Antialiased coverage computed from glyph outlines, independently of the platform rasterizer.
+//!
+//! Each line segment of the flattened outline adds its signed area to an accumulation buffer, and
+//! a running sum along each row then gives the coverage of every pixel. Only basic floating-point
+//! arithmetic is used, so the output is the same on every platform.
+
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::vector::Vector2I;
+
+use crate::canvas::{Canvas, Format};
+use crate::sdf::SegmentCollector;
+
+/// Rasterizes a flattened outline, already translated so that `origin` is at (0, 0), and copies
+/// the coverage to the `size`-pixel region of the canvas at `origin`, replacing what was there.
+pub(crate) fn render(
+    canvas: &mut Canvas,
+    collector: SegmentCollector,
+    origin: Vector2I,
+    size: Vector2I,
+) {
+    if size.x() <= 0 || size.y() <= 0 {
+        return;
+    }
+    let (width, height) = (size.x() as usize, size.y() as usize);
+
+    // Each row has an extra slot on the right to absorb area that lands on the last edge.
+    let row_length = width + 1;
+    let mut accumulator = vec![0.0; row_length * height];
+    for segment in collector.into_segments() {
+        accumulate_line(&mut accumulator, row_length, height, segment);
+    }
+
+    let mut coverage = vec![0; width * height];
+    for (accumulator_row, coverage_row) in accumulator
+        .chunks(row_length)
+        .zip(coverage.chunks_mut(width))
+    {
+        let mut area = 0.0f32;
+        for (accumulated, pixel) in accumulator_row.iter().zip(coverage_row.iter_mut()) {
+            area += accumulated;
+            *pixel = (area.abs().min(1.0) * 255.0).round() as u8;
+        }
+    }
+    canvas.blit_from(origin, &coverage, size, width, Format::A8);
+}
+
+// Adds the signed area that a line contributes to each pixel to the accumulator. Area to the
+// left of the buffer is moved onto its left edge, and area to the right onto its extra slot.
+fn accumulate_line(accumulator: &mut [f32], row_length: usize, height: usize, line: LineSegment2F) {
+    let (from, to) = (line.from(), line.to());
+    if from.y() == to.y() {
+        return;
+    }
+    let (direction, top, bottom) = if from.y() < to.y() {
+        (1.0, from, to)
+    } else {
+        (-1.0, to, from)
+    };
+    let max_x = (row_length - 1) as f32;
+    let dx_dy = (bottom.x() - top.x()) / (bottom.y() - top.y());
+
+    let first_row = top.y().max(0.0).floor() as usize;
+    let last_row = (bottom.y().ceil().max(0.0) as usize).min(height);
+    let mut x = top.x() + (top.y().max(0.0) - top.y()) * dx_dy;
+    for row in first_row..last_row {
+        let row_top = (row as f32).max(top.y());
+        let row_bottom = ((row + 1) as f32).min(bottom.y());
+        let dy = row_bottom - row_top;
+        let next_x = x + dx_dy * dy;
+        let signed_dy = dy * direction;
+        let row_start = row * row_length;
+
+        let (x0, x1) = if x < next_x { (x, next_x) } else { (next_x, x) };
+        let (x0, x1) = (x0.clamp(0.0, max_x), x1.clamp(0.0, max_x));
+        let x0_floor = x0.floor();
+        let x0_index = x0_floor as usize;
+        let x1_ceil = x1.ceil();
+        let x1_index = x1_ceil as usize;
+        if x1_index <= x0_index + 1 {
+            // The line stays within one pixel of this row.
+            let mid_x = (x0 + x1) * 0.5 - x0_floor;
+            accumulator[row_start + x0_index] += signed_dy - signed_dy * mid_x;
+            if x0_index + 1 < row_length {
+                accumulator[row_start + x0_index + 1] += signed_dy * mid_x;
+            }
+        } else {
+            // Spread the area over the pixels the line crosses, trapezoid by trapezoid.
+            let slope = 1.0 / (x1 - x0);
+            let x0_fraction = x0 - x0_floor;
+            let first_area = 0.5 * slope * (1.0 - x0_fraction) * (1.0 - x0_fraction);
+            let x1_fraction = x1 - x1_ceil + 1.0;
+            let last_area = 0.5 * slope * x1_fraction * x1_fraction;
+            accumulator[row_start + x0_index] += signed_dy * first_area;
+            if x1_index == x0_index + 2 {
+                accumulator[row_start + x0_index + 1] += signed_dy * (1.0 - first_area - last_area);
+            } else {
+                let second_area = slope * (1.5 - x0_fraction);
+                accumulator[row_start + x0_index + 1] += signed_dy * (second_area - first_area);
+                for index in (x0_index + 2)..(x1_index - 1) {
+                    accumulator[row_start + index] += signed_dy * slope;
+                }
+                let before_last_area = second_area + (x1_index - x0_index - 3) as f32 * slope;
+                accumulator[row_start + x1_index - 1] +=
+                    signed_dy * (1.0 - before_last_area - last_area);
+            }
+            accumulator[row_start + x1_index] += signed_dy * last_area;
+        }
+        x = next_x;
+    }
+}
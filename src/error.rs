@@ -14,6 +14,7 @@ use std::borrow::Cow;
 use std::convert::From;
 use std::error::Error;
 use std::io;
+use std::path::PathBuf;
 
 macro_rules! impl_display {
     ($enum:ident, {$($variant:pat => $fmt_string:expr),+$(,)* }) => {
@@ -48,9 +49,39 @@ pub enum FontLoadingError {
     NoFilesystem,
     /// A disk or similar I/O error occurred while attempting to load the font.
     Io(io::Error),
+    /// Loading the font at `path` failed with `error`.
+    ///
+    /// `Loader::from_handle()`, and therefore `Handle::load()`, attaches this context to errors
+    /// from path handles so that a failure in a batch of loads can be traced to its file. Use
+    /// `root_cause()` to get at the underlying error.
+    InFile {
+        /// The path of the file that failed to load.
+        path: PathBuf,
+        /// The index of the font within the file.
+        font_index: u32,
+        /// The error that occurred.
+        error: Box<FontLoadingError>,
+    },
+}
+
+impl FontLoadingError {
+    /// Returns the underlying error, skipping any `InFile` context.
+    pub fn root_cause(&self) -> &FontLoadingError {
+        match self {
+            FontLoadingError::InFile { error, .. } => error.root_cause(),
+            error => error,
+        }
+    }
 }
 
-impl Error for FontLoadingError {}
+impl Error for FontLoadingError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            FontLoadingError::InFile { error, .. } => Some(&**error),
+            _ => None,
+        }
+    }
+}
 
 impl_display! { FontLoadingError, {
         UnknownFormat => "unknown format",
@@ -58,6 +89,9 @@ impl_display! { FontLoadingError, {
         Parse => "parse error",
         NoFilesystem => "no filesystem present",
         Io(e) => format!("I/O error: {}", e),
+        InFile { path, font_index, error } => {
+            format!("failed to load font {} of {}: {}", font_index, path.display(), error)
+        },
     }
 }
 
@@ -74,6 +108,10 @@ pub enum GlyphLoadingError {
     NoSuchGlyph,
     /// A platform function returned an error.
     PlatformError,
+    /// The loader doesn't support the requested operation.
+    Unsupported,
+    /// The glyph's bounds were too large, or otherwise unusable, for a scratch canvas.
+    InvalidCanvasSize,
 }
 
 impl Error for GlyphLoadingError {}
@@ -81,6 +119,33 @@ impl Error for GlyphLoadingError {}
 impl_display! { GlyphLoadingError, {
         NoSuchGlyph => "no such glyph",
         PlatformError => "platform error",
+        Unsupported => "unsupported by this loader",
+        InvalidCanvasSize => "invalid canvas size",
+    }
+}
+
+impl From<CanvasError> for GlyphLoadingError {
+    #[inline]
+    fn from(_: CanvasError) -> GlyphLoadingError {
+        GlyphLoadingError::InvalidCanvasSize
+    }
+}
+
+/// Reasons why a canvas might fail to be created.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum CanvasError {
+    /// The width or height was zero or negative.
+    EmptySize,
+    /// The width or height was greater than `Canvas::MAX_DIMENSION`, or the pixels wouldn't fit
+    /// in memory.
+    TooLarge,
+}
+
+impl Error for CanvasError {}
+
+impl_display! { CanvasError, {
+        EmptySize => "canvas width or height is not positive",
+        TooLarge => "canvas is too large",
     }
 }
 
@@ -96,6 +161,9 @@ impl From<winapi::um::winnt::HRESULT> for GlyphLoadingError {
 pub enum SelectionError {
     /// No font matching the given query was found.
     NotFound,
+    /// The query was malformed, for example because a family name was empty or contained only
+    /// whitespace.
+    InvalidQuery,
     /// The source was inaccessible because of an I/O or similar error.
     CannotAccessSource {
         /// Additional diagnostic information may include file name
@@ -107,6 +175,7 @@ impl Error for SelectionError {}
 
 impl_display! { SelectionError, {
         NotFound => "no font found",
+        InvalidQuery => "invalid query",
         CannotAccessSource { reason: ref maybe_cow } => maybe_cow.as_deref().unwrap_or("failed to access source")
     }
 }
@@ -40,8 +40,13 @@ where
         Ok(Family { fonts })
     }
 
+    /// Loads every font in a family handle, such as one returned by
+    /// `Source::select_family_by_name()`.
+    ///
+    /// Fails if any of the fonts fails to load; `Source::select_family_loaded()` skips them
+    /// instead.
     #[inline]
-    pub(crate) fn from_handle(family_handle: &FamilyHandle) -> Result<Family<F>, FontLoadingError> {
+    pub fn from_handle(family_handle: &FamilyHandle) -> Result<Family<F>, FontLoadingError> {
         Family::from_font_handles(family_handle.fonts.iter())
     }
 
@@ -16,6 +16,10 @@ use crate::handle::Handle;
 #[derive(Debug)]
 pub struct FamilyHandle {
     pub(crate) fonts: Vec<Handle>,
+    pub(crate) display_name: Option<String>,
+    // The indices of the `MultiSource` subsources that this family was found in, outermost last,
+    // so that the source that found it can describe its fonts.
+    pub(crate) subsource_path: Vec<usize>,
 }
 
 impl Default for FamilyHandle {
@@ -28,7 +32,11 @@ impl FamilyHandle {
     /// Creates an empty set of family handles.
     #[inline]
     pub fn new() -> FamilyHandle {
-        FamilyHandle { fonts: vec![] }
+        FamilyHandle {
+            fonts: vec![],
+            display_name: None,
+            subsource_path: vec![],
+        }
     }
 
     /// Creates a set of font family handles.
@@ -39,6 +47,8 @@ impl FamilyHandle {
     {
         FamilyHandle {
             fonts: fonts.collect::<Vec<Handle>>(),
+            display_name: None,
+            subsource_path: vec![],
         }
     }
 
@@ -59,4 +69,21 @@ impl FamilyHandle {
     pub fn fonts(&self) -> &[Handle] {
         &self.fonts
     }
+
+    /// Returns the name of this family as it should be shown to the user, localized to the
+    /// user's language where the source supports it.
+    ///
+    /// This can differ from the name the family was looked up by: a CJK family selected by its
+    /// English name will typically be displayed under its native name. Returns `None` if the
+    /// source didn't report a name.
+    #[inline]
+    pub fn display_name(&self) -> Option<String> {
+        self.display_name.clone()
+    }
+
+    /// Sets the name of this family as it should be shown to the user.
+    #[inline]
+    pub fn set_display_name(&mut self, display_name: Option<String>) {
+        self.display_name = display_name
+    }
 }
@@ -16,7 +16,7 @@
 /// <https://drafts.csswg.org/css-fonts-3/#font-family-prop>.
 ///
 /// TODO(pcwalton): `system-ui`, `emoji`, `math`, `fangsong`
-#[derive(Clone, Debug, PartialEq, Hash)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub enum FamilyName {
     /// A specific font family, specified by name: e.g. "Arial", "times".
     Title(String),
@@ -35,3 +35,29 @@ pub enum FamilyName {
     /// expressive representations of characters.
     Fantasy,
 }
+
+impl FamilyName {
+    /// Parses one entry of a CSS `font-family` value.
+    ///
+    /// The generic family keywords (`serif`, `sans-serif`, `monospace`, `cursive`, and `fantasy`)
+    /// are matched case-insensitively. Anything else is a family name: quotes around it are
+    /// removed, and runs of whitespace in an unquoted name are collapsed to a single space. As in
+    /// CSS, a quoted keyword such as `"serif"` names a family rather than the generic family.
+    pub fn from_css(value: &str) -> FamilyName {
+        let value = value.trim();
+        for quote in ['"', '\''] {
+            if value.len() >= 2 && value.starts_with(quote) && value.ends_with(quote) {
+                return FamilyName::Title(value[1..value.len() - 1].to_owned());
+            }
+        }
+
+        match &*value.to_ascii_lowercase() {
+            "serif" => FamilyName::Serif,
+            "sans-serif" => FamilyName::SansSerif,
+            "monospace" => FamilyName::Monospace,
+            "cursive" => FamilyName::Cursive,
+            "fantasy" => FamilyName::Fantasy,
+            _ => FamilyName::Title(value.split_whitespace().collect::<Vec<_>>().join(" ")),
+        }
+    }
+}
@@ -0,0 +1,167 @@
+// font-kit/src/font_info.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Lightweight descriptions of fonts, read directly from a few of their tables.
+//!
+//! Loading a `Font` has the platform library parse the whole font, which dominates the time
+//! taken to scan a large collection of fonts for names and styles. `FontInfo` reads only the
+//! table directory and the handful of tables it needs.
+
+use std::io::{Cursor, Read, Seek, SeekFrom};
+
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::path::Path;
+
+use crate::error::FontLoadingError;
+use crate::properties::Properties;
+use crate::tables;
+use crate::utils;
+
+const SFNT_HEADER_LENGTH: usize = 12;
+const SFNT_TABLE_RECORD_LENGTH: usize = 16;
+const TTC_NUM_FONTS_OFFSET: usize = 8;
+const TTC_TABLE_DIRECTORY_OFFSETS_OFFSET: usize = 12;
+
+/// The names, properties, variability, and use of color of a font, read from its `name`, `head`,
+/// `OS/2`, and `fvar` tables and its table directory without loading it.
+///
+/// This is much cheaper than loading a `Font`, so it suits tools that scan thousands of fonts.
+/// Only TrueType and OpenType fonts and collections can be described this way; other formats,
+/// including WOFF, fail with `FontLoadingError::UnknownFormat`.
+///
+/// The family name and properties are chosen by the same rules as the FreeType loader uses, so
+/// they usually agree with those of a loaded `Font`. Names are decoded in full, though, whereas
+/// FreeType replaces non-ASCII characters in family names with `?`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FontInfo {
+    /// The name of the font family, or the empty string if the font has none.
+    pub family_name: String,
+    /// The PostScript name of the font, if it has one.
+    pub postscript_name: Option<String>,
+    /// The name of the font within its family, such as `"Bold Italic"`, if it has one.
+    pub style_name: Option<String>,
+    /// The style, weight, and width of the font.
+    pub properties: Properties,
+    /// True if the font has variation axes.
+    pub is_variable: bool,
+    /// True if the font has color glyphs, from `COLR`, `sbix`, or `CBDT` tables.
+    pub is_color: bool,
+}
+
+impl FontInfo {
+    /// Describes a font in raw font data (the contents of a `.ttf`/`.otf`/etc. file).
+    ///
+    /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
+    /// of the font to describe. If the data represents a single font, pass 0 for `font_index`.
+    pub fn from_bytes(font_data: &[u8], font_index: u32) -> Result<FontInfo, FontLoadingError> {
+        FontInfo::from_reader(&mut Cursor::new(font_data), font_index)
+    }
+
+    /// Describes a font in a `.ttf`/`.otf`/etc. file, reading only the parts of the file it needs.
+    ///
+    /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
+    /// font to describe. If the file represents a single font, pass 0 for `font_index`.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn from_path<P>(path: P, font_index: u32) -> Result<FontInfo, FontLoadingError>
+    where
+        P: AsRef<Path>,
+    {
+        FontInfo::from_reader(&mut File::open(path)?, font_index)
+    }
+
+    fn from_reader<R>(reader: &mut R, font_index: u32) -> Result<FontInfo, FontLoadingError>
+    where
+        R: Read + Seek,
+    {
+        let mut table_directory_offset = 0;
+        let mut header = read_at(reader, 0, SFNT_HEADER_LENGTH)?;
+        if header.starts_with(b"ttcf") {
+            let num_fonts =
+                tables::read_u32(&header, TTC_NUM_FONTS_OFFSET).ok_or(FontLoadingError::Parse)?;
+            if font_index >= num_fonts {
+                return Err(FontLoadingError::NoSuchFontInCollection);
+            }
+            let offset = TTC_TABLE_DIRECTORY_OFFSETS_OFFSET as u64 + font_index as u64 * 4;
+            let offset = read_at(reader, offset, 4)?;
+            table_directory_offset = tables::read_u32(&offset, 0).unwrap() as u64;
+            header = read_at(reader, table_directory_offset, SFNT_HEADER_LENGTH)?;
+        } else if font_index != 0 {
+            return Err(FontLoadingError::NoSuchFontInCollection);
+        }
+        if !utils::SFNT_VERSIONS
+            .iter()
+            .any(|version| header.starts_with(version))
+        {
+            return Err(FontLoadingError::UnknownFormat);
+        }
+
+        let num_tables = tables::read_u16(&header, 4).unwrap() as usize;
+        let table_records = read_at(
+            reader,
+            table_directory_offset + SFNT_HEADER_LENGTH as u64,
+            num_tables * SFNT_TABLE_RECORD_LENGTH,
+        )?;
+        let find_table_record = |tag: u32| {
+            table_records
+                .chunks_exact(SFNT_TABLE_RECORD_LENGTH)
+                .find(|record| tables::read_u32(record, 0) == Some(tag))
+        };
+        let is_color = [tables::TAG_COLR, tables::TAG_SBIX, tables::TAG_CBLC]
+            .iter()
+            .any(|&tag| find_table_record(tag).is_some());
+        let mut load_table = |tag: u32| -> Result<Option<Vec<u8>>, FontLoadingError> {
+            let record = match find_table_record(tag) {
+                Some(record) => record,
+                None => return Ok(None),
+            };
+            let offset = tables::read_u32(record, 8).unwrap() as u64;
+            let length = tables::read_u32(record, 12).unwrap() as usize;
+            read_at(reader, offset, length).map(Some)
+        };
+
+        let name = load_table(tables::TAG_NAME)?;
+        let head = load_table(tables::TAG_HEAD)?;
+        let os2 = load_table(tables::TAG_OS2)?;
+        let fvar = load_table(tables::TAG_FVAR)?;
+        let os2 = os2.as_deref();
+        Ok(FontInfo {
+            family_name: name
+                .as_deref()
+                .and_then(|name| tables::name_family_name(name, os2))
+                .unwrap_or_default(),
+            postscript_name: name
+                .as_deref()
+                .and_then(|name| tables::name_string(name, tables::NAME_ID_POSTSCRIPT_NAME)),
+            style_name: name
+                .as_deref()
+                .and_then(|name| tables::name_style_name(name, os2)),
+            properties: tables::sfnt_properties(os2, head.as_deref()),
+            is_variable: fvar.as_deref().is_some_and(tables::fvar_has_axes),
+            is_color,
+        })
+    }
+}
+
+// Reads `length` bytes at `offset`, treating a short read as a malformed font.
+fn read_at<R>(reader: &mut R, offset: u64, length: usize) -> Result<Vec<u8>, FontLoadingError>
+where
+    R: Read + Seek,
+{
+    // Don't trust `length` enough to allocate it all up front.
+    let mut buffer = vec![];
+    reader.seek(SeekFrom::Start(offset))?;
+    reader.take(length as u64).read_to_end(&mut buffer)?;
+    if buffer.len() < length {
+        return Err(FontLoadingError::Parse);
+    }
+    Ok(buffer)
+}
@@ -18,7 +18,14 @@ use std::any::Any;
 use std::path::PathBuf;
 use std::sync::Arc;
 
+#[cfg(not(target_arch = "wasm32"))]
+use std::fs::File;
+#[cfg(not(target_arch = "wasm32"))]
+use std::io::Read;
+
 use crate::error::FontLoadingError;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::file_type::FileType;
 use crate::font::Font;
 use crate::loader::Loader;
 
@@ -64,6 +71,26 @@ impl Handle {
         Handle::Path { path, font_index }
     }
 
+    /// Creates a handle for every font in the file at `path`.
+    ///
+    /// A collection (`.ttc`, `.otc`, etc.) yields one handle per font, with font indices from 0
+    /// up to the number of fonts; any other font file yields a single handle. Fails if the file
+    /// can't be read or isn't a supported font.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn all_faces_at_path<P>(path: P) -> Result<Vec<Handle>, FontLoadingError>
+    where
+        P: Into<PathBuf>,
+    {
+        let path = path.into();
+        let font_count = match Font::analyze_path(&path)? {
+            FileType::Single => 1,
+            FileType::Collection(font_count) => font_count,
+        };
+        Ok((0..font_count)
+            .map(|font_index| Handle::from_path(path.clone(), font_index))
+            .collect())
+    }
+
     /// Creates a new handle from raw TTF/OTF/etc. data in memory.
     ///
     /// `font_index` specifies the index of the font to choose if the memory represents a font
@@ -97,4 +124,78 @@ impl Handle {
     pub fn load(&self) -> Result<Font, FontLoadingError> {
         Font::from_handle(self)
     }
+
+    /// Cheaply checks that this handle still resolves to a font, without loading it.
+    ///
+    /// For `Path` handles, this opens the file and reads only its header; for `Memory` handles,
+    /// it inspects the header of the data. The header must carry a known font signature, and
+    /// `font_index` must be within range for collections (and 0 otherwise). `Native` handles are
+    /// always considered valid.
+    ///
+    /// Passing this check doesn't guarantee that the font will load, since the rest of the data
+    /// isn't parsed, but it's enough to prune handles to fonts that have been deleted or replaced.
+    pub fn check(&self) -> Result<(), FontLoadingError> {
+        match *self {
+            Handle::Memory {
+                ref bytes,
+                font_index,
+            } => check_header(bytes, font_index),
+            #[cfg(not(target_arch = "wasm32"))]
+            Handle::Path {
+                ref path,
+                font_index,
+            } => {
+                let mut header = Vec::with_capacity(FONT_HEADER_LENGTH);
+                File::open(path)?
+                    .take(FONT_HEADER_LENGTH as u64)
+                    .read_to_end(&mut header)?;
+                check_header(&header, font_index)
+            }
+            #[cfg(target_arch = "wasm32")]
+            Handle::Path { .. } => Err(FontLoadingError::NoFilesystem),
+            Handle::Native { .. } => Ok(()),
+        }
+    }
+}
+
+// Enough to cover the longest signature below.
+const FONT_HEADER_LENGTH: usize = 16;
+
+const SFNT_SIGNATURES: [&[u8]; 6] = [
+    b"\x00\x01\x00\x00",
+    b"OTTO",
+    b"true",
+    b"typ1",
+    b"wOFF",
+    b"wOF2",
+];
+
+// Formats that FreeType loads besides SFNT: PCF, PFB, and PFA Type 1 fonts.
+const OTHER_FONT_SIGNATURES: [&[u8]; 4] =
+    [b"\x01fcp", b"\x80\x01", b"%!PS-AdobeFont", b"%!FontType1"];
+
+fn check_header(header: &[u8], font_index: u32) -> Result<(), FontLoadingError> {
+    if header.starts_with(b"ttcf") {
+        let font_count = header
+            .get(8..12)
+            .map(|bytes| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .ok_or(FontLoadingError::Parse)?;
+        return if font_index < font_count {
+            Ok(())
+        } else {
+            Err(FontLoadingError::NoSuchFontInCollection)
+        };
+    }
+
+    let is_font = SFNT_SIGNATURES
+        .iter()
+        .chain(OTHER_FONT_SIGNATURES.iter())
+        .any(|signature| header.starts_with(signature));
+    if !is_font {
+        Err(FontLoadingError::UnknownFormat)
+    } else if font_index != 0 {
+        Err(FontLoadingError::NoSuchFontInCollection)
+    } else {
+        Ok(())
+    }
 }
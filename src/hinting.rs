@@ -54,3 +54,19 @@ impl HintingOptions {
         }
     }
 }
+
+/// The global hinting parameters of a CFF font, from its private dict.
+///
+/// Alignment zones are pairs of bottom and top edges, in font units.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CffHintParams {
+    /// The alignment zones from `BlueValues`. The first is the baseline overshoot zone; the rest
+    /// are overshoot zones at the tops of glyphs, such as the x-height and cap height.
+    pub blue_values: Vec<(f32, f32)>,
+    /// The alignment zones from `OtherBlues`, for the bottoms of descenders.
+    pub other_blues: Vec<(f32, f32)>,
+    /// The dominant width of horizontal stems (`StdHW`), if the font specifies one.
+    pub std_hw: Option<f32>,
+    /// The dominant width of vertical stems (`StdVW`), if the font specifies one.
+    pub std_vw: Option<f32>,
+}
@@ -71,6 +71,9 @@
 //! * Filesystem (cross-platform): A simple source that reads fonts from a path on disk. This is
 //!   the default on Android and OpenHarmony.
 //!
+//! * Android assets (Android): A source that reads fonts bundled in an APK's assets through the
+//!   NDK asset manager. This source is built only with the `android` Cargo feature.
+//!
 //! * Memory (cross-platform): A source that reads from a fixed set of fonts in memory.
 //!
 //! * Multi (cross-platform): A source that allows multiple sources to be queried at once.
@@ -124,17 +127,22 @@
 #[macro_use]
 extern crate bitflags;
 
+pub mod caching_font;
 pub mod canvas;
+pub mod classification;
+pub mod color;
 pub mod error;
 pub mod family;
 pub mod family_handle;
 pub mod family_name;
 pub mod file_type;
 pub mod font;
+pub mod font_info;
 pub mod handle;
 pub mod hinting;
 pub mod loader;
 pub mod loaders;
+pub mod math;
 pub mod metrics;
 pub mod outline;
 pub mod properties;
@@ -144,5 +152,9 @@ pub mod source;
 #[cfg(feature = "source")]
 pub mod sources;
 
+mod coverage;
 pub mod matching;
+mod sdf;
+mod subset;
+mod tables;
 mod utils;
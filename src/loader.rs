@@ -12,19 +12,32 @@
 //! fonts.
 
 use log::warn;
+use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::sync::Arc;
 
-use crate::canvas::{Canvas, RasterizationOptions};
+use crate::canvas::{
+    Canvas, ColorPreference, CoverageStats, Format, GlyphKey, RasterizationOptions,
+    RasterizeContext, DEFAULT_SDF_SPREAD,
+};
+use crate::classification::{self, CodePageRanges, EmbeddingRestrictions};
+use crate::color::{Color, Paint, PaintColor};
+use crate::coverage;
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
+use crate::hinting::{CffHintParams, HintingOptions};
+use crate::metrics::{BaselineTag, LineMetrics, Metrics, ScriptTag};
+use crate::outline::{
+    ContourPoints, FillRule, GlyphComponent, Outline, OutlineBuilder, OutlineFormat,
+    OutlineSegments, OutlineSink,
+};
 use crate::properties::Properties;
+use crate::sdf::{self, SegmentCollector};
+use crate::subset;
+use crate::tables;
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
@@ -43,6 +56,18 @@ pub trait Loader: Clone + Sized {
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
     fn from_bytes(font_data: Arc<Vec<u8>>, font_index: u32) -> Result<Self, FontLoadingError>;
 
+    /// Loads a font from raw font data held in any shared buffer, such as a memory-mapped file.
+    ///
+    /// The FreeType loader reads the buffer in place and keeps it alive for as long as the font
+    /// is. The default implementation, which is used by the Core Text and DirectWrite loaders,
+    /// copies the data into a vector and calls `from_bytes()`.
+    fn from_shared_bytes(
+        font_data: Arc<dyn AsRef<[u8]> + Send + Sync>,
+        font_index: u32,
+    ) -> Result<Self, FontLoadingError> {
+        Self::from_bytes(Arc::new((*font_data).as_ref().to_vec()), font_index)
+    }
+
     /// Loads a font from a `.ttf`/`.otf`/etc. file.
     ///
     /// If the file is a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index of the
@@ -63,14 +88,27 @@ pub trait Loader: Clone + Sized {
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// # Safety
+    ///
+    /// `native_font` must be a valid, live handle of the native font type for this loader.
     unsafe fn from_native_font(native_font: &Self::NativeFont) -> Self;
 
     /// Loads the font pointed to by a handle.
+    ///
+    /// If a path handle fails to load, the error is wrapped in `FontLoadingError::InFile` along
+    /// with the path and font index.
     fn from_handle(handle: &Handle) -> Result<Self, FontLoadingError> {
         match handle {
             Handle::Memory { bytes, font_index } => Self::from_bytes((*bytes).clone(), *font_index),
             #[cfg(not(target_arch = "wasm32"))]
-            Handle::Path { path, font_index } => Self::from_path(path, *font_index),
+            Handle::Path { path, font_index } => {
+                Self::from_path(path, *font_index).map_err(|error| FontLoadingError::InFile {
+                    path: path.clone(),
+                    font_index: *font_index,
+                    error: Box::new(error),
+                })
+            }
             #[cfg(target_arch = "wasm32")]
             Handle::Path { .. } => Err(FontLoadingError::NoFilesystem),
             Handle::Native { .. } => {
@@ -113,6 +151,47 @@ pub trait Loader: Clone + Sized {
     /// Returns the name of the font family.
     fn family_name(&self) -> String;
 
+    /// Returns the string with the ID `name_id` from the font's `name` table, or `None` if the
+    /// font has no such string.
+    ///
+    /// A string is often recorded several times, in different languages and encodings. English
+    /// in Unicode is preferred, then any Unicode string, then a Mac Roman one; strings in other
+    /// encodings are ignored. `designer()`, `description()`, and the like look up the IDs that
+    /// font detail panels typically show.
+    fn name_string(&self, name_id: u16) -> Option<String> {
+        tables::name_string(&self.load_font_table(tables::TAG_NAME)?, name_id)
+    }
+
+    /// Returns the text the designer suggests for showing off the font, from the `name` table.
+    #[inline]
+    fn sample_text(&self) -> Option<String> {
+        self.name_string(tables::NAME_ID_SAMPLE_TEXT)
+    }
+
+    /// Returns the description of the font, from the `name` table.
+    #[inline]
+    fn description(&self) -> Option<String> {
+        self.name_string(tables::NAME_ID_DESCRIPTION)
+    }
+
+    /// Returns the name of the font's designer, from the `name` table.
+    #[inline]
+    fn designer(&self) -> Option<String> {
+        self.name_string(tables::NAME_ID_DESIGNER)
+    }
+
+    /// Returns the name of the font's manufacturer, from the `name` table.
+    #[inline]
+    fn manufacturer(&self) -> Option<String> {
+        self.name_string(tables::NAME_ID_MANUFACTURER)
+    }
+
+    /// Returns the description of the font's license, from the `name` table.
+    #[inline]
+    fn license(&self) -> Option<String> {
+        self.name_string(tables::NAME_ID_LICENSE)
+    }
+
     /// Returns true if and only if the font is monospace (fixed-width).
     fn is_monospace(&self) -> bool;
 
@@ -129,8 +208,85 @@ pub trait Loader: Clone + Sized {
     /// Be careful with this function; typographically correct character-to-glyph mapping must be
     /// done using a *shaper* such as HarfBuzz. This function is only useful for best-effort simple
     /// use cases like "what does character X look like on its own".
+    ///
+    /// Characters beyond U+FFFF, such as emoji, are resolved through the font's format 12 `cmap`
+    /// subtables, even if the platform chose a BMP-only subtable for character mapping. Characters
+    /// that only a format 13 subtable maps, as in last-resort fonts, are resolved too.
+    ///
+    /// Unicode subtables always take precedence. Only if the font has none, as with some legacy
+    /// Macintosh fonts, is the character encoded as Mac Roman and looked up in the Mac Roman
+    /// subtable instead.
     fn glyph_for_char(&self, character: char) -> Option<u32>;
 
+    /// Returns true if the font has a real glyph for a Unicode character.
+    ///
+    /// Some fonts map whole ranges of characters they don't cover to the `.notdef` glyph (ID 0),
+    /// which renders as a missing-glyph box; such characters are not covered. The same caveats as
+    /// for `glyph_for_char()` apply.
+    #[inline]
+    fn covers_char(&self, character: char) -> bool {
+        matches!(self.glyph_for_char(character), Some(glyph_id) if glyph_id != 0)
+    }
+
+    /// Returns true if the font has a real glyph for every character in `chars`, as
+    /// `covers_char()` judges them.
+    ///
+    /// This stops at the first character that isn't covered. An empty run is covered.
+    fn covers_all<I>(&self, chars: I) -> bool
+    where
+        I: IntoIterator<Item = char>,
+    {
+        chars
+            .into_iter()
+            .all(|character| self.covers_char(character))
+    }
+
+    /// Returns the fraction of the characters in `chars` that the font has a real glyph for, as
+    /// `covers_char()` judges them, from 0.0 to 1.0.
+    ///
+    /// This is useful for ranking fallback fonts by how much of a run of text each one covers.
+    /// Repeated characters count each time they occur. An empty run has a ratio of 1.0.
+    fn coverage_ratio<I>(&self, chars: I) -> f32
+    where
+        I: IntoIterator<Item = char>,
+    {
+        let (mut covered_count, mut char_count) = (0, 0);
+        for character in chars {
+            char_count += 1;
+            if self.covers_char(character) {
+                covered_count += 1;
+            }
+        }
+        if char_count == 0 {
+            return 1.0;
+        }
+        covered_count as f32 / char_count as f32
+    }
+
+    /// Returns the fraction of a script's core repertoire that the font has real glyphs for, from
+    /// 0.0 to 1.0.
+    ///
+    /// The repertoires, which `classification::script_repertoire()` returns, hold the basic
+    /// characters of each script as of Unicode 15.1. This is useful for choosing the best fallback
+    /// font for each script in a run of text. Scripts without a repertoire score 0.0.
+    fn script_coverage(&self, script: ScriptTag) -> f32 {
+        let repertoire = classification::script_repertoire(script);
+        if repertoire.is_empty() {
+            return 0.0;
+        }
+        self.coverage_ratio(repertoire.iter().cloned().flatten())
+    }
+
+    /// Returns the usual glyph ID for a Unicode character, or the `.notdef` glyph (ID 0) if the
+    /// font doesn't cover it.
+    ///
+    /// This matches what the platform renderers draw for uncovered characters. The same caveats
+    /// as for `glyph_for_char()` apply.
+    #[inline]
+    fn glyph_for_char_or_notdef(&self, character: char) -> u32 {
+        self.glyph_for_char(character).unwrap_or(0)
+    }
+
     /// Returns the glyph ID for the specified glyph name.
     #[inline]
     fn glyph_by_name(&self, _name: &str) -> Option<u32> {
@@ -138,6 +294,26 @@ pub trait Loader: Clone + Sized {
         None
     }
 
+    /// Returns the ligature glyph that the font substitutes for a sequence of component glyphs,
+    /// if it has one.
+    ///
+    /// This reads the ligature substitutions of the `liga` (standard ligatures) and `dlig`
+    /// (discretionary ligatures) features in the `GSUB` table, preferring standard ones, which is
+    /// useful for mapping a rendered ligature back to the glyphs, and so the characters, it
+    /// stands for. It is not shaping: substitutions for every script and language are searched,
+    /// lookup flags are ignored, and only a ligature made of exactly `components` is returned.
+    fn ligature_glyph(&self, components: &[u32]) -> Option<u32> {
+        let gsub = self.load_font_table(tables::TAG_GSUB)?;
+        tables::gsub_ligature_glyph(
+            &gsub,
+            &[
+                tables::FEATURE_TAG_STANDARD_LIGATURES,
+                tables::FEATURE_TAG_DISCRETIONARY_LIGATURES,
+            ],
+            components,
+        )
+    }
+
     /// Sends the vector path for a glyph to a sink.
     ///
     /// If `hinting_mode` is not None, this function performs grid-fitting as requested before
@@ -153,10 +329,120 @@ pub trait Loader: Clone + Sized {
     where
         S: OutlineSink;
 
+    /// Sends the grid-fitted vector path for a glyph at the given point size to a sink.
+    ///
+    /// Unlike `outline()`, which scales hinted outlines back to font units, the points are emitted
+    /// in pixels at `point_size`, exactly as grid fitting left them, so that a vector backend can
+    /// render them directly and still benefit from hinting. The hinting mode is taken from
+    /// `hinting`; the size it carries is ignored in favor of `point_size`.
+    ///
+    /// Only the FreeType loader can retrieve hinted outlines; the default implementation returns
+    /// `GlyphLoadingError::Unsupported`.
+    fn hinted_outline<S>(
+        &self,
+        _glyph_id: u32,
+        _point_size: f32,
+        _hinting: HintingOptions,
+        _sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        Err(GlyphLoadingError::Unsupported)
+    }
+
+    /// Returns the vector path for a glyph as an iterator of path commands.
+    ///
+    /// This is an alternative to `outline()` for callers that would rather pull commands than
+    /// implement `OutlineSink`. None of the platform APIs can produce an outline lazily, so the
+    /// whole path is collected before this returns.
+    fn outline_segments(
+        &self,
+        glyph_id: u32,
+        hinting_mode: HintingOptions,
+    ) -> Result<OutlineSegments, GlyphLoadingError> {
+        let mut segments = vec![];
+        self.outline(glyph_id, hinting_mode, &mut segments)?;
+        Ok(OutlineSegments::new(segments))
+    }
+
+    /// Returns the filled area of a glyph as polygons in font units, for hit-testing.
+    ///
+    /// Each contour of the outline becomes one polygon, with curves flattened into lines that
+    /// stray no more than `tolerance` font units from them. Polygons are implicitly closed; the
+    /// first point isn't repeated at the end.
+    ///
+    /// Contours keep the directions they have in the font, so test points with the nonzero
+    /// winding rule: a point is filled if the polygons wind around it a nonzero number of times
+    /// in total. Counters, such as the inside of an "O", wind the opposite way from the contours
+    /// around them and so are unfilled. The even-odd rule gives the same answer for most glyphs,
+    /// but not where contours overlap, as they often do in variable fonts.
+    fn glyph_hit_region(
+        &self,
+        glyph_id: u32,
+        tolerance: f32,
+    ) -> Result<Vec<Vec<Vector2F>>, GlyphLoadingError> {
+        let mut flattener = ContourFlattener::new(tolerance);
+        self.outline(glyph_id, HintingOptions::None, &mut flattener)?;
+        flattener.close();
+        Ok(flattener.polygons)
+    }
+
+    /// Returns true if this font and `other` draw the same glyph shapes, within a small tolerance.
+    ///
+    /// This is much slower than comparing font data, but it also catches duplicates whose data
+    /// differs, such as a font that has been repackaged as WOFF or had its tables rebuilt. The
+    /// fonts must have the same number of glyphs and units per em, and each compared glyph must
+    /// have the same contours with the same kinds of points, at positions no more than 1/1000 em
+    /// apart. Fonts with up to 1024 glyphs have every glyph compared; for larger fonts, 1024
+    /// glyph IDs spread evenly from the first glyph to the last are sampled.
+    fn outlines_equal(&self, other: &Self) -> bool {
+        let (glyph_count, units_per_em) = (self.glyph_count(), self.metrics().units_per_em);
+        if other.glyph_count() != glyph_count || other.metrics().units_per_em != units_per_em {
+            return false;
+        }
+        let tolerance = units_per_em as f32 / 1000.0;
+        sampled_glyph_ids(glyph_count).all(|glyph_id| {
+            match (load_outline(self, glyph_id), load_outline(other, glyph_id)) {
+                (Ok(outline), Ok(other_outline)) => {
+                    outlines_approximately_equal(&outline, &other_outline, tolerance)
+                }
+                (Err(_), Err(_)) => true,
+                _ => false,
+            }
+        })
+    }
+
+    /// Returns a key that identifies this font by its content, for use in hash maps.
+    ///
+    /// See `FontKey` for what makes two keys equal.
+    fn font_key(&self) -> FontKey {
+        FontKey {
+            postscript_name: self.postscript_name(),
+            units_per_em: self.metrics().units_per_em,
+            glyph_count: self.glyph_count(),
+            checksum: self
+                .load_font_table(tables::TAG_HEAD)
+                .and_then(|head| tables::head_checksum_adjustment(&head)),
+        }
+    }
+
     /// Returns the boundaries of a glyph in font units. The origin of the coordinate
     /// space is at the bottom left.
     fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError>;
 
+    /// Returns how far the glyph with the given ID extends above and below the baseline, in font
+    /// units, as an `(ascent, descent)` pair.
+    ///
+    /// Both values come from the glyph's outline bounding box, so they fit that glyph tightly,
+    /// unlike the font-wide `ascent` and `descent` metrics; this is useful for packing lines
+    /// closely around drop caps, badges, and the like. A descent below the baseline is positive.
+    /// Glyphs with no outline, such as spaces, have extents of zero.
+    fn glyph_vertical_extents(&self, glyph_id: u32) -> Result<(f32, f32), GlyphLoadingError> {
+        let bounds = self.typographic_bounds(glyph_id)?;
+        Ok((bounds.max_y(), -bounds.min_y()))
+    }
+
     /// Returns the distance from the origin of the glyph with the given ID to the next, in font
     /// units.
     fn advance(&self, glyph_id: u32) -> Result<Vector2F, GlyphLoadingError>;
@@ -194,6 +480,17 @@ pub trait Loader: Clone + Sized {
         for_rasterization: bool,
     ) -> bool;
 
+    /// Returns how many horizontal positions within each pixel `rasterize_glyph()` can place a
+    /// glyph at with the given options, or `None` if it places glyphs exactly where asked.
+    ///
+    /// The fractional part of the translation in the transform passed to `rasterize_glyph()` and
+    /// related functions moves the glyph by part of a pixel. When this returns `Some(n)`, both
+    /// components of the translation are first rounded to the nearest multiple of `1/n` pixel,
+    /// ties going to the even multiple, so the glyph lands at one of only `n` positions per
+    /// pixel. A glyph cache that quantizes origins to a number of positions that divides `n`
+    /// asks only for positions the rasterizer reproduces exactly, so nothing is rounded twice.
+    fn subpixel_positions(&self, rasterization_options: RasterizationOptions) -> Option<u32>;
+
     /// Returns the pixel boundaries that the glyph will take up when rendered using this loader's
     /// rasterizer at the given `point_size` and `transform`. The origin of the coordinate space is
     /// at the top left.
@@ -205,17 +502,7 @@ pub trait Loader: Clone + Sized {
         _: HintingOptions,
         _: RasterizationOptions,
     ) -> Result<RectI, GlyphLoadingError> {
-        let typographic_bounds = self.typographic_bounds(glyph_id)?;
-        let typographic_raster_bounds =
-            typographic_bounds * (point_size / self.metrics().units_per_em as f32);
-
-        // Translate the origin to "origin is top left" coordinate system.
-        let new_origin = Vector2F::new(
-            typographic_raster_bounds.origin_x(),
-            -typographic_raster_bounds.origin_y() - typographic_raster_bounds.height(),
-        );
-        let typographic_raster_bounds = RectF::new(new_origin, typographic_raster_bounds.size());
-        Ok((transform * typographic_raster_bounds).round_out().to_i32())
+        typographic_raster_bounds(self, glyph_id, point_size, transform)
     }
 
     /// Rasterizes a glyph to a canvas with the given size and transform.
@@ -227,6 +514,20 @@ pub trait Loader: Clone + Sized {
     /// loader.
     ///
     /// If `hinting_options` is not None, the requested grid fitting is performed.
+    /// The FreeType loader grid-fits at the size the glyph is drawn at along each axis when
+    /// `transform` scales the two axes differently, as for artificially condensed or extended
+    /// text; with rotations and skews, it grid-fits at `point_size` before transforming.
+    ///
+    /// The translation in `transform` may place the glyph between pixels;
+    /// `subpixel_positions()` tells how precisely.
+    ///
+    /// The canvas is not cleared first. Pixels outside the glyph's bitmap are left untouched;
+    /// pixels inside it are overwritten by the FreeType and DirectWrite loaders and composited
+    /// over by the Core Text loader. When reusing a canvas, use `Canvas::clear()` or
+    /// `clear_and_rasterize_glyph()` to avoid seeing a previous glyph.
+    ///
+    /// `Sdf` canvases receive a signed distance field with the default spread, as
+    /// `rasterize_glyph_sdf()` generates it, and `rasterization_options` is ignored.
     fn rasterize_glyph(
         &self,
         canvas: &mut Canvas,
@@ -237,6 +538,439 @@ pub trait Loader: Clone + Sized {
         rasterization_options: RasterizationOptions,
     ) -> Result<(), GlyphLoadingError>;
 
+    /// Generates a signed distance field for a glyph on an `Sdf` canvas, with the given size and
+    /// transform.
+    ///
+    /// The field is computed from the glyph's outline, hinted as `hinting_options` requests, so
+    /// glyphs without outlines (such as bitmap glyphs) leave the canvas unchanged. `spread` is the
+    /// distance in pixels from the outline at which values reach 0 outside the glyph and 255
+    /// inside it; larger spreads allow wider effects such as outlines and glows, at the cost of
+    /// precision. Pixels take the larger of their old and new values, so several glyphs can share
+    /// a canvas. Returns `GlyphLoadingError::Unsupported` if the canvas format isn't `Sdf`.
+    fn rasterize_glyph_sdf(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        spread: f32,
+    ) -> Result<(), GlyphLoadingError> {
+        if canvas.format != Format::Sdf {
+            return Err(GlyphLoadingError::Unsupported);
+        }
+        let scale = point_size / self.metrics().units_per_em as f32;
+        let mut collector = SegmentCollector::new(
+            transform * Transform2F::from_scale(Vector2F::new(scale, -scale)),
+        );
+        self.outline(glyph_id, hinting_options, &mut collector)?;
+        sdf::render(canvas, collector, spread);
+        Ok(())
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, choosing whether `Rgba32`
+    /// output uses premultiplied or straight alpha.
+    ///
+    /// `rasterize_glyph()` always produces premultiplied alpha. If `premultiplied_alpha` is false
+    /// and the canvas is `Rgba32`, the glyph is drawn as `clear_and_rasterize_glyph()` draws it,
+    /// clearing the region it covers first, and the pixels of that region are then converted to
+    /// straight alpha, which is what compositing in linear space usually wants. Other canvas
+    /// formats have no separate alpha channel and are unaffected.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_with_alpha(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        premultiplied_alpha: bool,
+    ) -> Result<(), GlyphLoadingError> {
+        if premultiplied_alpha || canvas.format != Format::Rgba32 {
+            return self.rasterize_glyph(
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                rasterization_options,
+            );
+        }
+
+        // Pixels already on the canvas may be straight alpha themselves, so clear the region
+        // first so that only this glyph's pixels are converted.
+        self.clear_and_rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+        let touched_bounds = touched_bounds(
+            self,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            canvas.format,
+        )?;
+        canvas.unpremultiply_alpha(touched_bounds);
+        Ok(())
+    }
+
+    /// Clears the region of the canvas that the glyph will cover, then rasterizes the glyph into
+    /// it as `rasterize_glyph()` does.
+    ///
+    /// This prevents pixels left over from a previous rasterization from showing through the new
+    /// glyph, without the cost of clearing the entire canvas.
+    fn clear_and_rasterize_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        let touched_bounds = touched_bounds(
+            self,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            canvas.format,
+        )?;
+        let transparent_black = [0; 4];
+        canvas.fill_rect(
+            touched_bounds,
+            &transparent_black[..canvas.format.bytes_per_row(1)],
+        );
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, but leaves every pixel outside
+    /// `clip_rect` untouched.
+    ///
+    /// This keeps glyphs that spill out of their cell, such as a swash or a deep descender, from
+    /// bleeding into neighboring cells of a texture atlas. Inside `clip_rect`, the result is
+    /// exactly what `rasterize_glyph()` would have produced.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip_rect: RectI,
+    ) -> Result<(), GlyphLoadingError> {
+        let region = touched_bounds(
+            self,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            canvas.format,
+        )?
+        .intersection(clip_rect)
+        .and_then(|region| region.intersection(RectI::new(Vector2I::default(), canvas.size)));
+        let region = match region {
+            Some(region) => region,
+            None => return Ok(()),
+        };
+
+        // Rasterize into a copy of the region so that loaders that composite see the same pixels
+        // they would have, then copy only the region back.
+        let mut scratch = canvas.crop(region);
+        self.rasterize_glyph(
+            &mut scratch,
+            glyph_id,
+            point_size,
+            Transform2F::from_translation(-region.origin().to_f32()) * transform,
+            hinting_options,
+            rasterization_options,
+        )?;
+        canvas.blit_from(
+            region.origin(),
+            &scratch.pixels,
+            scratch.size,
+            scratch.stride,
+            scratch.format,
+        );
+        Ok(())
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, reusing the scratch buffers in
+    /// `context` instead of allocating new ones.
+    ///
+    /// The output is the same as that of `rasterize_glyph()`; only the allocation behavior
+    /// differs. Keep one context around for loops that rasterize many glyphs, such as filling a
+    /// glyph atlas, so that they don't allocate a bitmap per glyph. The FreeType loader renders
+    /// grayscale and bilevel glyphs straight into the context's buffer; other loaders and
+    /// rasterization modes fall back to `rasterize_glyph()`.
+    #[allow(clippy::too_many_arguments)]
+    fn rasterize_glyph_with_context(
+        &self,
+        _context: &mut RasterizeContext,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        self.rasterize_glyph(
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Rasterizes a glyph to a scratch buffer and summarizes its coverage, without keeping the
+    /// pixels.
+    ///
+    /// See `CoverageStats` for which pixels the statistics cover.
+    fn glyph_coverage_stats(&self, key: &GlyphKey) -> Result<CoverageStats, GlyphLoadingError> {
+        let (canvas, _) = rasterize_glyph_to_fit(
+            self,
+            key.glyph_id,
+            key.point_size,
+            key.transform,
+            key.hinting_options,
+            key.rasterization_options,
+            Format::A8,
+        )?;
+        Ok(CoverageStats::from_a8_canvas(&canvas))
+    }
+
+    /// Rasterizes a glyph in a solid color to an `Rgba32` canvas, producing premultiplied
+    /// pixels directly instead of coverage that has to be tinted afterward.
+    ///
+    /// The glyph's coverage is multiplied by `color`, which has straight alpha, and composited
+    /// over the canvas. With `SubpixelAa`, each channel is tinted by its own coverage. Returns
+    /// `GlyphLoadingError::Unsupported` if the canvas format isn't `Rgba32`.
+    ///
+    /// Color glyphs are drawn in their own colors instead, where this loader can draw them:
+    ///
+    /// * `COLR` glyphs made only of solid-colored layers, which includes every `COLR` version 0
+    ///   glyph, are drawn layer by layer with all loaders. Layers in the foreground color use
+    ///   `color`. Glyphs that need gradients, transforms, or compositing are drawn tinted.
+    ///
+    /// * `sbix` and `CBDT` bitmaps are drawn by the FreeType loader. The other loaders draw these
+    ///   glyphs tinted.
+    ///
+    /// Whether a glyph is a color glyph is decided glyph by glyph, so color fonts can mix color
+    /// and monochrome glyphs. With `key.color` set to `ColorPreference::Never`, color glyphs are
+    /// drawn tinted too. With `ColorPreference::Force`, glyphs without color data, or with color
+    /// data that this loader can't draw, return `GlyphLoadingError::Unsupported`.
+    fn rasterize_glyph_colored(
+        &self,
+        canvas: &mut Canvas,
+        key: &GlyphKey,
+        color: Color,
+    ) -> Result<(), GlyphLoadingError> {
+        if canvas.format != Format::Rgba32 {
+            return Err(GlyphLoadingError::Unsupported);
+        }
+        let paint = self.glyph_paint(key.glyph_id, 0);
+        let has_color_bitmap = has_color_bitmap(self, key.glyph_id);
+        let use_color = match key.color {
+            ColorPreference::Auto => true,
+            ColorPreference::Force if paint.is_none() && !has_color_bitmap => {
+                return Err(GlyphLoadingError::Unsupported)
+            }
+            ColorPreference::Force => true,
+            ColorPreference::Never => false,
+        };
+
+        if use_color {
+            if let Some(layers) = paint.as_ref().and_then(solid_color_layers) {
+                for (glyph_id, layer_color) in layers {
+                    let (coverage, origin) = rasterize_glyph_to_fit(
+                        self,
+                        glyph_id,
+                        key.point_size,
+                        key.transform,
+                        key.hinting_options,
+                        key.rasterization_options,
+                        coverage_format(key.rasterization_options),
+                    )?;
+                    canvas.composite_coverage(
+                        origin,
+                        &coverage,
+                        resolve_paint_color(layer_color, color),
+                    );
+                }
+                return Ok(());
+            }
+            if has_color_bitmap {
+                match self.rasterize_color_bitmap_glyph(key) {
+                    Ok((bitmap, origin)) => {
+                        canvas.composite_coverage(origin, &bitmap, color);
+                        return Ok(());
+                    }
+                    Err(GlyphLoadingError::Unsupported) => {}
+                    Err(error) => return Err(error),
+                }
+            }
+            // The glyph has color data that this loader can't draw.
+            if key.color == ColorPreference::Force {
+                return Err(GlyphLoadingError::Unsupported);
+            }
+        }
+
+        let (coverage, origin) = rasterize_glyph_to_fit(
+            self,
+            key.glyph_id,
+            key.point_size,
+            key.transform,
+            key.hinting_options,
+            key.rasterization_options,
+            coverage_format(key.rasterization_options),
+        )?;
+        canvas.composite_coverage(origin, &coverage, color);
+        Ok(())
+    }
+
+    /// Rasterizes a glyph from its `sbix` or `CBDT` color bitmap into a new `Rgba32` canvas sized
+    /// to fit it, returning the canvas, with premultiplied alpha, and the position of its top left
+    /// corner.
+    ///
+    /// The default implementation returns `GlyphLoadingError::Unsupported`; loaders that can draw
+    /// color bitmaps override it.
+    #[doc(hidden)]
+    fn rasterize_color_bitmap_glyph(
+        &self,
+        _key: &GlyphKey,
+    ) -> Result<(Canvas, Vector2I), GlyphLoadingError> {
+        Err(GlyphLoadingError::Unsupported)
+    }
+
+    /// Rasterizes a glyph whose origin falls at a fractional pixel position into a new canvas
+    /// sized to fit it, returning the canvas and the position of its top left corner.
+    ///
+    /// `subpixel_origin` is the position of the glyph's origin on the baseline, in pixels with y
+    /// pointing down, as in a canvas. It's split into whole pixels, which only move the returned
+    /// position, and a fraction, which the rasterizer renders as the glyph's subpixel phase, as
+    /// precisely as `subpixel_positions()` allows. Drawing the canvas at the returned position
+    /// puts the glyph exactly where it belongs, so text runs can place each glyph at its
+    /// unrounded pen position without losing sharpness.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise.
+    fn rasterize_glyph_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        subpixel_origin: Vector2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(Canvas, Vector2I), GlyphLoadingError> {
+        let pixel_origin = subpixel_origin.floor();
+        let (canvas, origin) = rasterize_glyph_to_fit(
+            self,
+            glyph_id,
+            point_size,
+            Transform2F::from_translation(subpixel_origin - pixel_origin),
+            hinting_options,
+            rasterization_options,
+            coverage_format(rasterization_options),
+        )?;
+        Ok((canvas, pixel_origin.to_i32() + origin))
+    }
+
+    /// Rasterizes a short string on one line into a new canvas sized to fit it, for labels and
+    /// other simple text.
+    ///
+    /// Each character is mapped to a glyph with `glyph_for_char()`, falling back to `.notdef`
+    /// (glyph 0), and glyphs are placed left to right by their advances, adjusted by the pair
+    /// kerning in the font's `kern` table. This is not text shaping: there are no ligatures,
+    /// contextual forms, `GPOS` kerning or mark positioning, bidirectional reordering, or line
+    /// breaking, so it is only suitable for simple left-to-right scripts such as Latin.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise. It spans the line's ascent and
+    /// descent at `point_size`, widened to include any ink that falls outside them, and the pen
+    /// starts at the left edge of the line on the baseline. Returns
+    /// `GlyphLoadingError::InvalidCanvasSize` if the line is too large for a canvas.
+    fn rasterize_line(
+        &self,
+        text: &str,
+        point_size: f32,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<Canvas, GlyphLoadingError> {
+        let scale = point_size / self.metrics().units_per_em as f32;
+        let kern = self.load_font_table(tables::TAG_KERN);
+
+        // Lay out and rasterize the glyphs with the baseline at y = 0.
+        let format = coverage_format(rasterization_options);
+        let mut glyphs = vec![];
+        let mut pen_x = 0.0;
+        let mut previous_glyph_id = None;
+        for character in text.chars() {
+            let glyph_id = self.glyph_for_char_or_notdef(character);
+            if let (Some(kern), Some(previous_glyph_id)) = (&kern, previous_glyph_id) {
+                pen_x +=
+                    tables::kern_pair_adjustment(kern, previous_glyph_id, glyph_id) as f32 * scale;
+            }
+            let (glyph_canvas, origin) = rasterize_glyph_to_fit(
+                self,
+                glyph_id,
+                point_size,
+                Transform2F::from_translation(Vector2F::new(pen_x, 0.0)),
+                hinting_options,
+                rasterization_options,
+                format,
+            )?;
+            if glyph_canvas.size.x() > 0 && glyph_canvas.size.y() > 0 {
+                glyphs.push((glyph_canvas, origin));
+            }
+            pen_x += self.advance(glyph_id)?.x() * scale;
+            previous_glyph_id = Some(glyph_id);
+        }
+
+        let line_metrics = self.line_metrics(point_size);
+        let line_bounds = RectI::from_points(
+            Vector2I::new(0, -line_metrics.ascent.ceil() as i32),
+            Vector2I::new(pen_x.ceil() as i32, (-line_metrics.descent).ceil() as i32),
+        );
+        let canvas_bounds = glyphs
+            .iter()
+            .fold(line_bounds, |bounds, (glyph_canvas, origin)| {
+                RectI::from_points(
+                    bounds.origin().min(*origin),
+                    bounds.lower_right().max(*origin + glyph_canvas.size),
+                )
+            });
+
+        if canvas_bounds.width() <= 0 || canvas_bounds.height() <= 0 {
+            return Ok(Canvas::new(
+                canvas_bounds.size().max(Vector2I::default()),
+                format,
+            ));
+        }
+        let mut canvas = Canvas::try_new(canvas_bounds.size(), format)?;
+        for (glyph_canvas, origin) in glyphs {
+            canvas.blend_max_from(origin - canvas_bounds.origin(), &glyph_canvas);
+        }
+        Ok(canvas)
+    }
+
     /// Get font fallback results for the given text and locale.
     ///
     /// The `locale` argument is a language tag such as `"en-US"` or `"zh-Hans-CN"`.
@@ -244,6 +978,447 @@ pub trait Loader: Clone + Sized {
 
     /// Returns the OpenType font table with the given tag, if the table exists.
     fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>>;
+
+    /// Returns the fill rule that this font's glyph outlines are designed to be filled with.
+    ///
+    /// Both TrueType (`glyf`) and PostScript (CFF) outlines use the nonzero winding rule, so
+    /// contours may overlap and are still filled correctly; this method exists so that
+    /// tessellators don't have to assume.
+    #[inline]
+    fn fill_rule(&self) -> FillRule {
+        FillRule::NonZero
+    }
+
+    /// Returns the format of this font's glyph shapes, judged by which tables are present.
+    ///
+    /// Fonts with more than one kind of outline table report the first of `glyf`, `CFF2`, and
+    /// `CFF `. Fonts with no outlines are `Bitmap` if they have bitmap strikes (see
+    /// `available_sizes()`) and `None` otherwise.
+    fn outline_format(&self) -> OutlineFormat {
+        let outline_formats = [
+            (tables::TAG_GLYF, OutlineFormat::TrueType),
+            (tables::TAG_CFF2, OutlineFormat::Cff2),
+            (tables::TAG_CFF, OutlineFormat::Cff),
+        ];
+        for &(table_tag, outline_format) in &outline_formats {
+            if self.load_font_table(table_tag).is_some() {
+                return outline_format;
+            }
+        }
+        if self.available_sizes().is_empty() {
+            OutlineFormat::None
+        } else {
+            OutlineFormat::Bitmap
+        }
+    }
+
+    /// Returns true if the font marks the glyph as containing overlapping contours or components,
+    /// via the TrueType `OVERLAP_SIMPLE` or `OVERLAP_COMPOUND` flags.
+    ///
+    /// Variable fonts set these flags so that rasterizers know to avoid overlap artifacts. A false
+    /// result does not guarantee that the outline is free of overlaps: fonts are not required to
+    /// set the flags, and CFF fonts have no equivalent.
+    fn glyph_has_overlap_flag(&self, glyph_id: u32) -> bool {
+        self.load_font_table(tables::TAG_HEAD)
+            .zip(self.load_font_table(tables::TAG_LOCA))
+            .zip(self.load_font_table(tables::TAG_GLYF))
+            .and_then(|((head, loca), glyf)| {
+                tables::glyf_glyph_data(&head, &loca, &glyf, glyph_id)
+                    .map(tables::glyf_overlap_flag)
+            })
+            .unwrap_or(false)
+    }
+
+    /// Returns true if the glyph draws nothing, like the space glyph, so that rasterizing it can
+    /// be skipped.
+    ///
+    /// For TrueType fonts this is a cheap check for a zero-length `loca` entry. For other fonts,
+    /// the glyph is empty if it has no outline contours and no bitmap extent. Glyph IDs that
+    /// don't exist in the font are reported as empty.
+    fn glyph_is_empty(&self, glyph_id: u32) -> bool {
+        if let (Some(head), Some(loca)) = (
+            self.load_font_table(tables::TAG_HEAD),
+            self.load_font_table(tables::TAG_LOCA),
+        ) {
+            return tables::loca_glyph_range(&head, &loca, glyph_id)
+                .map_or(true, |range| range.is_empty());
+        }
+
+        let mut contour_detector = ContourDetector {
+            found_contour: false,
+        };
+        if self
+            .outline(glyph_id, HintingOptions::None, &mut contour_detector)
+            .is_err()
+        {
+            return true;
+        }
+        if contour_detector.found_contour {
+            return false;
+        }
+        // Bitmap glyphs have no outline, but do have an extent.
+        match self.typographic_bounds(glyph_id) {
+            Ok(bounds) => bounds.width() <= 0.0 || bounds.height() <= 0.0,
+            Err(_) => true,
+        }
+    }
+
+    /// Returns the components of a composite glyph: the glyphs it references and the transform
+    /// applied to each.
+    ///
+    /// Returns `None` for simple glyphs, for glyphs that don't exist, and for fonts without
+    /// TrueType (`glyf`) outlines.
+    fn glyph_components(&self, glyph_id: u32) -> Option<Vec<GlyphComponent>> {
+        let head = self.load_font_table(tables::TAG_HEAD)?;
+        let loca = self.load_font_table(tables::TAG_LOCA)?;
+        let glyf = self.load_font_table(tables::TAG_GLYF)?;
+        let glyph_data = tables::glyf_glyph_data(&head, &loca, &glyf, glyph_id)?;
+        tables::glyf_composite_components(glyph_data)
+    }
+
+    /// Returns the points of each contour of a TrueType glyph as stored in the `glyf` table,
+    /// including whether each point is on or off the curve.
+    ///
+    /// Unlike `outline()`, no curves are constructed and no implied on-curve points are added, so
+    /// this is suited to tools that analyze how a font was drawn. Returns `None` for composite
+    /// glyphs (see `glyph_components()`), for glyphs that don't exist, and for fonts without
+    /// TrueType outlines, since CFF outlines have no quadratic point model. Glyphs without an
+    /// outline, such as the space, have no contours.
+    fn glyph_raw_points(&self, glyph_id: u32) -> Option<Vec<ContourPoints>> {
+        let head = self.load_font_table(tables::TAG_HEAD)?;
+        let loca = self.load_font_table(tables::TAG_LOCA)?;
+        let glyf = self.load_font_table(tables::TAG_GLYF)?;
+        let glyph_data = tables::glyf_glyph_data(&head, &loca, &glyf, glyph_id)?;
+        tables::glyf_simple_glyph_points(glyph_data)
+    }
+
+    /// Converts user-space variation axis values, such as `wght=550`, to the normalized
+    /// coordinates that a variable font's deltas are blended with.
+    ///
+    /// `coordinates` pairs OpenType axis tags with values; axes not mentioned stay at their
+    /// defaults. The result has one entry per axis, in the order of the font's `fvar` table.
+    /// Values are clamped to each axis's range, normalized to [-1, 1], and then run through the
+    /// `avar` segment maps if the font has them, so that the axis responds nonlinearly as its
+    /// designer intended. Any custom blending code should use these coordinates rather than
+    /// normalizing linearly itself. Returns an empty vector for fonts that aren't variable.
+    fn normalized_variation_coordinates(&self, coordinates: &[(u32, f32)]) -> Vec<f32> {
+        let axes = match self.load_font_table(tables::TAG_FVAR) {
+            Some(fvar) => tables::fvar_axes(&fvar),
+            None => return vec![],
+        };
+        let segment_maps = self
+            .load_font_table(tables::TAG_AVAR)
+            .map(|avar| tables::avar_segment_maps(&avar))
+            .unwrap_or_default();
+        axes.iter()
+            .enumerate()
+            .map(|(axis_index, axis)| {
+                let value = coordinates
+                    .iter()
+                    .rev()
+                    .find(|&&(tag, _)| tag == axis.tag)
+                    .map_or(axis.default_value, |&(_, value)| value);
+                let normalized = axis.normalize(value);
+                match segment_maps.get(axis_index) {
+                    Some(segment_map) => tables::apply_avar_segment_map(segment_map, normalized),
+                    None => normalized,
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the font-wide metrics of a variable font at the given user-space variation axis
+    /// values, such as `wght=700`.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The deltas in the
+    /// font's `MVAR` table are applied to the ascent, descent, line gap, underline position and
+    /// thickness, cap height, and x-height of `metrics()`, which describes the default instance.
+    /// Fonts without an `MVAR` table return `metrics()` unchanged.
+    fn metrics_at_variation(&self, coordinates: &[(u32, f32)]) -> Metrics {
+        let mut metrics = self.metrics();
+        let mvar = match self.load_font_table(tables::TAG_MVAR) {
+            Some(mvar) => mvar,
+            None => return metrics,
+        };
+        let normalized_coordinates = self.normalized_variation_coordinates(coordinates);
+        for (tag, delta) in tables::mvar_deltas(&mvar, &normalized_coordinates) {
+            let value = match tag {
+                tables::MVAR_TAG_HORIZONTAL_ASCENDER => &mut metrics.ascent,
+                tables::MVAR_TAG_HORIZONTAL_DESCENDER => &mut metrics.descent,
+                tables::MVAR_TAG_HORIZONTAL_LINE_GAP => &mut metrics.line_gap,
+                tables::MVAR_TAG_UNDERLINE_OFFSET => &mut metrics.underline_position,
+                tables::MVAR_TAG_UNDERLINE_SIZE => &mut metrics.underline_thickness,
+                tables::MVAR_TAG_CAP_HEIGHT => &mut metrics.cap_height,
+                tables::MVAR_TAG_X_HEIGHT => &mut metrics.x_height,
+                _ => continue,
+            };
+            *value += delta;
+        }
+        metrics
+    }
+
+    /// Returns the advance of the glyph with the given ID at the given user-space variation axis
+    /// values, such as `wght=700`, in font units.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The delta in the
+    /// font's `HVAR` table is added to the horizontal advance from `advance()`, which describes
+    /// the default instance; layout of variable text should use this instead. Fonts without an
+    /// `HVAR` table return `advance()` unchanged.
+    fn advance_at_variation(
+        &self,
+        glyph_id: u32,
+        coordinates: &[(u32, f32)],
+    ) -> Result<Vector2F, GlyphLoadingError> {
+        let advance = self.advance(glyph_id)?;
+        let hvar = match self.load_font_table(tables::TAG_HVAR) {
+            Some(hvar) => hvar,
+            None => return Ok(advance),
+        };
+        let normalized_coordinates = self.normalized_variation_coordinates(coordinates);
+        let delta = tables::hvar_advance_delta(&hvar, glyph_id, &normalized_coordinates);
+        Ok(advance + Vector2F::new(delta.unwrap_or(0.0), 0.0))
+    }
+
+    /// Builds a new font containing only the given glyphs, for embedding in documents such as
+    /// PDFs.
+    ///
+    /// The subset also contains `.notdef` and every glyph that the requested composite glyphs are
+    /// built from. Glyphs are renumbered in ascending order of their original IDs, with `.notdef`
+    /// staying at 0; the `cmap`, `hmtx`, `loca`, and `glyf` tables are rebuilt to match, and glyph
+    /// names and layout tables are dropped. Glyph IDs outside the font are ignored.
+    ///
+    /// Only TrueType (`glyf`) outlines are supported; fonts with CFF outlines return
+    /// `FontLoadingError::UnknownFormat`.
+    fn subset(&self, glyph_ids: &[u32]) -> Result<Vec<u8>, FontLoadingError> {
+        subset::subset_truetype(|table_tag| self.load_font_table(table_tag), glyph_ids)
+    }
+
+    /// Returns the ten PANOSE classification digits from the `OS/2` table.
+    ///
+    /// See the `classification` module for help decoding them. Returns `None` if the font has no
+    /// `OS/2` table or the table is too short.
+    fn panose(&self) -> Option<[u8; 10]> {
+        tables::os2_panose(&self.load_font_table(tables::TAG_OS2)?)
+    }
+
+    /// Returns the global hinting parameters from the private dict of a `CFF ` table: the
+    /// alignment zones and dominant stem widths that CFF hinters snap to.
+    ///
+    /// For CID-keyed fonts, the parameters of the first font dict are returned. Returns `None`
+    /// for fonts without a `CFF ` table, including TrueType and `CFF2` fonts, or if the table is
+    /// malformed.
+    fn cff_hint_params(&self) -> Option<CffHintParams> {
+        tables::cff_hint_params(&self.load_font_table(tables::TAG_CFF)?)
+    }
+
+    /// Returns the paint graph of a color glyph from the `COLR` table, with colors taken from
+    /// palette `palette_index` of the `CPAL` table.
+    ///
+    /// `COLR` version 1 glyphs can use gradients, transforms, and compositing; glyphs that only
+    /// have a version 0 layer list are returned as a `Paint::Layers` of solid-colored glyphs.
+    /// Palette 0 is the default palette. Returns `None` if the glyph isn't a color glyph, if the
+    /// palette doesn't exist, or if the font's color tables are malformed.
+    fn glyph_paint(&self, glyph_id: u32, palette_index: u16) -> Option<Paint> {
+        let colr = self.load_font_table(tables::TAG_COLR)?;
+        let cpal = self.load_font_table(tables::TAG_CPAL)?;
+        let palette = tables::cpal_palette(&cpal, palette_index)?;
+        tables::colr_glyph_paint(&colr, &palette, glyph_id)
+    }
+
+    /// Returns the metrics for laying out lines of text in this font at `point_size`.
+    ///
+    /// If the `OS/2` table sets the `USE_TYPO_METRICS` flag, its typographic ascender, descender,
+    /// and line gap are used; otherwise, the ascent, descent, and line gap come from `metrics()`.
+    fn line_metrics(&self, point_size: f32) -> LineMetrics {
+        let mut metrics = self.metrics();
+        if let Some((ascent, descent, line_gap)) = self
+            .load_font_table(tables::TAG_OS2)
+            .and_then(|os2| tables::os2_use_typo_metrics(&os2))
+        {
+            metrics.ascent = ascent as f32;
+            metrics.descent = descent as f32;
+            metrics.line_gap = line_gap as f32;
+        }
+        LineMetrics::from_metrics(&metrics, point_size)
+    }
+
+    /// Returns the height of `baseline` above the font's origin when setting text in `script`
+    /// horizontally, in font units, from the `BASE` table.
+    ///
+    /// This is how lines mixing scripts with different baselines, such as Latin and CJK, are
+    /// aligned. Returns `None` if the font has no `BASE` table or the table does not define that
+    /// baseline for the script.
+    fn baseline(&self, script: ScriptTag, baseline: BaselineTag) -> Option<i16> {
+        let base = self.load_font_table(tables::TAG_BASE)?;
+        tables::base_coordinate(&base, script, baseline)
+    }
+
+    /// Returns the legacy code pages that the font claims to be functional for, from the `OS/2`
+    /// table.
+    ///
+    /// Returns `None` if the font has no `OS/2` table or the table predates these fields.
+    fn code_page_ranges(&self) -> Option<CodePageRanges> {
+        let os2 = self.load_font_table(tables::TAG_OS2)?;
+        tables::os2_code_page_ranges(&os2).map(CodePageRanges::from_bits_retain)
+    }
+
+    /// Returns what the font's license allows documents that embed it to do, from the `fsType`
+    /// field of the `OS/2` table.
+    ///
+    /// Fonts without an `OS/2` table are treated as unrestricted.
+    fn embedding_restrictions(&self) -> EmbeddingRestrictions {
+        match self
+            .load_font_table(tables::TAG_OS2)
+            .and_then(|os2| tables::os2_fs_type(&os2))
+        {
+            Some(fs_type) => EmbeddingRestrictions::from_fs_type(fs_type),
+            None => EmbeddingRestrictions::default(),
+        }
+    }
+
+    /// Returns true if the font has a `DSIG` table with at least one signature in it.
+    ///
+    /// The signature is not verified. Many fonts carry a `DSIG` table with no signatures, added
+    /// only because old versions of Windows required one; those fonts are not signed.
+    fn is_signed(&self) -> bool {
+        self.load_font_table(tables::TAG_DSIG)
+            .and_then(|dsig| tables::dsig_num_signatures(&dsig))
+            .is_some_and(|num_signatures| num_signatures > 0)
+    }
+
+    /// Returns the slant of the font's upright strokes, in degrees counterclockwise from the
+    /// vertical, from the `post` table.
+    ///
+    /// Italic and oblique fonts that lean right have negative angles, and upright fonts 0. Fonts
+    /// without a `post` table report 0. Together with `properties().style`, this tells whether
+    /// an italic needs to be synthesized, and the angle to shear the upright face by if so.
+    fn italic_angle(&self) -> f32 {
+        self.load_font_table(tables::TAG_POST)
+            .and_then(|post| tables::post_italic_angle(&post))
+            .unwrap_or(0.0)
+    }
+
+    /// Returns the name of every glyph, indexed by glyph ID, from the `post` table.
+    ///
+    /// Version 1.0 tables use the standard Macintosh glyph names and version 2.0 tables list names
+    /// explicitly. Glyphs without a name, including every glyph of a font with a version 3.0
+    /// table or no table at all, are `None`; fonts with CFF outlines usually fall in this
+    /// category. This is much faster than looking names up one glyph at a time.
+    fn glyph_names(&self) -> Vec<Option<String>> {
+        let num_glyphs = self.glyph_count().min(u16::MAX as u32) as u16;
+        match self.load_font_table(tables::TAG_POST) {
+            Some(post) => tables::post_glyph_names(&post, num_glyphs),
+            None => vec![None; num_glyphs as usize],
+        }
+    }
+
+    /// Returns the horizontal advance of every glyph, indexed by glyph ID, straight from the
+    /// `hmtx` table.
+    ///
+    /// Values are in font units. This is much faster than calling `advance()` for each glyph.
+    /// Returns an empty vector if the font has no `hmtx` table.
+    fn all_horizontal_advances(&self) -> Vec<u16> {
+        load_long_metric_advances(self, tables::TAG_HHEA, tables::TAG_HMTX)
+    }
+
+    /// Returns the vertical advance of every glyph, indexed by glyph ID, straight from the
+    /// `vmtx` table.
+    ///
+    /// Values are in font units. Returns an empty vector if the font has no vertical metrics.
+    fn all_vertical_advances(&self) -> Vec<u16> {
+        load_long_metric_advances(self, tables::TAG_VHEA, tables::TAG_VMTX)
+    }
+
+    /// Returns the advance width of a glyph at `ppem` pixels per em, in whole pixels, as recorded
+    /// in the `hdmx` table.
+    ///
+    /// TrueType fonts may tabulate the hinted advance of every glyph at common sizes so that
+    /// layout on the pixel grid can match what the hinter produces without running it. Returns
+    /// `None` if the font has no `hdmx` table or the table has no record for `ppem`.
+    fn device_advance(&self, glyph_id: u32, ppem: u8) -> Option<u8> {
+        let hdmx = self.load_font_table(tables::TAG_HDMX)?;
+        let num_glyphs = tables::maxp_num_glyphs(&self.load_font_table(tables::TAG_MAXP)?)?;
+        tables::hdmx_device_advance(&hdmx, num_glyphs, glyph_id, ppem)
+    }
+
+    /// Returns the sizes, in pixels per em, of the bitmap strikes embedded in this font, sorted
+    /// in ascending order.
+    ///
+    /// Bitmap-only fonts render well only at these sizes, so callers may want to snap the
+    /// requested size to one of them. Returns an empty vector for purely scalable fonts.
+    fn available_sizes(&self) -> Vec<f32> {
+        let mut sizes = vec![];
+        for &table_tag in &[tables::TAG_EBLC, tables::TAG_CBLC, tables::TAG_BLOC] {
+            if let Some(table) = self.load_font_table(table_tag) {
+                sizes.extend(tables::bitmap_location_strike_sizes(&table));
+            }
+        }
+        if let Some(sbix) = self.load_font_table(tables::TAG_SBIX) {
+            sizes.extend(tables::sbix_strike_sizes(&sbix));
+        }
+        sizes.sort_unstable();
+        sizes.dedup();
+        sizes.into_iter().map(f32::from).collect()
+    }
+}
+
+// The most glyphs that `outlines_equal()` compares.
+const MAX_COMPARED_GLYPHS: u32 = 1024;
+
+// Returns the IDs of the glyphs that `outlines_equal()` compares, in ascending order.
+fn sampled_glyph_ids(glyph_count: u32) -> impl Iterator<Item = u32> {
+    let sample_count = glyph_count.min(MAX_COMPARED_GLYPHS);
+    (0..sample_count).map(move |index| {
+        if sample_count == glyph_count {
+            index
+        } else {
+            (index as u64 * (glyph_count - 1) as u64 / (sample_count - 1) as u64) as u32
+        }
+    })
+}
+
+fn load_outline<L>(loader: &L, glyph_id: u32) -> Result<Outline, GlyphLoadingError>
+where
+    L: Loader,
+{
+    let mut outline_builder = OutlineBuilder::new();
+    loader.outline(glyph_id, HintingOptions::None, &mut outline_builder)?;
+    Ok(outline_builder.into_outline())
+}
+
+fn outlines_approximately_equal(outline: &Outline, other: &Outline, tolerance: f32) -> bool {
+    outline.contours.len() == other.contours.len()
+        && outline
+            .contours
+            .iter()
+            .zip(other.contours.iter())
+            .all(|(contour, other_contour)| {
+                contour.flags == other_contour.flags
+                    && contour.positions.len() == other_contour.positions.len()
+                    && contour
+                        .positions
+                        .iter()
+                        .zip(other_contour.positions.iter())
+                        .all(|(&position, &other_position)| {
+                            let difference = position - other_position;
+                            difference.x().abs() <= tolerance && difference.y().abs() <= tolerance
+                        })
+            })
+}
+
+/// An identity for a font by content, not object, for keying hash maps and caches.
+///
+/// Fonts have equal keys if they have the same PostScript name, units per em, and number of
+/// glyphs, and their `head` tables record the same checksum for the whole font (or neither has a
+/// `head` table). Loading the same font twice, whether from the same file, a copy of it, or its
+/// bytes in memory, gives equal keys, while different versions of a font almost always differ
+/// in checksum. Loader settings, such as those that affect hinting, aren't part of the key.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct FontKey {
+    postscript_name: Option<String>,
+    units_per_em: u32,
+    glyph_count: u32,
+    checksum: Option<u32>,
 }
 
 /// The result of a fallback query.
@@ -264,3 +1439,342 @@ pub struct FallbackFont<Font> {
     pub scale: f32,
     // TODO: add font simulation data
 }
+
+// Returns the pixel boundaries of a glyph's typographic bounds at the given size and transform,
+// with the origin at the top left.
+pub(crate) fn typographic_raster_bounds<L>(
+    loader: &L,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+) -> Result<RectI, GlyphLoadingError>
+where
+    L: Loader,
+{
+    let typographic_bounds = loader.typographic_bounds(glyph_id)?;
+    let typographic_raster_bounds =
+        typographic_bounds * (point_size / loader.metrics().units_per_em as f32);
+
+    // Translate the origin to "origin is top left" coordinate system.
+    let new_origin = Vector2F::new(
+        typographic_raster_bounds.origin_x(),
+        -typographic_raster_bounds.origin_y() - typographic_raster_bounds.height(),
+    );
+    let typographic_raster_bounds = RectF::new(new_origin, typographic_raster_bounds.size());
+    Ok((transform * typographic_raster_bounds).round_out().to_i32())
+}
+
+// Returns the pixels that rasterizing a glyph to a `format` canvas may touch. Rasterizers may touch
+// a pixel or so outside the computed raster bounds, and distance fields extend past the outline
+// by their spread.
+fn touched_bounds<L>(
+    loader: &L,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+    hinting_options: HintingOptions,
+    rasterization_options: RasterizationOptions,
+    format: Format,
+) -> Result<RectI, GlyphLoadingError>
+where
+    L: Loader,
+{
+    let raster_bounds = loader.raster_bounds(
+        glyph_id,
+        point_size,
+        transform,
+        hinting_options,
+        rasterization_options,
+    )?;
+    let outset = match format {
+        Format::Sdf => 1 + DEFAULT_SDF_SPREAD.ceil() as i32,
+        _ => 1,
+    };
+    Ok(raster_bounds.contract(Vector2I::splat(-outset)))
+}
+
+// The canvas format that holds a glyph's coverage for the given rasterization options.
+fn coverage_format(rasterization_options: RasterizationOptions) -> Format {
+    match rasterization_options {
+        RasterizationOptions::SubpixelAa => Format::Rgb24,
+        _ => Format::A8,
+    }
+}
+
+// Returns true if the font has an `sbix` or `CBDT` color bitmap for the glyph.
+fn has_color_bitmap<L>(loader: &L, glyph_id: u32) -> bool
+where
+    L: Loader,
+{
+    loader
+        .load_font_table(tables::TAG_SBIX)
+        .is_some_and(|sbix| tables::sbix_has_glyph(&sbix, glyph_id))
+        || loader
+            .load_font_table(tables::TAG_CBLC)
+            .is_some_and(|cblc| tables::bitmap_location_has_glyph(&cblc, glyph_id))
+}
+
+// Flattens a color glyph made only of solid-colored glyph layers into its layers, from bottom to
+// top. Returns `None` if the paint graph uses anything else.
+fn solid_color_layers(paint: &Paint) -> Option<Vec<(u32, PaintColor)>> {
+    match *paint {
+        Paint::Layers(ref layers) => {
+            let mut solid_layers = vec![];
+            for layer in layers {
+                solid_layers.extend(solid_color_layers(layer)?);
+            }
+            Some(solid_layers)
+        }
+        Paint::Glyph {
+            glyph_id,
+            ref paint,
+        } => match **paint {
+            Paint::Solid(color) => Some(vec![(glyph_id, color)]),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+// Returns the color that a paint color stands for when text is drawn in `foreground`.
+fn resolve_paint_color(paint_color: PaintColor, foreground: Color) -> Color {
+    match paint_color {
+        PaintColor::Color(color) => color,
+        PaintColor::Foreground { alpha } => Color {
+            a: (foreground.a as f32 * alpha.clamp(0.0, 1.0)).round() as u8,
+            ..foreground
+        },
+    }
+}
+
+// Rasterizes a glyph into a new `format` canvas that just fits it, returning the canvas and the
+// position of its top left corner in the space that `transform` maps to. Glyphs that cover no
+// pixels produce an empty canvas.
+pub(crate) fn rasterize_glyph_to_fit<L>(
+    loader: &L,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+    hinting_options: HintingOptions,
+    rasterization_options: RasterizationOptions,
+    format: Format,
+) -> Result<(Canvas, Vector2I), GlyphLoadingError>
+where
+    L: Loader,
+{
+    let raster_bounds = touched_bounds(
+        loader,
+        glyph_id,
+        point_size,
+        transform,
+        hinting_options,
+        rasterization_options,
+        format,
+    )?;
+    if raster_bounds.width() <= 0 || raster_bounds.height() <= 0 {
+        return Ok((
+            Canvas::new(Vector2I::default(), format),
+            raster_bounds.origin(),
+        ));
+    }
+
+    let mut canvas = Canvas::try_new(raster_bounds.size(), format)?;
+    loader.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        point_size,
+        Transform2F::from_translation(-raster_bounds.origin().to_f32()) * transform,
+        hinting_options,
+        rasterization_options,
+    )?;
+    Ok((canvas, raster_bounds.origin()))
+}
+
+/// Rasterizes a glyph with font-kit's own rasterizer, as `RasterizationOptions::StableGrayscaleAa`
+/// requests, replacing the pixels within the glyph's raster bounds.
+///
+/// Loaders call this from `rasterize_glyph()` instead of the platform rasterizer.
+pub(crate) fn rasterize_glyph_stable<L>(
+    loader: &L,
+    canvas: &mut Canvas,
+    glyph_id: u32,
+    point_size: f32,
+    transform: Transform2F,
+) -> Result<(), GlyphLoadingError>
+where
+    L: Loader,
+{
+    let raster_bounds = typographic_raster_bounds(loader, glyph_id, point_size, transform)?;
+    let scale = point_size / loader.metrics().units_per_em as f32;
+    let mut collector = SegmentCollector::new(
+        Transform2F::from_translation(-raster_bounds.origin().to_f32())
+            * transform
+            * Transform2F::from_scale(Vector2F::new(scale, -scale)),
+    );
+    loader.outline(glyph_id, HintingOptions::None, &mut collector)?;
+    coverage::render(
+        canvas,
+        collector,
+        raster_bounds.origin(),
+        raster_bounds.size(),
+    );
+    Ok(())
+}
+
+/// Looks up a character that the platform didn't map directly in the font's format 12 and 13
+/// `cmap` subtables, or in its Mac Roman subtable if it has no Unicode one.
+///
+/// Loaders fall back to this when the platform doesn't map a character beyond the Basic
+/// Multilingual Plane, which happens when the platform chose a BMP-only subtable, a character
+/// that only a format 13 subtable covers, as in last-resort fonts, or any character of a legacy
+/// font whose only subtable is Mac Roman.
+pub(crate) fn glyph_for_unmapped_char<L>(loader: &L, character: char) -> Option<u32>
+where
+    L: Loader,
+{
+    let cmap = loader.load_font_table(tables::TAG_CMAP)?;
+    if (character as u32) > 0xffff {
+        if let Some(glyph_id) = tables::cmap_format_12_glyph_id(&cmap, character as u32) {
+            return Some(glyph_id);
+        }
+    }
+    if let Some(glyph_id) = tables::cmap_format_13_glyph_id(&cmap, character as u32) {
+        return Some(glyph_id);
+    }
+    if tables::cmap_unicode_subtable_offset(&cmap).is_some() {
+        return None;
+    }
+    tables::cmap_mac_roman_glyph_id(&cmap, character)
+}
+
+// Curves are never split into more lines than this, however small the tolerance.
+const MAX_FLATTENED_CURVE_LINES: f32 = 256.0;
+
+// An outline sink that flattens each contour into a polygon, splitting curves into enough equal
+// steps of their parameter to stay within a tolerance.
+struct ContourFlattener {
+    tolerance: f32,
+    polygons: Vec<Vec<Vector2F>>,
+    current_polygon: Vec<Vector2F>,
+}
+
+impl ContourFlattener {
+    fn new(tolerance: f32) -> ContourFlattener {
+        ContourFlattener {
+            tolerance,
+            polygons: vec![],
+            current_polygon: vec![],
+        }
+    }
+
+    fn current_point(&self) -> Vector2F {
+        self.current_polygon.last().cloned().unwrap_or_default()
+    }
+
+    // Splits a curve whose second derivative is at most `max_second_derivative` long into lines.
+    // The distance between such a curve and a chord spanning `1 / n` of its parameter is at most
+    // `max_second_derivative / (8 * n^2)`.
+    fn push_curve<F>(&mut self, max_second_derivative: f32, to: Vector2F, evaluate: F)
+    where
+        F: Fn(f32) -> Vector2F,
+    {
+        let line_count = (max_second_derivative / (8.0 * self.tolerance))
+            .sqrt()
+            .ceil()
+            .clamp(1.0, MAX_FLATTENED_CURVE_LINES);
+        // A tolerance of zero or NaN makes the count NaN, which casts to zero.
+        let line_count = if line_count.is_nan() {
+            MAX_FLATTENED_CURVE_LINES as u32
+        } else {
+            line_count as u32
+        };
+        for step in 1..line_count {
+            self.current_polygon
+                .push(evaluate(step as f32 / line_count as f32));
+        }
+        self.current_polygon.push(to);
+    }
+}
+
+impl OutlineSink for ContourFlattener {
+    fn move_to(&mut self, to: Vector2F) {
+        self.close();
+        self.current_polygon.push(to);
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.current_polygon.push(to);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let from = self.current_point();
+        let max_second_derivative = ((from - ctrl * 2.0 + to) * 2.0).length();
+        self.push_curve(max_second_derivative, to, |t| {
+            let u = 1.0 - t;
+            from * (u * u) + ctrl * (2.0 * u * t) + to * (t * t)
+        });
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let from = self.current_point();
+        let (ctrl0, ctrl1) = (ctrl.from(), ctrl.to());
+        let max_second_derivative = f32::max(
+            (from - ctrl0 * 2.0 + ctrl1).length(),
+            (ctrl0 - ctrl1 * 2.0 + to).length(),
+        ) * 6.0;
+        self.push_curve(max_second_derivative, to, |t| {
+            let u = 1.0 - t;
+            from * (u * u * u)
+                + ctrl0 * (3.0 * u * u * t)
+                + ctrl1 * (3.0 * u * t * t)
+                + to * (t * t * t)
+        });
+    }
+
+    fn close(&mut self) {
+        let mut polygon = std::mem::take(&mut self.current_polygon);
+        if polygon.len() > 1 && polygon.first() == polygon.last() {
+            polygon.pop();
+        }
+        if polygon.len() > 2 {
+            self.polygons.push(polygon);
+        }
+    }
+}
+
+// An outline sink that only records whether any contour was started.
+struct ContourDetector {
+    found_contour: bool,
+}
+
+impl OutlineSink for ContourDetector {
+    fn move_to(&mut self, _: Vector2F) {
+        self.found_contour = true
+    }
+    fn line_to(&mut self, _: Vector2F) {}
+    fn quadratic_curve_to(&mut self, _: Vector2F, _: Vector2F) {}
+    fn cubic_curve_to(&mut self, _: LineSegment2F, _: Vector2F) {}
+    fn close(&mut self) {}
+}
+
+fn load_long_metric_advances<L>(loader: &L, header_tag: u32, metrics_tag: u32) -> Vec<u16>
+where
+    L: Loader,
+{
+    let num_glyphs = match loader
+        .load_font_table(tables::TAG_MAXP)
+        .and_then(|maxp| tables::maxp_num_glyphs(&maxp))
+    {
+        Some(num_glyphs) => num_glyphs,
+        None => return vec![],
+    };
+    match (
+        loader.load_font_table(header_tag),
+        loader.load_font_table(metrics_tag),
+    ) {
+        (Some(header), Some(metrics)) => {
+            tables::long_metric_advances(&header, &metrics, num_glyphs)
+        }
+        _ => vec![],
+    }
+}
@@ -26,9 +26,8 @@ use log::warn;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
 use pathfinder_geometry::transform2d::Transform2F;
-use pathfinder_geometry::vector::Vector2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_simd::default::F32x4;
-use std::cmp::Ordering;
 use std::f32;
 use std::fmt::{self, Debug, Formatter};
 use std::fs::File;
@@ -37,16 +36,23 @@ use std::ops::Deref;
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::canvas::{Canvas, Format, RasterizationOptions};
+use crate::canvas::{
+    Canvas, CoverageStats, Format, GlyphKey, RasterizationOptions, RasterizeContext,
+    DEFAULT_SDF_SPREAD,
+};
+use crate::classification::{CodePageRanges, EmbeddingRestrictions};
+use crate::color::{Color, Paint};
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::loader::{FallbackResult, Loader};
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
-use crate::properties::{Properties, Stretch, Style, Weight};
-use crate::utils;
+use crate::hinting::{CffHintParams, HintingOptions};
+use crate::loader::{self, FallbackResult, FontKey, Loader};
+use crate::metrics::{BaselineTag, LineMetrics, Metrics, ScriptTag};
+use crate::outline::{
+    ContourPoints, FillRule, GlyphComponent, OutlineFormat, OutlineSegments, OutlineSink,
+};
+use crate::properties::{Properties, PropertyMapping, Style};
+use crate::utils::{self, DescriptionCache};
 
 const TTC_TAG: [u8; 4] = [b't', b't', b'c', b'f'];
 const OTTO_TAG: [u8; 4] = [b'O', b'T', b'T', b'O'];
@@ -58,8 +64,6 @@ const SFNT_HEX: u32 = 0x73666e74; // 'sfnt'
 #[allow(non_upper_case_globals)]
 const kCGImageAlphaOnly: u32 = 7;
 
-pub(crate) static FONT_WEIGHT_MAPPING: [f32; 9] = [-0.7, -0.5, -0.23, 0.0, 0.2, 0.3, 0.4, 0.6, 0.8];
-
 /// Core Text's representation of a font.
 pub type NativeFont = CTFont;
 
@@ -68,6 +72,7 @@ pub type NativeFont = CTFont;
 pub struct Font {
     core_text_font: CTFont,
     font_data: FontData,
+    description_cache: DescriptionCache,
 }
 
 impl Font {
@@ -99,6 +104,7 @@ impl Font {
         Ok(Font {
             core_text_font,
             font_data: FontData::Memory(font_data),
+            description_cache: DescriptionCache::default(),
         })
     }
 
@@ -122,14 +128,49 @@ impl Font {
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// The Core Text font is retained (its reference count is incremented), so the caller keeps
+    /// ownership of `core_text_font`.
+    ///
+    /// # Safety
+    ///
+    /// `core_text_font` must wrap a valid `CTFontRef`.
     pub unsafe fn from_native_font(core_text_font: &NativeFont) -> Font {
         Font::from_core_text_font_no_path(core_text_font.clone())
     }
+
+    /// Creates a font from a Core Text font handle, taking over the caller's reference to it.
+    ///
+    /// The `CTFont` is released when the returned font (and all of its clones) are dropped. If the
+    /// font is backed by a file on disk, the file is read so that `copy_font_data()` and
+    /// `handle()` work.
+    ///
+    /// This function is only available on the Core Text backend.
+    pub fn from_core_text_font(core_text_font: CTFont) -> Font {
+        let font_data = core_text_font
+            .copy_descriptor()
+            .font_path()
+            .and_then(|path| File::open(path).ok())
+            .and_then(|mut file| utils::slurp_file(&mut file).ok())
+            .map(|font_data| FontData::Memory(Arc::new(font_data)))
+            .unwrap_or(FontData::Unavailable);
+        Font {
+            core_text_font,
+            font_data,
+            description_cache: DescriptionCache::default(),
+        }
+    }
+
     /// Creates a font from a native API handle, without performing a lookup on the disk.
+    ///
+    /// # Safety
+    ///
+    /// `core_text_font` must wrap a valid `CTFontRef`.
     pub unsafe fn from_core_text_font_no_path(core_text_font: NativeFont) -> Font {
         Font {
             core_text_font,
             font_data: FontData::Unavailable,
+            description_cache: DescriptionCache::default(),
         }
     }
 
@@ -146,6 +187,9 @@ impl Font {
     }
 
     /// Loads the font pointed to by a handle.
+    ///
+    /// If a path handle fails to load, the error is wrapped in `FontLoadingError::InFile` along
+    /// with the path and font index.
     #[inline]
     pub fn from_handle(handle: &Handle) -> Result<Self, FontLoadingError> {
         <Self as Loader>::from_handle(handle)
@@ -184,6 +228,9 @@ impl Font {
     }
 
     /// Returns the wrapped native font handle.
+    ///
+    /// The returned `CTFont` is retained (its reference count is incremented) and is released
+    /// when it is dropped, so it may outlive this font.
     #[inline]
     pub fn native_font(&self) -> NativeFont {
         self.core_text_font.clone()
@@ -192,19 +239,28 @@ impl Font {
     /// Returns the PostScript name of the font. This should be globally unique.
     #[inline]
     pub fn postscript_name(&self) -> Option<String> {
-        Some(self.core_text_font.postscript_name())
+        self.description_cache
+            .postscript_name
+            .get_or_init(|| Some(self.core_text_font.postscript_name()))
+            .clone()
     }
 
     /// Returns the full name of the font (also known as "display name" on macOS).
     #[inline]
     pub fn full_name(&self) -> String {
-        self.core_text_font.display_name()
+        self.description_cache
+            .full_name
+            .get_or_init(|| self.core_text_font.display_name())
+            .clone()
     }
 
     /// Returns the name of the font family.
     #[inline]
     pub fn family_name(&self) -> String {
-        self.core_text_font.family_name()
+        self.description_cache
+            .family_name
+            .get_or_init(|| self.core_text_font.family_name())
+            .clone()
     }
 
     /// Returns the name of the font style, according to Core Text.
@@ -223,6 +279,21 @@ impl Font {
 
     /// Returns the values of various font properties, corresponding to those defined in CSS.
     pub fn properties(&self) -> Properties {
+        *self
+            .description_cache
+            .properties
+            .get_or_init(|| self.load_properties())
+    }
+
+    fn load_properties(&self) -> Properties {
+        self.properties_with_mapping(&PropertyMapping::CORE_TEXT)
+    }
+
+    /// Returns the values of various font properties, converting Core Text's normalized weight
+    /// and width to CSS values with a custom mapping.
+    ///
+    /// Unlike `properties()`, the result isn't cached.
+    pub fn properties_with_mapping(&self, mapping: &PropertyMapping) -> Properties {
         let symbolic_traits = self.core_text_font.symbolic_traits();
         let all_traits = self.core_text_font.all_traits();
 
@@ -234,8 +305,8 @@ impl Font {
             Style::Normal
         };
 
-        let weight = core_text_to_css_font_weight(all_traits.normalized_weight() as f32);
-        let stretch = core_text_width_to_css_stretchiness(all_traits.normalized_width() as f32);
+        let weight = mapping.weight_to_css(all_traits.normalized_weight() as f32);
+        let stretch = mapping.width_to_css(all_traits.normalized_width() as f32);
 
         Properties {
             style,
@@ -256,6 +327,14 @@ impl Font {
     /// Be careful with this function; typographically correct character-to-glyph mapping must be
     /// done using a *shaper* such as HarfBuzz. This function is only useful for best-effort simple
     /// use cases like "what does character X look like on its own".
+    ///
+    /// Characters beyond U+FFFF, such as emoji, are resolved through the font's format 12 `cmap`
+    /// subtables, even if the platform chose a BMP-only subtable for character mapping. Characters
+    /// that only a format 13 subtable maps, as in last-resort fonts, are resolved too.
+    ///
+    /// Unicode subtables always take precedence. Only if the font has none, as with some legacy
+    /// Macintosh fonts, is the character encoded as Mac Roman and looked up in the Mac Roman
+    /// subtable instead.
     pub fn glyph_for_char(&self, character: char) -> Option<u32> {
         unsafe {
             let (mut dest, mut src) = ([0, 0], [0, 0]);
@@ -267,7 +346,7 @@ impl Font {
             if id != 0 {
                 Some(id)
             } else {
-                None
+                loader::glyph_for_unmapped_char(self, character)
             }
         }
     }
@@ -456,6 +535,19 @@ impl Font {
     /// loader.
     ///
     /// If `hinting_options` is not None, the requested grid fitting is performed.
+    /// The FreeType loader grid-fits at the size the glyph is drawn at along each axis when
+    /// `transform` scales the two axes differently, as for artificially condensed or extended
+    /// text; with rotations and skews, it grid-fits at `point_size` before transforming.
+    ///
+    /// The translation in `transform` may place the glyph between pixels;
+    /// `subpixel_positions()` tells how precisely.
+    ///
+    /// The canvas is not cleared first: the glyph is composited over the existing contents. When
+    /// reusing a canvas, use `Canvas::clear()` or `clear_and_rasterize_glyph()` to avoid seeing a
+    /// previous glyph.
+    ///
+    /// `Sdf` canvases receive a signed distance field with the default spread, as
+    /// `rasterize_glyph_sdf()` generates it, and `rasterization_options` is ignored.
     ///
     /// TODO(pcwalton): This is woefully incomplete. See WebRender's code for a more complete
     /// implementation.
@@ -468,6 +560,20 @@ impl Font {
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
     ) -> Result<(), GlyphLoadingError> {
+        if canvas.format == Format::Sdf {
+            return <Self as Loader>::rasterize_glyph_sdf(
+                self,
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                DEFAULT_SDF_SPREAD,
+            );
+        }
+        if rasterization_options == RasterizationOptions::StableGrayscaleAa {
+            return loader::rasterize_glyph_stable(self, canvas, glyph_id, point_size, transform);
+        }
         if canvas.size.x() == 0 || canvas.size.y() == 0 {
             return Ok(());
         }
@@ -509,7 +615,9 @@ impl Font {
             Format::Rgba32 | Format::Rgb24 => {
                 core_graphics_context.set_rgb_fill_color(0.0, 0.0, 0.0, 0.0);
             }
-            Format::A8 => core_graphics_context.set_gray_fill_color(0.0, 0.0),
+            Format::A8 | Format::A16 | Format::AF32 | Format::Sdf | Format::A1 => {
+                core_graphics_context.set_gray_fill_color(0.0, 0.0)
+            }
         }
 
         let core_graphics_size = CGSize::new(canvas.size.x() as f64, canvas.size.y() as f64);
@@ -521,7 +629,9 @@ impl Font {
                 core_graphics_context.set_should_smooth_fonts(false);
                 core_graphics_context.set_should_antialias(false);
             }
-            RasterizationOptions::GrayscaleAa | RasterizationOptions::SubpixelAa => {
+            RasterizationOptions::GrayscaleAa
+            | RasterizationOptions::SubpixelAa
+            | RasterizationOptions::StableGrayscaleAa => {
                 // FIXME(pcwalton): These shouldn't be handled the same!
                 core_graphics_context.set_allows_font_smoothing(true);
                 core_graphics_context.set_should_smooth_fonts(true);
@@ -533,9 +643,17 @@ impl Font {
             Format::Rgba32 | Format::Rgb24 => {
                 core_graphics_context.set_rgb_fill_color(1.0, 1.0, 1.0, 1.0);
             }
-            Format::A8 => core_graphics_context.set_gray_fill_color(1.0, 1.0),
+            Format::A8 | Format::A16 | Format::AF32 | Format::Sdf | Format::A1 => {
+                core_graphics_context.set_gray_fill_color(1.0, 1.0)
+            }
         }
 
+        // Place the glyph exactly where asked, as `subpixel_positions()` documents.
+        core_graphics_context.set_allows_font_subpixel_positioning(true);
+        core_graphics_context.set_should_subpixel_position_fonts(true);
+        core_graphics_context.set_allows_font_subpixel_quantization(false);
+        core_graphics_context.set_should_subpixel_quantize_fonts(false);
+
         // CoreGraphics origin is in the bottom left. This makes behavior consistent.
         core_graphics_context.translate(0.0, canvas.size.y() as CGFloat);
         core_graphics_context.set_font(&self.core_text_font.copy_to_CGFont());
@@ -572,6 +690,22 @@ impl Font {
         }
     }
 
+    /// Returns how many horizontal positions within each pixel `rasterize_glyph()` can place a
+    /// glyph at with the given options, or `None` if it places glyphs exactly where asked.
+    ///
+    /// The fractional part of the translation in the transform passed to `rasterize_glyph()` and
+    /// related functions moves the glyph by part of a pixel. When this returns `Some(n)`, both
+    /// components of the translation are first rounded to the nearest multiple of `1/n` pixel,
+    /// ties going to the even multiple, so the glyph lands at one of only `n` positions per
+    /// pixel. A glyph cache that quantizes origins to a number of positions that divides `n`
+    /// asks only for positions the rasterizer reproduces exactly, so nothing is rounded twice.
+    ///
+    /// Core Graphics is asked not to quantize glyph positions, so this is always `None`.
+    #[inline]
+    pub fn subpixel_positions(&self, _: RasterizationOptions) -> Option<u32> {
+        None
+    }
+
     /// Get font fallback results for the given text and locale.
     ///
     /// Note: this is currently just a stub implementation, a proper implementation
@@ -600,6 +734,724 @@ impl Font {
             .get_font_table(table_tag)
             .map(|data| data.bytes().into())
     }
+
+    /// Returns the sizes, in pixels per em, of the bitmap strikes embedded in this font, sorted
+    /// in ascending order.
+    ///
+    /// Bitmap-only fonts render well only at these sizes, so callers may want to snap the
+    /// requested size to one of them. Returns an empty vector for purely scalable fonts.
+    #[inline]
+    pub fn available_sizes(&self) -> Vec<f32> {
+        <Self as Loader>::available_sizes(self)
+    }
+
+    /// Returns the components of a composite glyph: the glyphs it references and the transform
+    /// applied to each.
+    ///
+    /// Returns `None` for simple glyphs, for glyphs that don't exist, and for fonts without
+    /// TrueType (`glyf`) outlines.
+    #[inline]
+    pub fn glyph_components(&self, glyph_id: u32) -> Option<Vec<GlyphComponent>> {
+        <Self as Loader>::glyph_components(self, glyph_id)
+    }
+
+    /// Clears the region of the canvas that the glyph will cover, then rasterizes the glyph into
+    /// it as `rasterize_glyph()` does.
+    ///
+    /// This prevents pixels left over from a previous rasterization from showing through the new
+    /// glyph, without the cost of clearing the entire canvas.
+    #[inline]
+    pub fn clear_and_rasterize_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::clear_and_rasterize_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the horizontal advance of every glyph, indexed by glyph ID, straight from the
+    /// `hmtx` table.
+    ///
+    /// Values are in font units. This is much faster than calling `advance()` for each glyph.
+    /// Returns an empty vector if the font has no `hmtx` table.
+    #[inline]
+    pub fn all_horizontal_advances(&self) -> Vec<u16> {
+        <Self as Loader>::all_horizontal_advances(self)
+    }
+
+    /// Returns the vertical advance of every glyph, indexed by glyph ID, straight from the
+    /// `vmtx` table.
+    ///
+    /// Values are in font units. Returns an empty vector if the font has no vertical metrics.
+    #[inline]
+    pub fn all_vertical_advances(&self) -> Vec<u16> {
+        <Self as Loader>::all_vertical_advances(self)
+    }
+
+    /// Returns the usual glyph ID for a Unicode character, or the `.notdef` glyph (ID 0) if the
+    /// font doesn't cover it.
+    ///
+    /// This matches what the platform renderers draw for uncovered characters. The same caveats
+    /// as for `glyph_for_char()` apply.
+    #[inline]
+    pub fn glyph_for_char_or_notdef(&self, character: char) -> u32 {
+        <Self as Loader>::glyph_for_char_or_notdef(self, character)
+    }
+
+    /// Converts user-space variation axis values, such as `wght=550`, to the normalized
+    /// coordinates that a variable font's deltas are blended with.
+    ///
+    /// `coordinates` pairs OpenType axis tags with values; axes not mentioned stay at their
+    /// defaults. The result has one entry per axis, in the order of the font's `fvar` table.
+    /// Values are clamped to each axis's range, normalized to [-1, 1], and then run through the
+    /// `avar` segment maps if the font has them, so that the axis responds nonlinearly as its
+    /// designer intended. Any custom blending code should use these coordinates rather than
+    /// normalizing linearly itself. Returns an empty vector for fonts that aren't variable.
+    #[inline]
+    pub fn normalized_variation_coordinates(&self, coordinates: &[(u32, f32)]) -> Vec<f32> {
+        <Self as Loader>::normalized_variation_coordinates(self, coordinates)
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, choosing whether `Rgba32`
+    /// output uses premultiplied or straight alpha.
+    ///
+    /// `rasterize_glyph()` always produces premultiplied alpha. If `premultiplied_alpha` is false
+    /// and the canvas is `Rgba32`, the glyph is drawn as `clear_and_rasterize_glyph()` draws it,
+    /// clearing the region it covers first, and the pixels of that region are then converted to
+    /// straight alpha, which is what compositing in linear space usually wants. Other canvas
+    /// formats have no separate alpha channel and are unaffected.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn rasterize_glyph_with_alpha(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        premultiplied_alpha: bool,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_alpha(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            premultiplied_alpha,
+        )
+    }
+
+    /// Returns the fill rule that this font's glyph outlines are designed to be filled with.
+    ///
+    /// Both TrueType (`glyf`) and PostScript (CFF) outlines use the nonzero winding rule, so
+    /// contours may overlap and are still filled correctly; this method exists so that
+    /// tessellators don't have to assume.
+    #[inline]
+    pub fn fill_rule(&self) -> FillRule {
+        <Self as Loader>::fill_rule(self)
+    }
+
+    /// Returns true if the font marks the glyph as containing overlapping contours or components,
+    /// via the TrueType `OVERLAP_SIMPLE` or `OVERLAP_COMPOUND` flags.
+    ///
+    /// Variable fonts set these flags so that rasterizers know to avoid overlap artifacts. A false
+    /// result does not guarantee that the outline is free of overlaps: fonts are not required to
+    /// set the flags, and CFF fonts have no equivalent.
+    #[inline]
+    pub fn glyph_has_overlap_flag(&self, glyph_id: u32) -> bool {
+        <Self as Loader>::glyph_has_overlap_flag(self, glyph_id)
+    }
+
+    /// Returns the ten PANOSE classification digits from the `OS/2` table.
+    ///
+    /// See the `classification` module for help decoding them. Returns `None` if the font has no
+    /// `OS/2` table or the table is too short.
+    #[inline]
+    pub fn panose(&self) -> Option<[u8; 10]> {
+        <Self as Loader>::panose(self)
+    }
+
+    /// Returns the legacy code pages that the font claims to be functional for, from the `OS/2`
+    /// table.
+    ///
+    /// Returns `None` if the font has no `OS/2` table or the table predates these fields.
+    #[inline]
+    pub fn code_page_ranges(&self) -> Option<CodePageRanges> {
+        <Self as Loader>::code_page_ranges(self)
+    }
+
+    /// Builds a new font containing only the given glyphs, for embedding in documents such as
+    /// PDFs.
+    ///
+    /// The subset also contains `.notdef` and every glyph that the requested composite glyphs are
+    /// built from. Glyphs are renumbered in ascending order of their original IDs, with `.notdef`
+    /// staying at 0; the `cmap`, `hmtx`, `loca`, and `glyf` tables are rebuilt to match, and glyph
+    /// names and layout tables are dropped. Glyph IDs outside the font are ignored.
+    ///
+    /// Only TrueType (`glyf`) outlines are supported; fonts with CFF outlines return
+    /// `FontLoadingError::UnknownFormat`.
+    #[inline]
+    pub fn subset(&self, glyph_ids: &[u32]) -> Result<Vec<u8>, FontLoadingError> {
+        <Self as Loader>::subset(self, glyph_ids)
+    }
+
+    /// Sends the grid-fitted vector path for a glyph at the given point size to a path builder.
+    ///
+    /// This loader can't retrieve hinted outlines, so this always returns
+    /// `GlyphLoadingError::Unsupported`. Use `outline()` or the FreeType loader instead.
+    #[inline]
+    pub fn hinted_outline<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        <Self as Loader>::hinted_outline(self, glyph_id, point_size, hinting, sink)
+    }
+
+    /// Returns the name of every glyph, indexed by glyph ID, from the `post` table.
+    ///
+    /// Version 1.0 tables use the standard Macintosh glyph names and version 2.0 tables list names
+    /// explicitly. Glyphs without a name, including every glyph of a font with a version 3.0
+    /// table or no table at all, are `None`; fonts with CFF outlines usually fall in this
+    /// category. This is much faster than looking names up one glyph at a time.
+    #[inline]
+    pub fn glyph_names(&self) -> Vec<Option<String>> {
+        <Self as Loader>::glyph_names(self)
+    }
+
+    /// Returns true if the glyph draws nothing, like the space glyph, so that rasterizing it can
+    /// be skipped.
+    ///
+    /// For TrueType fonts this is a cheap check for a zero-length `loca` entry. For other fonts,
+    /// the glyph is empty if it has no outline contours and no bitmap extent. Glyph IDs that
+    /// don't exist in the font are reported as empty.
+    #[inline]
+    pub fn glyph_is_empty(&self, glyph_id: u32) -> bool {
+        <Self as Loader>::glyph_is_empty(self, glyph_id)
+    }
+
+    /// Loads a font from raw font data held in any shared buffer, such as a memory-mapped file.
+    ///
+    /// This loader copies the data into a vector; `font_index` has the same meaning as in
+    /// `from_bytes()`.
+    #[inline]
+    pub fn from_shared_bytes(
+        font_data: Arc<dyn AsRef<[u8]> + Send + Sync>,
+        font_index: u32,
+    ) -> Result<Font, FontLoadingError> {
+        <Self as Loader>::from_shared_bytes(font_data, font_index)
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, but leaves every pixel outside
+    /// `clip_rect` untouched.
+    ///
+    /// This keeps glyphs that spill out of their cell, such as a swash or a deep descender, from
+    /// bleeding into neighboring cells of a texture atlas. Inside `clip_rect`, the result is
+    /// exactly what `rasterize_glyph()` would have produced.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip_rect: RectI,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_clipped(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            clip_rect,
+        )
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, reusing the scratch buffers in
+    /// `context` instead of allocating new ones.
+    ///
+    /// The output is the same as that of `rasterize_glyph()`; only the allocation behavior
+    /// differs. Keep one context around for loops that rasterize many glyphs, such as filling a
+    /// glyph atlas, so that they don't allocate a bitmap per glyph.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn rasterize_glyph_with_context(
+        &self,
+        context: &mut RasterizeContext,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_context(
+            self,
+            context,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the paint graph of a color glyph from the `COLR` table, with colors taken from
+    /// palette `palette_index` of the `CPAL` table.
+    ///
+    /// `COLR` version 1 glyphs can use gradients, transforms, and compositing; glyphs that only
+    /// have a version 0 layer list are returned as a `Paint::Layers` of solid-colored glyphs.
+    /// Palette 0 is the default palette. Returns `None` if the glyph isn't a color glyph, if the
+    /// palette doesn't exist, or if the font's color tables are malformed.
+    #[inline]
+    pub fn glyph_paint(&self, glyph_id: u32, palette_index: u16) -> Option<Paint> {
+        <Self as Loader>::glyph_paint(self, glyph_id, palette_index)
+    }
+
+    /// Returns the metrics for laying out lines of text in this font at `point_size`.
+    ///
+    /// If the `OS/2` table sets the `USE_TYPO_METRICS` flag, its typographic ascender, descender,
+    /// and line gap are used; otherwise, the ascent, descent, and line gap come from `metrics()`.
+    #[inline]
+    pub fn line_metrics(&self, point_size: f32) -> LineMetrics {
+        <Self as Loader>::line_metrics(self, point_size)
+    }
+
+    /// Returns the advance width of a glyph at `ppem` pixels per em, in whole pixels, as recorded
+    /// in the `hdmx` table.
+    ///
+    /// TrueType fonts may tabulate the hinted advance of every glyph at common sizes so that
+    /// layout on the pixel grid can match what the hinter produces without running it. Returns
+    /// `None` if the font has no `hdmx` table or the table has no record for `ppem`.
+    #[inline]
+    pub fn device_advance(&self, glyph_id: u32, ppem: u8) -> Option<u8> {
+        <Self as Loader>::device_advance(self, glyph_id, ppem)
+    }
+
+    /// Rasterizes a glyph to a scratch buffer and summarizes its coverage, without keeping the
+    /// pixels.
+    ///
+    /// See `CoverageStats` for which pixels the statistics cover.
+    #[inline]
+    pub fn glyph_coverage_stats(&self, key: &GlyphKey) -> Result<CoverageStats, GlyphLoadingError> {
+        <Self as Loader>::glyph_coverage_stats(self, key)
+    }
+
+    /// Returns the height of `baseline` above the font's origin when setting text in `script`
+    /// horizontally, in font units, from the `BASE` table.
+    ///
+    /// This is how lines mixing scripts with different baselines, such as Latin and CJK, are
+    /// aligned. Returns `None` if the font has no `BASE` table or the table does not define that
+    /// baseline for the script.
+    #[inline]
+    pub fn baseline(&self, script: ScriptTag, baseline: BaselineTag) -> Option<i16> {
+        <Self as Loader>::baseline(self, script, baseline)
+    }
+
+    /// Returns the vector path for a glyph as an iterator of path commands.
+    ///
+    /// This is an alternative to `outline()` for callers that would rather pull commands than
+    /// implement `OutlineSink`. None of the platform APIs can produce an outline lazily, so the
+    /// whole path is collected before this returns.
+    #[inline]
+    pub fn outline_segments(
+        &self,
+        glyph_id: u32,
+        hinting_mode: HintingOptions,
+    ) -> Result<OutlineSegments, GlyphLoadingError> {
+        <Self as Loader>::outline_segments(self, glyph_id, hinting_mode)
+    }
+
+    /// Returns true if the font has a real glyph for a Unicode character.
+    ///
+    /// Some fonts map whole ranges of characters they don't cover to the `.notdef` glyph (ID 0),
+    /// which renders as a missing-glyph box; such characters are not covered. The same caveats as
+    /// for `glyph_for_char()` apply.
+    #[inline]
+    pub fn covers_char(&self, character: char) -> bool {
+        <Self as Loader>::covers_char(self, character)
+    }
+
+    /// Returns what the font's license allows documents that embed it to do, from the `fsType`
+    /// field of the `OS/2` table.
+    ///
+    /// Fonts without an `OS/2` table are treated as unrestricted.
+    #[inline]
+    pub fn embedding_restrictions(&self) -> EmbeddingRestrictions {
+        <Self as Loader>::embedding_restrictions(self)
+    }
+
+    /// Returns the points of each contour of a TrueType glyph as stored in the `glyf` table,
+    /// including whether each point is on or off the curve.
+    ///
+    /// Unlike `outline()`, no curves are constructed and no implied on-curve points are added, so
+    /// this is suited to tools that analyze how a font was drawn. Returns `None` for composite
+    /// glyphs (see `glyph_components()`), for glyphs that don't exist, and for fonts without
+    /// TrueType outlines, since CFF outlines have no quadratic point model. Glyphs without an
+    /// outline, such as the space, have no contours.
+    #[inline]
+    pub fn glyph_raw_points(&self, glyph_id: u32) -> Option<Vec<ContourPoints>> {
+        <Self as Loader>::glyph_raw_points(self, glyph_id)
+    }
+
+    /// Rasterizes a glyph in a solid color to an `Rgba32` canvas, producing premultiplied
+    /// pixels directly instead of coverage that has to be tinted afterward.
+    ///
+    /// The glyph's coverage is multiplied by `color`, which has straight alpha, and composited
+    /// over the canvas. With `SubpixelAa`, each channel is tinted by its own coverage. Returns
+    /// `GlyphLoadingError::Unsupported` if the canvas format isn't `Rgba32`.
+    ///
+    /// Color glyphs are drawn in their own colors instead, where this loader can draw them:
+    ///
+    /// * `COLR` glyphs made only of solid-colored layers, which includes every `COLR` version 0
+    ///   glyph, are drawn layer by layer with all loaders. Layers in the foreground color use
+    ///   `color`. Glyphs that need gradients, transforms, or compositing are drawn tinted.
+    ///
+    /// * `sbix` and `CBDT` bitmaps are drawn by the FreeType loader. The other loaders draw these
+    ///   glyphs tinted.
+    ///
+    /// Whether a glyph is a color glyph is decided glyph by glyph, so color fonts can mix color
+    /// and monochrome glyphs. With `key.color` set to `ColorPreference::Never`, color glyphs are
+    /// drawn tinted too. With `ColorPreference::Force`, glyphs without color data, or with color
+    /// data that this loader can't draw, return `GlyphLoadingError::Unsupported`.
+    #[inline]
+    pub fn rasterize_glyph_colored(
+        &self,
+        canvas: &mut Canvas,
+        key: &GlyphKey,
+        color: Color,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_colored(self, canvas, key, color)
+    }
+
+    /// Returns the format of this font's glyph shapes, judged by which tables are present.
+    ///
+    /// Fonts with more than one kind of outline table report the first of `glyf`, `CFF2`, and
+    /// `CFF `. Fonts with no outlines are `Bitmap` if they have bitmap strikes (see
+    /// `available_sizes()`) and `None` otherwise.
+    #[inline]
+    pub fn outline_format(&self) -> OutlineFormat {
+        <Self as Loader>::outline_format(self)
+    }
+
+    /// Rasterizes a short string on one line into a new canvas sized to fit it, for labels and
+    /// other simple text.
+    ///
+    /// Each character is mapped to a glyph with `glyph_for_char()`, falling back to `.notdef`
+    /// (glyph 0), and glyphs are placed left to right by their advances, adjusted by the pair
+    /// kerning in the font's `kern` table. This is not text shaping: there are no ligatures,
+    /// contextual forms, `GPOS` kerning or mark positioning, bidirectional reordering, or line
+    /// breaking, so it is only suitable for simple left-to-right scripts such as Latin.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise. It spans the line's ascent and
+    /// descent at `point_size`, widened to include any ink that falls outside them, and the pen
+    /// starts at the left edge of the line on the baseline. Returns
+    /// `GlyphLoadingError::InvalidCanvasSize` if the line is too large for a canvas.
+    #[inline]
+    pub fn rasterize_line(
+        &self,
+        text: &str,
+        point_size: f32,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<Canvas, GlyphLoadingError> {
+        <Self as Loader>::rasterize_line(
+            self,
+            text,
+            point_size,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns true if the font has a real glyph for every character in `chars`, as
+    /// `covers_char()` judges them.
+    ///
+    /// This stops at the first character that isn't covered. An empty run is covered.
+    #[inline]
+    pub fn covers_all<I>(&self, chars: I) -> bool
+    where
+        I: IntoIterator<Item = char>,
+    {
+        <Self as Loader>::covers_all(self, chars)
+    }
+
+    /// Returns the fraction of the characters in `chars` that the font has a real glyph for, as
+    /// `covers_char()` judges them, from 0.0 to 1.0.
+    ///
+    /// This is useful for ranking fallback fonts by how much of a run of text each one covers.
+    /// Repeated characters count each time they occur. An empty run has a ratio of 1.0.
+    #[inline]
+    pub fn coverage_ratio<I>(&self, chars: I) -> f32
+    where
+        I: IntoIterator<Item = char>,
+    {
+        <Self as Loader>::coverage_ratio(self, chars)
+    }
+
+    /// Returns true if the font has a `DSIG` table with at least one signature in it.
+    ///
+    /// The signature is not verified. Many fonts carry a `DSIG` table with no signatures, added
+    /// only because old versions of Windows required one; those fonts are not signed.
+    #[inline]
+    pub fn is_signed(&self) -> bool {
+        <Self as Loader>::is_signed(self)
+    }
+
+    /// Returns how far the glyph with the given ID extends above and below the baseline, in font
+    /// units, as an `(ascent, descent)` pair.
+    ///
+    /// Both values come from the glyph's outline bounding box, so they fit that glyph tightly,
+    /// unlike the font-wide `ascent` and `descent` metrics; this is useful for packing lines
+    /// closely around drop caps, badges, and the like. A descent below the baseline is positive.
+    /// Glyphs with no outline, such as spaces, have extents of zero.
+    #[inline]
+    pub fn glyph_vertical_extents(&self, glyph_id: u32) -> Result<(f32, f32), GlyphLoadingError> {
+        <Self as Loader>::glyph_vertical_extents(self, glyph_id)
+    }
+
+    /// Returns the font-wide metrics of a variable font at the given user-space variation axis
+    /// values, such as `wght=700`.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The deltas in the
+    /// font's `MVAR` table are applied to the ascent, descent, line gap, underline position and
+    /// thickness, cap height, and x-height of `metrics()`, which describes the default instance.
+    /// Fonts without an `MVAR` table return `metrics()` unchanged.
+    #[inline]
+    pub fn metrics_at_variation(&self, coordinates: &[(u32, f32)]) -> Metrics {
+        <Self as Loader>::metrics_at_variation(self, coordinates)
+    }
+
+    /// Returns the slant of the font's upright strokes, in degrees counterclockwise from the
+    /// vertical, from the `post` table.
+    ///
+    /// Italic and oblique fonts that lean right have negative angles, and upright fonts 0. Fonts
+    /// without a `post` table report 0. Together with `properties().style`, this tells whether
+    /// an italic needs to be synthesized, and the angle to shear the upright face by if so.
+    #[inline]
+    pub fn italic_angle(&self) -> f32 {
+        <Self as Loader>::italic_angle(self)
+    }
+
+    /// Generates a signed distance field for a glyph on an `Sdf` canvas, with the given size and
+    /// transform.
+    ///
+    /// The field is computed from the glyph's outline, hinted as `hinting_options` requests, so
+    /// glyphs without outlines (such as bitmap glyphs) leave the canvas unchanged. `spread` is the
+    /// distance in pixels from the outline at which values reach 0 outside the glyph and 255
+    /// inside it; larger spreads allow wider effects such as outlines and glows, at the cost of
+    /// precision. Pixels take the larger of their old and new values, so several glyphs can share
+    /// a canvas. Returns `GlyphLoadingError::Unsupported` if the canvas format isn't `Sdf`.
+    #[inline]
+    pub fn rasterize_glyph_sdf(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        spread: f32,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_sdf(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            spread,
+        )
+    }
+
+    /// Returns the filled area of a glyph as polygons in font units, for hit-testing.
+    ///
+    /// Each contour of the outline becomes one polygon, with curves flattened into lines that
+    /// stray no more than `tolerance` font units from them. Polygons are implicitly closed; the
+    /// first point isn't repeated at the end.
+    ///
+    /// Contours keep the directions they have in the font, so test points with the nonzero
+    /// winding rule: a point is filled if the polygons wind around it a nonzero number of times
+    /// in total. Counters, such as the inside of an "O", wind the opposite way from the contours
+    /// around them and so are unfilled. The even-odd rule gives the same answer for most glyphs,
+    /// but not where contours overlap, as they often do in variable fonts.
+    #[inline]
+    pub fn glyph_hit_region(
+        &self,
+        glyph_id: u32,
+        tolerance: f32,
+    ) -> Result<Vec<Vec<Vector2F>>, GlyphLoadingError> {
+        <Self as Loader>::glyph_hit_region(self, glyph_id, tolerance)
+    }
+
+    /// Returns the ligature glyph that the font substitutes for a sequence of component glyphs,
+    /// if it has one.
+    ///
+    /// This reads the ligature substitutions of the `liga` (standard ligatures) and `dlig`
+    /// (discretionary ligatures) features in the `GSUB` table, preferring standard ones, which is
+    /// useful for mapping a rendered ligature back to the glyphs, and so the characters, it
+    /// stands for. It is not shaping: substitutions for every script and language are searched,
+    /// lookup flags are ignored, and only a ligature made of exactly `components` is returned.
+    #[inline]
+    pub fn ligature_glyph(&self, components: &[u32]) -> Option<u32> {
+        <Self as Loader>::ligature_glyph(self, components)
+    }
+
+    /// Returns true if this font and `other` draw the same glyph shapes, within a small tolerance.
+    ///
+    /// This is much slower than comparing font data, but it also catches duplicates whose data
+    /// differs, such as a font that has been repackaged as WOFF or had its tables rebuilt. The
+    /// fonts must have the same number of glyphs and units per em, and each compared glyph must
+    /// have the same contours with the same kinds of points, at positions no more than 1/1000 em
+    /// apart. Fonts with up to 1024 glyphs have every glyph compared; for larger fonts, 1024
+    /// glyph IDs spread evenly from the first glyph to the last are sampled.
+    #[inline]
+    pub fn outlines_equal(&self, other: &Font) -> bool {
+        <Self as Loader>::outlines_equal(self, other)
+    }
+
+    /// Returns a key that identifies this font by its content, for use in hash maps.
+    ///
+    /// See `FontKey` for what makes two keys equal.
+    #[inline]
+    pub fn font_key(&self) -> FontKey {
+        <Self as Loader>::font_key(self)
+    }
+
+    /// Rasterizes a glyph whose origin falls at a fractional pixel position into a new canvas
+    /// sized to fit it, returning the canvas and the position of its top left corner.
+    ///
+    /// `subpixel_origin` is the position of the glyph's origin on the baseline, in pixels with y
+    /// pointing down, as in a canvas. It's split into whole pixels, which only move the returned
+    /// position, and a fraction, which the rasterizer renders as the glyph's subpixel phase, as
+    /// precisely as `subpixel_positions()` allows. Drawing the canvas at the returned position
+    /// puts the glyph exactly where it belongs, so text runs can place each glyph at its
+    /// unrounded pen position without losing sharpness.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise.
+    #[inline]
+    pub fn rasterize_glyph_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        subpixel_origin: Vector2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(Canvas, Vector2I), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_at(
+            self,
+            glyph_id,
+            point_size,
+            subpixel_origin,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the string with the ID `name_id` from the font's `name` table, or `None` if the
+    /// font has no such string.
+    ///
+    /// A string is often recorded several times, in different languages and encodings. English
+    /// in Unicode is preferred, then any Unicode string, then a Mac Roman one; strings in other
+    /// encodings are ignored. `designer()`, `description()`, and the like look up the IDs that
+    /// font detail panels typically show.
+    #[inline]
+    pub fn name_string(&self, name_id: u16) -> Option<String> {
+        <Self as Loader>::name_string(self, name_id)
+    }
+
+    /// Returns the text the designer suggests for showing off the font, from the `name` table.
+    #[inline]
+    pub fn sample_text(&self) -> Option<String> {
+        <Self as Loader>::sample_text(self)
+    }
+
+    /// Returns the description of the font, from the `name` table.
+    #[inline]
+    pub fn description(&self) -> Option<String> {
+        <Self as Loader>::description(self)
+    }
+
+    /// Returns the name of the font's designer, from the `name` table.
+    #[inline]
+    pub fn designer(&self) -> Option<String> {
+        <Self as Loader>::designer(self)
+    }
+
+    /// Returns the name of the font's manufacturer, from the `name` table.
+    #[inline]
+    pub fn manufacturer(&self) -> Option<String> {
+        <Self as Loader>::manufacturer(self)
+    }
+
+    /// Returns the description of the font's license, from the `name` table.
+    #[inline]
+    pub fn license(&self) -> Option<String> {
+        <Self as Loader>::license(self)
+    }
+
+    /// Returns the advance of the glyph with the given ID at the given user-space variation axis
+    /// values, such as `wght=700`, in font units.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The delta in the
+    /// font's `HVAR` table is added to the horizontal advance from `advance()`, which describes
+    /// the default instance; layout of variable text should use this instead. Fonts without an
+    /// `HVAR` table return `advance()` unchanged.
+    #[inline]
+    pub fn advance_at_variation(
+        &self,
+        glyph_id: u32,
+        coordinates: &[(u32, f32)],
+    ) -> Result<Vector2F, GlyphLoadingError> {
+        <Self as Loader>::advance_at_variation(self, glyph_id, coordinates)
+    }
+
+    /// Returns the fraction of a script's core repertoire that the font has real glyphs for, from
+    /// 0.0 to 1.0.
+    ///
+    /// The repertoires, which `classification::script_repertoire()` returns, hold the basic
+    /// characters of each script as of Unicode 15.1. This is useful for choosing the best fallback
+    /// font for each script in a run of text. Scripts without a repertoire score 0.0.
+    #[inline]
+    pub fn script_coverage(&self, script: ScriptTag) -> f32 {
+        <Self as Loader>::script_coverage(self, script)
+    }
+
+    /// Returns the global hinting parameters from the private dict of a `CFF ` table: the
+    /// alignment zones and dominant stem widths that CFF hinters snap to.
+    ///
+    /// For CID-keyed fonts, the parameters of the first font dict are returned. Returns `None`
+    /// for fonts without a `CFF ` table, including TrueType and `CFF2` fonts, or if the table is
+    /// malformed.
+    #[inline]
+    pub fn cff_hint_params(&self) -> Option<CffHintParams> {
+        <Self as Loader>::cff_hint_params(self)
+    }
 }
 
 impl Loader for Font {
@@ -722,6 +1574,11 @@ impl Loader for Font {
         self.supports_hinting_options(hinting_options, for_rasterization)
     }
 
+    #[inline]
+    fn subpixel_positions(&self, rasterization_options: RasterizationOptions) -> Option<u32> {
+        self.subpixel_positions(rasterization_options)
+    }
+
     #[inline]
     fn rasterize_glyph(
         &self,
@@ -786,19 +1643,6 @@ impl CGPointExt for CGPoint {
     }
 }
 
-fn core_text_to_css_font_weight(core_text_weight: f32) -> Weight {
-    let index = piecewise_linear_find_index(core_text_weight, &FONT_WEIGHT_MAPPING);
-
-    Weight(index * 100.0 + 100.0)
-}
-
-fn core_text_width_to_css_stretchiness(core_text_width: f32) -> Stretch {
-    Stretch(piecewise_linear_lookup(
-        (core_text_width + 1.0) * 4.0,
-        &Stretch::MAPPING,
-    ))
-}
-
 fn font_is_collection(header: &[u8]) -> bool {
     header.len() >= 4 && header[0..4] == TTC_TAG
 }
@@ -850,6 +1694,14 @@ fn format_to_cg_color_space_and_image_format(format: Format) -> Option<(CGColorS
             kCGImageAlphaPremultipliedLast,
         )),
         Format::A8 => Some((CGColorSpace::create_device_gray(), kCGImageAlphaOnly)),
+        Format::A16 | Format::AF32 => {
+            // Rasterize with 8-bit precision, then convert.
+            None
+        }
+        // Rasterize with 8-bit precision, then threshold.
+        Format::A1 => None,
+        // Distance fields are generated from outlines, never by Core Graphics.
+        Format::Sdf => None,
     }
 }
 
@@ -913,7 +1765,7 @@ fn unpack_data_fork_font(data: &mut [u8]) -> Result<(), FontLoadingError> {
 #[cfg(test)]
 mod test {
     use super::Font;
-    use crate::properties::{Stretch, Weight};
+    use crate::properties::{PropertyMapping, Stretch, Weight};
 
     #[cfg(feature = "source")]
     use crate::source::SystemSource;
@@ -937,57 +1789,26 @@ mod test {
     #[test]
     fn test_core_text_to_css_font_weight() {
         // Exact matches
-        assert_eq!(super::core_text_to_css_font_weight(-0.7), Weight(100.0));
-        assert_eq!(super::core_text_to_css_font_weight(0.0), Weight(400.0));
-        assert_eq!(super::core_text_to_css_font_weight(0.4), Weight(700.0));
-        assert_eq!(super::core_text_to_css_font_weight(0.8), Weight(900.0));
+        assert_eq!(
+            PropertyMapping::CORE_TEXT.weight_to_css(-0.7),
+            Weight(100.0)
+        );
+        assert_eq!(PropertyMapping::CORE_TEXT.weight_to_css(0.0), Weight(400.0));
+        assert_eq!(PropertyMapping::CORE_TEXT.weight_to_css(0.4), Weight(700.0));
+        assert_eq!(PropertyMapping::CORE_TEXT.weight_to_css(0.8), Weight(900.0));
 
         // Linear interpolation
-        assert_eq!(super::core_text_to_css_font_weight(0.1), Weight(450.0));
+        assert_eq!(PropertyMapping::CORE_TEXT.weight_to_css(0.1), Weight(450.0));
     }
 
     #[test]
     fn test_core_text_to_css_font_stretch() {
         // Exact matches
-        assert_eq!(
-            super::core_text_width_to_css_stretchiness(0.0),
-            Stretch(1.0)
-        );
-        assert_eq!(
-            super::core_text_width_to_css_stretchiness(-1.0),
-            Stretch(0.5)
-        );
-        assert_eq!(
-            super::core_text_width_to_css_stretchiness(1.0),
-            Stretch(2.0)
-        );
+        assert_eq!(PropertyMapping::CORE_TEXT.width_to_css(0.0), Stretch(1.0));
+        assert_eq!(PropertyMapping::CORE_TEXT.width_to_css(-1.0), Stretch(0.5));
+        assert_eq!(PropertyMapping::CORE_TEXT.width_to_css(1.0), Stretch(2.0));
 
         // Linear interpolation
-        assert_eq!(
-            super::core_text_width_to_css_stretchiness(0.85),
-            Stretch(1.7)
-        );
+        assert_eq!(PropertyMapping::CORE_TEXT.width_to_css(0.85), Stretch(1.7));
     }
 }
-
-pub(crate) fn piecewise_linear_lookup(index: f32, mapping: &[f32]) -> f32 {
-    let lower_value = mapping[f32::floor(index) as usize];
-    let upper_value = mapping[f32::ceil(index) as usize];
-    utils::lerp(lower_value, upper_value, f32::fract(index))
-}
-
-pub(crate) fn piecewise_linear_find_index(query_value: f32, mapping: &[f32]) -> f32 {
-    let upper_index = match mapping
-        .binary_search_by(|value| value.partial_cmp(&query_value).unwrap_or(Ordering::Less))
-    {
-        Ok(index) => return index as f32,
-        Err(upper_index) => upper_index,
-    };
-    if upper_index == 0 || upper_index >= mapping.len() {
-        return upper_index as f32;
-    }
-    let lower_index = upper_index - 1;
-    let (upper_value, lower_value) = (mapping[upper_index], mapping[lower_index]);
-    let t = (query_value - lower_value) / (upper_value - lower_value);
-    lower_index as f32 + t
-}
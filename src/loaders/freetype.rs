@@ -12,46 +12,68 @@
 //!
 //! On macOS and Windows, the Cargo feature `loader-freetype-default` can be used to opt into this
 //! loader by default.
+//!
+//! # Threads
+//!
+//! FreeType objects may not be used from several threads at once: an `FT_Library`, and every
+//! `FT_Face` created from it, must be confined to one thread or locked externally. This loader
+//! keeps one library per thread, created the first time a font is loaded on that thread and
+//! shared by every font loaded there afterward, so only the first load on each thread pays for
+//! `FT_Init_FreeType()`. The library is destroyed when the thread exits.
+//!
+//! For the same reason, `Font` is neither `Send` nor `Sync`. To work with a font on several
+//! threads, share its `Handle` (or the `Arc` holding its data, which isn't copied) and load a
+//! `Font` on each thread; bulk loaders should reuse a fixed set of worker threads rather than
+//! spawning one per font.
 
 use byteorder::{BigEndian, ReadBytesExt};
 use freetype_sys::{
-    ft_sfnt_os2, FT_Byte, FT_Done_Face, FT_Done_FreeType, FT_Error, FT_Face, FT_Fixed,
-    FT_Get_Char_Index, FT_Get_Name_Index, FT_Get_Postscript_Name, FT_Get_Sfnt_Name,
-    FT_Get_Sfnt_Name_Count, FT_Get_Sfnt_Table, FT_Init_FreeType, FT_Library,
-    FT_Library_SetLcdFilter, FT_Load_Glyph, FT_Long, FT_Matrix, FT_New_Memory_Face, FT_Pos,
-    FT_Reference_Face, FT_Set_Char_Size, FT_Set_Transform, FT_UInt, FT_ULong, FT_Vector,
-    FT_FACE_FLAG_FIXED_WIDTH, FT_LCD_FILTER_DEFAULT, FT_LOAD_DEFAULT, FT_LOAD_MONOCHROME,
-    FT_LOAD_NO_HINTING, FT_LOAD_RENDER, FT_LOAD_TARGET_LCD, FT_LOAD_TARGET_LIGHT,
-    FT_LOAD_TARGET_MONO, FT_LOAD_TARGET_NORMAL, FT_PIXEL_MODE_GRAY, FT_PIXEL_MODE_LCD,
+    ft_sfnt_os2, FT_BBox, FT_Bitmap, FT_Byte, FT_Done_Face, FT_Done_FreeType, FT_Encoding,
+    FT_Error, FT_Face, FT_Fixed, FT_Get_Char_Index, FT_Get_Name_Index, FT_Get_Postscript_Name,
+    FT_Get_Sfnt_Name, FT_Get_Sfnt_Name_Count, FT_Get_Sfnt_Table, FT_Init_FreeType, FT_Library,
+    FT_Library_SetLcdFilter, FT_Load_Glyph, FT_Long, FT_Matrix, FT_New_Memory_Face,
+    FT_Outline_Get_Bitmap, FT_Outline_Get_CBox, FT_Outline_Translate, FT_Pos, FT_Reference_Face,
+    FT_Set_Char_Size, FT_Set_Charmap, FT_Set_Transform, FT_UInt, FT_ULong, FT_Vector,
+    FT_ENCODING_ADOBE_CUSTOM, FT_ENCODING_ADOBE_EXPERT, FT_ENCODING_ADOBE_LATIN_1,
+    FT_ENCODING_ADOBE_STANDARD, FT_FACE_FLAG_FIXED_WIDTH, FT_GLYPH_FORMAT_OUTLINE, FT_IS_SCALABLE,
+    FT_LCD_FILTER_DEFAULT, FT_LOAD_COLOR, FT_LOAD_DEFAULT, FT_LOAD_MONOCHROME, FT_LOAD_NO_HINTING,
+    FT_LOAD_RENDER, FT_LOAD_TARGET_LCD, FT_LOAD_TARGET_LIGHT, FT_LOAD_TARGET_MONO,
+    FT_LOAD_TARGET_NORMAL, FT_PIXEL_MODE_BGRA, FT_PIXEL_MODE_GRAY, FT_PIXEL_MODE_LCD,
     FT_PIXEL_MODE_LCD_V, FT_PIXEL_MODE_MONO, FT_STYLE_FLAG_ITALIC, TT_OS2,
 };
 use log::warn;
 use pathfinder_geometry::line_segment::LineSegment2F;
 use pathfinder_geometry::rect::{RectF, RectI};
-use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use pathfinder_simd::default::F32x4;
 use std::f32;
 use std::ffi::{CStr, CString};
 use std::fmt::{self, Debug, Formatter};
 use std::io::{Seek, SeekFrom};
-use std::iter;
 use std::mem;
 use std::os::raw::{c_char, c_void};
 use std::ptr;
 use std::slice;
 use std::sync::Arc;
 
-use crate::canvas::{Canvas, Format, RasterizationOptions};
+use crate::canvas::{
+    Canvas, CoverageStats, Format, GlyphKey, RasterizationOptions, RasterizeContext,
+    DEFAULT_SDF_SPREAD,
+};
+use crate::classification::{CodePageRanges, EmbeddingRestrictions};
+use crate::color::{Color, Paint};
 use crate::error::{FontLoadingError, GlyphLoadingError};
 use crate::file_type::FileType;
 use crate::handle::Handle;
-use crate::hinting::HintingOptions;
-use crate::loader::{FallbackResult, Loader};
-use crate::metrics::Metrics;
-use crate::outline::OutlineSink;
+use crate::hinting::{CffHintParams, HintingOptions};
+use crate::loader::{self, FallbackResult, FontKey, Loader};
+use crate::metrics::{BaselineTag, LineMetrics, Metrics, ScriptTag};
+use crate::outline::{
+    ContourPoints, FillRule, GlyphComponent, OutlineFormat, OutlineSegments, OutlineSink,
+};
 use crate::properties::{Properties, Stretch, Style, Weight};
-use crate::utils;
+use crate::utils::{self, DescriptionCache};
 
 #[cfg(not(target_arch = "wasm32"))]
 use std::fs::File;
@@ -68,6 +90,9 @@ const FT_POINT_TAG_CUBIC_CONTROL: c_char = 0x02;
 
 const OS2_FS_SELECTION_OBLIQUE: u16 = 1 << 9;
 
+// Outline coordinates are 26.6 fixed point numbers.
+const FREETYPE_SUBPIXEL_POSITIONS: u32 = 64;
+
 // Not in our FreeType bindings, so we define these ourselves.
 #[allow(dead_code)]
 const BDF_PROPERTY_TYPE_NONE: BDF_PropertyType = 0;
@@ -78,6 +103,8 @@ const BDF_PROPERTY_TYPE_INTEGER: BDF_PropertyType = 2;
 #[allow(dead_code)]
 const BDF_PROPERTY_TYPE_CARDINAL: BDF_PropertyType = 3;
 
+// Each thread initializes its own library on first use, since FreeType libraries and their faces
+// must not be shared between threads.
 thread_local! {
     static FREETYPE_LIBRARY: FtLibrary = {
         unsafe {
@@ -118,12 +145,35 @@ struct BDF_PropertyRec {
 
 /// A cross-platform loader that uses the FreeType library to load and rasterize fonts.
 ///
+/// Fonts are created with the FreeType library of the thread that loads them and can't leave that
+/// thread; see the module documentation.
 ///
 /// On macOS and Windows, the Cargo feature `loader-freetype-default` can be used to opt into this
 /// loader by default.
 pub struct Font {
     freetype_face: FT_Face,
-    font_data: Arc<Vec<u8>>,
+    font_data: FontData,
+    load_flags: i32,
+    description_cache: DescriptionCache,
+    // For PostScript Type 1 fonts, the glyph ID for each of the 256 codes of the font's encoding.
+    type1_encoding: Option<Arc<[u32]>>,
+}
+
+// The buffer that `FT_New_Memory_Face` reads from. FreeType keeps a pointer into it, so it must
+// live as long as the face does.
+#[derive(Clone)]
+enum FontData {
+    Vec(Arc<Vec<u8>>),
+    Shared(Arc<dyn AsRef<[u8]> + Send + Sync>),
+}
+
+impl FontData {
+    fn as_slice(&self) -> &[u8] {
+        match *self {
+            FontData::Vec(ref font_data) => font_data,
+            FontData::Shared(ref font_data) => (**font_data).as_ref(),
+        }
+    }
 }
 
 impl Font {
@@ -131,13 +181,34 @@ impl Font {
     ///
     /// If the data represents a collection (`.ttc`/`.otc`/etc.), `font_index` specifies the index
     /// of the font to load from it. If the data represents a single font, pass 0 for `font_index`.
+    ///
+    /// PostScript Type 1 fonts (`.pfb`/`.pfa`) can be loaded too. They have no SFNT tables, so
+    /// table-based queries such as `load_font_table()` find nothing, but outlines, metrics, and
+    /// character mapping work as for any other font.
     pub fn from_bytes(font_data: Arc<Vec<u8>>, font_index: u32) -> Result<Font, FontLoadingError> {
+        Font::from_font_data(FontData::Vec(font_data), font_index)
+    }
+
+    /// Loads a font from raw font data held in any shared buffer, such as a memory-mapped file,
+    /// without copying it.
+    ///
+    /// The buffer is kept alive for as long as the font (or any clone of it) is. `font_index` has
+    /// the same meaning as in `from_bytes()`.
+    pub fn from_shared_bytes(
+        font_data: Arc<dyn AsRef<[u8]> + Send + Sync>,
+        font_index: u32,
+    ) -> Result<Font, FontLoadingError> {
+        Font::from_font_data(FontData::Shared(font_data), font_index)
+    }
+
+    fn from_font_data(font_data: FontData, font_index: u32) -> Result<Font, FontLoadingError> {
         FREETYPE_LIBRARY.with(|freetype_library| unsafe {
             let mut freetype_face = ptr::null_mut();
+            let bytes = font_data.as_slice();
             if FT_New_Memory_Face(
                 freetype_library.0,
-                (*font_data).as_ptr(),
-                font_data.len() as FT_Long,
+                bytes.as_ptr(),
+                bytes.len() as FT_Long,
                 font_index as FT_Long,
                 &mut freetype_face,
             ) != 0
@@ -146,10 +217,18 @@ impl Font {
             }
 
             setup_freetype_face(freetype_face);
+            let type1_encoding = if utils::is_type1_font(font_data.as_slice()) {
+                load_type1_encoding(freetype_face)
+            } else {
+                None
+            };
 
             Ok(Font {
                 freetype_face,
                 font_data,
+                load_flags: 0,
+                description_cache: DescriptionCache::default(),
+                type1_encoding,
             })
         })
     }
@@ -180,30 +259,39 @@ impl Font {
     }
 
     /// Creates a font from a native API handle.
+    ///
+    /// # Safety
+    ///
+    /// `freetype_face` must point to a valid FreeType face that was created by the FreeType
+    /// library on the current thread.
     pub unsafe fn from_native_font(freetype_face: &NativeFont) -> Font {
         // We make an in-memory copy of the underlying font data. This is because the native font
         // does not necessarily hold a strong reference to the memory backing it.
         let freetype_face = *freetype_face;
-        const CHUNK_SIZE: usize = 4096;
-        let mut font_data = vec![];
-        loop {
-            font_data.extend(iter::repeat(0).take(CHUNK_SIZE));
-            let freetype_stream = (*freetype_face).stream;
+        let freetype_stream = (*freetype_face).stream;
+        let font_data = if !(*freetype_stream).base.is_null() {
+            // Memory-based streams have no `read` callback; copy the buffer directly.
+            slice::from_raw_parts((*freetype_stream).base, (*freetype_stream).size as usize)
+                .to_vec()
+        } else {
+            let mut font_data = vec![0; (*freetype_stream).size as usize];
             let n_read = ((*freetype_stream).read)(
                 freetype_stream,
-                font_data.len() as FT_ULong,
+                0,
                 font_data.as_mut_ptr(),
-                CHUNK_SIZE as FT_ULong,
+                font_data.len() as FT_ULong,
             );
-            if n_read < CHUNK_SIZE as FT_ULong {
-                break;
-            }
-        }
+            font_data.truncate(n_read as usize);
+            font_data
+        };
 
         Font::from_bytes(Arc::new(font_data), (*freetype_face).face_index as u32).unwrap()
     }
 
     /// Loads the font pointed to by a handle.
+    ///
+    /// If a path handle fails to load, the error is wrapped in `FontLoadingError::InFile` along
+    /// with the path and font index.
     #[inline]
     pub fn from_handle(handle: &Handle) -> Result<Self, FontLoadingError> {
         <Self as Loader>::from_handle(handle)
@@ -283,8 +371,38 @@ impl Font {
         }
     }
 
+    /// Sets additional `FT_LOAD_*` flags that will be ORed into every glyph load this font
+    /// performs, such as `FT_LOAD_NO_SCALE`, `FT_LOAD_IGNORE_TRANSFORM`, or `FT_LOAD_COLOR`.
+    ///
+    /// This is an escape hatch for advanced callers and is specific to the FreeType loader; it is
+    /// *not* portable to the other loaders. Flags that change the units or format of the loaded
+    /// glyph (for example, `FT_LOAD_NO_SCALE`) affect the results of functions like `advance()`
+    /// and `outline()`, so use this with care. Pass 0 to restore the default behavior.
+    ///
+    /// With `FT_LOAD_COLOR`, `rasterize_glyph()` draws color glyphs in their own colors, as
+    /// premultiplied RGBA; canvases without color keep only their alpha.
+    #[inline]
+    pub fn set_load_flags(&mut self, flags: i32) {
+        self.load_flags = flags
+    }
+
+    /// Returns the additional `FT_LOAD_*` flags set with `set_load_flags()`.
+    ///
+    /// This is specific to the FreeType loader.
+    #[inline]
+    pub fn load_flags(&self) -> i32 {
+        self.load_flags
+    }
+
     /// Returns the PostScript name of the font. This should be globally unique.
     pub fn postscript_name(&self) -> Option<String> {
+        self.description_cache
+            .postscript_name
+            .get_or_init(|| self.load_postscript_name())
+            .clone()
+    }
+
+    fn load_postscript_name(&self) -> Option<String> {
         unsafe {
             let postscript_name = FT_Get_Postscript_Name(self.freetype_face);
             if !postscript_name.is_null() {
@@ -320,12 +438,24 @@ impl Font {
 
     /// Returns the full name of the font (also known as "display name" on macOS).
     pub fn full_name(&self) -> String {
-        self.get_type_1_or_sfnt_name(PS_DICT_FULL_NAME, TT_NAME_ID_FULL_NAME)
-            .unwrap_or_else(|| self.family_name())
+        self.description_cache
+            .full_name
+            .get_or_init(|| {
+                self.get_type_1_or_sfnt_name(PS_DICT_FULL_NAME, TT_NAME_ID_FULL_NAME)
+                    .unwrap_or_else(|| self.family_name())
+            })
+            .clone()
     }
 
     /// Returns the name of the font family.
     pub fn family_name(&self) -> String {
+        self.description_cache
+            .family_name
+            .get_or_init(|| self.load_family_name())
+            .clone()
+    }
+
+    fn load_family_name(&self) -> String {
         unsafe {
             let ptr = (*self.freetype_face).family_name;
             // FreeType doesn't guarantee a non-null family name (see issue #5).
@@ -343,30 +473,48 @@ impl Font {
     }
 
     /// Returns the values of various font properties, corresponding to those defined in CSS.
+    ///
+    /// If the font has no `OS/2` table, the properties are guessed from its style name with
+    /// `Properties::from_style_name()`.
     pub fn properties(&self) -> Properties {
+        *self
+            .description_cache
+            .properties
+            .get_or_init(|| self.load_properties())
+    }
+
+    fn load_properties(&self) -> Properties {
         unsafe {
-            let os2_table = self.get_os2_table();
-            let style = match os2_table {
-                Some(os2_table) if ((*os2_table).fsSelection & OS2_FS_SELECTION_OBLIQUE) != 0 => {
-                    Style::Oblique
-                }
-                _ if ((*self.freetype_face).style_flags & (FT_STYLE_FLAG_ITALIC) as FT_Long)
-                    != 0 =>
-                {
-                    Style::Italic
+            let os2_table = match self.get_os2_table() {
+                Some(os2_table) => os2_table,
+                None => {
+                    let style_name = (*self.freetype_face).style_name;
+                    let mut properties = if style_name.is_null() {
+                        Properties::new()
+                    } else {
+                        Properties::from_style_name(&CStr::from_ptr(style_name).to_string_lossy())
+                    };
+                    if properties.style == Style::Normal
+                        && ((*self.freetype_face).style_flags & (FT_STYLE_FLAG_ITALIC) as FT_Long)
+                            != 0
+                    {
+                        properties.style = Style::Italic;
+                    }
+                    return properties;
                 }
-                _ => Style::Normal,
             };
-            let stretch = match os2_table {
-                Some(os2_table) if (1..=9).contains(&(*os2_table).usWidthClass) => {
-                    Stretch(Stretch::MAPPING[((*os2_table).usWidthClass as usize) - 1])
-                }
-                _ => Stretch::NORMAL,
+            let style = if ((*os2_table).fsSelection & OS2_FS_SELECTION_OBLIQUE) != 0 {
+                Style::Oblique
+            } else if ((*self.freetype_face).style_flags & (FT_STYLE_FLAG_ITALIC) as FT_Long) != 0 {
+                Style::Italic
+            } else {
+                Style::Normal
             };
-            let weight = match os2_table {
-                None => Weight::NORMAL,
-                Some(os2_table) => Weight((*os2_table).usWeightClass as f32),
+            let stretch = match (*os2_table).usWidthClass {
+                width_class @ 1..=9 => Stretch(Stretch::MAPPING[(width_class as usize) - 1]),
+                _ => Stretch::NORMAL,
             };
+            let weight = Weight((*os2_table).usWeightClass as f32);
             Properties {
                 style,
                 stretch,
@@ -380,15 +528,33 @@ impl Font {
     /// Be careful with this function; typographically correct character-to-glyph mapping must be
     /// done using a *shaper* such as HarfBuzz. This function is only useful for best-effort simple
     /// use cases like "what does character X look like on its own".
+    ///
+    /// Characters beyond U+FFFF, such as emoji, are resolved through the font's format 12 `cmap`
+    /// subtables, even if the platform chose a BMP-only subtable for character mapping. Characters
+    /// that only a format 13 subtable maps, as in last-resort fonts, are resolved too.
+    ///
+    /// Unicode subtables always take precedence. Only if the font has none, as with some legacy
+    /// Macintosh fonts, is the character encoded as Mac Roman and looked up in the Mac Roman
+    /// subtable instead.
+    ///
+    /// For PostScript Type 1 fonts, characters are mapped by glyph name first. Characters below
+    /// U+0100 that no glyph name accounts for, as in symbol fonts with custom glyph names, are
+    /// then treated as codes in the font's own encoding.
     #[inline]
     pub fn glyph_for_char(&self, character: char) -> Option<u32> {
         unsafe {
             let res = FT_Get_Char_Index(self.freetype_face, character as FT_ULong);
-            match res {
-                0 => None,
-                _ => Some(res),
+            if res != 0 {
+                return Some(res);
             }
         }
+        if let Some(ref type1_encoding) = self.type1_encoding {
+            return type1_encoding
+                .get(character as usize)
+                .cloned()
+                .filter(|&glyph_id| glyph_id != 0);
+        }
+        loader::glyph_for_unmapped_char(self, character)
     }
 
     /// Returns the glyph ID for the specified glyph name.
@@ -446,140 +612,195 @@ impl Font {
                 return Err(GlyphLoadingError::NoSuchGlyph);
             }
 
-            let outline = &(*(*self.freetype_face).glyph).outline;
-            if outline.n_contours == 0 {
-                return Ok(());
+            // Scale hinted outlines back to font units.
+            self.send_loaded_outline_to_sink(sink, |point| {
+                let point = point.ft_fixed_26_6_to_f32();
+                match grid_fitting_size {
+                    Some(size) => point * (units_per_em as f32) / size,
+                    None => point,
+                }
+            });
+
+            if grid_fitting_size.is_some() {
+                reset_freetype_face_char_size(self.freetype_face)
+            }
+        }
+        Ok(())
+    }
+
+    /// Sends the grid-fitted vector path for a glyph at the given point size to a path builder.
+    ///
+    /// Unlike `outline()`, which scales hinted outlines back to font units, the points are emitted
+    /// in pixels at `point_size`, exactly as FreeType's grid fitting left them, so that a vector
+    /// backend can render them directly and still benefit from hinting. The hinting mode is taken
+    /// from `hinting`; the size it carries is ignored in favor of `point_size`. With
+    /// `HintingOptions::None`, the outline is merely scaled.
+    pub fn hinted_outline<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        unsafe {
+            let load_flags = self.hinting_and_rasterization_options_to_load_flags(
+                hinting,
+                RasterizationOptions::GrayscaleAa,
+            );
+            assert_eq!(
+                FT_Set_Char_Size(
+                    self.freetype_face,
+                    point_size.f32_to_ft_fixed_26_6(),
+                    0,
+                    0,
+                    0
+                ),
+                0
+            );
+            let result = if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
+                Err(GlyphLoadingError::NoSuchGlyph)
+            } else {
+                // Don't round to whole pixels, which would undo the subpixel precision of the
+                // grid-fitted points.
+                self.send_loaded_outline_to_sink(sink, |point| point.to_f32() * (1.0 / 64.0));
+                Ok(())
+            };
+            reset_freetype_face_char_size(self.freetype_face);
+            result
+        }
+    }
+
+    // Walks the outline of the glyph in the face's glyph slot, converting every point from 26.6
+    // fixed point with `convert_point`.
+    unsafe fn send_loaded_outline_to_sink<S, F>(&self, sink: &mut S, convert_point: F)
+    where
+        S: OutlineSink,
+        F: Fn(Vector2I) -> Vector2F,
+    {
+        let outline = &(*(*self.freetype_face).glyph).outline;
+        if outline.n_contours == 0 {
+            return;
+        }
+        let contours = slice::from_raw_parts(outline.contours, outline.n_contours as usize);
+        let point_positions = slice::from_raw_parts(outline.points, outline.n_points as usize);
+        let point_tags = slice::from_raw_parts(outline.tags, outline.n_points as usize);
+
+        let mut current_point_index = 0;
+        for &last_point_index_in_contour in contours {
+            let last_point_index_in_contour = last_point_index_in_contour as usize;
+            let (mut first_point, first_tag) = get_point(
+                &mut current_point_index,
+                point_positions,
+                point_tags,
+                last_point_index_in_contour,
+                &convert_point,
+            );
+            if (first_tag & FT_POINT_TAG_ON_CURVE) == 0 {
+                // Rare, but can happen; e.g. with Inconsolata (see pathfinder#84).
+                //
+                // FIXME(pcwalton): I'm not sure this is right.
+                let mut temp_point_index = last_point_index_in_contour;
+                let (last_point, last_tag) = get_point(
+                    &mut temp_point_index,
+                    point_positions,
+                    point_tags,
+                    last_point_index_in_contour,
+                    &convert_point,
+                );
+                if (last_tag & FT_POINT_TAG_ON_CURVE) != 0 {
+                    first_point = last_point
+                } else {
+                    first_point = last_point.lerp(first_point, 0.5)
+                }
+                // Back up so we properly process the first point as a control point.
+                current_point_index -= 1;
             }
-            let contours = slice::from_raw_parts(outline.contours, outline.n_contours as usize);
-            let point_positions = slice::from_raw_parts(outline.points, outline.n_points as usize);
-            let point_tags = slice::from_raw_parts(outline.tags, outline.n_points as usize);
-
-            let mut current_point_index = 0;
-            for &last_point_index_in_contour in contours {
-                let last_point_index_in_contour = last_point_index_in_contour as usize;
-                let (mut first_point, first_tag) = get_point(
+            sink.move_to(first_point);
+
+            while current_point_index <= last_point_index_in_contour {
+                let (mut point0, tag0) = get_point(
                     &mut current_point_index,
                     point_positions,
                     point_tags,
                     last_point_index_in_contour,
-                    grid_fitting_size,
-                    units_per_em,
+                    &convert_point,
                 );
-                if (first_tag & FT_POINT_TAG_ON_CURVE) == 0 {
-                    // Rare, but can happen; e.g. with Inconsolata (see pathfinder#84).
-                    //
-                    // FIXME(pcwalton): I'm not sure this is right.
-                    let mut temp_point_index = last_point_index_in_contour;
-                    let (last_point, last_tag) = get_point(
-                        &mut temp_point_index,
-                        point_positions,
-                        point_tags,
-                        last_point_index_in_contour,
-                        grid_fitting_size,
-                        units_per_em,
-                    );
-                    if (last_tag & FT_POINT_TAG_ON_CURVE) != 0 {
-                        first_point = last_point
-                    } else {
-                        first_point = last_point.lerp(first_point, 0.5)
-                    }
-                    // Back up so we properly process the first point as a control point.
-                    current_point_index -= 1;
+                if (tag0 & FT_POINT_TAG_ON_CURVE) != 0 {
+                    sink.line_to(point0);
+                    continue;
                 }
-                sink.move_to(first_point);
 
-                while current_point_index <= last_point_index_in_contour {
-                    let (mut point0, tag0) = get_point(
+                loop {
+                    if current_point_index > last_point_index_in_contour {
+                        // The *last* point in the contour is off the curve. So we just need to
+                        // close the contour with a quadratic Bézier curve.
+                        sink.quadratic_curve_to(point0, first_point);
+                        break;
+                    }
+
+                    let (point1, tag1) = get_point(
                         &mut current_point_index,
                         point_positions,
                         point_tags,
                         last_point_index_in_contour,
-                        grid_fitting_size,
-                        units_per_em,
+                        &convert_point,
                     );
-                    if (tag0 & FT_POINT_TAG_ON_CURVE) != 0 {
-                        sink.line_to(point0);
-                        continue;
-                    }
-
-                    loop {
-                        if current_point_index > last_point_index_in_contour {
-                            // The *last* point in the contour is off the curve. So we just need to
-                            // close the contour with a quadratic Bézier curve.
-                            sink.quadratic_curve_to(point0, first_point);
-                            break;
-                        }
-
-                        let (point1, tag1) = get_point(
-                            &mut current_point_index,
-                            point_positions,
-                            point_tags,
-                            last_point_index_in_contour,
-                            grid_fitting_size,
-                            units_per_em,
-                        );
-
-                        if (tag0 & FT_POINT_TAG_CUBIC_CONTROL) != 0 {
-                            let ctrl = LineSegment2F::new(point0, point1);
-                            if current_point_index <= last_point_index_in_contour {
-                                // FIXME(pcwalton): Can we have implied on-curve points for cubic
-                                // control points too?
-                                let (point2, _) = get_point(
-                                    &mut current_point_index,
-                                    point_positions,
-                                    point_tags,
-                                    last_point_index_in_contour,
-                                    grid_fitting_size,
-                                    units_per_em,
-                                );
-                                sink.cubic_curve_to(ctrl, point2);
-                            } else {
-                                // Last point on the contour. Use first_point as point2.
-                                sink.cubic_curve_to(ctrl, first_point);
-                            }
-                            break;
-                        }
 
-                        if (tag1 & FT_POINT_TAG_ON_CURVE) != 0 {
-                            sink.quadratic_curve_to(point0, point1);
-                            break;
+                    if (tag0 & FT_POINT_TAG_CUBIC_CONTROL) != 0 {
+                        let ctrl = LineSegment2F::new(point0, point1);
+                        if current_point_index <= last_point_index_in_contour {
+                            // FIXME(pcwalton): Can we have implied on-curve points for cubic
+                            // control points too?
+                            let (point2, _) = get_point(
+                                &mut current_point_index,
+                                point_positions,
+                                point_tags,
+                                last_point_index_in_contour,
+                                &convert_point,
+                            );
+                            sink.cubic_curve_to(ctrl, point2);
+                        } else {
+                            // Last point on the contour. Use first_point as point2.
+                            sink.cubic_curve_to(ctrl, first_point);
                         }
+                        break;
+                    }
 
-                        // We have an implied on-curve point midway between the two consecutive
-                        // off-curve points.
-                        let point_half = point0.lerp(point1, 0.5);
-                        sink.quadratic_curve_to(point0, point_half);
-                        point0 = point1;
+                    if (tag1 & FT_POINT_TAG_ON_CURVE) != 0 {
+                        sink.quadratic_curve_to(point0, point1);
+                        break;
                     }
-                }
-                sink.close();
-            }
 
-            if hinting.grid_fitting_size().is_some() {
-                reset_freetype_face_char_size(self.freetype_face)
+                    // We have an implied on-curve point midway between the two consecutive
+                    // off-curve points.
+                    let point_half = point0.lerp(point1, 0.5);
+                    sink.quadratic_curve_to(point0, point_half);
+                    point0 = point1;
+                }
             }
+            sink.close();
         }
 
-        return Ok(());
-
-        fn get_point(
+        fn get_point<F>(
             current_point_index: &mut usize,
             point_positions: &[FT_Vector],
             point_tags: &[c_char],
             last_point_index_in_contour: usize,
-            grid_fitting_size: Option<f32>,
-            units_per_em: u16,
-        ) -> (Vector2F, c_char) {
+            convert_point: &F,
+        ) -> (Vector2F, c_char)
+        where
+            F: Fn(Vector2I) -> Vector2F,
+        {
             assert!(*current_point_index <= last_point_index_in_contour);
             let point_position = point_positions[*current_point_index];
             let point_tag = point_tags[*current_point_index];
             *current_point_index += 1;
 
             let point_position = Vector2I::new(point_position.x as i32, point_position.y as i32);
-            let mut point_position = point_position.ft_fixed_26_6_to_f32();
-            if let Some(grid_fitting_size) = grid_fitting_size {
-                point_position = point_position * (units_per_em as f32) / grid_fitting_size;
-            }
+            let point_position = convert_point(point_position);
 
             (point_position, point_tag)
         }
@@ -591,7 +812,7 @@ impl Font {
             if FT_Load_Glyph(
                 self.freetype_face,
                 glyph_id,
-                FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING,
+                FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING | self.load_flags,
             ) != 0
             {
                 return Err(GlyphLoadingError::NoSuchGlyph);
@@ -616,7 +837,7 @@ impl Font {
             if FT_Load_Glyph(
                 self.freetype_face,
                 glyph_id,
-                FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING,
+                FT_LOAD_DEFAULT | FT_LOAD_NO_HINTING | self.load_flags,
             ) != 0
             {
                 return Err(GlyphLoadingError::NoSuchGlyph);
@@ -658,15 +879,27 @@ impl Font {
                 underline_thickness: underline_thickness as f32,
                 cap_height: os2_table
                     .map(|table| (*table).sCapHeight as f32)
-                    .unwrap_or(0.0),
+                    .filter(|&cap_height| cap_height != 0.0)
+                    .unwrap_or_else(|| self.measured_glyph_height('H')),
                 x_height: os2_table
                     .map(|table| (*table).sxHeight as f32)
-                    .unwrap_or(0.0),
+                    .filter(|&x_height| x_height != 0.0)
+                    .unwrap_or_else(|| self.measured_glyph_height('x')),
                 bounding_box: bounding_box.to_f32(),
             }
         }
     }
 
+    // Measures how far the outline of `character` rises above the baseline, for fonts whose
+    // `OS/2` table doesn't record the cap height or x-height. Returns 0 if the font has no glyph
+    // for the character.
+    fn measured_glyph_height(&self, character: char) -> f32 {
+        self.glyph_for_char(character)
+            .and_then(|glyph_id| self.typographic_bounds(glyph_id).ok())
+            .map(|bounds| bounds.max_y().max(0.0))
+            .unwrap_or(0.0)
+    }
+
     /// Returns true if and only if the font loader can perform hinting in the requested way.
     ///
     /// Some APIs support only rasterizing glyphs with hinting, not retrieving hinted outlines. If
@@ -690,6 +923,28 @@ impl Font {
         }
     }
 
+    /// Returns how many horizontal positions within each pixel `rasterize_glyph()` can place a
+    /// glyph at with the given options, or `None` if it places glyphs exactly where asked.
+    ///
+    /// The fractional part of the translation in the transform passed to `rasterize_glyph()` and
+    /// related functions moves the glyph by part of a pixel. When this returns `Some(n)`, both
+    /// components of the translation are first rounded to the nearest multiple of `1/n` pixel,
+    /// ties going to the even multiple, so the glyph lands at one of only `n` positions per
+    /// pixel. A glyph cache that quantizes origins to a number of positions that divides `n`
+    /// asks only for positions the rasterizer reproduces exactly, so nothing is rounded twice.
+    ///
+    /// FreeType positions outlines in 26.6 fixed point, so glyphs can take 64 positions per pixel,
+    /// except with `StableGrayscaleAa`, which doesn't use FreeType.
+    #[inline]
+    pub fn subpixel_positions(&self, rasterization_options: RasterizationOptions) -> Option<u32> {
+        match rasterization_options {
+            RasterizationOptions::StableGrayscaleAa => None,
+            RasterizationOptions::Bilevel
+            | RasterizationOptions::GrayscaleAa
+            | RasterizationOptions::SubpixelAa => Some(FREETYPE_SUBPIXEL_POSITIONS),
+        }
+    }
+
     fn get_type_1_or_sfnt_name(&self, type_1_id: u32, sfnt_id: u16) -> Option<String> {
         unsafe {
             let ps_value_size =
@@ -786,6 +1041,19 @@ impl Font {
     /// loader.
     ///
     /// If `hinting_options` is not None, the requested grid fitting is performed.
+    /// The FreeType loader grid-fits at the size the glyph is drawn at along each axis when
+    /// `transform` scales the two axes differently, as for artificially condensed or extended
+    /// text; with rotations and skews, it grid-fits at `point_size` before transforming.
+    ///
+    /// The translation in `transform` may place the glyph between pixels;
+    /// `subpixel_positions()` tells how precisely.
+    ///
+    /// The canvas is not cleared first: the pixels covered by the glyph's bitmap are overwritten
+    /// and the rest of the canvas is left untouched. When reusing a canvas, use `Canvas::clear()`
+    /// or `clear_and_rasterize_glyph()` to avoid seeing a previous glyph.
+    ///
+    /// `Sdf` canvases receive a signed distance field with the default spread, as
+    /// `rasterize_glyph_sdf()` generates it, and `rasterization_options` is ignored.
     pub fn rasterize_glyph(
         &self,
         canvas: &mut Canvas,
@@ -795,44 +1063,29 @@ impl Font {
         hinting_options: HintingOptions,
         rasterization_options: RasterizationOptions,
     ) -> Result<(), GlyphLoadingError> {
+        if canvas.format == Format::Sdf {
+            return <Self as Loader>::rasterize_glyph_sdf(
+                self,
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                DEFAULT_SDF_SPREAD,
+            );
+        }
+        if rasterization_options == RasterizationOptions::StableGrayscaleAa {
+            return loader::rasterize_glyph_stable(self, canvas, glyph_id, point_size, transform);
+        }
         // TODO(pcwalton): This is woefully incomplete. See WebRender's code for a more complete
         // implementation.
         unsafe {
-            let matrix = transform.matrix.0 * F32x4::new(65536.0, -65536.0, -65536.0, 65536.0);
-            let matrix = matrix.to_i32x4();
-            let vector = transform.vector.f32_to_ft_fixed_26_6();
-
-            let mut delta = FT_Vector {
-                x: vector.x() as FT_Pos,
-                y: -vector.y() as FT_Pos,
-            };
-            let mut ft_shape = FT_Matrix {
-                xx: matrix.x() as FT_Fixed,
-                xy: matrix.y() as FT_Fixed,
-                yx: matrix.z() as FT_Fixed,
-                yy: matrix.w() as FT_Fixed,
-            };
-            FT_Set_Transform(self.freetype_face, &mut ft_shape, &mut delta);
-
-            assert_eq!(
-                FT_Set_Char_Size(
-                    self.freetype_face,
-                    point_size.f32_to_ft_fixed_26_6(),
-                    0,
-                    0,
-                    0
-                ),
-                0
-            );
-
             let mut load_flags = FT_LOAD_DEFAULT | FT_LOAD_RENDER;
             load_flags |= self.hinting_and_rasterization_options_to_load_flags(
                 hinting_options,
                 rasterization_options,
             );
-            if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
-                return Err(GlyphLoadingError::NoSuchGlyph);
-            }
+            self.load_transformed_glyph(glyph_id, point_size, transform, load_flags)?;
 
             // TODO(pcwalton): Use the FreeType "direct" API to save a copy here. Note that we will
             // need to keep this around for bilevel rendering, as the direct API doesn't work with
@@ -873,6 +1126,22 @@ impl Font {
                     FT_PIXEL_MODE_MONO => {
                         canvas.blit_from_bitmap_1bpp(dst_point, buffer, bitmap_size, bitmap_stride);
                     }
+                    FT_PIXEL_MODE_BGRA => {
+                        // Color glyphs, loaded with `FT_LOAD_COLOR`, come as premultiplied BGRA.
+                        let row_length = bitmap_width as usize * 4;
+                        let pixels: Vec<u8> = buffer
+                            .chunks(bitmap_stride)
+                            .flat_map(|row| row[..row_length].chunks(4))
+                            .flat_map(|bgra| [bgra[2], bgra[1], bgra[0], bgra[3]])
+                            .collect();
+                        canvas.blit_from(
+                            dst_point,
+                            &pixels,
+                            bitmap_size,
+                            row_length,
+                            Format::Rgba32,
+                        );
+                    }
                     _ => panic!("Unexpected FreeType pixel mode!"),
                 }
             }
@@ -883,6 +1152,216 @@ impl Font {
         }
     }
 
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, reusing the scratch buffers in
+    /// `context` instead of allocating new ones.
+    ///
+    /// The output is the same as that of `rasterize_glyph()`; only the allocation behavior
+    /// differs. Keep one context around for loops that rasterize many glyphs, such as filling a
+    /// glyph atlas, so that they don't allocate a bitmap per glyph.
+    #[allow(clippy::too_many_arguments)]
+    pub fn rasterize_glyph_with_context(
+        &self,
+        context: &mut RasterizeContext,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        if canvas.format == Format::Sdf {
+            return <Self as Loader>::rasterize_glyph_sdf(
+                self,
+                canvas,
+                glyph_id,
+                point_size,
+                transform,
+                hinting_options,
+                DEFAULT_SDF_SPREAD,
+            );
+        }
+        // `FT_LOAD_RENDER` makes FreeType allocate a fresh bitmap for the glyph slot every time.
+        // Instead, load the outline alone and render it into our own buffer with
+        // `FT_Outline_Get_Bitmap()`. LCD rendering needs FreeType's filtering, which only the
+        // glyph slot renderer applies, so leave that to `rasterize_glyph()`, along with stable
+        // rendering, which doesn't use FreeType at all, and color glyphs, which only the glyph slot
+        // renderer composites.
+        let pixel_mode = match (hinting_options, rasterization_options) {
+            _ if self.load_flags & FT_LOAD_COLOR != 0 => {
+                return self.rasterize_glyph(
+                    canvas,
+                    glyph_id,
+                    point_size,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+            }
+            (_, RasterizationOptions::StableGrayscaleAa)
+            | (HintingOptions::VerticalSubpixel(_), _)
+            | (_, RasterizationOptions::SubpixelAa) => {
+                return self.rasterize_glyph(
+                    canvas,
+                    glyph_id,
+                    point_size,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+            }
+            (_, RasterizationOptions::Bilevel) => FT_PIXEL_MODE_MONO,
+            (_, RasterizationOptions::GrayscaleAa) => FT_PIXEL_MODE_GRAY,
+        };
+
+        unsafe {
+            let load_flags = FT_LOAD_DEFAULT
+                | self.hinting_and_rasterization_options_to_load_flags(
+                    hinting_options,
+                    rasterization_options,
+                );
+            self.load_transformed_glyph(glyph_id, point_size, transform, load_flags)?;
+
+            let glyph_slot = (*self.freetype_face).glyph;
+            if (*glyph_slot).format != FT_GLYPH_FORMAT_OUTLINE {
+                // Embedded bitmap strikes have nothing to render.
+                FT_Set_Transform(self.freetype_face, ptr::null_mut(), ptr::null_mut());
+                reset_freetype_face_char_size(self.freetype_face);
+                return self.rasterize_glyph(
+                    canvas,
+                    glyph_id,
+                    point_size,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                );
+            }
+
+            // Snap the control box outward to whole pixels, as FreeType's own renderers do.
+            let outline = &mut (*glyph_slot).outline;
+            let mut cbox = FT_BBox::default();
+            FT_Outline_Get_CBox(outline, &mut cbox);
+            let (x_min, y_min) = (cbox.xMin & !63, cbox.yMin & !63);
+            let (x_max, y_max) = ((cbox.xMax + 63) & !63, (cbox.yMax + 63) & !63);
+            let bitmap_size =
+                Vector2I::new(((x_max - x_min) >> 6) as i32, ((y_max - y_min) >> 6) as i32);
+
+            if bitmap_size.x() > 0 && bitmap_size.y() > 0 {
+                let stride = if pixel_mode == FT_PIXEL_MODE_MONO {
+                    (bitmap_size.x() as usize).div_ceil(8)
+                } else {
+                    bitmap_size.x() as usize
+                };
+                context.buffer.clear();
+                context.buffer.resize(stride * bitmap_size.y() as usize, 0);
+
+                let bitmap = FT_Bitmap {
+                    rows: bitmap_size.y(),
+                    width: bitmap_size.x(),
+                    pitch: stride as i32,
+                    buffer: context.buffer.as_mut_ptr(),
+                    num_grays: if pixel_mode == FT_PIXEL_MODE_MONO {
+                        2
+                    } else {
+                        256
+                    },
+                    pixel_mode: pixel_mode as c_char,
+                    palette_mode: 0,
+                    palette: ptr::null_mut(),
+                };
+                FT_Outline_Translate(outline, -x_min, -y_min);
+                let error = FT_Outline_Get_Bitmap((*glyph_slot).library, outline, &bitmap);
+                if error == 0 {
+                    let dst_point = Vector2I::new((x_min >> 6) as i32, -(y_max >> 6) as i32);
+                    if pixel_mode == FT_PIXEL_MODE_MONO {
+                        canvas.blit_from_bitmap_1bpp(
+                            dst_point,
+                            &context.buffer,
+                            bitmap_size,
+                            stride,
+                        );
+                    } else {
+                        canvas.blit_from(
+                            dst_point,
+                            &context.buffer,
+                            bitmap_size,
+                            stride,
+                            Format::A8,
+                        );
+                    }
+                }
+            }
+
+            FT_Set_Transform(self.freetype_face, ptr::null_mut(), ptr::null_mut());
+            reset_freetype_face_char_size(self.freetype_face);
+            Ok(())
+        }
+    }
+
+    // Sets up the transform and size for a rasterization and loads the glyph into the face's
+    // glyph slot. On failure, the face is reset before returning.
+    unsafe fn load_transformed_glyph(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        load_flags: i32,
+    ) -> Result<(), GlyphLoadingError> {
+        // FreeType hints glyphs at the character size and only then applies the transform, so
+        // hinting at `point_size` and stretching the result would snap stems to the wrong grid.
+        // Fold axis-aligned scales, including non-uniform ones, into separate horizontal and
+        // vertical character sizes instead. Other transforms, such as rotations and skews, are
+        // still applied after hinting at `point_size`.
+        let (matrix, char_size) = match transform.matrix {
+            matrix
+                if FT_IS_SCALABLE(self.freetype_face)
+                    && matrix.m12() == 0.0
+                    && matrix.m21() == 0.0
+                    && matrix.m11() != 0.0
+                    && matrix.m22() != 0.0 =>
+            {
+                let scale = Vector2F::new(matrix.m11(), matrix.m22());
+                let signs = Vector2F::new(scale.x().signum(), scale.y().signum());
+                (Matrix2x2F::from_scale(signs), scale * signs * point_size)
+            }
+            matrix => (matrix, Vector2F::splat(point_size)),
+        };
+        let matrix = matrix.0 * F32x4::new(65536.0, -65536.0, -65536.0, 65536.0);
+        let matrix = matrix.to_i32x4();
+        // This rounds the translation to the nearest 1/64 pixel, as `subpixel_positions()`
+        // documents.
+        let vector = transform.vector.f32_to_ft_fixed_26_6();
+
+        let mut delta = FT_Vector {
+            x: vector.x() as FT_Pos,
+            y: -vector.y() as FT_Pos,
+        };
+        let mut ft_shape = FT_Matrix {
+            xx: matrix.x() as FT_Fixed,
+            xy: matrix.y() as FT_Fixed,
+            yx: matrix.z() as FT_Fixed,
+            yy: matrix.w() as FT_Fixed,
+        };
+        FT_Set_Transform(self.freetype_face, &mut ft_shape, &mut delta);
+
+        assert_eq!(
+            FT_Set_Char_Size(
+                self.freetype_face,
+                char_size.x().f32_to_ft_fixed_26_6(),
+                char_size.y().f32_to_ft_fixed_26_6(),
+                0,
+                0
+            ),
+            0
+        );
+
+        if FT_Load_Glyph(self.freetype_face, glyph_id, load_flags) != 0 {
+            FT_Set_Transform(self.freetype_face, ptr::null_mut(), ptr::null_mut());
+            reset_freetype_face_char_size(self.freetype_face);
+            return Err(GlyphLoadingError::NoSuchGlyph);
+        }
+        Ok(())
+    }
+
     fn hinting_and_rasterization_options_to_load_flags(
         &self,
         hinting: HintingOptions,
@@ -901,7 +1380,7 @@ impl Font {
         if rasterization == RasterizationOptions::Bilevel {
             options |= FT_LOAD_MONOCHROME
         }
-        options
+        options | self.load_flags
     }
 
     /// Returns a handle to this font, if possible.
@@ -916,8 +1395,13 @@ impl Font {
     ///
     /// If this font is a member of a collection, this function returns the data for the entire
     /// collection.
+    ///
+    /// Fonts loaded with `from_shared_bytes()` have their data copied into a new vector.
     pub fn copy_font_data(&self) -> Option<Arc<Vec<u8>>> {
-        Some(self.font_data.clone())
+        match self.font_data {
+            FontData::Vec(ref font_data) => Some(font_data.clone()),
+            FontData::Shared(ref font_data) => Some(Arc::new((**font_data).as_ref().to_vec())),
+        }
     }
 
     /// Get font fallback results for the given text and locale.
@@ -966,31 +1450,708 @@ impl Font {
             Some(buf)
         }
     }
-}
 
-impl Clone for Font {
-    fn clone(&self) -> Font {
+    /// Returns the sizes, in pixels per em, of the bitmap strikes embedded in this font, sorted
+    /// in ascending order.
+    ///
+    /// Bitmap-only fonts render well only at these sizes, so callers may want to snap the
+    /// requested size to one of them. Returns an empty vector for purely scalable fonts.
+    pub fn available_sizes(&self) -> Vec<f32> {
         unsafe {
-            assert_eq!(FT_Reference_Face(self.freetype_face), 0);
-            Font {
-                freetype_face: self.freetype_face,
-                font_data: self.font_data.clone(),
+            let num_fixed_sizes = (*self.freetype_face).num_fixed_sizes as usize;
+            if num_fixed_sizes == 0 || (*self.freetype_face).available_sizes.is_null() {
+                return vec![];
             }
+            let available_sizes =
+                slice::from_raw_parts((*self.freetype_face).available_sizes, num_fixed_sizes);
+            let mut sizes: Vec<f32> = available_sizes
+                .iter()
+                .map(|size| match size.y_ppem {
+                    0 => size.height as f32,
+                    y_ppem => y_ppem as f32 / 64.0,
+                })
+                .collect();
+            sizes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+            sizes.dedup();
+            sizes
         }
     }
-}
 
-impl Drop for Font {
-    fn drop(&mut self) {
-        // The AccessError can be ignored, as it means FREETYPE_LIBRARY has already been
-        // destroyed, and it already destroys all FreeType resources.
-        // https://freetype.org/freetype2/docs/reference/ft2-module_management.html#ft_done_library
-        let _ = FREETYPE_LIBRARY.try_with(|freetype_library| unsafe {
-            if !freetype_library.0.is_null() && !self.freetype_face.is_null() {
-                assert_eq!(FT_Done_Face(self.freetype_face), 0);
-            }
-        });
-    }
+    /// Returns the components of a composite glyph: the glyphs it references and the transform
+    /// applied to each.
+    ///
+    /// Returns `None` for simple glyphs, for glyphs that don't exist, and for fonts without
+    /// TrueType (`glyf`) outlines.
+    #[inline]
+    pub fn glyph_components(&self, glyph_id: u32) -> Option<Vec<GlyphComponent>> {
+        <Self as Loader>::glyph_components(self, glyph_id)
+    }
+
+    /// Clears the region of the canvas that the glyph will cover, then rasterizes the glyph into
+    /// it as `rasterize_glyph()` does.
+    ///
+    /// This prevents pixels left over from a previous rasterization from showing through the new
+    /// glyph, without the cost of clearing the entire canvas.
+    #[inline]
+    pub fn clear_and_rasterize_glyph(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::clear_and_rasterize_glyph(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the horizontal advance of every glyph, indexed by glyph ID, straight from the
+    /// `hmtx` table.
+    ///
+    /// Values are in font units. This is much faster than calling `advance()` for each glyph.
+    /// Returns an empty vector if the font has no `hmtx` table.
+    #[inline]
+    pub fn all_horizontal_advances(&self) -> Vec<u16> {
+        <Self as Loader>::all_horizontal_advances(self)
+    }
+
+    /// Returns the vertical advance of every glyph, indexed by glyph ID, straight from the
+    /// `vmtx` table.
+    ///
+    /// Values are in font units. Returns an empty vector if the font has no vertical metrics.
+    #[inline]
+    pub fn all_vertical_advances(&self) -> Vec<u16> {
+        <Self as Loader>::all_vertical_advances(self)
+    }
+
+    /// Returns the usual glyph ID for a Unicode character, or the `.notdef` glyph (ID 0) if the
+    /// font doesn't cover it.
+    ///
+    /// This matches what the platform renderers draw for uncovered characters. The same caveats
+    /// as for `glyph_for_char()` apply.
+    #[inline]
+    pub fn glyph_for_char_or_notdef(&self, character: char) -> u32 {
+        <Self as Loader>::glyph_for_char_or_notdef(self, character)
+    }
+
+    /// Converts user-space variation axis values, such as `wght=550`, to the normalized
+    /// coordinates that a variable font's deltas are blended with.
+    ///
+    /// `coordinates` pairs OpenType axis tags with values; axes not mentioned stay at their
+    /// defaults. The result has one entry per axis, in the order of the font's `fvar` table.
+    /// Values are clamped to each axis's range, normalized to [-1, 1], and then run through the
+    /// `avar` segment maps if the font has them, so that the axis responds nonlinearly as its
+    /// designer intended. Any custom blending code should use these coordinates rather than
+    /// normalizing linearly itself. Returns an empty vector for fonts that aren't variable.
+    #[inline]
+    pub fn normalized_variation_coordinates(&self, coordinates: &[(u32, f32)]) -> Vec<f32> {
+        <Self as Loader>::normalized_variation_coordinates(self, coordinates)
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, choosing whether `Rgba32`
+    /// output uses premultiplied or straight alpha.
+    ///
+    /// `rasterize_glyph()` always produces premultiplied alpha. If `premultiplied_alpha` is false
+    /// and the canvas is `Rgba32`, the glyph is drawn as `clear_and_rasterize_glyph()` draws it,
+    /// clearing the region it covers first, and the pixels of that region are then converted to
+    /// straight alpha, which is what compositing in linear space usually wants. Other canvas
+    /// formats have no separate alpha channel and are unaffected.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn rasterize_glyph_with_alpha(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        premultiplied_alpha: bool,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_with_alpha(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            premultiplied_alpha,
+        )
+    }
+
+    /// Returns the fill rule that this font's glyph outlines are designed to be filled with.
+    ///
+    /// Both TrueType (`glyf`) and PostScript (CFF) outlines use the nonzero winding rule, so
+    /// contours may overlap and are still filled correctly; this method exists so that
+    /// tessellators don't have to assume.
+    #[inline]
+    pub fn fill_rule(&self) -> FillRule {
+        <Self as Loader>::fill_rule(self)
+    }
+
+    /// Returns true if the font marks the glyph as containing overlapping contours or components,
+    /// via the TrueType `OVERLAP_SIMPLE` or `OVERLAP_COMPOUND` flags.
+    ///
+    /// Variable fonts set these flags so that rasterizers know to avoid overlap artifacts. A false
+    /// result does not guarantee that the outline is free of overlaps: fonts are not required to
+    /// set the flags, and CFF fonts have no equivalent.
+    #[inline]
+    pub fn glyph_has_overlap_flag(&self, glyph_id: u32) -> bool {
+        <Self as Loader>::glyph_has_overlap_flag(self, glyph_id)
+    }
+
+    /// Returns the ten PANOSE classification digits from the `OS/2` table.
+    ///
+    /// See the `classification` module for help decoding them. Returns `None` if the font has no
+    /// `OS/2` table or the table is too short.
+    #[inline]
+    pub fn panose(&self) -> Option<[u8; 10]> {
+        <Self as Loader>::panose(self)
+    }
+
+    /// Returns the legacy code pages that the font claims to be functional for, from the `OS/2`
+    /// table.
+    ///
+    /// Returns `None` if the font has no `OS/2` table or the table predates these fields.
+    #[inline]
+    pub fn code_page_ranges(&self) -> Option<CodePageRanges> {
+        <Self as Loader>::code_page_ranges(self)
+    }
+
+    /// Builds a new font containing only the given glyphs, for embedding in documents such as
+    /// PDFs.
+    ///
+    /// The subset also contains `.notdef` and every glyph that the requested composite glyphs are
+    /// built from. Glyphs are renumbered in ascending order of their original IDs, with `.notdef`
+    /// staying at 0; the `cmap`, `hmtx`, `loca`, and `glyf` tables are rebuilt to match, and glyph
+    /// names and layout tables are dropped. Glyph IDs outside the font are ignored.
+    ///
+    /// Only TrueType (`glyf`) outlines are supported; fonts with CFF outlines return
+    /// `FontLoadingError::UnknownFormat`.
+    #[inline]
+    pub fn subset(&self, glyph_ids: &[u32]) -> Result<Vec<u8>, FontLoadingError> {
+        <Self as Loader>::subset(self, glyph_ids)
+    }
+
+    /// Returns the name of every glyph, indexed by glyph ID, from the `post` table.
+    ///
+    /// Version 1.0 tables use the standard Macintosh glyph names and version 2.0 tables list names
+    /// explicitly. Glyphs without a name, including every glyph of a font with a version 3.0
+    /// table or no table at all, are `None`; fonts with CFF outlines usually fall in this
+    /// category. This is much faster than looking names up one glyph at a time.
+    #[inline]
+    pub fn glyph_names(&self) -> Vec<Option<String>> {
+        <Self as Loader>::glyph_names(self)
+    }
+
+    /// Returns true if the glyph draws nothing, like the space glyph, so that rasterizing it can
+    /// be skipped.
+    ///
+    /// For TrueType fonts this is a cheap check for a zero-length `loca` entry. For other fonts,
+    /// the glyph is empty if it has no outline contours and no bitmap extent. Glyph IDs that
+    /// don't exist in the font are reported as empty.
+    #[inline]
+    pub fn glyph_is_empty(&self, glyph_id: u32) -> bool {
+        <Self as Loader>::glyph_is_empty(self, glyph_id)
+    }
+
+    /// Rasterizes a glyph to a canvas as `rasterize_glyph()` does, but leaves every pixel outside
+    /// `clip_rect` untouched.
+    ///
+    /// This keeps glyphs that spill out of their cell, such as a swash or a deep descender, from
+    /// bleeding into neighboring cells of a texture atlas. Inside `clip_rect`, the result is
+    /// exactly what `rasterize_glyph()` would have produced.
+    #[allow(clippy::too_many_arguments)]
+    #[inline]
+    pub fn rasterize_glyph_clipped(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+        clip_rect: RectI,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_clipped(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+            clip_rect,
+        )
+    }
+
+    /// Returns the paint graph of a color glyph from the `COLR` table, with colors taken from
+    /// palette `palette_index` of the `CPAL` table.
+    ///
+    /// `COLR` version 1 glyphs can use gradients, transforms, and compositing; glyphs that only
+    /// have a version 0 layer list are returned as a `Paint::Layers` of solid-colored glyphs.
+    /// Palette 0 is the default palette. Returns `None` if the glyph isn't a color glyph, if the
+    /// palette doesn't exist, or if the font's color tables are malformed.
+    #[inline]
+    pub fn glyph_paint(&self, glyph_id: u32, palette_index: u16) -> Option<Paint> {
+        <Self as Loader>::glyph_paint(self, glyph_id, palette_index)
+    }
+
+    /// Returns the metrics for laying out lines of text in this font at `point_size`.
+    ///
+    /// If the `OS/2` table sets the `USE_TYPO_METRICS` flag, its typographic ascender, descender,
+    /// and line gap are used; otherwise, the ascent, descent, and line gap come from `metrics()`.
+    #[inline]
+    pub fn line_metrics(&self, point_size: f32) -> LineMetrics {
+        <Self as Loader>::line_metrics(self, point_size)
+    }
+
+    /// Returns the advance width of a glyph at `ppem` pixels per em, in whole pixels, as recorded
+    /// in the `hdmx` table.
+    ///
+    /// TrueType fonts may tabulate the hinted advance of every glyph at common sizes so that
+    /// layout on the pixel grid can match what the hinter produces without running it. Returns
+    /// `None` if the font has no `hdmx` table or the table has no record for `ppem`.
+    #[inline]
+    pub fn device_advance(&self, glyph_id: u32, ppem: u8) -> Option<u8> {
+        <Self as Loader>::device_advance(self, glyph_id, ppem)
+    }
+
+    /// Rasterizes a glyph to a scratch buffer and summarizes its coverage, without keeping the
+    /// pixels.
+    ///
+    /// See `CoverageStats` for which pixels the statistics cover.
+    #[inline]
+    pub fn glyph_coverage_stats(&self, key: &GlyphKey) -> Result<CoverageStats, GlyphLoadingError> {
+        <Self as Loader>::glyph_coverage_stats(self, key)
+    }
+
+    /// Returns the height of `baseline` above the font's origin when setting text in `script`
+    /// horizontally, in font units, from the `BASE` table.
+    ///
+    /// This is how lines mixing scripts with different baselines, such as Latin and CJK, are
+    /// aligned. Returns `None` if the font has no `BASE` table or the table does not define that
+    /// baseline for the script.
+    #[inline]
+    pub fn baseline(&self, script: ScriptTag, baseline: BaselineTag) -> Option<i16> {
+        <Self as Loader>::baseline(self, script, baseline)
+    }
+
+    /// Returns the vector path for a glyph as an iterator of path commands.
+    ///
+    /// This is an alternative to `outline()` for callers that would rather pull commands than
+    /// implement `OutlineSink`. None of the platform APIs can produce an outline lazily, so the
+    /// whole path is collected before this returns.
+    #[inline]
+    pub fn outline_segments(
+        &self,
+        glyph_id: u32,
+        hinting_mode: HintingOptions,
+    ) -> Result<OutlineSegments, GlyphLoadingError> {
+        <Self as Loader>::outline_segments(self, glyph_id, hinting_mode)
+    }
+
+    /// Returns true if the font has a real glyph for a Unicode character.
+    ///
+    /// Some fonts map whole ranges of characters they don't cover to the `.notdef` glyph (ID 0),
+    /// which renders as a missing-glyph box; such characters are not covered. The same caveats as
+    /// for `glyph_for_char()` apply.
+    #[inline]
+    pub fn covers_char(&self, character: char) -> bool {
+        <Self as Loader>::covers_char(self, character)
+    }
+
+    /// Returns what the font's license allows documents that embed it to do, from the `fsType`
+    /// field of the `OS/2` table.
+    ///
+    /// Fonts without an `OS/2` table are treated as unrestricted.
+    #[inline]
+    pub fn embedding_restrictions(&self) -> EmbeddingRestrictions {
+        <Self as Loader>::embedding_restrictions(self)
+    }
+
+    /// Returns the points of each contour of a TrueType glyph as stored in the `glyf` table,
+    /// including whether each point is on or off the curve.
+    ///
+    /// Unlike `outline()`, no curves are constructed and no implied on-curve points are added, so
+    /// this is suited to tools that analyze how a font was drawn. Returns `None` for composite
+    /// glyphs (see `glyph_components()`), for glyphs that don't exist, and for fonts without
+    /// TrueType outlines, since CFF outlines have no quadratic point model. Glyphs without an
+    /// outline, such as the space, have no contours.
+    #[inline]
+    pub fn glyph_raw_points(&self, glyph_id: u32) -> Option<Vec<ContourPoints>> {
+        <Self as Loader>::glyph_raw_points(self, glyph_id)
+    }
+
+    /// Rasterizes a glyph in a solid color to an `Rgba32` canvas, producing premultiplied
+    /// pixels directly instead of coverage that has to be tinted afterward.
+    ///
+    /// The glyph's coverage is multiplied by `color`, which has straight alpha, and composited
+    /// over the canvas. With `SubpixelAa`, each channel is tinted by its own coverage. Returns
+    /// `GlyphLoadingError::Unsupported` if the canvas format isn't `Rgba32`.
+    ///
+    /// Color glyphs are drawn in their own colors instead, where this loader can draw them:
+    ///
+    /// * `COLR` glyphs made only of solid-colored layers, which includes every `COLR` version 0
+    ///   glyph, are drawn layer by layer with all loaders. Layers in the foreground color use
+    ///   `color`. Glyphs that need gradients, transforms, or compositing are drawn tinted.
+    ///
+    /// * `sbix` and `CBDT` bitmaps are drawn by the FreeType loader. The other loaders draw these
+    ///   glyphs tinted.
+    ///
+    /// Whether a glyph is a color glyph is decided glyph by glyph, so color fonts can mix color
+    /// and monochrome glyphs. With `key.color` set to `ColorPreference::Never`, color glyphs are
+    /// drawn tinted too. With `ColorPreference::Force`, glyphs without color data, or with color
+    /// data that this loader can't draw, return `GlyphLoadingError::Unsupported`.
+    #[inline]
+    pub fn rasterize_glyph_colored(
+        &self,
+        canvas: &mut Canvas,
+        key: &GlyphKey,
+        color: Color,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_colored(self, canvas, key, color)
+    }
+
+    /// Returns the format of this font's glyph shapes, judged by which tables are present.
+    ///
+    /// Fonts with more than one kind of outline table report the first of `glyf`, `CFF2`, and
+    /// `CFF `. Fonts with no outlines are `Bitmap` if they have bitmap strikes (see
+    /// `available_sizes()`) and `None` otherwise.
+    #[inline]
+    pub fn outline_format(&self) -> OutlineFormat {
+        <Self as Loader>::outline_format(self)
+    }
+
+    /// Rasterizes a short string on one line into a new canvas sized to fit it, for labels and
+    /// other simple text.
+    ///
+    /// Each character is mapped to a glyph with `glyph_for_char()`, falling back to `.notdef`
+    /// (glyph 0), and glyphs are placed left to right by their advances, adjusted by the pair
+    /// kerning in the font's `kern` table. This is not text shaping: there are no ligatures,
+    /// contextual forms, `GPOS` kerning or mark positioning, bidirectional reordering, or line
+    /// breaking, so it is only suitable for simple left-to-right scripts such as Latin.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise. It spans the line's ascent and
+    /// descent at `point_size`, widened to include any ink that falls outside them, and the pen
+    /// starts at the left edge of the line on the baseline. Returns
+    /// `GlyphLoadingError::InvalidCanvasSize` if the line is too large for a canvas.
+    #[inline]
+    pub fn rasterize_line(
+        &self,
+        text: &str,
+        point_size: f32,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<Canvas, GlyphLoadingError> {
+        <Self as Loader>::rasterize_line(
+            self,
+            text,
+            point_size,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns true if the font has a real glyph for every character in `chars`, as
+    /// `covers_char()` judges them.
+    ///
+    /// This stops at the first character that isn't covered. An empty run is covered.
+    #[inline]
+    pub fn covers_all<I>(&self, chars: I) -> bool
+    where
+        I: IntoIterator<Item = char>,
+    {
+        <Self as Loader>::covers_all(self, chars)
+    }
+
+    /// Returns the fraction of the characters in `chars` that the font has a real glyph for, as
+    /// `covers_char()` judges them, from 0.0 to 1.0.
+    ///
+    /// This is useful for ranking fallback fonts by how much of a run of text each one covers.
+    /// Repeated characters count each time they occur. An empty run has a ratio of 1.0.
+    #[inline]
+    pub fn coverage_ratio<I>(&self, chars: I) -> f32
+    where
+        I: IntoIterator<Item = char>,
+    {
+        <Self as Loader>::coverage_ratio(self, chars)
+    }
+
+    /// Returns true if the font has a `DSIG` table with at least one signature in it.
+    ///
+    /// The signature is not verified. Many fonts carry a `DSIG` table with no signatures, added
+    /// only because old versions of Windows required one; those fonts are not signed.
+    #[inline]
+    pub fn is_signed(&self) -> bool {
+        <Self as Loader>::is_signed(self)
+    }
+
+    /// Returns how far the glyph with the given ID extends above and below the baseline, in font
+    /// units, as an `(ascent, descent)` pair.
+    ///
+    /// Both values come from the glyph's outline bounding box, so they fit that glyph tightly,
+    /// unlike the font-wide `ascent` and `descent` metrics; this is useful for packing lines
+    /// closely around drop caps, badges, and the like. A descent below the baseline is positive.
+    /// Glyphs with no outline, such as spaces, have extents of zero.
+    #[inline]
+    pub fn glyph_vertical_extents(&self, glyph_id: u32) -> Result<(f32, f32), GlyphLoadingError> {
+        <Self as Loader>::glyph_vertical_extents(self, glyph_id)
+    }
+
+    /// Returns the font-wide metrics of a variable font at the given user-space variation axis
+    /// values, such as `wght=700`.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The deltas in the
+    /// font's `MVAR` table are applied to the ascent, descent, line gap, underline position and
+    /// thickness, cap height, and x-height of `metrics()`, which describes the default instance.
+    /// Fonts without an `MVAR` table return `metrics()` unchanged.
+    #[inline]
+    pub fn metrics_at_variation(&self, coordinates: &[(u32, f32)]) -> Metrics {
+        <Self as Loader>::metrics_at_variation(self, coordinates)
+    }
+
+    /// Returns the slant of the font's upright strokes, in degrees counterclockwise from the
+    /// vertical, from the `post` table.
+    ///
+    /// Italic and oblique fonts that lean right have negative angles, and upright fonts 0. Fonts
+    /// without a `post` table report 0. Together with `properties().style`, this tells whether
+    /// an italic needs to be synthesized, and the angle to shear the upright face by if so.
+    #[inline]
+    pub fn italic_angle(&self) -> f32 {
+        <Self as Loader>::italic_angle(self)
+    }
+
+    /// Generates a signed distance field for a glyph on an `Sdf` canvas, with the given size and
+    /// transform.
+    ///
+    /// The field is computed from the glyph's outline, hinted as `hinting_options` requests, so
+    /// glyphs without outlines (such as bitmap glyphs) leave the canvas unchanged. `spread` is the
+    /// distance in pixels from the outline at which values reach 0 outside the glyph and 255
+    /// inside it; larger spreads allow wider effects such as outlines and glows, at the cost of
+    /// precision. Pixels take the larger of their old and new values, so several glyphs can share
+    /// a canvas. Returns `GlyphLoadingError::Unsupported` if the canvas format isn't `Sdf`.
+    #[inline]
+    pub fn rasterize_glyph_sdf(
+        &self,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        spread: f32,
+    ) -> Result<(), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_sdf(
+            self,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            spread,
+        )
+    }
+
+    /// Returns the filled area of a glyph as polygons in font units, for hit-testing.
+    ///
+    /// Each contour of the outline becomes one polygon, with curves flattened into lines that
+    /// stray no more than `tolerance` font units from them. Polygons are implicitly closed; the
+    /// first point isn't repeated at the end.
+    ///
+    /// Contours keep the directions they have in the font, so test points with the nonzero
+    /// winding rule: a point is filled if the polygons wind around it a nonzero number of times
+    /// in total. Counters, such as the inside of an "O", wind the opposite way from the contours
+    /// around them and so are unfilled. The even-odd rule gives the same answer for most glyphs,
+    /// but not where contours overlap, as they often do in variable fonts.
+    #[inline]
+    pub fn glyph_hit_region(
+        &self,
+        glyph_id: u32,
+        tolerance: f32,
+    ) -> Result<Vec<Vec<Vector2F>>, GlyphLoadingError> {
+        <Self as Loader>::glyph_hit_region(self, glyph_id, tolerance)
+    }
+
+    /// Returns the ligature glyph that the font substitutes for a sequence of component glyphs,
+    /// if it has one.
+    ///
+    /// This reads the ligature substitutions of the `liga` (standard ligatures) and `dlig`
+    /// (discretionary ligatures) features in the `GSUB` table, preferring standard ones, which is
+    /// useful for mapping a rendered ligature back to the glyphs, and so the characters, it
+    /// stands for. It is not shaping: substitutions for every script and language are searched,
+    /// lookup flags are ignored, and only a ligature made of exactly `components` is returned.
+    #[inline]
+    pub fn ligature_glyph(&self, components: &[u32]) -> Option<u32> {
+        <Self as Loader>::ligature_glyph(self, components)
+    }
+
+    /// Returns true if this font and `other` draw the same glyph shapes, within a small tolerance.
+    ///
+    /// This is much slower than comparing font data, but it also catches duplicates whose data
+    /// differs, such as a font that has been repackaged as WOFF or had its tables rebuilt. The
+    /// fonts must have the same number of glyphs and units per em, and each compared glyph must
+    /// have the same contours with the same kinds of points, at positions no more than 1/1000 em
+    /// apart. Fonts with up to 1024 glyphs have every glyph compared; for larger fonts, 1024
+    /// glyph IDs spread evenly from the first glyph to the last are sampled.
+    #[inline]
+    pub fn outlines_equal(&self, other: &Font) -> bool {
+        <Self as Loader>::outlines_equal(self, other)
+    }
+
+    /// Returns a key that identifies this font by its content, for use in hash maps.
+    ///
+    /// See `FontKey` for what makes two keys equal.
+    #[inline]
+    pub fn font_key(&self) -> FontKey {
+        <Self as Loader>::font_key(self)
+    }
+
+    /// Rasterizes a glyph whose origin falls at a fractional pixel position into a new canvas
+    /// sized to fit it, returning the canvas and the position of its top left corner.
+    ///
+    /// `subpixel_origin` is the position of the glyph's origin on the baseline, in pixels with y
+    /// pointing down, as in a canvas. It's split into whole pixels, which only move the returned
+    /// position, and a fraction, which the rasterizer renders as the glyph's subpixel phase, as
+    /// precisely as `subpixel_positions()` allows. Drawing the canvas at the returned position
+    /// puts the glyph exactly where it belongs, so text runs can place each glyph at its
+    /// unrounded pen position without losing sharpness.
+    ///
+    /// The canvas is `Rgb24` for `SubpixelAa` and `A8` otherwise.
+    #[inline]
+    pub fn rasterize_glyph_at(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        subpixel_origin: Vector2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(Canvas, Vector2I), GlyphLoadingError> {
+        <Self as Loader>::rasterize_glyph_at(
+            self,
+            glyph_id,
+            point_size,
+            subpixel_origin,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    /// Returns the string with the ID `name_id` from the font's `name` table, or `None` if the
+    /// font has no such string.
+    ///
+    /// A string is often recorded several times, in different languages and encodings. English
+    /// in Unicode is preferred, then any Unicode string, then a Mac Roman one; strings in other
+    /// encodings are ignored. `designer()`, `description()`, and the like look up the IDs that
+    /// font detail panels typically show.
+    #[inline]
+    pub fn name_string(&self, name_id: u16) -> Option<String> {
+        <Self as Loader>::name_string(self, name_id)
+    }
+
+    /// Returns the text the designer suggests for showing off the font, from the `name` table.
+    #[inline]
+    pub fn sample_text(&self) -> Option<String> {
+        <Self as Loader>::sample_text(self)
+    }
+
+    /// Returns the description of the font, from the `name` table.
+    #[inline]
+    pub fn description(&self) -> Option<String> {
+        <Self as Loader>::description(self)
+    }
+
+    /// Returns the name of the font's designer, from the `name` table.
+    #[inline]
+    pub fn designer(&self) -> Option<String> {
+        <Self as Loader>::designer(self)
+    }
+
+    /// Returns the name of the font's manufacturer, from the `name` table.
+    #[inline]
+    pub fn manufacturer(&self) -> Option<String> {
+        <Self as Loader>::manufacturer(self)
+    }
+
+    /// Returns the description of the font's license, from the `name` table.
+    #[inline]
+    pub fn license(&self) -> Option<String> {
+        <Self as Loader>::license(self)
+    }
+
+    /// Returns the advance of the glyph with the given ID at the given user-space variation axis
+    /// values, such as `wght=700`, in font units.
+    ///
+    /// `coordinates` is interpreted as in `normalized_variation_coordinates()`. The delta in the
+    /// font's `HVAR` table is added to the horizontal advance from `advance()`, which describes
+    /// the default instance; layout of variable text should use this instead. Fonts without an
+    /// `HVAR` table return `advance()` unchanged.
+    #[inline]
+    pub fn advance_at_variation(
+        &self,
+        glyph_id: u32,
+        coordinates: &[(u32, f32)],
+    ) -> Result<Vector2F, GlyphLoadingError> {
+        <Self as Loader>::advance_at_variation(self, glyph_id, coordinates)
+    }
+
+    /// Returns the fraction of a script's core repertoire that the font has real glyphs for, from
+    /// 0.0 to 1.0.
+    ///
+    /// The repertoires, which `classification::script_repertoire()` returns, hold the basic
+    /// characters of each script as of Unicode 15.1. This is useful for choosing the best fallback
+    /// font for each script in a run of text. Scripts without a repertoire score 0.0.
+    #[inline]
+    pub fn script_coverage(&self, script: ScriptTag) -> f32 {
+        <Self as Loader>::script_coverage(self, script)
+    }
+
+    /// Returns the global hinting parameters from the private dict of a `CFF ` table: the
+    /// alignment zones and dominant stem widths that CFF hinters snap to.
+    ///
+    /// For CID-keyed fonts, the parameters of the first font dict are returned. Returns `None`
+    /// for fonts without a `CFF ` table, including TrueType and `CFF2` fonts, or if the table is
+    /// malformed.
+    #[inline]
+    pub fn cff_hint_params(&self) -> Option<CffHintParams> {
+        <Self as Loader>::cff_hint_params(self)
+    }
+}
+
+impl Clone for Font {
+    fn clone(&self) -> Font {
+        unsafe {
+            assert_eq!(FT_Reference_Face(self.freetype_face), 0);
+            Font {
+                freetype_face: self.freetype_face,
+                font_data: self.font_data.clone(),
+                load_flags: self.load_flags,
+                description_cache: self.description_cache.clone(),
+                type1_encoding: self.type1_encoding.clone(),
+            }
+        }
+    }
+}
+
+impl Drop for Font {
+    fn drop(&mut self) {
+        // The AccessError can be ignored, as it means FREETYPE_LIBRARY has already been
+        // destroyed, and it already destroys all FreeType resources.
+        // https://freetype.org/freetype2/docs/reference/ft2-module_management.html#ft_done_library
+        let _ = FREETYPE_LIBRARY.try_with(|freetype_library| unsafe {
+            if !freetype_library.0.is_null() && !self.freetype_face.is_null() {
+                assert_eq!(FT_Done_Face(self.freetype_face), 0);
+            }
+        });
+    }
 }
 
 impl Debug for Font {
@@ -1007,6 +2168,14 @@ impl Loader for Font {
         Font::from_bytes(font_data, font_index)
     }
 
+    #[inline]
+    fn from_shared_bytes(
+        font_data: Arc<dyn AsRef<[u8]> + Send + Sync>,
+        font_index: u32,
+    ) -> Result<Self, FontLoadingError> {
+        Font::from_shared_bytes(font_data, font_index)
+    }
+
     #[inline]
     #[cfg(not(target_arch = "wasm32"))]
     fn from_file(file: &mut File, font_index: u32) -> Result<Font, FontLoadingError> {
@@ -1086,6 +2255,20 @@ impl Loader for Font {
         self.outline(glyph_id, hinting_mode, sink)
     }
 
+    #[inline]
+    fn hinted_outline<S>(
+        &self,
+        glyph_id: u32,
+        point_size: f32,
+        hinting: HintingOptions,
+        sink: &mut S,
+    ) -> Result<(), GlyphLoadingError>
+    where
+        S: OutlineSink,
+    {
+        self.hinted_outline(glyph_id, point_size, hinting, sink)
+    }
+
     #[inline]
     fn typographic_bounds(&self, glyph_id: u32) -> Result<RectF, GlyphLoadingError> {
         self.typographic_bounds(glyph_id)
@@ -1120,6 +2303,11 @@ impl Loader for Font {
         self.supports_hinting_options(hinting_options, for_rasterization)
     }
 
+    #[inline]
+    fn subpixel_positions(&self, rasterization_options: RasterizationOptions) -> Option<u32> {
+        self.subpixel_positions(rasterization_options)
+    }
+
     #[inline]
     fn rasterize_glyph(
         &self,
@@ -1141,6 +2329,45 @@ impl Loader for Font {
     }
 
     #[inline]
+    fn rasterize_glyph_with_context(
+        &self,
+        context: &mut RasterizeContext,
+        canvas: &mut Canvas,
+        glyph_id: u32,
+        point_size: f32,
+        transform: Transform2F,
+        hinting_options: HintingOptions,
+        rasterization_options: RasterizationOptions,
+    ) -> Result<(), GlyphLoadingError> {
+        self.rasterize_glyph_with_context(
+            context,
+            canvas,
+            glyph_id,
+            point_size,
+            transform,
+            hinting_options,
+            rasterization_options,
+        )
+    }
+
+    #[inline]
+    fn rasterize_color_bitmap_glyph(
+        &self,
+        key: &GlyphKey,
+    ) -> Result<(Canvas, Vector2I), GlyphLoadingError> {
+        let mut font = self.clone();
+        font.load_flags |= FT_LOAD_COLOR;
+        loader::rasterize_glyph_to_fit(
+            &font,
+            key.glyph_id,
+            key.point_size,
+            key.transform,
+            key.hinting_options,
+            key.rasterization_options,
+            Format::Rgba32,
+        )
+    }
+
     fn get_fallbacks(&self, text: &str, locale: &str) -> FallbackResult<Self> {
         self.get_fallbacks(text, locale)
     }
@@ -1149,12 +2376,45 @@ impl Loader for Font {
     fn load_font_table(&self, table_tag: u32) -> Option<Box<[u8]>> {
         self.load_font_table(table_tag)
     }
+
+    #[inline]
+    fn available_sizes(&self) -> Vec<f32> {
+        self.available_sizes()
+    }
 }
 
 unsafe fn setup_freetype_face(face: FT_Face) {
     reset_freetype_face_char_size(face);
 }
 
+// Reads the encoding of a Type 1 font from the charmap that FreeType builds for it, leaving the
+// face's selected charmap as it was.
+unsafe fn load_type1_encoding(face: FT_Face) -> Option<Arc<[u32]>> {
+    const TYPE1_ENCODINGS: [FT_Encoding; 4] = [
+        FT_ENCODING_ADOBE_CUSTOM,
+        FT_ENCODING_ADOBE_STANDARD,
+        FT_ENCODING_ADOBE_EXPERT,
+        FT_ENCODING_ADOBE_LATIN_1,
+    ];
+
+    let charmaps = slice::from_raw_parts((*face).charmaps, (*face).num_charmaps as usize);
+    let charmap = *charmaps
+        .iter()
+        .find(|&&charmap| TYPE1_ENCODINGS.contains(&(*charmap).encoding))?;
+
+    let selected_charmap = (*face).charmap;
+    if FT_Set_Charmap(face, charmap) != 0 {
+        return None;
+    }
+    let encoding = (0..256).map(|code| FT_Get_Char_Index(face, code)).collect();
+    if selected_charmap.is_null() {
+        (*face).charmap = selected_charmap;
+    } else {
+        FT_Set_Charmap(face, selected_charmap);
+    }
+    Some(encoding)
+}
+
 unsafe fn reset_freetype_face_char_size(face: FT_Face) {
     // Apple Color Emoji has 0 units per em. Whee!
     let units_per_em = (*face).units_per_EM as i64;
@@ -1234,7 +2494,10 @@ extern "C" {
 #[cfg(test)]
 mod test {
     use crate::loaders::freetype::Font;
+    use crate::outline::OutlineFormat;
+    use freetype_sys::FT_LOAD_NO_SCALE;
 
+    static TTF_FONT_PATH: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.ttf";
     static PCF_FONT_PATH: &str = "resources/tests/times-roman-pcf/timR12.pcf";
     static PCF_FONT_POSTSCRIPT_NAME: &str = "Times-Roman";
 
@@ -1243,4 +2506,34 @@ mod test {
         let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
         assert_eq!(font.postscript_name().unwrap(), PCF_FONT_POSTSCRIPT_NAME);
     }
+
+    #[test]
+    fn get_pcf_available_sizes() {
+        let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
+        // 12-point Times at 100 DPI.
+        assert_eq!(font.available_sizes(), vec![17.0]);
+    }
+
+    #[test]
+    fn get_pcf_outline_format() {
+        let font = Font::from_path(PCF_FONT_PATH, 0).unwrap();
+        assert_eq!(font.outline_format(), OutlineFormat::Bitmap);
+    }
+
+    #[test]
+    fn set_load_flags() {
+        let mut font = Font::from_path(TTF_FONT_PATH, 0).unwrap();
+        let glyph = font.glyph_for_char('A').unwrap();
+        let scaled_advance = font.advance(glyph).unwrap();
+        assert_eq!(font.load_flags(), 0);
+
+        font.set_load_flags(FT_LOAD_NO_SCALE);
+        assert_eq!(font.load_flags(), FT_LOAD_NO_SCALE);
+        assert_eq!(font.clone().load_flags(), FT_LOAD_NO_SCALE);
+        // Unscaled advances are in font units, not 26.6 fixed point.
+        assert!(font.advance(glyph).unwrap().x() < scaled_advance.x() / 32.0);
+
+        font.set_load_flags(0);
+        assert_eq!(font.advance(glyph).unwrap(), scaled_advance);
+    }
 }
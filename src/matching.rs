@@ -13,7 +13,49 @@
 use float_ord::FloatOrd;
 
 use crate::error::SelectionError;
-use crate::properties::{Properties, Stretch, Style, Weight};
+use crate::properties::{Properties, Stretch, StretchRange, Style, Weight};
+
+/// The order in which font matching narrows down candidates by width, style, and weight.
+///
+/// Matching settles each property in turn, so whichever comes first is the one that is matched
+/// most closely, at the expense of the others.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum MatchPriority {
+    /// Width, then style, then weight, as CSS specifies.
+    #[default]
+    StretchStyleWeight,
+    /// Width, then weight, then style.
+    StretchWeightStyle,
+    /// Style, then width, then weight.
+    StyleStretchWeight,
+    /// Style, then weight, then width.
+    StyleWeightStretch,
+    /// Weight, then width, then style.
+    WeightStretchStyle,
+    /// Weight, then style, then width.
+    WeightStyleStretch,
+}
+
+#[derive(Clone, Copy)]
+enum MatchStep {
+    Stretch,
+    Style,
+    Weight,
+}
+
+impl MatchPriority {
+    fn steps(self) -> [MatchStep; 3] {
+        use self::MatchStep::{Stretch, Style, Weight};
+        match self {
+            MatchPriority::StretchStyleWeight => [Stretch, Style, Weight],
+            MatchPriority::StretchWeightStyle => [Stretch, Weight, Style],
+            MatchPriority::StyleStretchWeight => [Style, Stretch, Weight],
+            MatchPriority::StyleWeightStretch => [Style, Weight, Stretch],
+            MatchPriority::WeightStretchStyle => [Weight, Stretch, Style],
+            MatchPriority::WeightStyleStretch => [Weight, Style, Stretch],
+        }
+    }
+}
 
 /// This follows CSS Fonts Level 3 § 5.2 [1].
 ///
@@ -21,6 +63,20 @@ use crate::properties::{Properties, Stretch, Style, Weight};
 pub fn find_best_match(
     candidates: &[Properties],
     query: &Properties,
+) -> Result<usize, SelectionError> {
+    find_best_match_with_priority(candidates, query, MatchPriority::default())
+}
+
+/// Like `find_best_match()`, but settles width, style, and weight in the order given by
+/// `priority` instead of the order CSS specifies.
+///
+/// Each property is matched with the same rules as in CSS; only the order changes. For example,
+/// `MatchPriority::WeightStyleStretch` picks an upright face of the requested weight over an
+/// italic face of a different weight when an italic is requested.
+pub fn find_best_match_with_priority(
+    candidates: &[Properties],
+    query: &Properties,
+    priority: MatchPriority,
 ) -> Result<usize, SelectionError> {
     // Step 4.
     let mut matching_set: Vec<usize> = (0..candidates.len()).collect();
@@ -28,6 +84,24 @@ pub fn find_best_match(
         return Err(SelectionError::NotFound);
     }
 
+    for step in priority.steps() {
+        match step {
+            MatchStep::Stretch => match_stretch(candidates, query, &mut matching_set),
+            MatchStep::Style => match_style(candidates, query, &mut matching_set),
+            MatchStep::Weight => match_weight(candidates, query, &mut matching_set),
+        }
+    }
+
+    // Step 4d concerns `font-size`, but fonts in `font-kit` are unsized, so we ignore that.
+
+    // Return the result.
+    matching_set
+        .into_iter()
+        .next()
+        .ok_or(SelectionError::NotFound)
+}
+
+fn match_stretch(candidates: &[Properties], query: &Properties, matching_set: &mut Vec<usize>) {
     // Step 4a (`font-stretch`).
     let matching_stretch = if matching_set
         .iter()
@@ -69,7 +143,9 @@ pub fn find_best_match(
         }
     };
     matching_set.retain(|&index| candidates[index].stretch == matching_stretch);
+}
 
+fn match_style(candidates: &[Properties], query: &Properties, matching_set: &mut Vec<usize>) {
     // Step 4b (`font-style`).
     let style_preference = match query.style {
         Style::Italic => [Style::Italic, Style::Oblique, Style::Normal],
@@ -85,44 +161,48 @@ pub fn find_best_match(
         })
         .unwrap();
     matching_set.retain(|&index| candidates[index].style == matching_style);
+}
 
+fn match_weight(candidates: &[Properties], query: &Properties, matching_set: &mut Vec<usize>) {
     // Step 4c (`font-weight`).
     //
-    // The spec doesn't say what to do if the weight is between 400 and 500 exclusive, so we
-    // just use 450 as the cutoff.
+    // Weights outside the range that CSS Fonts Level 4 allows are clamped to it first. The spec
+    // doesn't say what to do if the weight is between 400 and 500 exclusive, so we just use 450
+    // as the cutoff.
+    let query_weight = query.weight.clamped();
     let matching_weight = if matching_set
         .iter()
-        .any(|&index| candidates[index].weight == query.weight)
+        .any(|&index| candidates[index].weight == query_weight)
     {
-        query.weight
-    } else if query.weight >= Weight(400.0)
-        && query.weight < Weight(450.0)
+        query_weight
+    } else if query_weight >= Weight(400.0)
+        && query_weight < Weight(450.0)
         && matching_set
             .iter()
             .any(|&index| candidates[index].weight == Weight(500.0))
     {
         // Check 500 first.
         Weight(500.0)
-    } else if query.weight >= Weight(450.0)
-        && query.weight <= Weight(500.0)
+    } else if query_weight >= Weight(450.0)
+        && query_weight <= Weight(500.0)
         && matching_set
             .iter()
             .any(|&index| candidates[index].weight == Weight(400.0))
     {
         // Check 400 first.
         Weight(400.0)
-    } else if query.weight <= Weight(500.0) {
+    } else if query_weight <= Weight(500.0) {
         // Closest weight, first checking thinner values and then fatter ones.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].weight <= query.weight)
-            .min_by_key(|&&index| FloatOrd(query.weight.0 - candidates[index].weight.0))
+            .filter(|&&index| candidates[index].weight <= query_weight)
+            .min_by_key(|&&index| FloatOrd(query_weight.0 - candidates[index].weight.0))
         {
             Some(&matching_index) => candidates[matching_index].weight,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query.weight.0))
+                    .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query_weight.0))
                     .unwrap();
                 candidates[matching_index].weight
             }
@@ -131,26 +211,78 @@ pub fn find_best_match(
         // Closest weight, first checking fatter values and then thinner ones.
         match matching_set
             .iter()
-            .filter(|&&index| candidates[index].weight >= query.weight)
-            .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query.weight.0))
+            .filter(|&&index| candidates[index].weight >= query_weight)
+            .min_by_key(|&&index| FloatOrd(candidates[index].weight.0 - query_weight.0))
         {
             Some(&matching_index) => candidates[matching_index].weight,
             None => {
                 let matching_index = *matching_set
                     .iter()
-                    .min_by_key(|&&index| FloatOrd(query.weight.0 - candidates[index].weight.0))
+                    .min_by_key(|&&index| FloatOrd(query_weight.0 - candidates[index].weight.0))
                     .unwrap();
                 candidates[matching_index].weight
             }
         }
     };
     matching_set.retain(|&index| candidates[index].weight == matching_weight);
+}
 
-    // Step 4d concerns `font-size`, but fonts in `font-kit` are unsized, so we ignore that.
+/// Returns true if `query` asks for an italic or oblique face but `face` is upright, so that a
+/// renderer should shear the face's glyphs to fake the slant.
+pub fn needs_synthetic_italic(face: &Properties, query: &Properties) -> bool {
+    query.style != Style::Normal && face.style == Style::Normal
+}
 
-    // Return the result.
-    matching_set
-        .into_iter()
-        .next()
-        .ok_or(SelectionError::NotFound)
+/// Returns true if `query` asks for a bold face but `face` is lighter, so that a renderer should
+/// embolden the face's glyphs.
+///
+/// As in browsers, weights of `Weight::SEMIBOLD` (600) and up count as bold.
+pub fn needs_synthetic_bold(face: &Properties, query: &Properties) -> bool {
+    query.weight >= Weight::SEMIBOLD && face.weight < Weight::SEMIBOLD
+}
+
+/// The result of matching against candidates that may cover a range of widths.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StretchMatch {
+    /// The index of the best candidate.
+    pub index: usize,
+    /// The width at which the candidate should be used. For variable fonts, this is the position
+    /// on the `wdth` axis to instantiate; for static fonts, it is the font's own width.
+    pub stretch: Stretch,
+}
+
+impl StretchMatch {
+    /// Returns the `wdth` axis coordinate corresponding to the matched width, as a percentage of
+    /// the normal width.
+    #[inline]
+    pub fn wdth(&self) -> f32 {
+        self.stretch.0 * 100.0
+    }
+}
+
+/// Like `find_best_match()`, but each candidate covers a range of widths, as variable fonts with
+/// a `wdth` axis do (CSS Fonts Level 4 § 5.2 [1]).
+///
+/// A candidate whose range contains the requested stretch matches it exactly; otherwise the
+/// nearest end of its range is used, and the usual narrower-then-wider (or wider-then-narrower)
+/// preference applies. Candidates covering a single width behave exactly as they do in
+/// `find_best_match()`.
+///
+/// [1]: https://drafts.csswg.org/css-fonts-4/#font-style-matching
+pub fn find_best_match_in_stretch_ranges(
+    candidates: &[(Properties, StretchRange)],
+    query: &Properties,
+) -> Result<StretchMatch, SelectionError> {
+    let clamped_candidates: Vec<Properties> = candidates
+        .iter()
+        .map(|&(mut properties, stretch_range)| {
+            properties.stretch = stretch_range.clamp(query.stretch);
+            properties
+        })
+        .collect();
+    let index = find_best_match(&clamped_candidates, query)?;
+    Ok(StretchMatch {
+        index,
+        stretch: clamped_candidates[index].stretch,
+    })
 }
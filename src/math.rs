@@ -0,0 +1,90 @@
+// font-kit/src/math.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Numeric helpers for interpolating font properties.
+//!
+//! These are the functions that `PropertyMapping` uses to convert between CSS weights and widths
+//! and the values of platform APIs such as Core Text.
+
+use std::cmp::Ordering;
+
+/// Linearly interpolates from `a` to `b` by `t`.
+///
+/// `t` isn't clamped, so values outside 0.0 to 1.0 extrapolate.
+///
+///     # use font_kit::math::lerp;
+///     assert_eq!(lerp(400.0, 700.0, 0.5), 550.0);
+///     assert_eq!(lerp(400.0, 700.0, 2.0), 1000.0);
+#[inline]
+pub fn lerp(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+/// Restricts `x` to the range from `min` to `max` inclusive.
+///
+/// Unlike `f32::clamp()`, this doesn't panic if `min` is greater than `max`; `min` wins. NaN is
+/// returned unchanged.
+///
+///     # use font_kit::math::clamp;
+///     assert_eq!(clamp(1200.0, 1.0, 1000.0), 1000.0);
+///     assert_eq!(clamp(-3.0, 1.0, 1000.0), 1.0);
+///     assert_eq!(clamp(350.0, 1.0, 1000.0), 350.0);
+#[inline]
+pub fn clamp(x: f32, min: f32, max: f32) -> f32 {
+    if x < min {
+        min
+    } else if x > max {
+        max
+    } else {
+        x
+    }
+}
+
+/// Looks up a fractional `index` in a table of values, interpolating linearly between the
+/// entries on either side.
+///
+/// `index` must lie between 0.0 and the last index of `mapping`; this panics otherwise.
+///
+///     # use font_kit::math::piecewise_linear_lookup;
+///     let mapping = [100.0, 400.0, 700.0];
+///     assert_eq!(piecewise_linear_lookup(1.0, &mapping), 400.0);
+///     assert_eq!(piecewise_linear_lookup(1.5, &mapping), 550.0);
+pub fn piecewise_linear_lookup(index: f32, mapping: &[f32]) -> f32 {
+    let lower_value = mapping[f32::floor(index) as usize];
+    let upper_value = mapping[f32::ceil(index) as usize];
+    lerp(lower_value, upper_value, f32::fract(index))
+}
+
+/// Finds the fractional index at which `value` falls in a table of ascending values; the inverse
+/// of `piecewise_linear_lookup()`.
+///
+/// Values below the first entry return 0.0, and values above the last entry return the length of
+/// `mapping`.
+///
+///     # use font_kit::math::piecewise_linear_find_index;
+///     let mapping = [100.0, 400.0, 700.0];
+///     assert_eq!(piecewise_linear_find_index(400.0, &mapping), 1.0);
+///     assert_eq!(piecewise_linear_find_index(550.0, &mapping), 1.5);
+///     assert_eq!(piecewise_linear_find_index(50.0, &mapping), 0.0);
+pub fn piecewise_linear_find_index(value: f32, mapping: &[f32]) -> f32 {
+    let upper_index = match mapping
+        .binary_search_by(|entry| entry.partial_cmp(&value).unwrap_or(Ordering::Less))
+    {
+        Ok(index) => return index as f32,
+        Err(upper_index) => upper_index,
+    };
+    if upper_index == 0 || upper_index >= mapping.len() {
+        return upper_index as f32;
+    }
+    let lower_index = upper_index - 1;
+    let (upper_value, lower_value) = (mapping[upper_index], mapping[lower_index]);
+    let t = (value - lower_value) / (upper_value - lower_value);
+    lower_index as f32 + t
+}
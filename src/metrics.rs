@@ -45,10 +45,16 @@ pub struct Metrics {
     pub underline_thickness: f32,
 
     /// The approximate amount that uppercase letters rise above the baseline, in font units.
+    ///
+    /// If the font's `OS/2` table is missing or leaves this value zero, the FreeType loader
+    /// measures the outline of the glyph for `H` instead.
     pub cap_height: f32,
 
     /// The approximate amount that non-ascending lowercase letters rise above the baseline, in
     /// font units.
+    ///
+    /// If the font's `OS/2` table is missing or leaves this value zero, the FreeType loader
+    /// measures the outline of the glyph for `x` instead.
     pub x_height: f32,
 
     /// A rectangle that surrounds all bounding boxes of all glyphs, in font units.
@@ -56,3 +62,114 @@ pub struct Metrics {
     /// This corresponds to the `xMin`/`xMax`/`yMin`/`yMax` values in the OpenType `head` table.
     pub bounding_box: RectF,
 }
+
+/// Vertical metrics for laying out lines of text at a particular size.
+///
+/// All values are in the same units as the point size they were computed for, usually pixels.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineMetrics {
+    /// The distance from the baseline to the top of the glyphs' ascent.
+    pub ascent: f32,
+
+    /// The distance from the baseline to the bottom of the glyphs' descent.
+    ///
+    /// As in `Metrics`, this is typically negative.
+    pub descent: f32,
+
+    /// The extra space to leave between lines. Negative line gaps are treated as zero.
+    pub leading: f32,
+
+    /// The distance from the top of a line to its baseline.
+    ///
+    /// Half of the leading goes above the ascent and half below the descent, as in CSS.
+    pub baseline_offset: f32,
+
+    /// The recommended distance between the baselines of successive lines.
+    pub line_height: f32,
+}
+
+impl LineMetrics {
+    /// Scales the ascent, descent, and line gap of `metrics` to `point_size`.
+    ///
+    /// This uses the metrics exactly as given. To honor the `USE_TYPO_METRICS` flag in the `OS/2`
+    /// table, get line metrics from the font with `line_metrics()` instead.
+    pub fn from_metrics(metrics: &Metrics, point_size: f32) -> LineMetrics {
+        let scale = point_size / metrics.units_per_em as f32;
+        let ascent = metrics.ascent * scale;
+        let descent = metrics.descent * scale;
+        let leading = metrics.line_gap.max(0.0) * scale;
+        LineMetrics {
+            ascent,
+            descent,
+            leading,
+            baseline_offset: leading * 0.5 + ascent,
+            line_height: ascent - descent + leading,
+        }
+    }
+}
+
+/// An OpenType script tag, such as `latn` for Latin or `hani` for CJK ideographs.
+///
+/// The tag's four ASCII bytes are stored big-endian, as in the font tables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct ScriptTag(pub u32);
+
+impl ScriptTag {
+    /// Fallback for scripts the font does not list, `DFLT`.
+    pub const DEFAULT: ScriptTag = ScriptTag(u32::from_be_bytes(*b"DFLT"));
+    /// Latin, `latn`.
+    pub const LATIN: ScriptTag = ScriptTag(u32::from_be_bytes(*b"latn"));
+    /// Cyrillic, `cyrl`.
+    pub const CYRILLIC: ScriptTag = ScriptTag(u32::from_be_bytes(*b"cyrl"));
+    /// Greek, `grek`.
+    pub const GREEK: ScriptTag = ScriptTag(u32::from_be_bytes(*b"grek"));
+    /// CJK ideographs, `hani`.
+    pub const HAN: ScriptTag = ScriptTag(u32::from_be_bytes(*b"hani"));
+    /// Japanese kana, `kana`.
+    pub const KANA: ScriptTag = ScriptTag(u32::from_be_bytes(*b"kana"));
+    /// Hangul, `hang`.
+    pub const HANGUL: ScriptTag = ScriptTag(u32::from_be_bytes(*b"hang"));
+    /// Devanagari, `deva`.
+    pub const DEVANAGARI: ScriptTag = ScriptTag(u32::from_be_bytes(*b"deva"));
+    /// Arabic, `arab`.
+    pub const ARABIC: ScriptTag = ScriptTag(u32::from_be_bytes(*b"arab"));
+    /// Hebrew, `hebr`.
+    pub const HEBREW: ScriptTag = ScriptTag(u32::from_be_bytes(*b"hebr"));
+    /// Thai, `thai`.
+    pub const THAI: ScriptTag = ScriptTag(u32::from_be_bytes(*b"thai"));
+
+    /// Creates a script tag from its four ASCII characters.
+    #[inline]
+    pub const fn new(tag: &[u8; 4]) -> ScriptTag {
+        ScriptTag(u32::from_be_bytes(*tag))
+    }
+}
+
+/// An OpenType baseline tag, identifying one of the baselines in the `BASE` table.
+///
+/// The tag's four ASCII bytes are stored big-endian, as in the font tables.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub struct BaselineTag(pub u32);
+
+impl BaselineTag {
+    /// The alphabetic baseline used by Latin, Cyrillic, and Greek, `romn`.
+    pub const ROMAN: BaselineTag = BaselineTag(u32::from_be_bytes(*b"romn"));
+    /// The hanging baseline used by scripts such as Devanagari, `hang`.
+    pub const HANGING: BaselineTag = BaselineTag(u32::from_be_bytes(*b"hang"));
+    /// The bottom edge of the ideographic em box, `ideo`.
+    pub const IDEOGRAPHIC: BaselineTag = BaselineTag(u32::from_be_bytes(*b"ideo"));
+    /// The top edge of the ideographic em box, `idtp`.
+    pub const IDEOGRAPHIC_TOP: BaselineTag = BaselineTag(u32::from_be_bytes(*b"idtp"));
+    /// The bottom edge of the ideographic character face, `icfb`.
+    pub const IDEOGRAPHIC_FACE_BOTTOM: BaselineTag = BaselineTag(u32::from_be_bytes(*b"icfb"));
+    /// The top edge of the ideographic character face, `icft`.
+    pub const IDEOGRAPHIC_FACE_TOP: BaselineTag = BaselineTag(u32::from_be_bytes(*b"icft"));
+    /// The baseline for mathematical characters, `math`.
+    pub const MATH: BaselineTag = BaselineTag(u32::from_be_bytes(*b"math"));
+
+    /// Creates a baseline tag from its four ASCII characters.
+    #[inline]
+    pub const fn new(tag: &[u8; 4]) -> BaselineTag {
+        BaselineTag(u32::from_be_bytes(*tag))
+    }
+}
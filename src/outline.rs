@@ -11,8 +11,10 @@
 //! Bézier paths.
 
 use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::transform2d::Matrix2x2F;
 use pathfinder_geometry::vector::Vector2F;
 use std::mem;
+use std::vec;
 
 /// Receives Bézier path rendering commands.
 pub trait OutlineSink {
@@ -28,6 +30,117 @@ pub trait OutlineSink {
     fn close(&mut self);
 }
 
+/// One Bézier path command, as received by an `OutlineSink`.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum PathSegment {
+    /// Moves the pen to a point.
+    MoveTo(Vector2F),
+    /// Draws a line to a point.
+    LineTo(Vector2F),
+    /// Draws a quadratic Bézier curve through a control point to a point.
+    QuadraticCurveTo {
+        /// The control point.
+        ctrl: Vector2F,
+        /// The end point.
+        to: Vector2F,
+    },
+    /// Draws a cubic Bézier curve through two control points to a point.
+    CubicCurveTo {
+        /// The two control points.
+        ctrl: LineSegment2F,
+        /// The end point.
+        to: Vector2F,
+    },
+    /// Closes the path, returning to the first point in it.
+    Close,
+}
+
+impl OutlineSink for Vec<PathSegment> {
+    #[inline]
+    fn move_to(&mut self, to: Vector2F) {
+        self.push(PathSegment::MoveTo(to));
+    }
+
+    #[inline]
+    fn line_to(&mut self, to: Vector2F) {
+        self.push(PathSegment::LineTo(to));
+    }
+
+    #[inline]
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        self.push(PathSegment::QuadraticCurveTo { ctrl, to });
+    }
+
+    #[inline]
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        self.push(PathSegment::CubicCurveTo { ctrl, to });
+    }
+
+    #[inline]
+    fn close(&mut self) {
+        self.push(PathSegment::Close);
+    }
+}
+
+/// An iterator over the commands of a glyph outline, returned by `outline_segments()`.
+#[derive(Clone, Debug)]
+pub struct OutlineSegments {
+    segments: vec::IntoIter<PathSegment>,
+}
+
+impl OutlineSegments {
+    #[inline]
+    pub(crate) fn new(segments: Vec<PathSegment>) -> OutlineSegments {
+        OutlineSegments {
+            segments: segments.into_iter(),
+        }
+    }
+}
+
+impl Iterator for OutlineSegments {
+    type Item = PathSegment;
+
+    #[inline]
+    fn next(&mut self) -> Option<PathSegment> {
+        self.segments.next()
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.segments.size_hint()
+    }
+}
+
+impl ExactSizeIterator for OutlineSegments {}
+
+/// How a font stores the shapes of its glyphs.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum OutlineFormat {
+    /// Quadratic outlines in a `glyf` table.
+    TrueType,
+    /// Cubic outlines in a `CFF ` table.
+    Cff,
+    /// Cubic outlines in a `CFF2` table, as used by variable PostScript-flavored fonts.
+    Cff2,
+    /// Only embedded bitmaps, such as `EBDT`, `CBDT`, or `sbix` strikes or a PCF font.
+    Bitmap,
+    /// No glyph shapes that font-kit recognizes.
+    None,
+}
+
+/// The rule that determines which regions a set of contours encloses.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum FillRule {
+    /// A point is inside the outline if the contours wind around it a nonzero number of times,
+    /// counting clockwise and counterclockwise windings with opposite signs.
+    ///
+    /// Overlapping contours with the same direction are filled once.
+    NonZero,
+    /// A point is inside the outline if a ray from it crosses the contours an odd number of
+    /// times.
+    EvenOdd,
+}
+
 /// A glyph vector outline or path.
 #[derive(Clone, PartialEq, Debug)]
 pub struct Outline {
@@ -64,6 +177,40 @@ bitflags! {
     }
 }
 
+/// One component of a composite glyph: a reference to another glyph, placed with a transform.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct GlyphComponent {
+    /// The ID of the glyph that this component draws.
+    pub glyph_id: u32,
+    /// The offset of the component, in font units.
+    ///
+    /// This is zero if the component is positioned by matching points instead; see
+    /// `anchor_points`.
+    pub offset: Vector2F,
+    /// The 2x2 linear transform (scale, rotation, and skew) applied to the component.
+    pub matrix: Matrix2x2F,
+    /// If the component is positioned by aligning a point in the composite glyph with a point in
+    /// the component, the indices of those two points, in that order.
+    pub anchor_points: Option<(u32, u32)>,
+}
+
+/// The points of one contour of a TrueType glyph, exactly as stored in the `glyf` table.
+///
+/// Unlike `Contour`, no points are implied: two consecutive off-curve points are both listed,
+/// without the on-curve point at their midpoint that a renderer would insert.
+#[derive(Clone, PartialEq, Debug)]
+pub struct ContourPoints {
+    /// Positions of each point, in font units.
+    ///
+    /// This must have the same length as the `on_curve` field.
+    pub positions: Vec<Vector2F>,
+    /// Whether the corresponding point lies on the curve. Off-curve points are quadratic Bézier
+    /// control points.
+    ///
+    /// This must have the same length as the `positions` field.
+    pub on_curve: Vec<bool>,
+}
+
 /// Accumulates Bézier path rendering commands into an `Outline` structure.
 #[derive(Clone, Debug)]
 pub struct OutlineBuilder {
@@ -178,7 +325,7 @@ impl OutlineBuilder {
     pub fn take_outline(&mut self) -> Outline {
         assert!(self.current_contour.positions.is_empty());
         self.current_contour = Contour::new();
-        mem::replace(&mut self.outline, Outline::new())
+        mem::take(&mut self.outline)
     }
 }
 
@@ -212,6 +359,6 @@ impl OutlineSink for OutlineBuilder {
     fn close(&mut self) {
         self.outline
             .contours
-            .push(mem::replace(&mut self.current_contour, Contour::new()));
+            .push(mem::take(&mut self.current_contour));
     }
 }
@@ -15,6 +15,9 @@
 
 use std::fmt::{self, Debug, Display, Formatter};
 
+use crate::matching;
+use crate::math::{piecewise_linear_find_index, piecewise_linear_lookup};
+
 /// Properties that specify which font in a family to use: e.g. style, weight, and stretchiness.
 ///
 /// This object supports a method chaining style for idiomatic initialization; e.g.
@@ -59,10 +62,74 @@ impl Properties {
         self.stretch = stretch;
         self
     }
+
+    /// Guesses a property set from a style name such as "Bold Italic Condensed".
+    ///
+    /// Weight, width, and style keywords are recognized in any order and case. Words may be
+    /// separated by spaces, hyphens, or underscores, or run together in camel case as in
+    /// PostScript names, so "Semi Bold", "Semi-Bold", and "SemiBold" all mean semibold. Other
+    /// words are ignored, and properties without a keyword keep their default values.
+    ///
+    /// Loaders use this for fonts whose `OS/2` table is missing.
+    pub fn from_style_name(name: &str) -> Properties {
+        let mut words: Vec<String> = vec![];
+        let mut previous_char = ' ';
+        for character in name.chars() {
+            if character.is_whitespace() || character == '-' || character == '_' {
+                previous_char = ' ';
+                continue;
+            }
+            let starts_word =
+                previous_char == ' ' || (character.is_uppercase() && previous_char.is_lowercase());
+            if starts_word {
+                words.push(String::new());
+            }
+            if let Some(word) = words.last_mut() {
+                word.extend(character.to_lowercase());
+            }
+            previous_char = character;
+        }
+
+        let mut properties = Properties::new();
+        let mut words = words.into_iter().peekable();
+        while let Some(mut word) = words.next() {
+            // Join modifiers to the word they modify: "extra light" becomes "extralight".
+            if matches!(&*word, "semi" | "demi" | "extra" | "ultra") {
+                let modifies = |next_word: &String| {
+                    matches!(&**next_word, "light" | "bold" | "condensed" | "expanded")
+                };
+                if let Some(next_word) = words.next_if(modifies) {
+                    word.push_str(&next_word);
+                }
+            }
+            match &*word {
+                "italic" => properties.style = Style::Italic,
+                "oblique" | "slanted" => properties.style = Style::Oblique,
+                "thin" | "hairline" => properties.weight = Weight::THIN,
+                "extralight" | "ultralight" => properties.weight = Weight::EXTRA_LIGHT,
+                "light" => properties.weight = Weight::LIGHT,
+                "medium" => properties.weight = Weight::MEDIUM,
+                "semibold" | "demibold" | "demi" => properties.weight = Weight::SEMIBOLD,
+                "bold" => properties.weight = Weight::BOLD,
+                "extrabold" | "ultrabold" => properties.weight = Weight::EXTRA_BOLD,
+                "black" | "heavy" => properties.weight = Weight::BLACK,
+                "ultracondensed" => properties.stretch = Stretch::ULTRA_CONDENSED,
+                "extracondensed" => properties.stretch = Stretch::EXTRA_CONDENSED,
+                "condensed" | "narrow" => properties.stretch = Stretch::CONDENSED,
+                "semicondensed" => properties.stretch = Stretch::SEMI_CONDENSED,
+                "semiexpanded" => properties.stretch = Stretch::SEMI_EXPANDED,
+                "expanded" | "extended" => properties.stretch = Stretch::EXPANDED,
+                "extraexpanded" => properties.stretch = Stretch::EXTRA_EXPANDED,
+                "ultraexpanded" => properties.stretch = Stretch::ULTRA_EXPANDED,
+                _ => {}
+            }
+        }
+        properties
+    }
 }
 
 /// Allows italic or oblique faces to be selected.
-#[derive(Clone, Copy, PartialEq, Debug, Hash, Default)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash, Default)]
 pub enum Style {
     /// A face that is neither italic not obliqued.
     #[default]
@@ -81,6 +148,10 @@ impl Display for Style {
 
 /// The degree of blackness or stroke thickness of a font. This value ranges from 100.0 to 900.0,
 /// with 400.0 as normal.
+///
+/// CSS Fonts Level 4 allows any weight from 1.0 to 1000.0. Font matching clamps weights to that
+/// range, so a query for `Weight(0.0)` finds the lightest face and one for `Weight(1200.0)` the
+/// heaviest.
 #[derive(Clone, Copy, Debug, PartialEq, PartialOrd)]
 pub struct Weight(pub f32);
 
@@ -110,6 +181,66 @@ impl Weight {
     pub const EXTRA_BOLD: Weight = Weight(800.0);
     /// Black weight (900), the thickest value.
     pub const BLACK: Weight = Weight(900.0);
+    /// The lightest weight that CSS Fonts Level 4 allows (1).
+    pub const MIN: Weight = Weight(1.0);
+    /// The heaviest weight that CSS Fonts Level 4 allows (1000).
+    pub const MAX: Weight = Weight(1000.0);
+
+    /// Clamps this weight to the range from `Weight::MIN` to `Weight::MAX`.
+    ///
+    /// NaN becomes `Weight::NORMAL`.
+    #[inline]
+    pub fn clamped(self) -> Weight {
+        if self.0.is_nan() {
+            Weight::NORMAL
+        } else {
+            Weight(self.0.clamp(Weight::MIN.0, Weight::MAX.0))
+        }
+    }
+
+    // The named weights and the keywords for them, lightest first.
+    const NAMED: [(Weight, &'static str); 9] = [
+        (Weight::THIN, "thin"),
+        (Weight::EXTRA_LIGHT, "extra-light"),
+        (Weight::LIGHT, "light"),
+        (Weight::NORMAL, "normal"),
+        (Weight::MEDIUM, "medium"),
+        (Weight::SEMIBOLD, "semi-bold"),
+        (Weight::BOLD, "bold"),
+        (Weight::EXTRA_BOLD, "extra-bold"),
+        (Weight::BLACK, "black"),
+    ];
+
+    /// Returns the named weight closest to this one, from `Weight::THIN` to `Weight::BLACK`,
+    /// along with its common name in the CSS specification, such as `"semi-bold"`.
+    ///
+    /// A weight exactly halfway between two named weights, such as 350, snaps to the heavier
+    /// one. Weights below 100 snap to `Weight::THIN` and above 900 to `Weight::BLACK`; NaN
+    /// snaps to `Weight::NORMAL`.
+    pub fn nearest_named(self) -> (Weight, &'static str) {
+        if self.0.is_nan() {
+            return (Weight::NORMAL, "normal");
+        }
+        let weight = self.0.clamp(Weight::THIN.0, Weight::BLACK.0);
+        let index = (weight / 100.0 + 0.5).floor() as usize - 1;
+        Weight::NAMED[index]
+    }
+
+    /// Returns the weight in `available`, such as the weights of the faces of a family, that
+    /// CSS font matching would select for this weight.
+    ///
+    /// Matching prefers lighter weights for requests up to 500 and heavier weights above that;
+    /// see `matching::find_best_match()`. Returns this weight unchanged if `available` is empty.
+    pub fn clamp_to_available(self, available: &[Weight]) -> Weight {
+        let candidates: Vec<Properties> = available
+            .iter()
+            .map(|&weight| *Properties::new().weight(weight))
+            .collect();
+        match matching::find_best_match(&candidates, Properties::new().weight(self)) {
+            Ok(index) => available[index],
+            Err(_) => self,
+        }
+    }
 }
 
 /// The width of a font as an approximate fraction of the normal width.
@@ -158,3 +289,113 @@ impl Stretch {
         Stretch::ULTRA_EXPANDED.0,
     ];
 }
+
+/// The range of widths that a font covers.
+///
+/// Static fonts cover a single width. Variable fonts with a `wdth` axis cover every width between
+/// the minimum and maximum of that axis, per CSS Fonts Level 4.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StretchRange {
+    /// The narrowest width covered, inclusive.
+    pub min: Stretch,
+    /// The widest width covered, inclusive.
+    pub max: Stretch,
+}
+
+impl StretchRange {
+    /// Creates a range covering the widths from `min` to `max` inclusive.
+    #[inline]
+    pub fn new(min: Stretch, max: Stretch) -> StretchRange {
+        StretchRange { min, max }
+    }
+
+    /// Creates a range from the minimum and maximum values of a `wdth` variation axis, which are
+    /// expressed as percentages of the normal width.
+    #[inline]
+    pub fn from_wdth_axis(min: f32, max: f32) -> StretchRange {
+        StretchRange::new(Stretch(min / 100.0), Stretch(max / 100.0))
+    }
+
+    /// Returns true if and only if `stretch` lies within this range.
+    #[inline]
+    pub fn contains(&self, stretch: Stretch) -> bool {
+        self.min <= stretch && stretch <= self.max
+    }
+
+    /// Returns the width in this range that is closest to `stretch`.
+    #[inline]
+    pub fn clamp(&self, stretch: Stretch) -> Stretch {
+        if stretch < self.min {
+            self.min
+        } else if stretch > self.max {
+            self.max
+        } else {
+            stretch
+        }
+    }
+}
+
+impl From<Stretch> for StretchRange {
+    #[inline]
+    fn from(stretch: Stretch) -> StretchRange {
+        StretchRange::new(stretch, stretch)
+    }
+}
+
+/// Tables that convert between a platform's normalized weight and width scales and CSS values.
+///
+/// Core Text describes weights and widths as values from -1.0 to 1.0. The default mapping,
+/// `PropertyMapping::CORE_TEXT`, matches the system fonts; supply your own to give fonts with
+/// custom weight semantics (e.g. those of a design system) precise CSS values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PropertyMapping {
+    /// The normalized weights that correspond to the CSS weights 100, 200, ..., 900, in ascending
+    /// order.
+    pub weights: [f32; 9],
+    /// The CSS stretch values that correspond to the normalized widths -1.0, -0.75, ..., 1.0, in
+    /// ascending order.
+    pub widths: [f32; 9],
+}
+
+impl PropertyMapping {
+    /// The mapping that Core Text uses for its system fonts.
+    pub const CORE_TEXT: PropertyMapping = PropertyMapping {
+        weights: [-0.7, -0.5, -0.23, 0.0, 0.2, 0.3, 0.4, 0.6, 0.8],
+        widths: Stretch::MAPPING,
+    };
+
+    /// Converts a normalized weight to a CSS weight.
+    ///
+    /// Weights beyond the ends of the table map to the ends of the CSS range it covers.
+    pub fn weight_to_css(&self, normalized_weight: f32) -> Weight {
+        let index = piecewise_linear_find_index(normalized_weight, &self.weights);
+        Weight(index * 100.0 + 100.0)
+    }
+
+    /// Converts a CSS weight to a normalized weight.
+    ///
+    /// The table only covers the named weights, so lighter and heavier weights map to its ends.
+    pub fn weight_from_css(&self, weight: Weight) -> f32 {
+        let weight = weight.clamped().0.clamp(100.0, 900.0);
+        piecewise_linear_lookup(weight / 100.0 - 1.0, &self.weights)
+    }
+
+    /// Converts a normalized width to a CSS stretch value.
+    pub fn width_to_css(&self, normalized_width: f32) -> Stretch {
+        let index = (normalized_width.clamp(-1.0, 1.0) + 1.0) * 4.0;
+        Stretch(piecewise_linear_lookup(index, &self.widths))
+    }
+
+    /// Converts a CSS stretch value to a normalized width.
+    pub fn width_from_css(&self, stretch: Stretch) -> f32 {
+        let stretch = stretch.0.clamp(self.widths[0], self.widths[8]);
+        0.25 * piecewise_linear_find_index(stretch, &self.widths) - 1.0
+    }
+}
+
+impl Default for PropertyMapping {
+    #[inline]
+    fn default() -> PropertyMapping {
+        PropertyMapping::CORE_TEXT
+    }
+}
@@ -0,0 +1,183 @@
+// font-kit/src/sdf.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Signed distance fields generated from glyph outlines.
+//!
+//! Curves are flattened to line segments in canvas space. Each pixel then takes the distance from
+//! its center to the nearest segment, signed by whether the center is inside the outline under
+//! the nonzero winding rule.
+
+use pathfinder_geometry::line_segment::LineSegment2F;
+use pathfinder_geometry::rect::RectI;
+use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::vector::{Vector2F, Vector2I};
+
+use crate::canvas::Canvas;
+use crate::outline::OutlineSink;
+
+// The longest a flattened piece of a curve may be, in pixels.
+const MAX_FLATTENED_SEGMENT_LENGTH: f32 = 1.0;
+// Curves are never split into more pieces than this.
+const MAX_CURVE_SUBDIVISIONS: u32 = 64;
+
+/// An outline sink that flattens an outline into line segments, mapping each point into canvas
+/// space with a transform.
+pub(crate) struct SegmentCollector {
+    transform: Transform2F,
+    segments: Vec<LineSegment2F>,
+    first_point: Vector2F,
+    current_point: Vector2F,
+}
+
+impl SegmentCollector {
+    pub(crate) fn new(transform: Transform2F) -> SegmentCollector {
+        SegmentCollector {
+            transform,
+            segments: vec![],
+            first_point: Vector2F::default(),
+            current_point: Vector2F::default(),
+        }
+    }
+
+    /// Closes the last contour and returns the segments collected.
+    pub(crate) fn into_segments(mut self) -> Vec<LineSegment2F> {
+        self.close();
+        self.segments
+    }
+
+    fn push_line(&mut self, to: Vector2F) {
+        if to != self.current_point {
+            self.segments
+                .push(LineSegment2F::new(self.current_point, to));
+        }
+        self.current_point = to;
+    }
+
+    fn push_curve<F>(&mut self, control_polygon_length: f32, to: Vector2F, evaluate: F)
+    where
+        F: Fn(f32) -> Vector2F,
+    {
+        let subdivisions = (control_polygon_length / MAX_FLATTENED_SEGMENT_LENGTH)
+            .ceil()
+            .clamp(1.0, MAX_CURVE_SUBDIVISIONS as f32) as u32;
+        for step in 1..subdivisions {
+            self.push_line(evaluate(step as f32 / subdivisions as f32));
+        }
+        self.push_line(to);
+    }
+}
+
+impl OutlineSink for SegmentCollector {
+    fn move_to(&mut self, to: Vector2F) {
+        self.close();
+        self.first_point = self.transform * to;
+        self.current_point = self.first_point;
+    }
+
+    fn line_to(&mut self, to: Vector2F) {
+        self.push_line(self.transform * to);
+    }
+
+    fn quadratic_curve_to(&mut self, ctrl: Vector2F, to: Vector2F) {
+        let (from, ctrl, to) = (
+            self.current_point,
+            self.transform * ctrl,
+            self.transform * to,
+        );
+        let length = (ctrl - from).length() + (to - ctrl).length();
+        self.push_curve(length, to, |t| {
+            let u = 1.0 - t;
+            from * (u * u) + ctrl * (2.0 * u * t) + to * (t * t)
+        });
+    }
+
+    fn cubic_curve_to(&mut self, ctrl: LineSegment2F, to: Vector2F) {
+        let from = self.current_point;
+        let (ctrl0, ctrl1) = (self.transform * ctrl.from(), self.transform * ctrl.to());
+        let to = self.transform * to;
+        let length = (ctrl0 - from).length() + (ctrl1 - ctrl0).length() + (to - ctrl1).length();
+        self.push_curve(length, to, |t| {
+            let u = 1.0 - t;
+            from * (u * u * u)
+                + ctrl0 * (3.0 * u * u * t)
+                + ctrl1 * (3.0 * u * t * t)
+                + to * (t * t * t)
+        });
+    }
+
+    fn close(&mut self) {
+        let first_point = self.first_point;
+        self.push_line(first_point);
+    }
+}
+
+/// Writes the signed distance field of a flattened outline to an 8-bit canvas.
+///
+/// Values of 128 lie on the outline, rising to 255 at `spread` pixels inside it and falling to 0
+/// at `spread` pixels outside. Each pixel keeps the larger of its old and new values, so that
+/// fields drawn to the same canvas combine into the field of the union of their shapes.
+pub(crate) fn render(canvas: &mut Canvas, collector: SegmentCollector, spread: f32) {
+    let segments = collector.into_segments();
+    if segments.is_empty() || spread <= 0.0 {
+        return;
+    }
+
+    let (mut min, mut max) = (segments[0].from(), segments[0].from());
+    for segment in &segments {
+        min = min.min(segment.to());
+        max = max.max(segment.to());
+    }
+    let bounds = RectI::from_points(
+        (min - Vector2F::splat(spread)).floor().to_i32(),
+        (max + Vector2F::splat(spread)).ceil().to_i32(),
+    );
+    let bounds = match bounds.intersection(RectI::new(Vector2I::default(), canvas.size)) {
+        Some(bounds) => bounds,
+        None => return,
+    };
+
+    for y in bounds.min_y()..bounds.max_y() {
+        let row = canvas.row_mut(y as usize);
+        for x in bounds.min_x()..bounds.max_x() {
+            let center = Vector2F::new(x as f32 + 0.5, y as f32 + 0.5);
+            let distance = signed_distance(&segments, center);
+            let value = ((0.5 + distance / (2.0 * spread)).clamp(0.0, 1.0) * 255.0).round() as u8;
+            let pixel = &mut row[x as usize];
+            *pixel = (*pixel).max(value);
+        }
+    }
+}
+
+// Returns the distance from `point` to the nearest segment, positive inside the outline and
+// negative outside.
+fn signed_distance(segments: &[LineSegment2F], point: Vector2F) -> f32 {
+    let mut min_square_distance = f32::INFINITY;
+    let mut winding = 0;
+    for segment in segments {
+        let (from, to) = (segment.from(), segment.to());
+        let vector = to - from;
+        let t = ((point - from).dot(vector) / vector.square_length()).clamp(0.0, 1.0);
+        min_square_distance = min_square_distance.min((from + vector * t - point).square_length());
+
+        // Count crossings of a ray from the point toward positive x.
+        if (from.y() <= point.y()) != (to.y() <= point.y()) {
+            let crossing_x = from.x() + (point.y() - from.y()) / vector.y() * vector.x();
+            if crossing_x > point.x() {
+                winding += if to.y() > from.y() { 1 } else { -1 };
+            }
+        }
+    }
+    let distance = min_square_distance.sqrt();
+    if winding != 0 {
+        distance
+    } else {
+        -distance
+    }
+}
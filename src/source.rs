@@ -15,10 +15,17 @@ use crate::family::Family;
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::font::Font;
+use crate::font_info::FontInfo;
 use crate::handle::Handle;
-use crate::matching;
-use crate::properties::Properties;
+use crate::matching::{self, MatchPriority};
+use crate::properties::{Properties, Style};
+use crate::tables;
 use std::any::Any;
+use std::mem;
+use std::ops::{ControlFlow, RangeInclusive};
+
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
 
 #[cfg(all(
     any(target_os = "macos", target_os = "ios"),
@@ -47,6 +54,9 @@ pub use crate::sources::fs::FsSource as SystemSource;
 #[cfg(target_env = "ohos")]
 pub use crate::sources::fs::FsSource as SystemSource;
 
+// These are only used by sources that can't resolve generic families themselves. The Fontconfig
+// and Core Text sources ask the platform instead.
+//
 // FIXME(pcwalton): These could expand to multiple fonts, and they could be language-specific.
 #[cfg(any(target_family = "windows", target_os = "macos", target_os = "ios"))]
 const DEFAULT_FONT_FAMILY_SERIF: &'static str = "Times New Roman";
@@ -86,6 +96,67 @@ const DEFAULT_FONT_FAMILY_CURSIVE: &str = "cursive";
 #[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
 const DEFAULT_FONT_FAMILY_FANTASY: &str = "fantasy";
 
+bitflags! {
+    /// The optional `Source` operations that a source performs natively, rather than with the
+    /// trait's generic fallback.
+    ///
+    /// Every operation works on every source; a missing flag means only that it is emulated,
+    /// which is typically slower (loading every font, for example) or less faithful to the
+    /// platform. See `Source::capabilities()`.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+    pub struct SourceCapabilities: u32 {
+        /// `select_by_postscript_name()` looks the name up directly instead of loading every
+        /// font in the source.
+        const POSTSCRIPT_NAME_LOOKUP = 0x01;
+        /// `select_by_postscript_name_prefix()` searches an index of names instead of loading
+        /// every font in the source.
+        const POSTSCRIPT_NAME_PREFIX_LOOKUP = 0x02;
+        /// Generic families such as `FamilyName::SansSerif` are resolved by the platform's
+        /// configuration instead of a fixed list of family names.
+        const GENERIC_FAMILIES = 0x04;
+        /// `default_ui_font()` reports the platform's user interface font instead of the best
+        /// sans-serif match.
+        const DEFAULT_UI_FONT = 0x08;
+        /// `default_fallback_families()` reports the platform's fallback chain instead of just
+        /// `FamilyName::SansSerif`.
+        const FALLBACK_FAMILIES = 0x10;
+    }
+}
+
+/// What a font handle refers to: the names and properties of the face, as returned by
+/// `Source::describe_handle()`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaceDescription {
+    /// The name of the font family, as `Font::family_name()` reports it.
+    pub family_name: String,
+    /// The name of the face within its family, such as `"Bold Italic"`.
+    pub style_name: String,
+    /// The PostScript name of the face, if it has one.
+    pub postscript_name: Option<String>,
+    /// The style, weight, and width of the face.
+    pub properties: Properties,
+    /// True if the face has variation axes.
+    pub is_variable: bool,
+    /// True if the face has color glyphs, from `COLR`, `sbix`, or `CBDT` tables.
+    pub is_color: bool,
+}
+
+/// The face chosen by `Source::select_best_match_with_synthesis()`, along with the styling that a
+/// renderer has to fake because the face lacks it.
+#[derive(Clone, Debug)]
+pub struct BestMatch {
+    /// The handle of the face.
+    pub handle: Handle,
+    /// The style, weight, and width of the face.
+    pub properties: Properties,
+    /// True if an italic or oblique face was asked for but the face is upright; see
+    /// `matching::needs_synthetic_italic()`.
+    pub needs_synthetic_italic: bool,
+    /// True if a bold face was asked for but the face is lighter; see
+    /// `matching::needs_synthetic_bold()`.
+    pub needs_synthetic_bold: bool,
+}
+
 /// A database of installed fonts that can be queried.
 ///
 /// This trait is object-safe.
@@ -96,9 +167,70 @@ pub trait Source: Any {
     /// Returns the names of all families installed on the system.
     fn all_families(&self) -> Result<Vec<String>, SelectionError>;
 
+    /// Returns the names of all families installed on the system, sorted for display and with
+    /// case-insensitive duplicates removed.
+    ///
+    /// This is a locale-independent sort on case- and accent-folded names, not Unicode
+    /// collation. Names are lowercased, and accented Latin letters and ligatures are folded
+    /// onto their base letters, so that "Ångström" sorts next to "Angus" rather than after
+    /// "Zapfino"; everything else compares by code point. Names that fold to the same key are
+    /// then ordered by their accents and finally by case. No locale's conventions are applied
+    /// (such as Swedish placing "Å" after "Z"). Use `all_families()` to get the names in the
+    /// order the backend reports them.
+    fn all_families_sorted_by_folded_name(&self) -> Result<Vec<String>, SelectionError> {
+        let mut families = self.all_families()?;
+        families
+            .sort_by_cached_key(|name| (folded_sort_key(name), name.to_lowercase(), name.clone()));
+        families.dedup_by(|a, b| a.to_lowercase() == b.to_lowercase());
+        Ok(families)
+    }
+
+    /// Calls `f` with the name of each family installed on the system, without collecting the
+    /// names into a vector first.
+    ///
+    /// If `f` returns `ControlFlow::Break`, iteration stops immediately and this method returns
+    /// `Ok(ControlFlow::Break(()))`; otherwise, it returns `Ok(ControlFlow::Continue(()))` once
+    /// every family has been visited. Names are not guaranteed to be sorted or, for sources that
+    /// aggregate other sources, unique.
+    ///
+    /// The default implementation visits the names returned by `all_families()`, so it saves
+    /// nothing over calling that directly; the built-in sources override it to stream names from
+    /// the backend.
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        for family_name in self.all_families()? {
+            if f(&family_name).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
+        }
+        Ok(ControlFlow::Continue(()))
+    }
+
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
+    ///
+    /// The built-in sources return `SelectionError::InvalidQuery` if the name is empty, consists
+    /// only of whitespace, or contains a NUL character.
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError>;
 
+    /// Looks up a font family by name, as `select_family_by_name()` does, and loads all the
+    /// fonts in it.
+    ///
+    /// Fonts that fail to load are logged and skipped, so the result may be shorter than the
+    /// family, or even empty.
+    fn select_family_loaded(&self, family_name: &str) -> Result<Vec<Font>, SelectionError> {
+        let family_handle = self.select_family_by_name(family_name)?;
+        let mut fonts = vec![];
+        for font_handle in family_handle.fonts() {
+            match Font::from_handle(font_handle) {
+                Ok(font) => fonts.push(font),
+                Err(e) => log::warn!("Error loading font from handle: {:?}", e),
+            }
+        }
+        Ok(fonts)
+    }
+
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
     /// The default implementation, which is used by the DirectWrite and the filesystem backends,
@@ -121,8 +253,41 @@ pub trait Source: Any {
         Err(SelectionError::NotFound)
     }
 
-    // FIXME(pcwalton): This only returns one family instead of multiple families for the generic
-    // family names.
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// This is useful for picking out all the faces of a family whose fonts are named
+    /// systematically, such as `Roboto-` for `Roboto-Bold` and `Roboto-Italic`. The comparison is
+    /// case-sensitive, like PostScript names themselves. Returns `SelectionError::NotFound` if no
+    /// font matches.
+    ///
+    /// The default implementation loads every font in the source to read its name. Sources that
+    /// keep an index of PostScript names, or that can ask the platform for them, override it;
+    /// they report `SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP`.
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let mut matches = vec![];
+        for family_name in self.all_families()? {
+            if let Ok(family_handle) = self.select_family_by_name(&family_name) {
+                if let Ok(family) = Family::<Font>::from_handle(&family_handle) {
+                    for (handle, font) in family_handle.fonts().iter().zip(family.fonts().iter()) {
+                        if let Some(postscript_name) = font.postscript_name() {
+                            if postscript_name.starts_with(prefix) {
+                                matches.push((postscript_name, handle.clone()));
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        sort_by_postscript_name(matches)
+    }
+
+    // Returns the single family that best represents a generic family name; see
+    // `select_families_by_generic_name()` for the whole fallback chain.
+    //
+    // Sources that can ask the platform which family a generic name stands for override this.
     #[doc(hidden)]
     fn select_family_by_generic_name(
         &self,
@@ -130,31 +295,297 @@ pub trait Source: Any {
     ) -> Result<FamilyHandle, SelectionError> {
         match *family_name {
             FamilyName::Title(ref title) => self.select_family_by_name(title),
-            FamilyName::Serif => self.select_family_by_name(DEFAULT_FONT_FAMILY_SERIF),
-            FamilyName::SansSerif => self.select_family_by_name(DEFAULT_FONT_FAMILY_SANS_SERIF),
-            FamilyName::Monospace => self.select_family_by_name(DEFAULT_FONT_FAMILY_MONOSPACE),
-            FamilyName::Cursive => self.select_family_by_name(DEFAULT_FONT_FAMILY_CURSIVE),
-            FamilyName::Fantasy => self.select_family_by_name(DEFAULT_FONT_FAMILY_FANTASY),
+            ref generic_name => {
+                self.select_family_by_name(default_generic_family_name(generic_name))
+            }
         }
     }
 
+    /// Looks up a family name and returns the handles of the fonts in every family it stands
+    /// for, best first.
+    ///
+    /// A `FamilyName::Title` stands for just the family with that name. The generic families can
+    /// stand for several: the Fontconfig source expands them into the platform's whole fallback
+    /// chain, so that a generic like `sans-serif` includes families covering other scripts. The
+    /// default implementation, which is used by the other sources, returns just the family that
+    /// `FamilyName::SansSerif` and the like map to.
+    fn select_families_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<Vec<FamilyHandle>, SelectionError> {
+        Ok(vec![self.select_family_by_generic_name(family_name)?])
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
+    ///
+    /// Generic family names are expanded with `select_families_by_generic_name()`, and each of the
+    /// families they stand for is tried in turn.
+    ///
+    /// Returns `SelectionError::InvalidQuery` if any of the family names is malformed; see
+    /// `select_family_by_name()`.
     #[inline]
     fn select_best_match(
         &self,
         family_names: &[FamilyName],
         properties: &Properties,
     ) -> Result<Handle, SelectionError> {
-        for family_name in family_names {
-            if let Ok(family_handle) = self.select_family_by_generic_name(family_name) {
-                let candidates = self.select_descriptions_in_family(&family_handle)?;
-                if let Ok(index) = matching::find_best_match(&candidates, properties) {
-                    return Ok(family_handle.fonts[index].clone());
+        find_in_families(self, family_names, |family_handle| {
+            let candidates = self.select_descriptions_in_family(family_handle)?;
+            Ok(matching::find_best_match(&candidates, properties)
+                .ok()
+                .map(|index| family_handle.fonts[index].clone()))
+        })
+    }
+
+    /// Performs font matching like `select_best_match()`, and also reports whether the face
+    /// found lacks the slant or boldness that `properties` asks for.
+    ///
+    /// Matching falls back to an upright face when a family has no italic or oblique faces, and
+    /// to a lighter face when it has no bold ones, without saying so. The flags in the result
+    /// tell a renderer to make up the difference by shearing the glyphs, conventionally by about
+    /// 12 degrees, or by emboldening them.
+    fn select_best_match_with_synthesis(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<BestMatch, SelectionError> {
+        find_in_families(self, family_names, |family_handle| {
+            let candidates = self.select_descriptions_in_family(family_handle)?;
+            Ok(matching::find_best_match(&candidates, properties)
+                .ok()
+                .map(|index| {
+                    let face = candidates[index];
+                    BestMatch {
+                        handle: family_handle.fonts[index].clone(),
+                        properties: face,
+                        needs_synthetic_italic: matching::needs_synthetic_italic(&face, properties),
+                        needs_synthetic_bold: matching::needs_synthetic_bold(&face, properties),
+                    }
+                }))
+        })
+    }
+
+    /// Performs font matching like `select_best_match()`, but settles width, style, and weight
+    /// in the order given by `priority`.
+    ///
+    /// This suits applications where one property must dominate, such as a design tool that
+    /// would rather show the requested weight in an upright face than an italic of the wrong
+    /// weight. `MatchPriority::default()` is the CSS order and behaves like
+    /// `select_best_match()`.
+    fn select_best_match_with_priority(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        priority: MatchPriority,
+    ) -> Result<Handle, SelectionError> {
+        if priority == MatchPriority::default() {
+            return self.select_best_match(family_names, properties);
+        }
+        find_in_families(self, family_names, |family_handle| {
+            let candidates = self.select_descriptions_in_family(family_handle)?;
+            Ok(
+                matching::find_best_match_with_priority(&candidates, properties, priority)
+                    .ok()
+                    .map(|index| family_handle.fonts[index].clone()),
+            )
+        })
+    }
+
+    /// Performs font matching like `select_best_match()`, but only considers faces whose
+    /// character maps cover every character in `unicode_range`.
+    ///
+    /// This is like restricting `@font-face` rules with the CSS `unicode-range` descriptor: it
+    /// keeps matching from settling on a face that lacks the glyphs the text needs. Pass just the
+    /// characters that must be present; a range is covered only if all of its characters are. If
+    /// no face in a family qualifies, the next family is tried. `None` imposes no constraint.
+    fn select_best_match_in_range(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+        unicode_range: Option<&[RangeInclusive<char>]>,
+    ) -> Result<Handle, SelectionError> {
+        let unicode_range = match unicode_range {
+            Some(unicode_range) => unicode_range,
+            None => return self.select_best_match(family_names, properties),
+        };
+        find_in_families(self, family_names, |family_handle| {
+            let (mut handles, mut candidates) = (vec![], vec![]);
+            for font_handle in family_handle.fonts() {
+                match Font::from_handle(font_handle) {
+                    Ok(font) => {
+                        let covers_range = unicode_range
+                            .iter()
+                            .flat_map(|range| range.clone())
+                            .all(|character| font.glyph_for_char(character).is_some());
+                        if covers_range {
+                            handles.push(font_handle.clone());
+                            candidates.push(font.properties());
+                        }
+                    }
+                    Err(e) => log::warn!("Error loading font from handle: {:?}", e),
                 }
             }
+            Ok(matching::find_best_match(&candidates, properties)
+                .ok()
+                .map(|index| handles[index].clone()))
+        })
+    }
+
+    /// Looks up a font family by name and returns the handles of all the fonts in that family,
+    /// sorted in the order a font picker would typically display them.
+    ///
+    /// Faces are ordered by stretch first (most condensed to most expanded), then by weight
+    /// (thinnest to blackest), then by style (normal, then italic, then oblique). Faces that
+    /// compare equal keep the order the source returned them in. Each face is loaded once to
+    /// read its properties; faces that fail to load are omitted.
+    fn select_family_sorted_by_properties(
+        &self,
+        family_name: &str,
+    ) -> Result<FamilyHandle, SelectionError> {
+        let family_handle = self.select_family_by_name(family_name)?;
+        let mut faces = vec![];
+        for font_handle in family_handle.fonts() {
+            match Font::from_handle(font_handle) {
+                Ok(font) => faces.push((font_handle.clone(), font.properties())),
+                Err(e) => log::warn!("Error loading font from handle: {:?}", e),
+            }
         }
-        Err(SelectionError::NotFound)
+        faces.sort_by(|(_, a), (_, b)| {
+            a.stretch
+                .0
+                .total_cmp(&b.stretch.0)
+                .then(a.weight.0.total_cmp(&b.weight.0))
+                .then(style_sort_rank(a.style).cmp(&style_sort_rank(b.style)))
+        });
+
+        let mut sorted =
+            FamilyHandle::from_font_handles(faces.into_iter().map(|(handle, _)| handle));
+        sorted.set_display_name(family_handle.display_name());
+        Ok(sorted)
+    }
+
+    /// Looks up the font family whose name is closest to `family_name`, tolerating typos and
+    /// small differences in spelling.
+    ///
+    /// If `select_family_by_name()` finds `family_name` itself, that family is returned.
+    /// Otherwise, each installed family name is compared with `family_name` by Levenshtein
+    /// distance: the number of single-character insertions, deletions, and substitutions needed to
+    /// turn one into the other, counted in Unicode scalar values after lowercasing both names. The
+    /// family with the smallest distance is returned, provided that distance is at most
+    /// `max_distance`; ties go to the name that comes first in
+    /// `all_families_sorted_by_folded_name()`. Returns `SelectionError::NotFound` if no family is
+    /// close enough.
+    ///
+    /// Every family name is examined, so this is much slower than an exact lookup.
+    fn select_family_fuzzy(
+        &self,
+        family_name: &str,
+        max_distance: usize,
+    ) -> Result<FamilyHandle, SelectionError> {
+        check_family_name(family_name)?;
+        match self.select_family_by_name(family_name) {
+            Err(SelectionError::NotFound) => {}
+            result => return result,
+        }
+
+        let query: Vec<char> = family_name.to_lowercase().chars().collect();
+        let mut closest: Option<(usize, String)> = None;
+        for candidate in self.all_families_sorted_by_folded_name()? {
+            let candidate_chars: Vec<char> = candidate.to_lowercase().chars().collect();
+            let distance = levenshtein_distance(&query, &candidate_chars);
+            let is_closer = match closest {
+                Some((closest_distance, _)) => distance < closest_distance,
+                None => true,
+            };
+            if distance <= max_distance && is_closer {
+                closest = Some((distance, candidate));
+            }
+        }
+        match closest {
+            Some((_, closest_name)) => self.select_family_by_name(&closest_name),
+            None => Err(SelectionError::NotFound),
+        }
+    }
+
+    /// Returns the font that the platform uses for user interface text, such as menus and dialog
+    /// boxes.
+    ///
+    /// The system sources ask the platform: Core Text for its system font, DirectWrite for the
+    /// message box font that Windows is configured with, and Fontconfig for whatever the
+    /// `system-ui` alias resolves to. The default implementation, which is used by the other
+    /// sources, returns the best match for `FamilyName::SansSerif`.
+    fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        self.select_best_match(&[FamilyName::SansSerif], &Properties::new())
+    }
+
+    /// Returns the families that the platform falls back to, in order, when the user interface
+    /// font lacks a glyph.
+    ///
+    /// The system sources report the platform's own fallback chain, which is typically
+    /// language-dependent and much longer than the list of generic families. The default
+    /// implementation returns just `FamilyName::SansSerif`.
+    fn default_fallback_families(&self) -> Vec<FamilyName> {
+        vec![FamilyName::SansSerif]
+    }
+
+    /// Describes the face that a handle refers to, whether or not it came from this source.
+    ///
+    /// The style name comes from the font's `name` table, matching the family name. Fonts
+    /// without one, such as PCF fonts, take it from the full name with the family name removed,
+    /// or `"Regular"` if nothing is left. The default implementation, which is used by all the
+    /// built-in sources, loads the font.
+    fn describe_handle(&self, handle: &Handle) -> Result<FaceDescription, SelectionError> {
+        describe_handle(handle)
+    }
+
+    /// Returns every font in this source along with a description of it, in the order
+    /// `all_fonts()` reports them.
+    ///
+    /// This is meant for font managers that list every face at once. TrueType and OpenType fonts
+    /// are described from their tables alone, as `FontInfo` reads them, without loading them;
+    /// their family names are decoded in full, so they may differ slightly from those of a loaded
+    /// `Font`. Other fonts are described by `describe_handle()`. Fonts that can't be described
+    /// are logged and skipped.
+    fn all_face_descriptions(&self) -> Result<Vec<(Handle, FaceDescription)>, SelectionError> {
+        let mut descriptions = vec![];
+        for handle in self.all_fonts()? {
+            let description = match describe_sfnt_handle(&handle) {
+                Some(description) => description,
+                None => match self.describe_handle(&handle) {
+                    Ok(description) => description,
+                    Err(e) => {
+                        log::warn!("Error describing font: {:?}", e);
+                        continue;
+                    }
+                },
+            };
+            descriptions.push((handle, description));
+        }
+        Ok(descriptions)
+    }
+
+    /// Returns every font in this source along with a description of it, loading the fonts on
+    /// several threads at once.
+    ///
+    /// The handles are enumerated on the calling thread with `all_fonts()`, since sources aren't
+    /// shared between threads. They are then described on Rayon's global thread pool as the
+    /// default implementation of `describe_handle()` does. Each font is loaded and dropped on the
+    /// thread that describes it, because FreeType faces can't move between threads. Fonts that fail
+    /// to load are skipped, and the rest are returned in the order `all_fonts()` reports them.
+    ///
+    /// This is meant for bulk indexing tools and is only available with the `rayon` feature.
+    #[cfg(feature = "rayon")]
+    fn all_fonts_parallel(&self) -> Result<Vec<(Handle, FaceDescription)>, SelectionError> {
+        Ok(describe_handles_in_parallel(self.all_fonts()?))
+    }
+
+    /// Reports which optional operations this source performs natively, so that generic code can
+    /// choose efficient paths.
+    ///
+    /// The default implementation reports that everything is emulated.
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::empty()
     }
 
     #[doc(hidden)]
@@ -162,14 +593,7 @@ pub trait Source: Any {
         &self,
         family: &FamilyHandle,
     ) -> Result<Vec<Properties>, SelectionError> {
-        let mut fields = vec![];
-        for font_handle in family.fonts() {
-            match Font::from_handle(font_handle) {
-                Ok(font) => fields.push(font.properties()),
-                Err(e) => log::warn!("Error loading font from handle: {:?}", e),
-            }
-        }
-        Ok(fields)
+        Ok(load_descriptions_in_family(family))
     }
 
     /// Accesses this `Source` as `Any`, which allows downcasting back to a concrete type from a
@@ -180,3 +604,234 @@ pub trait Source: Any {
     /// trait object.
     fn as_mut_any(&mut self) -> &mut dyn Any;
 }
+
+// Loads each font in a family to find its properties, skipping those that fail to load.
+pub(crate) fn load_descriptions_in_family(family: &FamilyHandle) -> Vec<Properties> {
+    let mut fields = vec![];
+    for font_handle in family.fonts() {
+        match Font::from_handle(font_handle) {
+            Ok(font) => fields.push(font.properties()),
+            Err(e) => log::warn!("Error loading font from handle: {:?}", e),
+        }
+    }
+    fields
+}
+
+// Loads the font a handle refers to and describes it.
+fn describe_handle(handle: &Handle) -> Result<FaceDescription, SelectionError> {
+    let font = handle
+        .load()
+        .map_err(|err| SelectionError::CannotAccessSource {
+            reason: Some(err.to_string().into()),
+        })?;
+    let family_name = font.family_name();
+    let style_name = font
+        .load_font_table(tables::TAG_NAME)
+        .and_then(|name| {
+            let os2 = font.load_font_table(tables::TAG_OS2);
+            tables::name_style_name(&name, os2.as_deref())
+        })
+        .unwrap_or_else(|| {
+            let full_name = font.full_name();
+            let style_name = full_name
+                .strip_prefix(&family_name)
+                .unwrap_or(&full_name)
+                .trim();
+            if style_name.is_empty() {
+                "Regular".to_owned()
+            } else {
+                style_name.to_owned()
+            }
+        });
+    Ok(FaceDescription {
+        family_name,
+        style_name,
+        postscript_name: font.postscript_name(),
+        properties: font.properties(),
+        is_variable: font
+            .load_font_table(tables::TAG_FVAR)
+            .is_some_and(|fvar| tables::fvar_has_axes(&fvar)),
+        is_color: [tables::TAG_COLR, tables::TAG_SBIX, tables::TAG_CBLC]
+            .iter()
+            .any(|&tag| font.load_font_table(tag).is_some()),
+    })
+}
+
+// Describes a TrueType or OpenType font from its tables, without loading it. Returns `None` for
+// other fonts and for fonts without family or style names, which `describe_handle()` handles.
+fn describe_sfnt_handle(handle: &Handle) -> Option<FaceDescription> {
+    let font_info = match *handle {
+        #[cfg(not(target_arch = "wasm32"))]
+        Handle::Path {
+            ref path,
+            font_index,
+        } => FontInfo::from_path(path, font_index).ok()?,
+        Handle::Memory {
+            ref bytes,
+            font_index,
+        } => FontInfo::from_bytes(bytes, font_index).ok()?,
+        _ => return None,
+    };
+    if font_info.family_name.is_empty() {
+        return None;
+    }
+    Some(FaceDescription {
+        family_name: font_info.family_name,
+        style_name: font_info.style_name?,
+        postscript_name: font_info.postscript_name,
+        properties: font_info.properties,
+        is_variable: font_info.is_variable,
+        is_color: font_info.is_color,
+    })
+}
+
+// Describes fonts on Rayon's thread pool, preserving the order of `handles` and skipping fonts
+// that fail to load.
+#[cfg(feature = "rayon")]
+fn describe_handles_in_parallel(handles: Vec<Handle>) -> Vec<(Handle, FaceDescription)> {
+    handles
+        .into_par_iter()
+        .filter_map(|handle| {
+            let description = describe_handle(&handle).ok()?;
+            Some((handle, description))
+        })
+        .collect()
+}
+
+// Returns the family that stands in for a generic family name when the source can't resolve it
+// natively. Titles are returned unchanged.
+pub(crate) fn default_generic_family_name(family_name: &FamilyName) -> &str {
+    match *family_name {
+        FamilyName::Title(ref title) => title,
+        FamilyName::Serif => DEFAULT_FONT_FAMILY_SERIF,
+        FamilyName::SansSerif => DEFAULT_FONT_FAMILY_SANS_SERIF,
+        FamilyName::Monospace => DEFAULT_FONT_FAMILY_MONOSPACE,
+        FamilyName::Cursive => DEFAULT_FONT_FAMILY_CURSIVE,
+        FamilyName::Fantasy => DEFAULT_FONT_FAMILY_FANTASY,
+    }
+}
+
+// Tries each family that `family_names` stands for in turn, expanding generic names with
+// `select_families_by_generic_name()`, and returns the first match that `find_match` finds among
+// the faces of a family.
+fn find_in_families<S, T, F>(
+    source: &S,
+    family_names: &[FamilyName],
+    mut find_match: F,
+) -> Result<T, SelectionError>
+where
+    S: Source + ?Sized,
+    F: FnMut(&FamilyHandle) -> Result<Option<T>, SelectionError>,
+{
+    check_family_names(family_names)?;
+    for family_name in family_names {
+        let family_handles = source
+            .select_families_by_generic_name(family_name)
+            .unwrap_or_default();
+        for family_handle in family_handles {
+            if let Some(found) = find_match(&family_handle)? {
+                return Ok(found);
+            }
+        }
+    }
+    Err(SelectionError::NotFound)
+}
+
+/// Rejects family names that can't name any family, so that sources report a programmer error
+/// instead of `SelectionError::NotFound`.
+pub(crate) fn check_family_name(family_name: &str) -> Result<(), SelectionError> {
+    if family_name.trim().is_empty() || family_name.contains('\0') {
+        return Err(SelectionError::InvalidQuery);
+    }
+    Ok(())
+}
+
+// Sorts fonts by PostScript name, keeping fonts with the same name in their original order, and
+// returns their handles.
+pub(crate) fn sort_by_postscript_name(
+    mut matches: Vec<(String, Handle)>,
+) -> Result<Vec<Handle>, SelectionError> {
+    if matches.is_empty() {
+        return Err(SelectionError::NotFound);
+    }
+    matches.sort_by(|(a, _), (b, _)| a.cmp(b));
+    Ok(matches.into_iter().map(|(_, handle)| handle).collect())
+}
+
+fn check_family_names(family_names: &[FamilyName]) -> Result<(), SelectionError> {
+    for family_name in family_names {
+        if let FamilyName::Title(ref title) = *family_name {
+            check_family_name(title)?;
+        }
+    }
+    Ok(())
+}
+
+// Computes the Levenshtein distance between two strings with the two-row dynamic programming
+// algorithm.
+fn levenshtein_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous_row: Vec<usize> = (0..=b.len()).collect();
+    let mut current_row = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current_row[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution_cost = if a_char == b_char { 0 } else { 1 };
+            current_row[j + 1] = (previous_row[j] + substitution_cost)
+                .min(previous_row[j + 1] + 1)
+                .min(current_row[j] + 1);
+        }
+        mem::swap(&mut previous_row, &mut current_row);
+    }
+    previous_row[b.len()]
+}
+
+fn style_sort_rank(style: Style) -> u8 {
+    match style {
+        Style::Normal => 0,
+        Style::Italic => 1,
+        Style::Oblique => 2,
+    }
+}
+
+// Returns `name` lowercased, with accented Latin letters replaced by their base letters and
+// ligatures spelled out, so that names mostly compare the way they would in a dictionary.
+fn folded_sort_key(name: &str) -> String {
+    let mut key = String::with_capacity(name.len());
+    for character in name.chars() {
+        match fold_latin_letter(character) {
+            Some(folded) => key.push_str(folded),
+            None => key.extend(character.to_lowercase()),
+        }
+    }
+    key
+}
+
+fn fold_latin_letter(character: char) -> Option<&'static str> {
+    Some(match character {
+        'À'..='Å' | 'à'..='å' | '\u{100}'..='\u{105}' => "a",
+        'Æ' | 'æ' => "ae",
+        'Ç' | 'ç' | '\u{106}'..='\u{10d}' => "c",
+        'Ð' | 'ð' | '\u{10e}'..='\u{111}' => "d",
+        'È'..='Ë' | 'è'..='ë' | '\u{112}'..='\u{11b}' => "e",
+        '\u{11c}'..='\u{123}' => "g",
+        '\u{124}'..='\u{127}' => "h",
+        'Ì'..='Ï' | 'ì'..='ï' | '\u{128}'..='\u{131}' => "i",
+        '\u{132}' | '\u{133}' => "ij",
+        '\u{134}' | '\u{135}' => "j",
+        '\u{136}'..='\u{138}' => "k",
+        '\u{139}'..='\u{142}' => "l",
+        'Ñ' | 'ñ' | '\u{143}'..='\u{14b}' => "n",
+        'Ò'..='Ö' | 'Ø' | 'ò'..='ö' | 'ø' | '\u{14c}'..='\u{151}' => "o",
+        '\u{152}' | '\u{153}' => "oe",
+        '\u{154}'..='\u{159}' => "r",
+        'ß' => "ss",
+        '\u{15a}'..='\u{161}' | '\u{17f}' => "s",
+        '\u{162}'..='\u{167}' => "t",
+        'Þ' | 'þ' => "th",
+        'Ù'..='Ü' | 'ù'..='ü' | '\u{168}'..='\u{173}' => "u",
+        '\u{174}' | '\u{175}' => "w",
+        'Ý' | 'ý' | 'ÿ' | '\u{176}'..='\u{178}' => "y",
+        '\u{179}'..='\u{17e}' => "z",
+        _ => return None,
+    })
+}
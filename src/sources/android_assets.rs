@@ -0,0 +1,261 @@
+// font-kit/src/sources/android_assets.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A source that loads fonts bundled in an Android application's assets, via the NDK
+//! `AAssetManager`.
+//!
+//! The `FsSource` only sees the fonts installed in `/system/fonts`; fonts shipped inside an APK
+//! are not files on disk and have to be read through the asset manager instead. This source reads
+//! each font into memory once, when it is created.
+//!
+//! This source is only available on Android with the `android` feature enabled. It links against
+//! the NDK's `libandroid`. The asset manager is owned by the Java side of the application: obtain
+//! it from JNI with `AAssetManager_fromJava(env, context.getAssets())`, or from the `asset_manager`
+//! of your `android-activity`/`ndk` application object, and keep the corresponding Java
+//! `AssetManager` alive for as long as the pointer is in use.
+
+use std::any::Any;
+use std::ffi::{CStr, CString};
+use std::ops::ControlFlow;
+use std::os::raw::{c_char, c_int, c_void};
+use std::slice;
+use std::sync::Arc;
+
+use crate::error::SelectionError;
+use crate::family_handle::FamilyHandle;
+use crate::family_name::FamilyName;
+use crate::file_type::FileType;
+use crate::font::Font;
+use crate::handle::Handle;
+use crate::properties::Properties;
+use crate::source::{Source, SourceCapabilities};
+use crate::sources::mem::MemSource;
+
+/// The NDK's opaque asset manager type.
+///
+/// Pointers to this type are interchangeable with those from other NDK bindings, such as
+/// `ndk_sys::AAssetManager`; use `pointer::cast()` to convert between them.
+#[repr(C)]
+#[allow(missing_copy_implementations, missing_debug_implementations)]
+pub struct AAssetManager {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct AAssetDir {
+    _private: [u8; 0],
+}
+
+#[repr(C)]
+struct AAsset {
+    _private: [u8; 0],
+}
+
+const AASSET_MODE_BUFFER: c_int = 3;
+
+#[link(name = "android")]
+extern "C" {
+    fn AAssetManager_openDir(
+        manager: *mut AAssetManager,
+        dir_name: *const c_char,
+    ) -> *mut AAssetDir;
+    fn AAssetManager_open(
+        manager: *mut AAssetManager,
+        file_name: *const c_char,
+        mode: c_int,
+    ) -> *mut AAsset;
+    fn AAssetDir_getNextFileName(asset_dir: *mut AAssetDir) -> *const c_char;
+    fn AAssetDir_close(asset_dir: *mut AAssetDir);
+    fn AAsset_getBuffer(asset: *mut AAsset) -> *const c_void;
+    fn AAsset_getLength64(asset: *mut AAsset) -> i64;
+    fn AAsset_close(asset: *mut AAsset);
+}
+
+/// A source that loads fonts bundled in an Android application's assets, via the NDK
+/// `AAssetManager`.
+///
+/// All fonts are read into memory when the source is created, so the handles it returns are
+/// in-memory handles.
+#[allow(missing_debug_implementations)]
+pub struct AssetSource {
+    mem_source: MemSource,
+}
+
+impl AssetSource {
+    /// Indexes the fonts in the given asset directory (for example `"fonts"`, or `""` for the
+    /// root of the assets).
+    ///
+    /// The asset manager only lists files, so subdirectories are not searched. Assets that aren't
+    /// fonts are skipped.
+    ///
+    /// # Safety
+    ///
+    /// `asset_manager` must be a valid `AAssetManager` pointer that stays alive for the duration
+    /// of this call. See the module documentation for how to obtain one.
+    pub unsafe fn from_asset_manager(
+        asset_manager: *mut AAssetManager,
+        directory: &str,
+    ) -> AssetSource {
+        AssetSource {
+            mem_source: MemSource::from_fonts(
+                Self::discover_fonts(asset_manager, directory).into_iter(),
+            )
+            .unwrap(),
+        }
+    }
+
+    unsafe fn discover_fonts(asset_manager: *mut AAssetManager, directory: &str) -> Vec<Handle> {
+        let mut fonts = vec![];
+        let c_directory = match CString::new(directory) {
+            Ok(c_directory) => c_directory,
+            Err(_) => return fonts,
+        };
+        let asset_dir = AAssetManager_openDir(asset_manager, c_directory.as_ptr());
+        if asset_dir.is_null() {
+            return fonts;
+        }
+
+        loop {
+            let file_name = AAssetDir_getNextFileName(asset_dir);
+            if file_name.is_null() {
+                break;
+            }
+            let file_name = CStr::from_ptr(file_name).to_string_lossy();
+            let path = if directory.is_empty() {
+                file_name.into_owned()
+            } else {
+                format!("{}/{}", directory.trim_end_matches('/'), file_name)
+            };
+            let bytes = match read_asset(asset_manager, &path) {
+                None => continue,
+                Some(bytes) => Arc::new(bytes),
+            };
+            match Font::analyze_bytes(bytes.clone()) {
+                Err(_) => continue,
+                Ok(FileType::Single) => fonts.push(Handle::from_memory(bytes, 0)),
+                Ok(FileType::Collection(font_count)) => {
+                    for font_index in 0..font_count {
+                        fonts.push(Handle::from_memory(bytes.clone(), font_index))
+                    }
+                }
+            }
+        }
+
+        AAssetDir_close(asset_dir);
+        fonts
+    }
+
+    /// Returns handles to all the fonts in this source.
+    pub fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.mem_source.all_fonts()
+    }
+
+    /// Returns the names of all families in this source.
+    pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.mem_source.all_families()
+    }
+
+    /// Calls `f` with the name of each family in this source, stopping early if it returns
+    /// `ControlFlow::Break`.
+    pub fn for_each_family<F>(&self, f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
+        self.mem_source.for_each_family(f)
+    }
+
+    /// Looks up a font family by name and returns the handles of all the fonts in that family.
+    pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        self.mem_source.select_family_by_name(family_name)
+    }
+
+    /// Selects a font by PostScript name, which should be a unique identifier.
+    pub fn select_by_postscript_name(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Handle, SelectionError> {
+        self.mem_source.select_by_postscript_name(postscript_name)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
+    /// handle.
+    #[inline]
+    pub fn select_best_match(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        <Self as Source>::select_best_match(self, family_names, properties)
+    }
+}
+
+impl Source for AssetSource {
+    #[inline]
+    fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.all_fonts()
+    }
+
+    #[inline]
+    fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.all_families()
+    }
+
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
+    #[inline]
+    fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        self.select_family_by_name(family_name)
+    }
+
+    #[inline]
+    fn select_by_postscript_name(&self, postscript_name: &str) -> Result<Handle, SelectionError> {
+        self.select_by_postscript_name(postscript_name)
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+unsafe fn read_asset(asset_manager: *mut AAssetManager, path: &str) -> Option<Vec<u8>> {
+    let c_path = CString::new(path).ok()?;
+    let asset = AAssetManager_open(asset_manager, c_path.as_ptr(), AASSET_MODE_BUFFER);
+    if asset.is_null() {
+        return None;
+    }
+
+    let buffer = AAsset_getBuffer(asset);
+    let length = AAsset_getLength64(asset);
+    let bytes = if buffer.is_null() || length < 0 {
+        None
+    } else {
+        Some(slice::from_raw_parts(buffer as *const u8, length as usize).to_vec())
+    };
+
+    AAsset_close(asset);
+    bytes
+}
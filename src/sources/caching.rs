@@ -0,0 +1,305 @@
+// font-kit/src/sources/caching.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A source that wraps another source and memoizes the results of font matching.
+//!
+//! Applications that resolve the same family lists over and over (for example, on every layout)
+//! can wrap their source in a `CachingSource` so that only the first query pays the cost of
+//! loading and comparing the candidate fonts.
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::ops::ControlFlow;
+use std::sync::{Mutex, MutexGuard};
+
+use crate::error::SelectionError;
+use crate::family_handle::FamilyHandle;
+use crate::family_name::FamilyName;
+use crate::handle::Handle;
+use crate::properties::{Properties, Style};
+use crate::source::{Source, SourceCapabilities};
+
+/// The number of matches that `CachingSource::new()` remembers.
+pub const DEFAULT_CACHE_CAPACITY: usize = 256;
+
+/// A source that wraps another source and memoizes the results of `select_best_match()`.
+///
+/// Results are keyed by the list of family names and by the requested properties, quantized so
+/// that weights differing by less than one unit and stretches differing by less than 0.1% share
+/// an entry. Only successful matches and `SelectionError::NotFound` are cached; other errors are
+/// passed through so that the query is retried next time. Once the cache is full, the oldest
+/// entry is evicted.
+///
+/// All other queries are delegated to the wrapped source directly. The cache is protected by a
+/// mutex, so this source is `Send` and `Sync` whenever the wrapped source is.
+#[allow(missing_debug_implementations)]
+pub struct CachingSource<S>
+where
+    S: Source,
+{
+    source: S,
+    cache: Mutex<MatchCache>,
+}
+
+struct MatchCache {
+    entries: HashMap<MatchKey, Result<Handle, SelectionError>>,
+    insertion_order: VecDeque<MatchKey>,
+    capacity: usize,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MatchKey {
+    family_names: Vec<FamilyName>,
+    style: Style,
+    weight: i32,
+    stretch: i32,
+}
+
+impl MatchKey {
+    fn new(family_names: &[FamilyName], properties: &Properties) -> MatchKey {
+        MatchKey {
+            family_names: family_names.to_vec(),
+            style: properties.style,
+            weight: properties.weight.0.round() as i32,
+            stretch: (properties.stretch.0 * 1000.0).round() as i32,
+        }
+    }
+}
+
+impl<S> CachingSource<S>
+where
+    S: Source,
+{
+    /// Wraps `source`, remembering up to `DEFAULT_CACHE_CAPACITY` matches.
+    #[inline]
+    pub fn new(source: S) -> CachingSource<S> {
+        CachingSource::with_capacity(source, DEFAULT_CACHE_CAPACITY)
+    }
+
+    /// Wraps `source`, remembering up to `capacity` matches.
+    ///
+    /// A capacity of zero disables caching.
+    pub fn with_capacity(source: S, capacity: usize) -> CachingSource<S> {
+        CachingSource {
+            source,
+            cache: Mutex::new(MatchCache {
+                entries: HashMap::new(),
+                insertion_order: VecDeque::new(),
+                capacity,
+            }),
+        }
+    }
+
+    /// Returns the wrapped source.
+    #[inline]
+    pub fn source(&self) -> &S {
+        &self.source
+    }
+
+    /// Unwraps this source, discarding the cache.
+    #[inline]
+    pub fn into_source(self) -> S {
+        self.source
+    }
+
+    /// Discards all cached matches.
+    ///
+    /// Call this when the set of fonts in the wrapped source changes.
+    pub fn clear(&self) {
+        let mut cache = self.lock_cache();
+        cache.entries.clear();
+        cache.insertion_order.clear();
+    }
+
+    /// Returns the number of cached matches.
+    pub fn cached_match_count(&self) -> usize {
+        self.lock_cache().entries.len()
+    }
+
+    /// Returns paths of all fonts installed on the system.
+    #[inline]
+    pub fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.source.all_fonts()
+    }
+
+    /// Returns the names of all families installed on the system.
+    #[inline]
+    pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.source.all_families()
+    }
+
+    /// Looks up a font family by name and returns the handles of all the fonts in that family.
+    #[inline]
+    pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        self.source.select_family_by_name(family_name)
+    }
+
+    /// Selects a font by PostScript name, which should be a unique identifier.
+    #[inline]
+    pub fn select_by_postscript_name(
+        &self,
+        postscript_name: &str,
+    ) -> Result<Handle, SelectionError> {
+        self.source.select_by_postscript_name(postscript_name)
+    }
+
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// These queries are not cached.
+    #[inline]
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.source.select_by_postscript_name_prefix(prefix)
+    }
+
+    /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
+    /// handle, consulting the cache first.
+    pub fn select_best_match(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        let key = MatchKey::new(family_names, properties);
+        if let Some(result) = self.lock_cache().entries.get(&key) {
+            return result.clone();
+        }
+
+        // Don't hold the lock while matching, which may be slow.
+        let result = self.source.select_best_match(family_names, properties);
+        match result {
+            Ok(_) | Err(SelectionError::NotFound) => self.lock_cache().insert(key, result.clone()),
+            Err(_) => {}
+        }
+        result
+    }
+
+    fn lock_cache(&self) -> MutexGuard<'_, MatchCache> {
+        // The cache is always left consistent, so a poisoned lock is safe to reuse.
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl MatchCache {
+    fn insert(&mut self, key: MatchKey, result: Result<Handle, SelectionError>) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.entries.insert(key.clone(), result).is_some() {
+            return;
+        }
+        self.insertion_order.push_back(key);
+        while self.insertion_order.len() > self.capacity {
+            if let Some(oldest_key) = self.insertion_order.pop_front() {
+                self.entries.remove(&oldest_key);
+            }
+        }
+    }
+}
+
+impl<S> Source for CachingSource<S>
+where
+    S: Source,
+{
+    #[inline]
+    fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+        self.all_fonts()
+    }
+
+    #[inline]
+    fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        self.all_families()
+    }
+
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.source.for_each_family(f)
+    }
+
+    #[inline]
+    fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        self.select_family_by_name(family_name)
+    }
+
+    #[inline]
+    fn select_by_postscript_name(&self, postscript_name: &str) -> Result<Handle, SelectionError> {
+        self.select_by_postscript_name(postscript_name)
+    }
+
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    #[inline]
+    fn select_family_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<FamilyHandle, SelectionError> {
+        self.source.select_family_by_generic_name(family_name)
+    }
+
+    #[inline]
+    fn select_families_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<Vec<FamilyHandle>, SelectionError> {
+        self.source.select_families_by_generic_name(family_name)
+    }
+
+    #[inline]
+    fn select_best_match(
+        &self,
+        family_names: &[FamilyName],
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        self.select_best_match(family_names, properties)
+    }
+
+    #[inline]
+    fn select_descriptions_in_family(
+        &self,
+        family: &FamilyHandle,
+    ) -> Result<Vec<Properties>, SelectionError> {
+        self.source.select_descriptions_in_family(family)
+    }
+
+    #[inline]
+    fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        self.source.default_ui_font()
+    }
+
+    #[inline]
+    fn default_fallback_families(&self) -> Vec<FamilyName> {
+        self.source.default_fallback_families()
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        self.source.capabilities()
+    }
+
+    #[inline]
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    #[inline]
+    fn as_mut_any(&mut self) -> &mut dyn Any {
+        self
+    }
+}
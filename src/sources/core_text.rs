@@ -14,13 +14,20 @@ use core_foundation::array::CFArray;
 use core_foundation::base::{CFType, TCFType};
 use core_foundation::dictionary::CFDictionary;
 use core_foundation::string::CFString;
-use core_text::font::new_from_descriptor;
+use core_text::font::{
+    self as core_text_font, kCTFontSystemFontType, kCTFontUserFixedPitchFontType,
+    kCTFontUserFontType, new_from_descriptor, new_ui_font_for_language,
+};
 use core_text::font_collection::{self, CTFontCollection};
-use core_text::font_descriptor::{self, CTFontDescriptor};
+use core_text::font_descriptor::{
+    self, kCTFontFamilyNameAttribute, CTFontDescriptor, CTFontDescriptorCopyLocalizedAttribute,
+};
 use core_text::font_manager;
 use std::any::Any;
 use std::f32;
 use std::fs::File;
+use std::ops::ControlFlow;
+use std::ptr;
 use std::sync::Arc;
 
 use crate::error::SelectionError;
@@ -29,9 +36,8 @@ use crate::family_name::FamilyName;
 use crate::file_type::FileType;
 use crate::font::Font;
 use crate::handle::Handle;
-use crate::loaders::core_text::{self as core_text_loader, FONT_WEIGHT_MAPPING};
-use crate::properties::{Properties, Stretch, Weight};
-use crate::source::Source;
+use crate::properties::{Properties, PropertyMapping, Stretch, Weight};
+use crate::source::{self, Source, SourceCapabilities};
 use crate::utils;
 
 /// A source that contains the installed fonts on macOS.
@@ -57,16 +63,32 @@ impl CoreTextSource {
 
     /// Returns the names of all families installed on the system.
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        let mut families = vec![];
+        let _ = self.for_each_family(|family_name| {
+            families.push(family_name.to_owned());
+            ControlFlow::Continue(())
+        })?;
+        Ok(families)
+    }
+
+    /// Calls `f` with the name of each family installed on the system, stopping early if it
+    /// returns `ControlFlow::Break`.
+    pub fn for_each_family<F>(&self, mut f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
         let core_text_family_names = font_manager::copy_available_font_family_names();
-        let mut families = Vec::with_capacity(core_text_family_names.len() as usize);
         for core_text_family_name in core_text_family_names.iter() {
-            families.push(core_text_family_name.to_string())
+            if f(&core_text_family_name.to_string()).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
         }
-        Ok(families)
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        source::check_family_name(family_name)?;
         let attributes: CFDictionary<CFString, CFType> = CFDictionary::from_CFType_pairs(&[(
             CFString::new("NSFontFamilyAttribute"),
             CFString::new(family_name).as_CFType(),
@@ -75,8 +97,13 @@ impl CoreTextSource {
         let descriptor = font_descriptor::new_from_attributes(&attributes);
         let descriptors = CFArray::from_CFTypes(&[descriptor]);
         let collection = font_collection::new_from_descriptors(&descriptors);
+        let display_name = collection
+            .get_descriptors()
+            .and_then(|descriptors| localized_family_name(&*descriptors.get(0)?));
         let handles = create_handles_from_core_text_collection(collection)?;
-        Ok(FamilyHandle::from_font_handles(handles.into_iter()))
+        let mut family = FamilyHandle::from_font_handles(handles.into_iter());
+        family.set_display_name(display_name);
+        Ok(family)
     }
 
     /// Selects a font by PostScript name, which should be a unique identifier.
@@ -98,6 +125,55 @@ impl CoreTextSource {
         }
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// The names come from the descriptors of the installed fonts, so only the fonts that match
+    /// are opened.
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let collection = font_collection::create_for_all_families();
+        let mut matches = vec![];
+        if let Some(descriptors) = collection.get_descriptors() {
+            for index in 0..descriptors.len() {
+                let descriptor = descriptors.get(index).unwrap();
+                let postscript_name = descriptor.font_name();
+                if postscript_name.starts_with(prefix) {
+                    let native = new_from_descriptor(&descriptor, 16.);
+                    let font = unsafe { Font::from_core_text_font_no_path(native) };
+                    matches.push((postscript_name, Handle::from_native(&font)));
+                }
+            }
+        }
+        source::sort_by_postscript_name(matches)
+    }
+
+    /// Returns the font that Core Text uses for user interface text (the "system font").
+    pub fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        let ui_font = new_ui_font_for_language(kCTFontSystemFontType, 0.0, None);
+        let font = unsafe { Font::from_core_text_font_no_path(ui_font) };
+        Ok(Handle::from_native(&font))
+    }
+
+    /// Returns the families that Core Text falls back to, in order, when the system font lacks a
+    /// glyph, starting with the family of the system font itself.
+    ///
+    /// The rest of the list is Core Text's default cascade list for the system font.
+    pub fn default_fallback_families(&self) -> Vec<FamilyName> {
+        let ui_font = new_ui_font_for_language(kCTFontSystemFontType, 0.0, None);
+        let mut families = vec![ui_font.family_name()];
+        let cascade_list =
+            core_text_font::cascade_list_for_languages(&ui_font, &CFArray::from_CFTypes(&[]));
+        for descriptor in cascade_list.iter() {
+            let family = descriptor.family_name();
+            if !families.contains(&family) {
+                families.push(family);
+            }
+        }
+        families.into_iter().map(FamilyName::Title).collect()
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -119,6 +195,14 @@ impl Source for CoreTextSource {
         self.all_families()
     }
 
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
     }
@@ -127,6 +211,50 @@ impl Source for CoreTextSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    // The sans-serif and monospace generic families are whatever the user's default fonts are;
+    // Core Text has no notion of the others.
+    fn select_family_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<FamilyHandle, SelectionError> {
+        let ui_font_type = match *family_name {
+            FamilyName::SansSerif => kCTFontUserFontType,
+            FamilyName::Monospace => kCTFontUserFixedPitchFontType,
+            ref family_name => {
+                return self.select_family_by_name(source::default_generic_family_name(family_name))
+            }
+        };
+        let user_font = new_ui_font_for_language(ui_font_type, 0.0, None);
+        self.select_family_by_name(&user_font.family_name())
+    }
+
+    #[inline]
+    fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        self.default_ui_font()
+    }
+
+    #[inline]
+    fn default_fallback_families(&self) -> Vec<FamilyName> {
+        self.default_fallback_families()
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+            | SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+            | SourceCapabilities::GENERIC_FAMILIES
+            | SourceCapabilities::DEFAULT_UI_FONT
+            | SourceCapabilities::FALLBACK_FAMILIES
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -140,16 +268,12 @@ impl Source for CoreTextSource {
 
 #[allow(dead_code)]
 fn css_to_core_text_font_weight(css_weight: Weight) -> f32 {
-    core_text_loader::piecewise_linear_lookup(
-        f32::max(100.0, css_weight.0) / 100.0 - 1.0,
-        &FONT_WEIGHT_MAPPING,
-    )
+    PropertyMapping::CORE_TEXT.weight_from_css(css_weight)
 }
 
 #[allow(dead_code)]
 fn css_stretchiness_to_core_text_width(css_stretchiness: Stretch) -> f32 {
-    let css_stretchiness = utils::clamp(css_stretchiness.0, 0.5, 2.0);
-    0.25 * core_text_loader::piecewise_linear_find_index(css_stretchiness, &Stretch::MAPPING) - 1.0
+    PropertyMapping::CORE_TEXT.width_from_css(css_stretchiness)
 }
 
 fn create_handles_from_core_text_collection(
@@ -172,6 +296,23 @@ fn create_handles_from_core_text_collection(
     }
 }
 
+// Returns the family name of the font in the user's preferred language.
+fn localized_family_name(descriptor: &CTFontDescriptor) -> Option<String> {
+    unsafe {
+        let name = CTFontDescriptorCopyLocalizedAttribute(
+            descriptor.as_concrete_TypeRef(),
+            kCTFontFamilyNameAttribute,
+            ptr::null_mut(),
+        );
+        if name.is_null() {
+            return None;
+        }
+        CFType::wrap_under_create_rule(name)
+            .downcast_into::<CFString>()
+            .map(|name| name.to_string())
+    }
+}
+
 fn create_handle_from_descriptor(descriptor: &CTFontDescriptor) -> Result<Handle, SelectionError> {
     let font_path = descriptor.font_path().unwrap();
 
@@ -223,6 +364,10 @@ mod test {
 
         // Linear interpolation
         assert_eq!(super::css_to_core_text_font_weight(Weight(450.0)), 0.1);
+
+        // Out of range
+        assert_eq!(super::css_to_core_text_font_weight(Weight(0.0)), -0.7);
+        assert_eq!(super::css_to_core_text_font_weight(Weight(1200.0)), 0.8);
     }
 
     #[test]
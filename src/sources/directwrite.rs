@@ -12,14 +12,45 @@
 
 use dwrote::Font as DWriteFont;
 use dwrote::FontCollection as DWriteFontCollection;
+use dwrote::InformationalStringId;
 use std::any::Any;
+use std::mem;
+use std::ops::ControlFlow;
+use winapi::shared::minwindef::LPVOID;
+use winapi::um::winuser::{self, NONCLIENTMETRICSW, SPI_GETNONCLIENTMETRICS};
 
 use crate::error::SelectionError;
 use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::handle::Handle;
 use crate::properties::Properties;
-use crate::source::Source;
+use crate::source::{self, Source, SourceCapabilities};
+
+// The font that Windows falls back to for user interface text when the message box font isn't
+// available.
+const DEFAULT_UI_FAMILY_NAME: &str = "Segoe UI";
+
+// The families that Windows links to its user interface font to cover other scripts, symbols,
+// and emoji, in the order it consults them.
+static FALLBACK_FAMILY_NAMES: [&str; 17] = [
+    "Segoe UI Symbol",
+    "Segoe UI Emoji",
+    "Segoe UI Historic",
+    "Microsoft YaHei UI",
+    "Microsoft JhengHei UI",
+    "Yu Gothic UI",
+    "Malgun Gothic",
+    "Nirmala UI",
+    "Leelawadee UI",
+    "Ebrima",
+    "Gadugi",
+    "Javanese Text",
+    "Myanmar Text",
+    "Mongolian Baiti",
+    "Microsoft Himalaya",
+    "Microsoft Yi Baiti",
+    "Tahoma",
+];
 
 /// A source that contains the installed fonts on Windows.
 #[allow(missing_debug_implementations)]
@@ -51,17 +82,37 @@ impl DirectWriteSource {
 
     /// Returns the names of all families installed on the system.
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
-        Ok(self
-            .system_font_collection
-            .families_iter()
-            .map(|dwrite_family| dwrite_family.name())
-            .collect())
+        let mut families = vec![];
+        let _ = self.for_each_family(|family_name| {
+            families.push(family_name.to_owned());
+            ControlFlow::Continue(())
+        })?;
+        Ok(families)
+    }
+
+    /// Calls `f` with the name of each family installed on the system, stopping early if it
+    /// returns `ControlFlow::Break`.
+    pub fn for_each_family<F>(&self, mut f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
+        for dwrite_family in self.system_font_collection.families_iter() {
+            let family_name = match dwrite_family.family_name() {
+                Ok(family_name) => family_name,
+                Err(_) => continue,
+            };
+            if f(&family_name).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
+        }
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     ///
     /// TODO(pcwalton): Case-insensitivity.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        source::check_family_name(family_name)?;
         let mut family = FamilyHandle::new();
         let dwrite_family = match self
             .system_font_collection
@@ -74,6 +125,8 @@ impl DirectWriteSource {
             let dwrite_font = dwrite_family.get_font(font_index);
             family.push(self.create_handle_from_dwrite_font(dwrite_font))
         }
+        // DirectWrite picks the name for the user's locale, falling back to English.
+        family.set_display_name(dwrite_family.family_name().ok());
         Ok(family)
     }
 
@@ -88,6 +141,73 @@ impl DirectWriteSource {
         <Self as Source>::select_by_postscript_name(self, postscript_name)
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// The names come from DirectWrite's informational strings, so only the fonts that match are
+    /// opened.
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let mut matches = vec![];
+        for dwrite_family in self.system_font_collection.families_iter() {
+            for font_index in 0..dwrite_family.get_font_count() {
+                let dwrite_font = match dwrite_family.font(font_index) {
+                    Ok(dwrite_font) => dwrite_font,
+                    Err(_) => continue,
+                };
+                let postscript_name =
+                    match dwrite_font.informational_string(InformationalStringId::PostscriptName) {
+                        Some(postscript_name) => postscript_name,
+                        None => continue,
+                    };
+                if postscript_name.starts_with(prefix) {
+                    matches.push((
+                        postscript_name,
+                        self.create_handle_from_dwrite_font(dwrite_font),
+                    ));
+                }
+            }
+        }
+        source::sort_by_postscript_name(matches)
+    }
+
+    /// Returns the font that Windows uses for user interface text.
+    ///
+    /// This is the regular face of the message box font from the system's non-client metrics,
+    /// which is Segoe UI unless the user has changed it.
+    pub fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        let family_name = message_font_family_name()
+            .filter(|family_name| self.is_family_installed(family_name))
+            .unwrap_or_else(|| DEFAULT_UI_FAMILY_NAME.to_owned());
+        self.select_best_match(&[FamilyName::Title(family_name)], &Properties::new())
+    }
+
+    /// Returns the families that Windows falls back to, in order, when the user interface font
+    /// lacks a glyph, starting with the user interface font itself.
+    ///
+    /// DirectWrite doesn't expose its fallback chain, so this is the font linking list that
+    /// Windows uses for Segoe UI, restricted to the families that are installed.
+    pub fn default_fallback_families(&self) -> Vec<FamilyName> {
+        let ui_family_name = message_font_family_name()
+            .filter(|family_name| self.is_family_installed(family_name))
+            .unwrap_or_else(|| DEFAULT_UI_FAMILY_NAME.to_owned());
+        let mut families = vec![ui_family_name];
+        for &family_name in &FALLBACK_FAMILY_NAMES {
+            if self.is_family_installed(family_name) && !families.iter().any(|f| f == family_name) {
+                families.push(family_name.to_owned());
+            }
+        }
+        families.into_iter().map(FamilyName::Title).collect()
+    }
+
+    fn is_family_installed(&self, family_name: &str) -> bool {
+        matches!(
+            self.system_font_collection.font_family_by_name(family_name),
+            Ok(Some(_))
+        )
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -120,11 +240,44 @@ impl Source for DirectWriteSource {
         self.all_families()
     }
 
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     #[inline]
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    #[inline]
+    fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        self.default_ui_font()
+    }
+
+    #[inline]
+    fn default_fallback_families(&self) -> Vec<FamilyName> {
+        self.default_fallback_families()
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+            | SourceCapabilities::DEFAULT_UI_FONT
+            | SourceCapabilities::FALLBACK_FAMILIES
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -135,3 +288,29 @@ impl Source for DirectWriteSource {
         self
     }
 }
+
+// Returns the family name of the font that Windows uses in message boxes, which is the closest
+// thing it has to a single user interface font.
+fn message_font_family_name() -> Option<String> {
+    unsafe {
+        let mut metrics: NONCLIENTMETRICSW = mem::zeroed();
+        metrics.cbSize = mem::size_of::<NONCLIENTMETRICSW>() as u32;
+        if winuser::SystemParametersInfoW(
+            SPI_GETNONCLIENTMETRICS,
+            metrics.cbSize,
+            &mut metrics as *mut NONCLIENTMETRICSW as LPVOID,
+            0,
+        ) == 0
+        {
+            return None;
+        }
+        let face_name = &metrics.lfMessageFont.lfFaceName;
+        let len = face_name
+            .iter()
+            .position(|&c| c == 0)
+            .unwrap_or(face_name.len());
+        String::from_utf16(&face_name[..len])
+            .ok()
+            .filter(|family_name| !family_name.is_empty())
+    }
+}
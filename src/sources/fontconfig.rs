@@ -20,8 +20,9 @@ use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::handle::Handle;
 use crate::properties::Properties;
-use crate::source::Source;
+use crate::source::{self, Source, SourceCapabilities};
 use std::any::Any;
+use std::ops::ControlFlow;
 
 /// A source that contains the fonts installed on the system, as reported by the Fontconfig
 /// library.
@@ -88,6 +89,30 @@ impl FontconfigSource {
 
     /// Returns the names of all families installed on the system.
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+        let mut result_families = vec![];
+        let _ = self.for_each_family(|family_name| {
+            result_families.push(family_name.to_owned());
+            ControlFlow::Continue(())
+        })?;
+
+        result_families.sort();
+        result_families.dedup();
+
+        if !result_families.is_empty() {
+            Ok(result_families)
+        } else {
+            Err(SelectionError::NotFound)
+        }
+    }
+
+    /// Calls `f` with the name of each family installed on the system, stopping early if it
+    /// returns `ControlFlow::Break`.
+    ///
+    /// Family names are reported in the order Fontconfig lists them and may repeat.
+    pub fn for_each_family<F>(&self, mut f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
         let pattern = fc::Pattern::new();
 
         // We want the family name.
@@ -98,25 +123,20 @@ impl FontconfigSource {
             .list(&self.config, object_set)
             .map_err(|_| SelectionError::NotFound)?;
 
-        let mut result_families = vec![];
         for patt in patterns {
             if let Some(family) = patt.get_string(fc::Object::Family) {
-                result_families.push(family);
+                if f(&family).is_break() {
+                    return Ok(ControlFlow::Break(()));
+                }
             }
         }
 
-        result_families.sort();
-        result_families.dedup();
-
-        if !result_families.is_empty() {
-            Ok(result_families)
-        } else {
-            Err(SelectionError::NotFound)
-        }
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        source::check_family_name(family_name)?;
         use std::borrow::Cow;
 
         let family_name = match family_name {
@@ -131,21 +151,29 @@ impl FontconfigSource {
         let mut object_set = fc::ObjectSet::new();
         object_set.push_string(fc::Object::File);
         object_set.push_string(fc::Object::Index);
+        object_set.push_string(fc::Object::Family);
+        object_set.push_string(fc::Object::FamilyLang);
 
         let patterns = pattern
             .list(&self.config, object_set)
             .map_err(|_| SelectionError::NotFound)?;
 
         let mut handles = vec![];
+        let mut display_name = None;
         for patt in patterns {
             let font_path = patt.get_string(fc::Object::File).unwrap();
             let font_index = patt.get_integer(fc::Object::Index).unwrap() as u32;
             let handle = Handle::from_path(std::path::PathBuf::from(font_path), font_index);
             handles.push(handle);
+            if display_name.is_none() {
+                display_name = localized_family_name(&patt);
+            }
         }
 
         if !handles.is_empty() {
-            Ok(FamilyHandle::from_font_handles(handles.into_iter()))
+            let mut family = FamilyHandle::from_font_handles(handles.into_iter());
+            family.set_display_name(display_name);
+            Ok(family)
         } else {
             Err(SelectionError::NotFound)
         }
@@ -155,23 +183,93 @@ impl FontconfigSource {
     ///
     /// Accepts: serif, sans-serif, monospace, cursive and fantasy.
     fn select_generic_font(&self, name: &str) -> Result<String, SelectionError> {
+        if let Some(patt) = self.sorted_fonts_for_alias(name)?.next() {
+            if let Some(family) = patt.get_string(fc::Object::Family) {
+                return Ok(family);
+            }
+        }
+
+        Err(SelectionError::NotFound)
+    }
+
+    // Runs the configured substitutions on a family name or alias and returns the matching fonts,
+    // best first, trimmed to those that add coverage.
+    fn sorted_fonts_for_alias(&self, name: &str) -> Result<fc::FontSet, SelectionError> {
         let mut pattern = fc::Pattern::from_name(name);
         pattern.config_substitute(fc::MatchKind::Pattern);
         pattern.default_substitute();
-
-        let patterns = pattern
+        pattern
             .sorted(&self.config)
-            .map_err(|_| SelectionError::NotFound)?;
+            .map_err(|_| SelectionError::NotFound)
+    }
 
-        if let Some(patt) = patterns.into_iter().next() {
-            if let Some(family) = patt.get_string(fc::Object::Family) {
-                return Ok(family);
+    /// Returns the font that the desktop uses for user interface text.
+    ///
+    /// This is the best match for the `system-ui` alias. Configurations that don't define it
+    /// treat it like any unknown family and fall back to `sans-serif`.
+    pub fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        for patt in self.sorted_fonts_for_alias("system-ui")? {
+            if let (Some(font_path), Some(font_index)) = (
+                patt.get_string(fc::Object::File),
+                patt.get_integer(fc::Object::Index),
+            ) {
+                return Ok(Handle::from_path(font_path.into(), font_index as u32));
             }
         }
-
         Err(SelectionError::NotFound)
     }
 
+    /// Returns the families that Fontconfig falls back to, in order, for `sans-serif` text.
+    ///
+    /// Only families that contribute glyphs the earlier ones lack are included.
+    pub fn default_fallback_families(&self) -> Vec<FamilyName> {
+        self.families_for_alias("sans-serif")
+            .into_iter()
+            .map(FamilyName::Title)
+            .collect()
+    }
+
+    /// Looks up a family name and returns the handles of the fonts in every family it stands
+    /// for, best first.
+    ///
+    /// Generic family names are expanded into the families that Fontconfig falls back to for
+    /// them, keeping only those that contribute glyphs the earlier ones lack. A
+    /// `FamilyName::Title` stands for just the family with that name.
+    pub fn select_families_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<Vec<FamilyHandle>, SelectionError> {
+        let alias = match *family_name {
+            FamilyName::Title(ref title) => return Ok(vec![self.select_family_by_name(title)?]),
+            ref generic_name => generic_alias(generic_name),
+        };
+        let families: Vec<_> = self
+            .families_for_alias(alias)
+            .iter()
+            .filter_map(|family_name| self.select_family_by_name(family_name).ok())
+            .collect();
+        if families.is_empty() {
+            return Err(SelectionError::NotFound);
+        }
+        Ok(families)
+    }
+
+    // Returns the names of the families that an alias resolves to, best first and without
+    // duplicates.
+    fn families_for_alias(&self, alias: &str) -> Vec<String> {
+        let mut families: Vec<String> = vec![];
+        if let Ok(patterns) = self.sorted_fonts_for_alias(alias) {
+            for patt in patterns {
+                if let Some(family) = patt.get_string(fc::Object::Family) {
+                    if !families.contains(&family) {
+                        families.push(family);
+                    }
+                }
+            }
+        }
+        families
+    }
+
     /// Selects a font by PostScript name, which should be a unique identifier.
     ///
     /// The default implementation, which is used by the DirectWrite and the filesystem backends,
@@ -202,6 +300,42 @@ impl FontconfigSource {
         }
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// Fontconfig patterns can't match prefixes, so this lists the PostScript names of all fonts
+    /// from Fontconfig's cache and filters them, without loading any fonts.
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let pattern = fc::Pattern::new();
+
+        let mut object_set = fc::ObjectSet::new();
+        object_set.push_string(fc::Object::PostScriptName);
+        object_set.push_string(fc::Object::File);
+        object_set.push_string(fc::Object::Index);
+
+        let patterns = pattern
+            .list(&self.config, object_set)
+            .map_err(|_| SelectionError::NotFound)?;
+
+        let mut matches = vec![];
+        for patt in patterns {
+            let postscript_name = match patt.get_string(fc::Object::PostScriptName) {
+                Some(postscript_name) if postscript_name.starts_with(prefix) => postscript_name,
+                _ => continue,
+            };
+            if let (Some(font_path), Some(font_index)) = (
+                patt.get_string(fc::Object::File),
+                patt.get_integer(fc::Object::Index),
+            ) {
+                let handle = Handle::from_path(font_path.into(), font_index as u32);
+                matches.push((postscript_name, handle));
+            }
+        }
+        source::sort_by_postscript_name(matches)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -225,6 +359,14 @@ impl Source for FontconfigSource {
         self.all_families()
     }
 
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     #[inline]
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
@@ -235,6 +377,50 @@ impl Source for FontconfigSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    // Fontconfig's configuration defines the generic families as aliases, which
+    // `select_family_by_name()` resolves.
+    fn select_family_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<FamilyHandle, SelectionError> {
+        self.select_family_by_name(generic_alias(family_name))
+    }
+
+    #[inline]
+    fn select_families_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<Vec<FamilyHandle>, SelectionError> {
+        self.select_families_by_generic_name(family_name)
+    }
+
+    #[inline]
+    fn default_ui_font(&self) -> Result<Handle, SelectionError> {
+        self.default_ui_font()
+    }
+
+    #[inline]
+    fn default_fallback_families(&self) -> Vec<FamilyName> {
+        self.default_fallback_families()
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+            | SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+            | SourceCapabilities::GENERIC_FAMILIES
+            | SourceCapabilities::DEFAULT_UI_FONT
+            | SourceCapabilities::FALLBACK_FAMILIES
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -246,6 +432,53 @@ impl Source for FontconfigSource {
     }
 }
 
+// Returns the Fontconfig alias for a generic family name. Titles are returned unchanged.
+fn generic_alias(family_name: &FamilyName) -> &str {
+    match *family_name {
+        FamilyName::Title(ref title) => title,
+        FamilyName::Serif => "serif",
+        FamilyName::SansSerif => "sans-serif",
+        FamilyName::Monospace => "monospace",
+        FamilyName::Cursive => "cursive",
+        FamilyName::Fantasy => "fantasy",
+    }
+}
+
+// Fontconfig lists a family's names in each language alongside the corresponding language tags.
+// Picks the one for the user's locale, falling back to English and then to the first name.
+fn localized_family_name(pattern: &fc::PatternRef) -> Option<String> {
+    let mut names = vec![];
+    while let Some(name) = pattern.get_string_at(fc::Object::Family, names.len() as i32) {
+        let language = pattern
+            .get_string_at(fc::Object::FamilyLang, names.len() as i32)
+            .unwrap_or_default();
+        names.push((language.to_lowercase(), name));
+    }
+
+    let user_language = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|variable| std::env::var(variable).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            // "zh_CN.UTF-8" becomes "zh-cn".
+            let value = value.split(['.', '@']).next().unwrap_or_default();
+            value.replace('_', "-").to_lowercase()
+        })
+        .unwrap_or_default();
+    let primary_language = user_language.split('-').next().unwrap_or_default();
+
+    [user_language.as_str(), primary_language, "en"]
+        .iter()
+        .filter(|language| !language.is_empty())
+        .find_map(|language| {
+            names
+                .iter()
+                .find(|(name_language, _)| name_language == language)
+        })
+        .or_else(|| names.first())
+        .map(|(_, name)| name.clone())
+}
+
 // A minimal fontconfig wrapper.
 mod fc {
     #![allow(dead_code)]
@@ -291,6 +524,7 @@ mod fc {
     #[derive(Clone, Copy)]
     pub enum Object {
         Family,
+        FamilyLang,
         File,
         Index,
         PostScriptName,
@@ -300,6 +534,7 @@ mod fc {
         fn as_bytes(&self) -> &[u8] {
             match self {
                 Object::Family => b"family\0",
+                Object::FamilyLang => b"familylang\0",
                 Object::File => b"file\0",
                 Object::Index => b"index\0",
                 Object::PostScriptName => b"postscriptname\0",
@@ -491,6 +726,11 @@ mod fc {
     impl PatternRef {
         // FcPatternGetString
         pub fn get_string(&self, object: Object) -> Option<String> {
+            self.get_string_at(object, 0)
+        }
+
+        // FcPatternGetString, for objects with multiple values
+        pub fn get_string_at(&self, object: Object, index: i32) -> Option<String> {
             unsafe {
                 let mut string = ptr::null_mut();
                 let res = ffi_dispatch!(
@@ -499,7 +739,7 @@ mod fc {
                     FcPatternGetString,
                     self.d,
                     object.as_ptr(),
-                    0,
+                    index,
                     &mut string
                 );
                 if res != ffi::FcResultMatch {
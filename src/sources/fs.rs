@@ -15,15 +15,19 @@
 //! This is the native source on Android and OpenHarmony.
 
 use std::any::Any;
-use std::fs::File;
+use std::collections::{HashMap, HashSet};
+use std::ffi::OsString;
+use std::fs::{self, File, Metadata};
+use std::io::{self, Read};
+use std::iter;
+use std::ops::ControlFlow;
 use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
 use walkdir::WalkDir;
 
 #[cfg(not(any(target_os = "android", target_family = "windows", target_env = "ohos")))]
 use dirs;
 #[cfg(target_family = "windows")]
-use std::ffi::OsString;
-#[cfg(target_family = "windows")]
 use std::os::windows::ffi::OsStringExt;
 #[cfg(target_family = "windows")]
 use winapi::shared::minwindef::{MAX_PATH, UINT};
@@ -35,10 +39,34 @@ use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::file_type::FileType;
 use crate::font::Font;
+use crate::font_info::FontInfo;
 use crate::handle::Handle;
-use crate::properties::Properties;
-use crate::source::Source;
+use crate::properties::{Properties, Stretch, Style, Weight};
+use crate::source::{Source, SourceCapabilities};
 use crate::sources::mem::MemSource;
+use crate::utils;
+
+bitflags! {
+    /// The kinds of font file that an `FsSource` indexes.
+    ///
+    /// Files are told apart by their headers, not their extensions. Whether a file of an included
+    /// kind is actually indexed depends on the loader: the FreeType loader opens all of them,
+    /// decompressing WOFF and WOFF2 fonts as it loads them, while the native loaders on macOS and
+    /// Windows may skip any that aren't TrueType or OpenType.
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub struct FsFormats: u8 {
+        /// TrueType and OpenType fonts and collections.
+        const SFNT = 0x01;
+        /// WOFF 1.0 web fonts.
+        const WOFF = 0x02;
+        /// WOFF 2.0 web fonts.
+        const WOFF2 = 0x04;
+        /// PostScript Type 1 fonts, in either PFB or PFA form.
+        const TYPE1 = 0x08;
+        /// Any other file that the loader can open, such as PCF bitmap fonts.
+        const OTHER = 0x10;
+    }
+}
 
 /// A source that loads fonts from a directory or directories on disk.
 ///
@@ -48,6 +76,8 @@ use crate::sources::mem::MemSource;
 #[allow(missing_debug_implementations)]
 pub struct FsSource {
     mem_source: MemSource,
+    // The properties of each face, by path and font index, if they were recorded when indexing.
+    properties: HashMap<(PathBuf, u32), Properties>,
 }
 
 impl Default for FsSource {
@@ -62,18 +92,13 @@ impl FsSource {
     /// Do not rely on this function for systems other than Android or OpenHarmony. It makes a best
     /// effort to locate fonts in the typical platform directories, but it is too simple to pick up
     /// fonts that are stored in unusual locations but nevertheless properly installed.
+    ///
+    /// Copies of the same font in several directories are indexed once; see `in_paths()`.
     pub fn new() -> FsSource {
-        let mut fonts = vec![];
-        for font_directory in default_font_directories() {
-            fonts.extend(Self::discover_fonts(&font_directory));
-        }
-
-        FsSource {
-            mem_source: MemSource::from_fonts(fonts.into_iter()).unwrap(),
-        }
+        Self::in_paths(default_font_directories(), false)
     }
 
-    fn discover_fonts(path: &Path) -> Vec<Handle> {
+    fn discover_fonts(path: &Path, formats: FsFormats) -> Vec<Handle> {
         let mut fonts = vec![];
         for directory_entry in WalkDir::new(path).into_iter() {
             let directory_entry = match directory_entry {
@@ -85,6 +110,14 @@ impl FsSource {
                 Err(_) => continue,
                 Ok(file) => file,
             };
+            let mut header = [0; 16];
+            let header_length = match file.read(&mut header) {
+                Err(_) => continue,
+                Ok(header_length) => header_length,
+            };
+            if !formats.contains(file_format(&header[..header_length])) {
+                continue;
+            }
             match Font::analyze_file(&mut file) {
                 Err(_) => continue,
                 Ok(FileType::Single) => fonts.push(Handle::from_path(path.to_owned(), 0)),
@@ -99,13 +132,121 @@ impl FsSource {
     }
 
     /// Indexes all fonts found in `path`
+    ///
+    /// Copies of the same font in several subdirectories are indexed once; see `in_paths()`.
     pub fn in_path<P>(path: P) -> FsSource
     where
         P: AsRef<Path>,
     {
-        let fonts = Self::discover_fonts(path.as_ref());
+        Self::in_paths(iter::once(path), false)
+    }
+
+    /// Indexes all fonts found in each of `paths`, in order.
+    ///
+    /// The same font is often installed in several directories. Unless `keep_duplicates` is true,
+    /// only the first path found for each font is indexed, so that families don't list the same
+    /// face more than once. Two files are taken to hold the same font if they are the same size
+    /// and the font at the same index in each has the same PostScript name. Fonts whose
+    /// PostScript names can't be read without loading them, such as PCF fonts, are always kept.
+    ///
+    /// Every kind of font file that the loader can open is indexed; use `in_paths_with_formats()`
+    /// to choose which.
+    pub fn in_paths<I>(paths: I, keep_duplicates: bool) -> FsSource
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        Self::in_paths_with_formats(paths, keep_duplicates, FsFormats::all())
+    }
+
+    /// Indexes the fonts found in each of `paths`, in order, skipping files whose kind isn't in
+    /// `formats`.
+    ///
+    /// Duplicates are handled as in `in_paths()`.
+    pub fn in_paths_with_formats<I>(paths: I, keep_duplicates: bool, formats: FsFormats) -> FsSource
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+    {
+        let mut fonts = vec![];
+        for path in paths {
+            fonts.extend(Self::discover_fonts(path.as_ref(), formats));
+        }
+        if !keep_duplicates {
+            fonts = remove_duplicate_fonts(fonts);
+        }
+
         FsSource {
             mem_source: MemSource::from_fonts(fonts.into_iter()).unwrap(),
+            properties: HashMap::new(),
+        }
+    }
+
+    /// Indexes all fonts found in each of `paths`, as `in_paths()` does, reusing what a cache
+    /// file at `cache_path` recorded on earlier runs.
+    ///
+    /// Indexing means opening and parsing every font file, which can take hundreds of
+    /// milliseconds for a large collection. The cache records the faces found in each file,
+    /// with their names and properties, along with the file's size and modification time. The
+    /// directories are still walked on every run, but a file whose size and modification time
+    /// match the cache isn't opened; only new and changed files are parsed. The cache is then
+    /// rewritten to match the directories. A missing or unreadable cache just means that every
+    /// file is parsed, and failing to write the cache is logged and otherwise ignored.
+    ///
+    /// Font matching uses the recorded properties instead of loading the faces of each family.
+    /// Duplicates are removed as in `in_paths()`, but fonts without PostScript names, which
+    /// can't be looked up anyway, aren't indexed.
+    pub fn with_cache<I, P>(paths: I, cache_path: P) -> FsSource
+    where
+        I: IntoIterator,
+        I::Item: AsRef<Path>,
+        P: AsRef<Path>,
+    {
+        let cache_path = cache_path.as_ref();
+        let mut cache = read_cache(cache_path).unwrap_or_default();
+        let mut files = vec![];
+        for path in paths {
+            for directory_entry in WalkDir::new(path.as_ref()).into_iter() {
+                let path = match directory_entry {
+                    Ok(directory_entry) => directory_entry.into_path(),
+                    Err(_) => continue,
+                };
+                let metadata = match fs::metadata(&path) {
+                    Ok(metadata) if metadata.is_file() => metadata,
+                    _ => continue,
+                };
+                let (size, modified) = (metadata.len(), modification_time(&metadata));
+                let file = match cache.remove(&path) {
+                    Some(file) if file.size == size && file.modified == modified => file,
+                    _ => CachedFile {
+                        size,
+                        modified,
+                        faces: scan_file(&path),
+                    },
+                };
+                files.push((path, file));
+            }
+        }
+        if let Err(error) = write_cache(cache_path, &files) {
+            log::warn!("Failed to write font cache {:?}: {}", cache_path, error);
+        }
+
+        let mut seen_fonts = HashSet::new();
+        let (mut fonts, mut properties) = (vec![], HashMap::new());
+        for (path, file) in files {
+            for face in file.faces {
+                if !seen_fonts.insert((file.size, face.font_index, face.postscript_name.clone())) {
+                    continue;
+                }
+                let handle = Handle::from_path(path.clone(), face.font_index);
+                fonts.push((handle, face.family_name, face.postscript_name));
+                properties.insert((path.clone(), face.font_index), face.properties);
+            }
+        }
+
+        FsSource {
+            mem_source: MemSource::from_named_fonts(fonts.into_iter()),
+            properties,
         }
     }
 
@@ -119,6 +260,15 @@ impl FsSource {
         self.mem_source.all_families()
     }
 
+    /// Calls `f` with the name of each family installed on the system, stopping early if it
+    /// returns `ControlFlow::Break`.
+    pub fn for_each_family<F>(&self, f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
+        self.mem_source.for_each_family(f)
+    }
+
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.mem_source.select_family_by_name(family_name)
@@ -135,6 +285,17 @@ impl FsSource {
         self.mem_source.select_by_postscript_name(postscript_name)
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// This searches the index of PostScript names built when the directories were scanned.
+    #[inline]
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.mem_source.select_by_postscript_name_prefix(prefix)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -158,6 +319,14 @@ impl Source for FsSource {
         self.all_families()
     }
 
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
     }
@@ -166,6 +335,45 @@ impl Source for FsSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    // Uses the properties recorded by `with_cache()` instead of loading fonts where it can.
+    fn select_descriptions_in_family(
+        &self,
+        family: &FamilyHandle,
+    ) -> Result<Vec<Properties>, SelectionError> {
+        let mut descriptions = vec![];
+        for font_handle in family.fonts() {
+            let properties = match *font_handle {
+                Handle::Path {
+                    ref path,
+                    font_index,
+                } => self.properties.get(&(path.clone(), font_index)).cloned(),
+                _ => None,
+            };
+            match properties {
+                Some(properties) => descriptions.push(properties),
+                None => match Font::from_handle(font_handle) {
+                    Ok(font) => descriptions.push(font.properties()),
+                    Err(e) => log::warn!("Error loading font from handle: {:?}", e),
+                },
+            }
+        }
+        Ok(descriptions)
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+            | SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
@@ -177,6 +385,213 @@ impl Source for FsSource {
     }
 }
 
+// Classifies a font file by the first bytes of its header.
+fn file_format(header: &[u8]) -> FsFormats {
+    const SFNT_SIGNATURES: [&[u8]; 5] = [b"\x00\x01\x00\x00", b"OTTO", b"true", b"typ1", b"ttcf"];
+
+    if SFNT_SIGNATURES
+        .iter()
+        .any(|signature| header.starts_with(signature))
+    {
+        FsFormats::SFNT
+    } else if header.starts_with(b"wOFF") {
+        FsFormats::WOFF
+    } else if header.starts_with(b"wOF2") {
+        FsFormats::WOFF2
+    } else if utils::is_type1_font(header) {
+        FsFormats::TYPE1
+    } else {
+        FsFormats::OTHER
+    }
+}
+
+// Keeps the first of each set of fonts that share a file size, font index, and PostScript name.
+fn remove_duplicate_fonts(fonts: Vec<Handle>) -> Vec<Handle> {
+    let mut seen_fonts = HashSet::new();
+    fonts
+        .into_iter()
+        .filter(|handle| {
+            let (path, font_index) = match *handle {
+                Handle::Path {
+                    ref path,
+                    font_index,
+                } => (path, font_index),
+                _ => return true,
+            };
+            let file_size = match fs::metadata(path) {
+                Ok(metadata) => metadata.len(),
+                Err(_) => return true,
+            };
+            match FontInfo::from_path(path, font_index) {
+                Ok(FontInfo {
+                    postscript_name: Some(postscript_name),
+                    ..
+                }) => seen_fonts.insert((file_size, font_index, postscript_name)),
+                _ => true,
+            }
+        })
+        .collect()
+}
+
+// The first line of a cache file written by `FsSource::with_cache()`.
+const CACHE_HEADER: &str = "font-kit fs cache 1";
+
+// What the cache records about a font file: its size and modification time when it was scanned,
+// and the faces found in it.
+struct CachedFile {
+    size: u64,
+    modified: Option<(u64, u32)>,
+    faces: Vec<CachedFace>,
+}
+
+struct CachedFace {
+    font_index: u32,
+    postscript_name: String,
+    family_name: String,
+    properties: Properties,
+}
+
+// Returns a file's modification time as seconds and nanoseconds since the Unix epoch.
+fn modification_time(metadata: &Metadata) -> Option<(u64, u32)> {
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((modified.as_secs(), modified.subsec_nanos()))
+}
+
+// Loads each face in a file to find its names and properties.
+fn scan_file(path: &Path) -> Vec<CachedFace> {
+    FsSource::discover_fonts(path, FsFormats::all())
+        .into_iter()
+        .filter_map(|handle| {
+            let font = Font::from_handle(&handle).ok()?;
+            let font_index = match handle {
+                Handle::Path { font_index, .. } => font_index,
+                _ => return None,
+            };
+            Some(CachedFace {
+                font_index,
+                postscript_name: font.postscript_name()?,
+                family_name: font.family_name(),
+                properties: font.properties(),
+            })
+        })
+        .collect()
+}
+
+// The cache is a text file: after the header, each file is described by a line of the form
+// `file <size> <seconds> <nanoseconds> <path>`, followed by a line of the form
+// `face <font index> <style> <weight> <stretch> <PostScript name> <family name>` for each of
+// its faces. Fields are separated by tabs, and tabs, newlines, and backslashes within them are
+// escaped with backslashes.
+fn read_cache(cache_path: &Path) -> Option<HashMap<PathBuf, CachedFile>> {
+    let contents = fs::read_to_string(cache_path).ok()?;
+    let mut lines = contents.lines();
+    if lines.next()? != CACHE_HEADER {
+        return None;
+    }
+
+    let mut files = HashMap::new();
+    let mut current_file: Option<(PathBuf, CachedFile)> = None;
+    for line in lines {
+        let fields: Vec<String> = line.split('\t').map(unescape).collect::<Option<_>>()?;
+        match (fields[0].as_str(), &fields[1..]) {
+            ("file", [size, seconds, nanoseconds, path]) => {
+                files.extend(current_file.take());
+                let file = CachedFile {
+                    size: size.parse().ok()?,
+                    modified: Some((seconds.parse().ok()?, nanoseconds.parse().ok()?)),
+                    faces: vec![],
+                };
+                current_file = Some((PathBuf::from(path), file));
+            }
+            ("face", [font_index, style, weight, stretch, postscript_name, family_name]) => {
+                let style = match style.as_str() {
+                    "Normal" => Style::Normal,
+                    "Italic" => Style::Italic,
+                    "Oblique" => Style::Oblique,
+                    _ => return None,
+                };
+                current_file.as_mut()?.1.faces.push(CachedFace {
+                    font_index: font_index.parse().ok()?,
+                    postscript_name: postscript_name.clone(),
+                    family_name: family_name.clone(),
+                    properties: Properties {
+                        style,
+                        weight: Weight(weight.parse().ok()?),
+                        stretch: Stretch(stretch.parse().ok()?),
+                    },
+                });
+            }
+            _ => return None,
+        }
+    }
+    files.extend(current_file);
+    Some(files)
+}
+
+// Writes the cache to a temporary file first and then moves it into place, so that a reader
+// never sees a partial cache. Files whose paths aren't Unicode or whose modification times are
+// unknown are left out, so they're scanned every time.
+fn write_cache(cache_path: &Path, files: &[(PathBuf, CachedFile)]) -> io::Result<()> {
+    let mut contents = String::from(CACHE_HEADER);
+    contents.push('\n');
+    for (path, file) in files {
+        let (path, (seconds, nanoseconds)) = match (path.to_str(), file.modified) {
+            (Some(path), Some(modified)) => (path, modified),
+            _ => continue,
+        };
+        contents.push_str(&format!(
+            "file\t{}\t{}\t{}\t{}\n",
+            file.size,
+            seconds,
+            nanoseconds,
+            escape(path)
+        ));
+        for face in &file.faces {
+            contents.push_str(&format!(
+                "face\t{}\t{}\t{}\t{}\t{}\t{}\n",
+                face.font_index,
+                face.properties.style,
+                face.properties.weight.0,
+                face.properties.stretch.0,
+                escape(&face.postscript_name),
+                escape(&face.family_name)
+            ));
+        }
+    }
+
+    if let Some(parent) = cache_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let mut temporary_path = OsString::from(cache_path);
+    temporary_path.push(".tmp");
+    fs::write(&temporary_path, contents)?;
+    fs::rename(&temporary_path, cache_path)
+}
+
+fn escape(field: &str) -> String {
+    field
+        .replace('\\', "\\\\")
+        .replace('\t', "\\t")
+        .replace('\n', "\\n")
+}
+
+fn unescape(field: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(field.len());
+    let mut chars = field.chars();
+    while let Some(character) = chars.next() {
+        unescaped.push(match character {
+            '\\' => match chars.next()? {
+                '\\' => '\\',
+                't' => '\t',
+                'n' => '\n',
+                _ => return None,
+            },
+            character => character,
+        });
+    }
+    Some(unescaped)
+}
+
 #[cfg(any(target_os = "android", target_env = "ohos"))]
 fn default_font_directories() -> Vec<PathBuf> {
     vec![PathBuf::from("/system/fonts")]
@@ -16,8 +16,9 @@ use crate::family_name::FamilyName;
 use crate::font::Font;
 use crate::handle::Handle;
 use crate::properties::Properties;
-use crate::source::Source;
+use crate::source::{self, Source, SourceCapabilities};
 use std::any::Any;
+use std::ops::ControlFlow;
 
 /// A source that keeps fonts in memory.
 #[allow(missing_debug_implementations)]
@@ -46,20 +47,60 @@ impl MemSource {
         Ok(MemSource { families })
     }
 
+    // Creates a memory source from fonts whose family and PostScript names are already known,
+    // without loading them.
+    pub(crate) fn from_named_fonts<I>(fonts: I) -> MemSource
+    where
+        I: Iterator<Item = (Handle, String, String)>,
+    {
+        let mut families: Vec<FamilyEntry> = fonts
+            .map(|(font, family_name, postscript_name)| FamilyEntry {
+                family_name,
+                postscript_name,
+                font,
+            })
+            .collect();
+        families.sort_by(|a, b| a.family_name.cmp(&b.family_name));
+        MemSource { families }
+    }
+
     /// Add an existing font handle to a `MemSource`.
     ///
     /// Returns the font that was just added.
     ///
-    /// Note that adding fonts to an existing `MemSource` is slower than creating a new one from a
-    /// `Handle` iterator, since this method sorts after every addition, rather than once at the
-    /// end.
+    /// The font is inserted into the family index in place, so fonts can be registered one at a
+    /// time as they become available. Registering many fonts at once is still faster with
+    /// `add_fonts()` or `from_fonts()`, which sort only once at the end.
+    ///
+    /// Because this method takes `&mut self`, a `MemSource` that is shared with other threads
+    /// must be wrapped in a lock such as `RwLock`; readers then see each font either not at all or
+    /// fully registered.
     pub fn add_font(&mut self, handle: Handle) -> Result<Font, FontLoadingError> {
-        let font = add_font(handle, &mut self.families)?;
-        self.families
-            .sort_by(|a, b| a.family_name.cmp(&b.family_name));
+        let mut new_families = vec![];
+        let font = add_font(handle, &mut new_families)?;
+        for family in new_families {
+            // Insert after any existing fonts of the same family to keep insertion order stable.
+            let index = self
+                .families
+                .partition_point(|existing| existing.family_name <= family.family_name);
+            self.families.insert(index, family);
+        }
         Ok(font)
     }
 
+    /// Removes the font with the given PostScript name from a `MemSource`.
+    ///
+    /// Returns the handle of the font that was removed, or `None` if no font in this source has
+    /// that name. The family index is updated in place; a family whose last font is removed no
+    /// longer appears in `all_families()`.
+    pub fn remove_font(&mut self, postscript_name: &str) -> Option<Handle> {
+        let index = self
+            .families
+            .iter()
+            .position(|family| family.postscript_name == postscript_name)?;
+        Some(self.families.remove(index).font)
+    }
+
     /// Add a number of existing font handles to a `MemSource`.
     ///
     /// Note that adding fonts to an existing `MemSource` is slower than creating a new one from a
@@ -89,21 +130,39 @@ impl MemSource {
     /// Returns the names of all families installed on the system.
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
         let mut families = vec![];
+        let _ = self.for_each_family(|family_name| {
+            families.push(family_name.to_owned());
+            ControlFlow::Continue(())
+        })?;
+        Ok(families)
+    }
+
+    /// Calls `f` with the name of each family in this source, stopping early if it
+    /// returns `ControlFlow::Break`.
+    pub fn for_each_family<F>(&self, mut f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
+        let mut last_family_name: Option<&str> = None;
         for family in &self.families {
-            if families.last() == Some(&family.family_name) {
+            if last_family_name == Some(&family.family_name) {
                 continue;
             }
+            last_family_name = Some(&family.family_name);
 
-            families.push(family.family_name.clone());
+            if f(&family.family_name).is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
         }
 
-        Ok(families)
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     ///
     /// FIXME(pcwalton): Case-insensitive comparison.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+        source::check_family_name(family_name)?;
         let mut first_family_index = self
             .families
             .binary_search_by(|family| (*family.family_name).cmp(family_name))
@@ -122,9 +181,10 @@ impl MemSource {
         }
 
         let families = &self.families[first_family_index..(last_family_index + 1)];
-        Ok(FamilyHandle::from_font_handles(
-            families.iter().map(|family| family.font.clone()),
-        ))
+        let mut family_handle =
+            FamilyHandle::from_font_handles(families.iter().map(|family| family.font.clone()));
+        family_handle.set_display_name(Some(families[0].family_name.clone()));
+        Ok(family_handle)
     }
 
     /// Selects a font by PostScript name, which should be a unique identifier.
@@ -143,6 +203,28 @@ impl MemSource {
             .ok_or(SelectionError::NotFound)
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`, sorted by PostScript name.
+    ///
+    /// This uses the PostScript names recorded when the fonts were added, so no fonts are
+    /// loaded.
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let matches = self
+            .families
+            .iter()
+            .filter(|family_entry| family_entry.postscript_name.starts_with(prefix))
+            .map(|family_entry| {
+                (
+                    family_entry.postscript_name.clone(),
+                    family_entry.font.clone(),
+                )
+            })
+            .collect();
+        source::sort_by_postscript_name(matches)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -166,6 +248,13 @@ impl Source for MemSource {
         self.all_families()
     }
 
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
     }
@@ -174,6 +263,20 @@ impl Source for MemSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    #[inline]
+    fn capabilities(&self) -> SourceCapabilities {
+        SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+            | SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+    }
+
     #[inline]
     fn as_any(&self) -> &dyn Any {
         self
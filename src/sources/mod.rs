@@ -12,7 +12,13 @@
 //!
 //! The system-specific sources (Core Text, DirectWrite, and Fontconfig) contain the fonts that are
 //! installed on the system. The remaining databases (`fs`, `mem`, and `multi`) allow `font-kit` to
-//! query fonts not installed on the system.
+//! query fonts not installed on the system, and `caching` speeds up repeated queries against any
+//! other source.
+
+#[cfg(all(target_os = "android", feature = "android"))]
+pub mod android_assets;
+
+pub mod caching;
 
 #[cfg(any(target_os = "macos", target_os = "ios"))]
 pub mod core_text;
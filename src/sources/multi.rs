@@ -18,11 +18,11 @@ use crate::family_handle::FamilyHandle;
 use crate::family_name::FamilyName;
 use crate::handle::Handle;
 use crate::properties::Properties;
-use crate::source::Source;
+use crate::source::{load_descriptions_in_family, Source, SourceCapabilities};
 use std::{
     any::Any,
     fmt,
-    ops::{Index, IndexMut},
+    ops::{ControlFlow, Index, IndexMut, RangeInclusive},
     slice,
 };
 
@@ -33,19 +33,67 @@ use std::{
 #[allow(missing_debug_implementations)]
 pub struct MultiSource {
     subsources: Vec<Box<dyn Source>>,
+    fallback_overrides: Vec<(RangeInclusive<char>, Handle)>,
 }
 
 impl MultiSource {
     /// Creates a new source that contains all the fonts in the supplied sources.
     pub fn from_sources(subsources: Vec<Box<dyn Source>>) -> MultiSource {
-        MultiSource { subsources }
+        MultiSource {
+            subsources,
+            fallback_overrides: vec![],
+        }
+    }
+
+    /// Makes `select_fallback_for_char()` choose `handle` for every character in `range`, ahead
+    /// of the fallback families.
+    ///
+    /// This pins a range of characters to a particular font, such as emoji to a bundled emoji
+    /// font, regardless of what the subsources would pick. The font doesn't have to belong to any
+    /// subsource. Overrides are consulted in the order they were added, so the first one whose
+    /// range contains a character wins.
+    pub fn add_fallback_override(&mut self, range: RangeInclusive<char>, handle: Handle) {
+        self.fallback_overrides.push((range, handle));
+    }
+
+    /// Chooses a font to render `character` with when the font the text asked for lacks it.
+    ///
+    /// A fallback override registered with `add_fallback_override()` is chosen first, without
+    /// checking whether its font covers the character. Otherwise, the best match for
+    /// `properties` in each of `default_fallback_families()` is loaded in turn, and the first that
+    /// covers the character is chosen. Returns `SelectionError::NotFound` if none does.
+    pub fn select_fallback_for_char(
+        &self,
+        character: char,
+        properties: &Properties,
+    ) -> Result<Handle, SelectionError> {
+        if let Some((_, handle)) = self
+            .fallback_overrides
+            .iter()
+            .find(|(range, _)| range.contains(&character))
+        {
+            return Ok(handle.clone());
+        }
+        for family_name in self.default_fallback_families() {
+            let handle = match self.select_best_match(&[family_name], properties) {
+                Ok(handle) => handle,
+                Err(SelectionError::NotFound) => continue,
+                Err(err) => return Err(err),
+            };
+            if let Ok(font) = handle.load() {
+                if font.covers_char(character) {
+                    return Ok(handle);
+                }
+            }
+        }
+        Err(SelectionError::NotFound)
     }
 
     /// Returns paths of all fonts installed on the system.
     pub fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
         let mut handles = vec![];
         for subsource in &self.subsources {
-            handles.extend(subsource.all_fonts()?.into_iter())
+            handles.extend(subsource.all_fonts()?)
         }
         Ok(handles)
     }
@@ -53,17 +101,34 @@ impl MultiSource {
     /// Returns the names of all families installed on the system.
     pub fn all_families(&self) -> Result<Vec<String>, SelectionError> {
         let mut families = vec![];
+        let _ = self.for_each_family(|family_name| {
+            families.push(family_name.to_owned());
+            ControlFlow::Continue(())
+        })?;
+        Ok(families)
+    }
+
+    /// Calls `f` with the name of each family in each subsource in turn, stopping early if it
+    /// returns `ControlFlow::Break`.
+    ///
+    /// Families present in more than one subsource are reported once per subsource.
+    pub fn for_each_family<F>(&self, mut f: F) -> Result<ControlFlow<()>, SelectionError>
+    where
+        F: FnMut(&str) -> ControlFlow<()>,
+    {
         for subsource in &self.subsources {
-            families.extend(subsource.all_families()?.into_iter())
+            if subsource.for_each_family(&mut f)?.is_break() {
+                return Ok(ControlFlow::Break(()));
+            }
         }
-        Ok(families)
+        Ok(ControlFlow::Continue(()))
     }
 
     /// Looks up a font family by name and returns the handles of all the fonts in that family.
     pub fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
-        for subsource in &self.subsources {
+        for (index, subsource) in self.subsources.iter().enumerate() {
             match subsource.select_family_by_name(family_name) {
-                Ok(family) => return Ok(family),
+                Ok(family) => return Ok(tag_family(family, index)),
                 Err(SelectionError::NotFound) => {}
                 Err(err) => return Err(err),
             }
@@ -86,6 +151,28 @@ impl MultiSource {
         Err(SelectionError::NotFound)
     }
 
+    /// Selects every font whose PostScript name starts with `prefix`.
+    ///
+    /// The fonts of each subsource are sorted by PostScript name, and subsources are searched in
+    /// order.
+    pub fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        let mut handles = vec![];
+        for subsource in &self.subsources {
+            match subsource.select_by_postscript_name_prefix(prefix) {
+                Ok(subsource_handles) => handles.extend(subsource_handles),
+                Err(SelectionError::NotFound) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        if handles.is_empty() {
+            return Err(SelectionError::NotFound);
+        }
+        Ok(handles)
+    }
+
     /// Performs font matching according to the CSS Fonts Level 3 specification and returns the
     /// handle.
     #[inline]
@@ -135,6 +222,14 @@ impl Source for MultiSource {
         self.all_families()
     }
 
+    #[inline]
+    fn for_each_family(
+        &self,
+        f: &mut dyn FnMut(&str) -> ControlFlow<()>,
+    ) -> Result<ControlFlow<()>, SelectionError> {
+        self.for_each_family(f)
+    }
+
     #[inline]
     fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
         self.select_family_by_name(family_name)
@@ -145,6 +240,82 @@ impl Source for MultiSource {
         self.select_by_postscript_name(postscript_name)
     }
 
+    #[inline]
+    fn select_by_postscript_name_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Vec<Handle>, SelectionError> {
+        self.select_by_postscript_name_prefix(prefix)
+    }
+
+    // Lets each subsource resolve generic names in its own way.
+    fn select_family_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<FamilyHandle, SelectionError> {
+        for (index, subsource) in self.subsources.iter().enumerate() {
+            match subsource.select_family_by_generic_name(family_name) {
+                Ok(family) => return Ok(tag_family(family, index)),
+                Err(SelectionError::NotFound) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Err(SelectionError::NotFound)
+    }
+
+    fn select_families_by_generic_name(
+        &self,
+        family_name: &FamilyName,
+    ) -> Result<Vec<FamilyHandle>, SelectionError> {
+        for (index, subsource) in self.subsources.iter().enumerate() {
+            match subsource.select_families_by_generic_name(family_name) {
+                Ok(families) => {
+                    return Ok(families
+                        .into_iter()
+                        .map(|family| tag_family(family, index))
+                        .collect())
+                }
+                Err(SelectionError::NotFound) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        Err(SelectionError::NotFound)
+    }
+
+    // Hands the family back to the subsource that found it, which may know the properties of its
+    // fonts without loading them.
+    fn select_descriptions_in_family(
+        &self,
+        family: &FamilyHandle,
+    ) -> Result<Vec<Properties>, SelectionError> {
+        let mut subsource_path = family.subsource_path.clone();
+        match subsource_path
+            .pop()
+            .and_then(|index| self.subsources.get(index))
+        {
+            Some(subsource) => subsource.select_descriptions_in_family(&FamilyHandle {
+                fonts: family.fonts.clone(),
+                display_name: family.display_name.clone(),
+                subsource_path,
+            }),
+            None => Ok(load_descriptions_in_family(family)),
+        }
+    }
+
+    // An operation is native only if every subsource performs it natively. The UI font and
+    // fallback families always come from the trait's defaults.
+    fn capabilities(&self) -> SourceCapabilities {
+        if self.subsources.is_empty() {
+            return SourceCapabilities::empty();
+        }
+        self.subsources.iter().fold(
+            SourceCapabilities::POSTSCRIPT_NAME_LOOKUP
+                | SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP
+                | SourceCapabilities::GENERIC_FAMILIES,
+            |capabilities, subsource| capabilities & subsource.capabilities(),
+        )
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -154,6 +325,12 @@ impl Source for MultiSource {
     }
 }
 
+// Records that `family` was found in the subsource at `index`.
+fn tag_family(mut family: FamilyHandle, index: usize) -> FamilyHandle {
+    family.subsource_path.push(index);
+    family
+}
+
 impl Index<usize> for MultiSource {
     type Output = dyn Source;
 
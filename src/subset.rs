@@ -0,0 +1,390 @@
+// font-kit/src/subset.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Builds TrueType subsets containing only a chosen set of glyphs.
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+use crate::error::FontLoadingError;
+use crate::tables::{self, read_u16, read_u32};
+
+const TAG_CVT: u32 = 0x63767420; // 'cvt '
+const TAG_FPGM: u32 = 0x6670676d; // 'fpgm'
+const TAG_GASP: u32 = 0x67617370; // 'gasp'
+const TAG_NAME: u32 = 0x6e616d65; // 'name'
+const TAG_PREP: u32 = 0x70726570; // 'prep'
+
+// Tables that don't refer to glyph IDs and can be copied into the subset unchanged.
+const PASSTHROUGH_TABLES: [u32; 6] = [
+    TAG_CVT,
+    TAG_FPGM,
+    TAG_GASP,
+    TAG_NAME,
+    tables::TAG_OS2,
+    TAG_PREP,
+];
+
+const HEAD_CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+const HEAD_INDEX_TO_LOC_FORMAT_OFFSET: usize = 50;
+const HHEA_NUMBER_OF_H_METRICS_OFFSET: usize = 34;
+const MAXP_NUM_GLYPHS_OFFSET: usize = 4;
+const POST_HEADER_LENGTH: usize = 32;
+
+const CHECKSUM_MAGIC: u32 = 0xb1b0afba;
+
+/// Builds a TrueType font containing only the given glyphs, the glyphs they are composed of, and
+/// `.notdef`.
+///
+/// Glyphs are renumbered in ascending order of their original IDs. `load_font_table` supplies
+/// the tables of the original font.
+pub(crate) fn subset_truetype<F>(
+    load_font_table: F,
+    glyph_ids: &[u32],
+) -> Result<Vec<u8>, FontLoadingError>
+where
+    F: Fn(u32) -> Option<Box<[u8]>>,
+{
+    let glyf = load_font_table(tables::TAG_GLYF).ok_or(FontLoadingError::UnknownFormat)?;
+    let head = load_font_table(tables::TAG_HEAD).ok_or(FontLoadingError::Parse)?;
+    let loca = load_font_table(tables::TAG_LOCA).ok_or(FontLoadingError::Parse)?;
+    let maxp = load_font_table(tables::TAG_MAXP).ok_or(FontLoadingError::Parse)?;
+    let hhea = load_font_table(tables::TAG_HHEA).ok_or(FontLoadingError::Parse)?;
+    let hmtx = load_font_table(tables::TAG_HMTX).ok_or(FontLoadingError::Parse)?;
+    let num_glyphs = tables::maxp_num_glyphs(&maxp).ok_or(FontLoadingError::Parse)? as u32;
+    let glyph_data =
+        |glyph_id| tables::glyf_glyph_data(&head, &loca, &glyf, glyph_id).unwrap_or(&[]);
+
+    // Gather the requested glyphs and, transitively, the components of composite glyphs.
+    let mut old_glyph_ids = BTreeSet::new();
+    let mut pending: Vec<u32> = Some(0)
+        .into_iter()
+        .chain(glyph_ids.iter().cloned())
+        .filter(|&glyph_id| glyph_id < num_glyphs)
+        .collect();
+    while let Some(glyph_id) = pending.pop() {
+        if !old_glyph_ids.insert(glyph_id) {
+            continue;
+        }
+        if let Some(components) = tables::glyf_composite_components(glyph_data(glyph_id)) {
+            pending.extend(
+                components
+                    .iter()
+                    .map(|component| component.glyph_id)
+                    .filter(|&glyph_id| glyph_id < num_glyphs),
+            );
+        }
+    }
+    let old_glyph_ids: Vec<u32> = old_glyph_ids.into_iter().collect();
+    let new_glyph_id = |old_glyph_id: u32| {
+        old_glyph_ids
+            .binary_search(&old_glyph_id)
+            .ok()
+            .map(|index| index as u16)
+    };
+
+    // `glyf` and `loca`, with component references renumbered.
+    let mut new_glyf = vec![];
+    let mut new_loca = vec![];
+    for &old_glyph_id in &old_glyph_ids {
+        push_u32(&mut new_loca, new_glyf.len() as u32);
+        let mut data = glyph_data(old_glyph_id).to_vec();
+        if let Some(offsets) = tables::glyf_composite_glyph_index_offsets(&data) {
+            for offset in offsets {
+                let old_component_id = read_u16(&data, offset).ok_or(FontLoadingError::Parse)?;
+                let new_component_id =
+                    new_glyph_id(old_component_id as u32).ok_or(FontLoadingError::Parse)?;
+                data[offset..offset + 2].copy_from_slice(&new_component_id.to_be_bytes());
+            }
+        }
+        new_glyf.extend_from_slice(&data);
+        pad_to_4_bytes(&mut new_glyf);
+    }
+    push_u32(&mut new_loca, new_glyf.len() as u32);
+
+    // `hmtx`, with a full metric for every glyph.
+    let advances = tables::long_metric_advances(&hhea, &hmtx, num_glyphs as u16);
+    let num_long_metrics =
+        read_u16(&hhea, HHEA_NUMBER_OF_H_METRICS_OFFSET).ok_or(FontLoadingError::Parse)? as usize;
+    let mut new_hmtx = vec![];
+    for &old_glyph_id in &old_glyph_ids {
+        let old_glyph_id = old_glyph_id as usize;
+        let lsb_offset = if old_glyph_id < num_long_metrics {
+            old_glyph_id * 4 + 2
+        } else {
+            num_long_metrics * 4 + (old_glyph_id - num_long_metrics) * 2
+        };
+        let advance = advances.get(old_glyph_id).cloned().unwrap_or(0);
+        let lsb = read_u16(&hmtx, lsb_offset).unwrap_or(0);
+        push_u16(&mut new_hmtx, advance);
+        push_u16(&mut new_hmtx, lsb);
+    }
+
+    let new_num_glyphs = old_glyph_ids.len() as u16;
+    let mut new_head = head.to_vec();
+    set_u32(&mut new_head, HEAD_CHECKSUM_ADJUSTMENT_OFFSET, 0)?;
+    set_u16(&mut new_head, HEAD_INDEX_TO_LOC_FORMAT_OFFSET, 1)?;
+    let mut new_hhea = hhea.to_vec();
+    set_u16(
+        &mut new_hhea,
+        HHEA_NUMBER_OF_H_METRICS_OFFSET,
+        new_num_glyphs,
+    )?;
+    let mut new_maxp = maxp.to_vec();
+    set_u16(&mut new_maxp, MAXP_NUM_GLYPHS_OFFSET, new_num_glyphs)?;
+
+    // `cmap`, keeping only the characters that map to retained glyphs.
+    let mappings: Vec<(u32, u16)> = load_font_table(tables::TAG_CMAP)
+        .map(|cmap| tables::cmap_unicode_mappings(&cmap))
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(character, glyph_id)| Some((character, new_glyph_id(glyph_id)?)))
+        .collect();
+
+    let mut new_tables = vec![
+        (tables::TAG_CMAP, build_cmap(&mappings)),
+        (tables::TAG_GLYF, new_glyf),
+        (tables::TAG_HEAD, new_head),
+        (tables::TAG_HHEA, new_hhea),
+        (tables::TAG_HMTX, new_hmtx),
+        (tables::TAG_LOCA, new_loca),
+        (tables::TAG_MAXP, new_maxp),
+    ];
+    // Glyph names refer to the old glyph IDs, so drop them by switching to version 3.
+    if let Some(post) = load_font_table(tables::TAG_POST) {
+        if let Some(post_header) = post.get(..POST_HEADER_LENGTH) {
+            let mut new_post = post_header.to_vec();
+            set_u32(&mut new_post, 0, 0x00030000)?;
+            new_tables.push((tables::TAG_POST, new_post));
+        }
+    }
+    for &table_tag in &PASSTHROUGH_TABLES {
+        if let Some(table) = load_font_table(table_tag) {
+            new_tables.push((table_tag, table.to_vec()));
+        }
+    }
+
+    build_sfnt(new_tables)
+}
+
+// Builds a `cmap` table with a format 4 subtable for the BMP and, if needed, a format 12 subtable
+// for the full Unicode range. If the BMP mappings are too fragmented for a format 4 subtable,
+// which is limited to 64K, only the format 12 subtable is written.
+fn build_cmap(mappings: &[(u32, u16)]) -> Vec<u8> {
+    let bmp_mappings: Vec<(u32, u16)> = mappings
+        .iter()
+        .cloned()
+        .filter(|&(character, _)| character < 0xffff)
+        .collect();
+    let mut subtables = vec![];
+    match build_cmap_format_4(&bmp_mappings) {
+        Some(format_4_subtable) => {
+            subtables.push((3, 1, format_4_subtable));
+            if bmp_mappings.len() < mappings.len() {
+                subtables.push((3, 10, build_cmap_format_12(mappings)));
+            }
+        }
+        None => subtables.push((3, 10, build_cmap_format_12(mappings))),
+    }
+
+    let mut cmap = vec![];
+    push_u16(&mut cmap, 0);
+    push_u16(&mut cmap, subtables.len() as u16);
+    let mut subtable_offset = 4 + subtables.len() * 8;
+    for (platform_id, encoding_id, subtable) in &subtables {
+        push_u16(&mut cmap, *platform_id);
+        push_u16(&mut cmap, *encoding_id);
+        push_u32(&mut cmap, subtable_offset as u32);
+        subtable_offset += subtable.len();
+    }
+    for (_, _, subtable) in &subtables {
+        cmap.extend_from_slice(subtable);
+    }
+    cmap
+}
+
+// Groups sorted mappings into runs of consecutive characters mapped to consecutive glyphs.
+fn cmap_runs(mappings: &[(u32, u16)]) -> Vec<(u32, u32, u16)> {
+    let mut runs: Vec<(u32, u32, u16)> = vec![];
+    for &(character, glyph_id) in mappings {
+        match runs.last_mut() {
+            Some((start, end, start_glyph_id))
+                if character == *end + 1
+                    && glyph_id as u32 == *start_glyph_id as u32 + (character - *start) =>
+            {
+                *end = character
+            }
+            _ => runs.push((character, character, glyph_id)),
+        }
+    }
+    runs
+}
+
+// Returns `None` if the subtable would be too long for its 16-bit length field.
+fn build_cmap_format_4(mappings: &[(u32, u16)]) -> Option<Vec<u8>> {
+    let mut segments = cmap_runs(mappings);
+    // The last segment must map 0xFFFF to `.notdef`.
+    segments.push((0xffff, 0xffff, 0));
+    let seg_count = segments.len();
+    let length = u16::try_from(16 + seg_count * 8).ok()?;
+    let seg_count_x2 = u16::try_from(seg_count * 2).ok()?;
+    let entry_selector = (seg_count as f32).log2().floor() as u16;
+    let search_range = 2 * (1 << entry_selector);
+
+    let mut subtable = vec![];
+    push_u16(&mut subtable, 4);
+    push_u16(&mut subtable, length);
+    push_u16(&mut subtable, 0);
+    push_u16(&mut subtable, seg_count_x2);
+    push_u16(&mut subtable, search_range);
+    push_u16(&mut subtable, entry_selector);
+    push_u16(&mut subtable, seg_count_x2 - search_range);
+    for &(_, end, _) in &segments {
+        push_u16(&mut subtable, end as u16);
+    }
+    push_u16(&mut subtable, 0);
+    for &(start, _, _) in &segments {
+        push_u16(&mut subtable, start as u16);
+    }
+    for &(start, _, start_glyph_id) in &segments {
+        let id_delta = if start_glyph_id == 0 {
+            1
+        } else {
+            start_glyph_id.wrapping_sub(start as u16)
+        };
+        push_u16(&mut subtable, id_delta);
+    }
+    for _ in &segments {
+        push_u16(&mut subtable, 0);
+    }
+    Some(subtable)
+}
+
+fn build_cmap_format_12(mappings: &[(u32, u16)]) -> Vec<u8> {
+    let groups = cmap_runs(mappings);
+    let mut subtable = vec![];
+    push_u16(&mut subtable, 12);
+    push_u16(&mut subtable, 0);
+    push_u32(&mut subtable, (16 + groups.len() * 12) as u32);
+    push_u32(&mut subtable, 0);
+    push_u32(&mut subtable, groups.len() as u32);
+    for &(start, end, start_glyph_id) in &groups {
+        push_u32(&mut subtable, start);
+        push_u32(&mut subtable, end);
+        push_u32(&mut subtable, start_glyph_id as u32);
+    }
+    subtable
+}
+
+fn build_sfnt(mut new_tables: Vec<(u32, Vec<u8>)>) -> Result<Vec<u8>, FontLoadingError> {
+    new_tables.sort_by_key(|&(table_tag, _)| table_tag);
+    let num_tables = new_tables.len();
+    let entry_selector = (num_tables as f32).log2().floor() as u16;
+    let search_range = 16 * (1 << entry_selector);
+
+    let mut font_data = vec![];
+    push_u32(&mut font_data, 0x00010000);
+    push_u16(&mut font_data, num_tables as u16);
+    push_u16(&mut font_data, search_range);
+    push_u16(&mut font_data, entry_selector);
+    push_u16(&mut font_data, num_tables as u16 * 16 - search_range);
+
+    let mut table_offset = 12 + num_tables * 16;
+    for (table_tag, table) in &new_tables {
+        push_u32(&mut font_data, *table_tag);
+        push_u32(&mut font_data, checksum(table));
+        push_u32(&mut font_data, table_offset as u32);
+        push_u32(&mut font_data, table.len() as u32);
+        table_offset += (table.len() + 3) & !3;
+    }
+
+    let mut head_offset = None;
+    for (table_tag, table) in &new_tables {
+        if *table_tag == tables::TAG_HEAD {
+            head_offset = Some(font_data.len());
+        }
+        font_data.extend_from_slice(table);
+        pad_to_4_bytes(&mut font_data);
+    }
+
+    let head_offset = head_offset.ok_or(FontLoadingError::Parse)?;
+    let checksum_adjustment = CHECKSUM_MAGIC.wrapping_sub(checksum(&font_data));
+    set_u32(
+        &mut font_data,
+        head_offset + HEAD_CHECKSUM_ADJUSTMENT_OFFSET,
+        checksum_adjustment,
+    )?;
+    Ok(font_data)
+}
+
+fn checksum(data: &[u8]) -> u32 {
+    data.chunks(4).fold(0u32, |sum, chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        sum.wrapping_add(u32::from_be_bytes(word))
+    })
+}
+
+fn pad_to_4_bytes(data: &mut Vec<u8>) {
+    data.resize((data.len() + 3) & !3, 0)
+}
+
+fn push_u16(data: &mut Vec<u8>, value: u16) {
+    data.extend_from_slice(&value.to_be_bytes())
+}
+
+fn push_u32(data: &mut Vec<u8>, value: u32) {
+    data.extend_from_slice(&value.to_be_bytes())
+}
+
+fn set_u16(data: &mut [u8], offset: usize, value: u16) -> Result<(), FontLoadingError> {
+    read_u16(data, offset).ok_or(FontLoadingError::Parse)?;
+    data[offset..offset + 2].copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+fn set_u32(data: &mut [u8], offset: usize, value: u32) -> Result<(), FontLoadingError> {
+    read_u32(data, offset).ok_or(FontLoadingError::Parse)?;
+    data[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::build_cmap;
+    use crate::tables::{cmap_format_12_glyph_id, cmap_unicode_mappings, read_u16};
+
+    fn as_unicode_mappings(mappings: &[(u32, u16)]) -> Vec<(u32, u32)> {
+        mappings
+            .iter()
+            .map(|&(character, glyph_id)| (character, glyph_id as u32))
+            .collect()
+    }
+
+    #[test]
+    fn build_cmap_with_format_4_and_12() {
+        let mappings = [(0x41, 1), (0x42, 2), (0x1f600, 3)];
+        let cmap = build_cmap(&mappings);
+        assert_eq!(read_u16(&cmap, 2), Some(2));
+        assert_eq!(cmap_unicode_mappings(&cmap), as_unicode_mappings(&mappings));
+    }
+
+    #[test]
+    fn build_cmap_omits_format_4_when_too_long() {
+        // Every other character, so that each mapping needs a segment of its own.
+        let mappings: Vec<(u32, u16)> = (0..10000)
+            .map(|index| (index * 2, index as u16 + 1))
+            .collect();
+        let cmap = build_cmap(&mappings);
+        assert_eq!(read_u16(&cmap, 2), Some(1));
+        assert_eq!(cmap_format_12_glyph_id(&cmap, 19998), Some(10000));
+        assert_eq!(cmap_unicode_mappings(&cmap), as_unicode_mappings(&mappings));
+    }
+}
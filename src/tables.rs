@@ -0,0 +1,2791 @@
+// font-kit/src/tables.rs
+//
+// Copyright © 2018 The Pathfinder Project Developers.
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Minimal parsers for the OpenType tables that font-kit reads directly, for information that
+//! the platform APIs don't expose.
+
+use byteorder::{BigEndian, ByteOrder};
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
+use pathfinder_geometry::vector::Vector2F;
+use std::cmp::Ordering;
+use std::convert::{TryFrom, TryInto};
+use std::f32;
+use std::ops::Range;
+
+use crate::color::{Color, ColorLine, ColorStop, CompositeMode, Extend, Paint, PaintColor};
+use crate::hinting::CffHintParams;
+use crate::metrics::{BaselineTag, ScriptTag};
+use crate::outline::{ContourPoints, GlyphComponent};
+use crate::properties::{Properties, Stretch, Style, Weight};
+
+pub(crate) const TAG_AVAR: u32 = 0x61766172; // 'avar'
+pub(crate) const TAG_BASE: u32 = 0x42415345; // 'BASE'
+pub(crate) const TAG_BLOC: u32 = 0x626c6f63; // 'bloc'
+pub(crate) const TAG_CBLC: u32 = 0x43424c43; // 'CBLC'
+pub(crate) const TAG_CFF: u32 = 0x43464620; // 'CFF '
+pub(crate) const TAG_CFF2: u32 = 0x43464632; // 'CFF2'
+pub(crate) const TAG_CMAP: u32 = 0x636d6170; // 'cmap'
+pub(crate) const TAG_COLR: u32 = 0x434f4c52; // 'COLR'
+pub(crate) const TAG_CPAL: u32 = 0x4350414c; // 'CPAL'
+pub(crate) const TAG_DSIG: u32 = 0x44534947; // 'DSIG'
+pub(crate) const TAG_EBLC: u32 = 0x45424c43; // 'EBLC'
+pub(crate) const TAG_FVAR: u32 = 0x66766172; // 'fvar'
+pub(crate) const TAG_GLYF: u32 = 0x676c7966; // 'glyf'
+pub(crate) const TAG_GSUB: u32 = 0x47535542; // 'GSUB'
+pub(crate) const TAG_HDMX: u32 = 0x68646d78; // 'hdmx'
+pub(crate) const TAG_HEAD: u32 = 0x68656164; // 'head'
+pub(crate) const TAG_HHEA: u32 = 0x68686561; // 'hhea'
+pub(crate) const TAG_HMTX: u32 = 0x686d7478; // 'hmtx'
+pub(crate) const TAG_HVAR: u32 = 0x48564152; // 'HVAR'
+pub(crate) const TAG_KERN: u32 = 0x6b65726e; // 'kern'
+pub(crate) const TAG_LOCA: u32 = 0x6c6f6361; // 'loca'
+pub(crate) const TAG_MAXP: u32 = 0x6d617870; // 'maxp'
+pub(crate) const TAG_MVAR: u32 = 0x4d564152; // 'MVAR'
+pub(crate) const TAG_NAME: u32 = 0x6e616d65; // 'name'
+pub(crate) const TAG_OS2: u32 = 0x4f532f32; // 'OS/2'
+pub(crate) const TAG_POST: u32 = 0x706f7374; // 'post'
+pub(crate) const TAG_SBIX: u32 = 0x73626978; // 'sbix'
+pub(crate) const TAG_VHEA: u32 = 0x76686561; // 'vhea'
+pub(crate) const TAG_VMTX: u32 = 0x766d7478; // 'vmtx'
+
+const BITMAP_SIZE_RECORD_LENGTH: usize = 48;
+
+const FVAR_AXES_ARRAY_OFFSET_OFFSET: usize = 4;
+const FVAR_AXIS_COUNT_OFFSET: usize = 8;
+const FVAR_AXIS_SIZE_OFFSET: usize = 10;
+
+const AVAR_AXIS_COUNT_OFFSET: usize = 6;
+const AVAR_SEGMENT_MAPS_OFFSET: usize = 8;
+
+const MVAR_VALUE_RECORD_SIZE_OFFSET: usize = 6;
+const MVAR_VALUE_RECORD_COUNT_OFFSET: usize = 8;
+const MVAR_ITEM_VARIATION_STORE_OFFSET_OFFSET: usize = 10;
+const MVAR_VALUE_RECORDS_OFFSET: usize = 12;
+
+pub(crate) const MVAR_TAG_HORIZONTAL_ASCENDER: u32 = 0x68617363; // 'hasc'
+pub(crate) const MVAR_TAG_HORIZONTAL_DESCENDER: u32 = 0x68647363; // 'hdsc'
+pub(crate) const MVAR_TAG_HORIZONTAL_LINE_GAP: u32 = 0x686c6770; // 'hlgp'
+pub(crate) const MVAR_TAG_UNDERLINE_OFFSET: u32 = 0x756e646f; // 'undo'
+pub(crate) const MVAR_TAG_UNDERLINE_SIZE: u32 = 0x756e6473; // 'unds'
+pub(crate) const MVAR_TAG_CAP_HEIGHT: u32 = 0x63706874; // 'cpht'
+pub(crate) const MVAR_TAG_X_HEIGHT: u32 = 0x78686774; // 'xhgt'
+
+const HVAR_ITEM_VARIATION_STORE_OFFSET_OFFSET: usize = 4;
+const HVAR_ADVANCE_WIDTH_MAPPING_OFFSET_OFFSET: usize = 8;
+
+const DELTA_SET_INDEX_MAP_INNER_INDEX_BIT_COUNT_MASK: u8 = 0x0f;
+const DELTA_SET_INDEX_MAP_ENTRY_SIZE_MASK: u8 = 0x30;
+
+const ITEM_VARIATION_STORE_FORMAT: u16 = 1;
+const ITEM_VARIATION_DATA_LONG_WORDS: u16 = 0x8000;
+const ITEM_VARIATION_DATA_WORD_COUNT_MASK: u16 = 0x7fff;
+
+const BASE_HORIZ_AXIS_OFFSET_OFFSET: usize = 4;
+const BASE_AXIS_BASE_SCRIPT_LIST_OFFSET_OFFSET: usize = 2;
+const BASE_SCRIPT_RECORD_LENGTH: usize = 6;
+const BASE_VALUES_BASE_COORD_COUNT_OFFSET: usize = 2;
+const BASE_VALUES_BASE_COORD_OFFSETS_OFFSET: usize = 4;
+const BASE_COORD_COORDINATE_OFFSET: usize = 2;
+
+const DSIG_NUM_SIGNATURES_OFFSET: usize = 4;
+
+const HDMX_NUM_RECORDS_OFFSET: usize = 2;
+const HDMX_SIZE_DEVICE_RECORD_OFFSET: usize = 4;
+const HDMX_RECORDS_OFFSET: usize = 8;
+const HDMX_DEVICE_RECORD_WIDTHS_OFFSET: usize = 2;
+
+const KERN_NUM_TABLES_OFFSET: usize = 2;
+const KERN_SUBTABLES_OFFSET: usize = 4;
+const KERN_SUBTABLE_LENGTH_OFFSET: usize = 2;
+const KERN_SUBTABLE_COVERAGE_OFFSET: usize = 4;
+const KERN_SUBTABLE_NUM_PAIRS_OFFSET: usize = 6;
+const KERN_SUBTABLE_PAIRS_OFFSET: usize = 14;
+const KERN_PAIR_LENGTH: usize = 6;
+
+// The low byte of a subtable's coverage holds these flags, and the high byte its format.
+const KERN_COVERAGE_HORIZONTAL: u16 = 0x0001;
+const KERN_COVERAGE_MINIMUM: u16 = 0x0002;
+const KERN_COVERAGE_CROSS_STREAM: u16 = 0x0004;
+const KERN_COVERAGE_FORMAT_MASK: u16 = 0xff00;
+
+const GSUB_FEATURE_LIST_OFFSET_OFFSET: usize = 6;
+const GSUB_LOOKUP_LIST_OFFSET_OFFSET: usize = 8;
+const GSUB_FEATURE_RECORD_LENGTH: usize = 6;
+const GSUB_LOOKUP_TYPE_LIGATURE: u16 = 4;
+const GSUB_LOOKUP_TYPE_CONTEXT: u16 = 5;
+const GSUB_LOOKUP_TYPE_CHAINED_CONTEXT: u16 = 6;
+const GSUB_LOOKUP_TYPE_EXTENSION: u16 = 7;
+
+pub(crate) const FEATURE_TAG_STANDARD_LIGATURES: u32 = 0x6c696761; // 'liga'
+pub(crate) const FEATURE_TAG_DISCRETIONARY_LIGATURES: u32 = 0x646c6967; // 'dlig'
+
+const HEAD_CHECKSUM_ADJUSTMENT_OFFSET: usize = 8;
+const HEAD_MAC_STYLE_OFFSET: usize = 44;
+const HEAD_INDEX_TO_LOC_FORMAT_OFFSET: usize = 50;
+
+const HEAD_MAC_STYLE_ITALIC: u16 = 0x0002;
+
+const MAXP_NUM_GLYPHS_OFFSET: usize = 4;
+
+const NAME_COUNT_OFFSET: usize = 2;
+const NAME_STORAGE_OFFSET_OFFSET: usize = 4;
+const NAME_RECORDS_OFFSET: usize = 6;
+const NAME_RECORD_LENGTH: usize = 12;
+
+const NAME_ID_FAMILY: u16 = 1;
+const NAME_ID_SUBFAMILY: u16 = 2;
+pub(crate) const NAME_ID_POSTSCRIPT_NAME: u16 = 6;
+pub(crate) const NAME_ID_MANUFACTURER: u16 = 8;
+pub(crate) const NAME_ID_DESIGNER: u16 = 9;
+pub(crate) const NAME_ID_DESCRIPTION: u16 = 10;
+pub(crate) const NAME_ID_LICENSE: u16 = 13;
+const NAME_ID_TYPOGRAPHIC_FAMILY: u16 = 16;
+const NAME_ID_TYPOGRAPHIC_SUBFAMILY: u16 = 17;
+pub(crate) const NAME_ID_SAMPLE_TEXT: u16 = 19;
+const NAME_ID_WWS_FAMILY: u16 = 21;
+const NAME_ID_WWS_SUBFAMILY: u16 = 22;
+
+const OS2_WEIGHT_CLASS_OFFSET: usize = 4;
+const OS2_WIDTH_CLASS_OFFSET: usize = 6;
+const OS2_FS_TYPE_OFFSET: usize = 8;
+const OS2_PANOSE_OFFSET: usize = 32;
+const OS2_CODE_PAGE_RANGE_1_OFFSET: usize = 78;
+const OS2_CODE_PAGE_RANGE_2_OFFSET: usize = 82;
+const OS2_FS_SELECTION_OFFSET: usize = 62;
+const OS2_TYPO_ASCENDER_OFFSET: usize = 68;
+const OS2_TYPO_DESCENDER_OFFSET: usize = 70;
+const OS2_TYPO_LINE_GAP_OFFSET: usize = 72;
+
+const OS2_FS_SELECTION_ITALIC: u16 = 0x0001;
+const OS2_FS_SELECTION_USE_TYPO_METRICS: u16 = 0x0080;
+const OS2_FS_SELECTION_WWS: u16 = 0x0100;
+const OS2_FS_SELECTION_OBLIQUE: u16 = 0x0200;
+
+const POST_ITALIC_ANGLE_OFFSET: usize = 4;
+const POST_NUM_GLYPHS_OFFSET: usize = 32;
+const POST_GLYPH_NAME_INDEX_OFFSET: usize = 34;
+
+// `numberOfHMetrics` in `hhea` and `numOfLongVerMetrics` in `vhea` share this offset.
+const METRICS_HEADER_NUM_LONG_METRICS_OFFSET: usize = 34;
+
+const LONG_METRIC_RECORD_LENGTH: usize = 4;
+
+const CFF_HEADER_SIZE_OFFSET: usize = 2;
+
+const CFF_OP_BLUE_VALUES: u16 = 6;
+const CFF_OP_OTHER_BLUES: u16 = 7;
+const CFF_OP_STD_HW: u16 = 10;
+const CFF_OP_STD_VW: u16 = 11;
+const CFF_OP_PRIVATE: u16 = 18;
+// Two-byte operators are numbered 1200 and up, after their escape byte of 12.
+const CFF_OP_ESCAPE: u8 = 12;
+const CFF_OP_FD_ARRAY: u16 = 1236;
+
+const COMPOSITE_ARG_1_AND_2_ARE_WORDS: u16 = 0x0001;
+const COMPOSITE_ARGS_ARE_XY_VALUES: u16 = 0x0002;
+const COMPOSITE_WE_HAVE_A_SCALE: u16 = 0x0008;
+const COMPOSITE_MORE_COMPONENTS: u16 = 0x0020;
+const COMPOSITE_WE_HAVE_AN_X_AND_Y_SCALE: u16 = 0x0040;
+const COMPOSITE_WE_HAVE_A_TWO_BY_TWO: u16 = 0x0080;
+const COMPOSITE_OVERLAP_COMPOUND: u16 = 0x0400;
+
+const SIMPLE_ON_CURVE_POINT: u8 = 0x01;
+const SIMPLE_X_SHORT_VECTOR: u8 = 0x02;
+const SIMPLE_Y_SHORT_VECTOR: u8 = 0x04;
+const SIMPLE_REPEAT_FLAG: u8 = 0x08;
+const SIMPLE_X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR: u8 = 0x10;
+const SIMPLE_Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR: u8 = 0x20;
+const SIMPLE_OVERLAP_SIMPLE: u8 = 0x40;
+
+const CPAL_NUM_PALETTE_ENTRIES_OFFSET: usize = 2;
+const CPAL_NUM_PALETTES_OFFSET: usize = 4;
+const CPAL_COLOR_RECORDS_ARRAY_OFFSET_OFFSET: usize = 8;
+const CPAL_COLOR_RECORD_INDICES_OFFSET: usize = 12;
+
+const COLR_NUM_BASE_GLYPH_RECORDS_OFFSET: usize = 2;
+const COLR_BASE_GLYPH_RECORDS_OFFSET_OFFSET: usize = 4;
+const COLR_LAYER_RECORDS_OFFSET_OFFSET: usize = 8;
+const COLR_BASE_GLYPH_LIST_OFFSET_OFFSET: usize = 14;
+const COLR_LAYER_LIST_OFFSET_OFFSET: usize = 18;
+
+const COLR_FOREGROUND_PALETTE_ENTRY: u16 = 0xffff;
+// Deeper paint graphs are treated as malformed, which also stops reference cycles.
+const COLR_MAX_PAINT_DEPTH: u32 = 64;
+const COLR_MAX_PAINT_COUNT: u32 = 65536;
+
+const COLR_PAINT_COLR_LAYERS: u8 = 1;
+const COLR_PAINT_SOLID: u8 = 2;
+const COLR_PAINT_VAR_SOLID: u8 = 3;
+const COLR_PAINT_LINEAR_GRADIENT: u8 = 4;
+const COLR_PAINT_VAR_LINEAR_GRADIENT: u8 = 5;
+const COLR_PAINT_RADIAL_GRADIENT: u8 = 6;
+const COLR_PAINT_VAR_RADIAL_GRADIENT: u8 = 7;
+const COLR_PAINT_SWEEP_GRADIENT: u8 = 8;
+const COLR_PAINT_VAR_SWEEP_GRADIENT: u8 = 9;
+const COLR_PAINT_GLYPH: u8 = 10;
+const COLR_PAINT_COLR_GLYPH: u8 = 11;
+const COLR_PAINT_TRANSFORM: u8 = 12;
+const COLR_PAINT_TRANSLATE: u8 = 14;
+const COLR_PAINT_SCALE: u8 = 16;
+const COLR_PAINT_SCALE_AROUND_CENTER: u8 = 18;
+const COLR_PAINT_SCALE_UNIFORM: u8 = 20;
+const COLR_PAINT_SCALE_UNIFORM_AROUND_CENTER: u8 = 22;
+const COLR_PAINT_ROTATE: u8 = 24;
+const COLR_PAINT_ROTATE_AROUND_CENTER: u8 = 26;
+const COLR_PAINT_SKEW: u8 = 28;
+const COLR_PAINT_SKEW_AROUND_CENTER: u8 = 30;
+const COLR_PAINT_VAR_SKEW_AROUND_CENTER: u8 = 31;
+const COLR_PAINT_COMPOSITE: u8 = 32;
+
+#[inline]
+pub(crate) fn read_u8(data: &[u8], offset: usize) -> Option<u8> {
+    data.get(offset).cloned()
+}
+
+#[inline]
+pub(crate) fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    data.get(offset..offset + 2).map(BigEndian::read_u16)
+}
+
+#[inline]
+pub(crate) fn read_i16(data: &[u8], offset: usize) -> Option<i16> {
+    data.get(offset..offset + 2).map(BigEndian::read_i16)
+}
+
+#[inline]
+pub(crate) fn read_i32(data: &[u8], offset: usize) -> Option<i32> {
+    data.get(offset..offset + 4).map(BigEndian::read_i32)
+}
+
+#[inline]
+fn read_u24(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 3).map(BigEndian::read_u24)
+}
+
+#[inline]
+pub(crate) fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    data.get(offset..offset + 4).map(BigEndian::read_u32)
+}
+
+/// Returns the vertical pixels-per-em of each strike in an `EBLC`, `CBLC`, or `bloc` table.
+///
+/// These three tables share the same header and `BitmapSize` record layout.
+pub(crate) fn bitmap_location_strike_sizes(eblc: &[u8]) -> Vec<u16> {
+    let num_sizes = read_u32(eblc, 4).unwrap_or(0) as usize;
+    (0..num_sizes)
+        .map_while(|size_index| {
+            // `ppemY` lives at byte 45 of each `BitmapSize` record.
+            read_u8(eblc, 8 + size_index * BITMAP_SIZE_RECORD_LENGTH + 45).map(u16::from)
+        })
+        .collect()
+}
+
+/// Returns true if a strike in a `CBLC` or `EBLC` table has an index subtable covering
+/// `glyph_id`.
+pub(crate) fn bitmap_location_has_glyph(cblc: &[u8], glyph_id: u32) -> bool {
+    let num_sizes = read_u32(cblc, 4).unwrap_or(0) as usize;
+    (0..num_sizes)
+        .map_while(|size_index| {
+            let record = 8 + size_index * BITMAP_SIZE_RECORD_LENGTH;
+            let array_offset = read_u32(cblc, record)? as usize;
+            let num_subtables = read_u32(cblc, record + 8)? as usize;
+            for subtable_index in 0..num_subtables {
+                // Each `IndexSubTableArray` entry starts with its first and last glyph IDs.
+                let entry = array_offset + subtable_index * 8;
+                let glyph_ids = read_u16(cblc, entry)? as u32..=read_u16(cblc, entry + 2)? as u32;
+                if glyph_ids.contains(&glyph_id) {
+                    return Some(true);
+                }
+            }
+            Some(false)
+        })
+        .any(|covers_glyph| covers_glyph)
+}
+
+/// Returns true if a strike in an `sbix` table has image data for `glyph_id`.
+pub(crate) fn sbix_has_glyph(sbix: &[u8], glyph_id: u32) -> bool {
+    let num_strikes = read_u32(sbix, 4).unwrap_or(0) as usize;
+    (0..num_strikes)
+        .map_while(|strike_index| {
+            let strike_offset = read_u32(sbix, 8 + strike_index * 4)? as usize;
+            // The glyph data offsets follow the strike's `ppem` and `ppi`.
+            let data_offset = strike_offset + 4 + glyph_id as usize * 4;
+            Some(read_u32(sbix, data_offset + 4)? > read_u32(sbix, data_offset)?)
+        })
+        .any(|has_data| has_data)
+}
+
+/// Returns the pixels-per-em of each strike in an `sbix` table.
+pub(crate) fn sbix_strike_sizes(sbix: &[u8]) -> Vec<u16> {
+    let num_strikes = read_u32(sbix, 4).unwrap_or(0) as usize;
+    (0..num_strikes)
+        .map_while(|strike_index| {
+            let strike_offset = read_u32(sbix, 8 + strike_index * 4)? as usize;
+            read_u16(sbix, strike_offset)
+        })
+        .collect()
+}
+
+/// Returns the number of glyphs in the font, from the `maxp` table.
+pub(crate) fn maxp_num_glyphs(maxp: &[u8]) -> Option<u16> {
+    read_u16(maxp, MAXP_NUM_GLYPHS_OFFSET)
+}
+
+/// Returns the offset of the best Unicode subtable in a `cmap` table, preferring subtables that
+/// cover the full Unicode range (format 12) over BMP-only ones (format 4).
+pub(crate) fn cmap_unicode_subtable_offset(cmap: &[u8]) -> Option<usize> {
+    let num_tables = read_u16(cmap, 2)? as usize;
+    let mut best: Option<(u16, usize)> = None;
+    for table_index in 0..num_tables {
+        let record_offset = 4 + table_index * 8;
+        let platform_id = read_u16(cmap, record_offset)?;
+        let encoding_id = read_u16(cmap, record_offset + 2)?;
+        let subtable_offset = read_u32(cmap, record_offset + 4)? as usize;
+        let is_unicode = match platform_id {
+            0 => true,
+            3 => encoding_id == 1 || encoding_id == 10,
+            _ => false,
+        };
+        if !is_unicode {
+            continue;
+        }
+        let format = match read_u16(cmap, subtable_offset)? {
+            format @ (4 | 12) => format,
+            _ => continue,
+        };
+        if best.map_or(true, |(best_format, _)| format > best_format) {
+            best = Some((format, subtable_offset));
+        }
+    }
+    best.map(|(_, subtable_offset)| subtable_offset)
+}
+
+/// Returns every character-to-glyph mapping in the best Unicode subtable of a `cmap` table,
+/// sorted by character.
+pub(crate) fn cmap_unicode_mappings(cmap: &[u8]) -> Vec<(u32, u32)> {
+    let subtable_offset = match cmap_unicode_subtable_offset(cmap) {
+        Some(subtable_offset) => subtable_offset,
+        None => return vec![],
+    };
+    let subtable = &cmap[subtable_offset..];
+    let mut mappings = match read_u16(subtable, 0) {
+        Some(4) => cmap_format_4_mappings(subtable),
+        Some(12) => cmap_format_12_mappings(subtable),
+        _ => None,
+    }
+    .unwrap_or_default();
+    mappings.retain(|&(_, glyph_id)| glyph_id != 0);
+    mappings.sort_unstable();
+    mappings.dedup_by_key(|&mut (character, _)| character);
+    mappings
+}
+
+fn cmap_format_4_mappings(subtable: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let seg_count = read_u16(subtable, 6)? as usize / 2;
+    let end_codes_offset = 14;
+    let start_codes_offset = end_codes_offset + seg_count * 2 + 2;
+    let id_deltas_offset = start_codes_offset + seg_count * 2;
+    let id_range_offsets_offset = id_deltas_offset + seg_count * 2;
+
+    let mut mappings = vec![];
+    for segment_index in 0..seg_count {
+        let end_code = read_u16(subtable, end_codes_offset + segment_index * 2)?;
+        let start_code = read_u16(subtable, start_codes_offset + segment_index * 2)?;
+        let id_delta = read_u16(subtable, id_deltas_offset + segment_index * 2)?;
+        let id_range_offset_offset = id_range_offsets_offset + segment_index * 2;
+        let id_range_offset = read_u16(subtable, id_range_offset_offset)? as usize;
+        if start_code > end_code {
+            continue;
+        }
+        for character in start_code..=end_code {
+            if character == 0xffff {
+                break;
+            }
+            let glyph_id = if id_range_offset == 0 {
+                character.wrapping_add(id_delta)
+            } else {
+                let glyph_id_offset = id_range_offset_offset
+                    + id_range_offset
+                    + (character - start_code) as usize * 2;
+                match read_u16(subtable, glyph_id_offset) {
+                    Some(0) | None => 0,
+                    Some(glyph_id) => glyph_id.wrapping_add(id_delta),
+                }
+            };
+            mappings.push((character as u32, glyph_id as u32));
+        }
+    }
+    Some(mappings)
+}
+
+/// Looks up a character in every Unicode format 12 subtable of a `cmap` table, whichever encoding
+/// record it's listed under, and returns the first nonzero glyph ID found.
+///
+/// Platform rasterizers pick a single subtable to map characters with, and some pick a BMP-only
+/// format 4 subtable even when the font also has a format 12 one. This lets characters beyond
+/// U+FFFF be resolved regardless.
+#[inline]
+pub(crate) fn cmap_format_12_glyph_id(cmap: &[u8], character: u32) -> Option<u32> {
+    cmap_grouped_glyph_id(cmap, 12, character)
+}
+
+/// Looks up a character in every Unicode format 13 subtable of a `cmap` table and returns the
+/// first nonzero glyph ID found.
+///
+/// Format 13 maps whole ranges of characters to a single glyph. Last-resort fonts use it to draw
+/// one placeholder glyph for each Unicode block, and platform rasterizers often ignore it.
+#[inline]
+pub(crate) fn cmap_format_13_glyph_id(cmap: &[u8], character: u32) -> Option<u32> {
+    cmap_grouped_glyph_id(cmap, 13, character)
+}
+
+/// Looks up a character in the Mac Roman (platform 1, encoding 0) subtable of a `cmap` table,
+/// after encoding it as Mac Roman, and returns the glyph ID if it's nonzero.
+///
+/// Legacy Macintosh fonts may have only this subtable. Formats 0, 4, and 6 are supported.
+pub(crate) fn cmap_mac_roman_glyph_id(cmap: &[u8], character: char) -> Option<u32> {
+    let byte = mac_roman_byte(character)?;
+    let num_tables = read_u16(cmap, 2)? as usize;
+    let subtable_offset = (0..num_tables)
+        .map(|table_index| 4 + table_index * 8)
+        .find(|&record_offset| {
+            read_u16(cmap, record_offset) == Some(1) && read_u16(cmap, record_offset + 2) == Some(0)
+        })
+        .and_then(|record_offset| read_u32(cmap, record_offset + 4))?;
+    let subtable = cmap.get(subtable_offset as usize..)?;
+    let glyph_id = match read_u16(subtable, 0)? {
+        0 => read_u8(subtable, 6 + byte as usize)? as u32,
+        4 => {
+            cmap_format_4_mappings(subtable)?
+                .into_iter()
+                .find(|&(mapped_character, _)| mapped_character == byte as u32)?
+                .1
+        }
+        6 => {
+            let first_code = read_u16(subtable, 6)?;
+            let index = (byte as u16).checked_sub(first_code)?;
+            if index >= read_u16(subtable, 8)? {
+                return None;
+            }
+            read_u16(subtable, 10 + index as usize * 2)? as u32
+        }
+        _ => return None,
+    };
+    Some(glyph_id).filter(|&glyph_id| glyph_id != 0)
+}
+
+// Formats 12 and 13 share a layout: sorted groups of a start character, an end character, and a
+// glyph ID. In format 12 the glyph ID belongs to the start character and rises with the
+// character; in format 13 every character in the group maps to it.
+fn cmap_grouped_glyph_id(cmap: &[u8], format: u16, character: u32) -> Option<u32> {
+    let num_tables = read_u16(cmap, 2)? as usize;
+    for table_index in 0..num_tables {
+        let record_offset = 4 + table_index * 8;
+        let platform_id = read_u16(cmap, record_offset)?;
+        let encoding_id = read_u16(cmap, record_offset + 2)?;
+        let subtable_offset = read_u32(cmap, record_offset + 4)? as usize;
+        let is_unicode = match platform_id {
+            0 => true,
+            3 => encoding_id == 1 || encoding_id == 10,
+            _ => false,
+        };
+        if !is_unicode || read_u16(cmap, subtable_offset) != Some(format) {
+            continue;
+        }
+        let subtable = &cmap[subtable_offset..];
+        if let Some(glyph_id) = cmap_group_lookup(subtable, format == 13, character) {
+            return Some(glyph_id);
+        }
+    }
+    None
+}
+
+fn cmap_group_lookup(subtable: &[u8], many_to_one: bool, character: u32) -> Option<u32> {
+    // Groups are sorted by start character code, so binary search for the last group starting at
+    // or before the character.
+    let num_groups = read_u32(subtable, 12)? as usize;
+    let (mut low, mut high) = (0, num_groups);
+    while low < high {
+        let mid = (low + high) / 2;
+        let group_offset = 16 + mid * 12;
+        let start_char_code = read_u32(subtable, group_offset)?;
+        let end_char_code = read_u32(subtable, group_offset + 4)?;
+        if character < start_char_code {
+            high = mid;
+        } else if character > end_char_code {
+            low = mid + 1;
+        } else {
+            let glyph_id = read_u32(subtable, group_offset + 8)?;
+            let glyph_id = if many_to_one {
+                glyph_id
+            } else {
+                // Malformed groups can run past the largest glyph ID.
+                glyph_id.checked_add(character - start_char_code)?
+            };
+            return match glyph_id {
+                0 => None,
+                glyph_id => Some(glyph_id),
+            };
+        }
+    }
+    None
+}
+
+fn cmap_format_12_mappings(subtable: &[u8]) -> Option<Vec<(u32, u32)>> {
+    let num_groups = read_u32(subtable, 12)? as usize;
+    let mut mappings = vec![];
+    for group_index in 0..num_groups {
+        let group_offset = 16 + group_index * 12;
+        let start_char_code = read_u32(subtable, group_offset)?;
+        let end_char_code = read_u32(subtable, group_offset + 4)?;
+        let start_glyph_id = read_u32(subtable, group_offset + 8)?;
+        if start_char_code > end_char_code || end_char_code > 0x10ffff {
+            continue;
+        }
+        for character in start_char_code..=end_char_code {
+            // Malformed groups can run past the largest glyph ID.
+            match start_glyph_id.checked_add(character - start_char_code) {
+                Some(glyph_id) => mappings.push((character, glyph_id)),
+                None => break,
+            }
+        }
+    }
+    Some(mappings)
+}
+
+/// Returns the ten PANOSE classification digits from an `OS/2` table.
+pub(crate) fn os2_panose(os2: &[u8]) -> Option<[u8; 10]> {
+    os2.get(OS2_PANOSE_OFFSET..OS2_PANOSE_OFFSET + 10)?
+        .try_into()
+        .ok()
+}
+
+/// Returns the `fsType` embedding permissions of an `OS/2` table.
+#[inline]
+pub(crate) fn os2_fs_type(os2: &[u8]) -> Option<u16> {
+    read_u16(os2, OS2_FS_TYPE_OFFSET)
+}
+
+/// Returns the italic angle of a `post` table, in degrees counterclockwise from the vertical.
+pub(crate) fn post_italic_angle(post: &[u8]) -> Option<f32> {
+    read_i32(post, POST_ITALIC_ANGLE_OFFSET).map(fixed_to_f32)
+}
+
+/// Returns the number of signature records in a `DSIG` table.
+///
+/// Only version 1 tables are understood.
+pub(crate) fn dsig_num_signatures(dsig: &[u8]) -> Option<u16> {
+    if read_u32(dsig, 0)? != 1 {
+        return None;
+    }
+    read_u16(dsig, DSIG_NUM_SIGNATURES_OFFSET)
+}
+
+/// Returns the two `ulCodePageRange` fields of an `OS/2` table combined into one 64-bit value,
+/// the first in the low bits.
+///
+/// These fields are only present from version 1 of the table on.
+pub(crate) fn os2_code_page_ranges(os2: &[u8]) -> Option<u64> {
+    if read_u16(os2, 0)? < 1 {
+        return None;
+    }
+    let range_1 = read_u32(os2, OS2_CODE_PAGE_RANGE_1_OFFSET)?;
+    let range_2 = read_u32(os2, OS2_CODE_PAGE_RANGE_2_OFFSET)?;
+    Some((range_2 as u64) << 32 | range_1 as u64)
+}
+
+/// Returns the typographic ascender, descender, and line gap from an `OS/2` table, if the table
+/// sets `USE_TYPO_METRICS` to ask that they take precedence over the `hhea` values.
+pub(crate) fn os2_use_typo_metrics(os2: &[u8]) -> Option<(i16, i16, i16)> {
+    if read_u16(os2, OS2_FS_SELECTION_OFFSET)? & OS2_FS_SELECTION_USE_TYPO_METRICS == 0 {
+        return None;
+    }
+    Some((
+        read_i16(os2, OS2_TYPO_ASCENDER_OFFSET)?,
+        read_i16(os2, OS2_TYPO_DESCENDER_OFFSET)?,
+        read_i16(os2, OS2_TYPO_LINE_GAP_OFFSET)?,
+    ))
+}
+
+/// Returns the name of every glyph from a `post` table, indexed by glyph ID.
+///
+/// Version 1.0 tables name glyphs with the standard Macintosh glyph names, and version 2.0 tables
+/// mix those with names of their own. Other versions, including 3.0, which deliberately omits
+/// names, yield `None` for every glyph.
+pub(crate) fn post_glyph_names(post: &[u8], num_glyphs: u16) -> Vec<Option<String>> {
+    let mut names = vec![None; num_glyphs as usize];
+    match read_u32(post, 0) {
+        Some(0x00010000) => {
+            for (name, &mac_name) in names.iter_mut().zip(MAC_GLYPH_NAMES.iter()) {
+                *name = Some(mac_name.to_owned());
+            }
+        }
+        Some(0x00020000) => {
+            let name_indices = match post_glyph_name_indices(post) {
+                Some(name_indices) => name_indices,
+                None => return names,
+            };
+            let custom_names = post_custom_glyph_names(
+                post,
+                POST_GLYPH_NAME_INDEX_OFFSET + name_indices.len() * 2,
+            );
+            for (name, &name_index) in names.iter_mut().zip(name_indices.iter()) {
+                let name_index = name_index as usize;
+                *name = match MAC_GLYPH_NAMES.get(name_index) {
+                    Some(&mac_name) => Some(mac_name.to_owned()),
+                    None => custom_names
+                        .get(name_index - MAC_GLYPH_NAMES.len())
+                        .cloned(),
+                };
+            }
+        }
+        _ => {}
+    }
+    names
+}
+
+fn post_glyph_name_indices(post: &[u8]) -> Option<Vec<u16>> {
+    let num_glyphs = read_u16(post, POST_NUM_GLYPHS_OFFSET)? as usize;
+    (0..num_glyphs)
+        .map(|glyph_id| read_u16(post, POST_GLYPH_NAME_INDEX_OFFSET + glyph_id * 2))
+        .collect()
+}
+
+// Reads the Pascal strings that follow the glyph name indices, stopping at the end of the table.
+fn post_custom_glyph_names(post: &[u8], mut offset: usize) -> Vec<String> {
+    let mut custom_names = vec![];
+    while let Some(length) = read_u8(post, offset) {
+        let name = match post.get(offset + 1..offset + 1 + length as usize) {
+            Some(name) => name,
+            None => break,
+        };
+        custom_names.push(String::from_utf8_lossy(name).into_owned());
+        offset += 1 + length as usize;
+    }
+    custom_names
+}
+
+/// Returns the string with `name_id` from a `name` table.
+///
+/// English names in Unicode are preferred, as FreeType does. Names in encodings other than
+/// Unicode and Mac Roman are ignored.
+pub(crate) fn name_string(name: &[u8], name_id: u16) -> Option<String> {
+    let count = read_u16(name, NAME_COUNT_OFFSET)? as usize;
+    let storage = read_u16(name, NAME_STORAGE_OFFSET_OFFSET)? as usize;
+    let mut best: Option<(u8, usize)> = None;
+    for index in 0..count {
+        let record = NAME_RECORDS_OFFSET + index * NAME_RECORD_LENGTH;
+        if read_u16(name, record + 6) != Some(name_id) || read_u16(name, record + 8) == Some(0) {
+            continue;
+        }
+        let platform_id = read_u16(name, record)?;
+        let encoding_id = read_u16(name, record + 2)?;
+        let language_id = read_u16(name, record + 4)?;
+        // Lower is better.
+        let rank = match (platform_id, encoding_id) {
+            (3, 0) | (3, 1) | (3, 10) if language_id & 0x3ff == 0x009 => 0,
+            (3, 0) | (3, 1) | (3, 10) => 1,
+            (0, _) => 2,
+            (1, 0) if language_id == 0 => 3,
+            (1, 0) => 4,
+            _ => continue,
+        };
+        if best.map_or(true, |(best_rank, _)| rank < best_rank) {
+            best = Some((rank, record));
+        }
+    }
+
+    let (rank, record) = best?;
+    let length = read_u16(name, record + 8)? as usize;
+    let start = storage + read_u16(name, record + 10)? as usize;
+    let bytes = name.get(start..start + length)?;
+    if rank >= 3 {
+        return Some(bytes.iter().map(|&byte| mac_roman_char(byte)).collect());
+    }
+    let units: Vec<u16> = bytes
+        .chunks_exact(2)
+        .map(|unit| u16::from_be_bytes([unit[0], unit[1]]))
+        .collect();
+    Some(String::from_utf16_lossy(&units))
+}
+
+#[inline]
+fn mac_roman_char(byte: u8) -> char {
+    match byte {
+        0x00..=0x7f => byte as char,
+        _ => MAC_ROMAN_HIGH_CHARS[(byte - 0x80) as usize],
+    }
+}
+
+fn mac_roman_byte(character: char) -> Option<u8> {
+    match character {
+        '\0'..='\x7f' => Some(character as u8),
+        _ => MAC_ROMAN_HIGH_CHARS
+            .iter()
+            .position(|&high_char| high_char == character)
+            .map(|index| 0x80 + index as u8),
+    }
+}
+
+// The characters that bytes 0x80 to 0xff stand for in the Mac Roman encoding.
+static MAC_ROMAN_HIGH_CHARS: [char; 128] = [
+    '\u{00c4}', '\u{00c5}', '\u{00c7}', '\u{00c9}', '\u{00d1}', '\u{00d6}', '\u{00dc}', '\u{00e1}',
+    '\u{00e0}', '\u{00e2}', '\u{00e4}', '\u{00e3}', '\u{00e5}', '\u{00e7}', '\u{00e9}', '\u{00e8}',
+    '\u{00ea}', '\u{00eb}', '\u{00ed}', '\u{00ec}', '\u{00ee}', '\u{00ef}', '\u{00f1}', '\u{00f3}',
+    '\u{00f2}', '\u{00f4}', '\u{00f6}', '\u{00f5}', '\u{00fa}', '\u{00f9}', '\u{00fb}', '\u{00fc}',
+    '\u{2020}', '\u{00b0}', '\u{00a2}', '\u{00a3}', '\u{00a7}', '\u{2022}', '\u{00b6}', '\u{00df}',
+    '\u{00ae}', '\u{00a9}', '\u{2122}', '\u{00b4}', '\u{00a8}', '\u{2260}', '\u{00c6}', '\u{00d8}',
+    '\u{221e}', '\u{00b1}', '\u{2264}', '\u{2265}', '\u{00a5}', '\u{00b5}', '\u{2202}', '\u{2211}',
+    '\u{220f}', '\u{03c0}', '\u{222b}', '\u{00aa}', '\u{00ba}', '\u{03a9}', '\u{00e6}', '\u{00f8}',
+    '\u{00bf}', '\u{00a1}', '\u{00ac}', '\u{221a}', '\u{0192}', '\u{2248}', '\u{2206}', '\u{00ab}',
+    '\u{00bb}', '\u{2026}', '\u{00a0}', '\u{00c0}', '\u{00c3}', '\u{00d5}', '\u{0152}', '\u{0153}',
+    '\u{2013}', '\u{2014}', '\u{201c}', '\u{201d}', '\u{2018}', '\u{2019}', '\u{00f7}', '\u{25ca}',
+    '\u{00ff}', '\u{0178}', '\u{2044}', '\u{20ac}', '\u{2039}', '\u{203a}', '\u{fb01}', '\u{fb02}',
+    '\u{2021}', '\u{00b7}', '\u{201a}', '\u{201e}', '\u{2030}', '\u{00c2}', '\u{00ca}', '\u{00c1}',
+    '\u{00cb}', '\u{00c8}', '\u{00cd}', '\u{00ce}', '\u{00cf}', '\u{00cc}', '\u{00d3}', '\u{00d4}',
+    '\u{f8ff}', '\u{00d2}', '\u{00da}', '\u{00db}', '\u{00d9}', '\u{0131}', '\u{02c6}', '\u{02dc}',
+    '\u{00af}', '\u{02d8}', '\u{02d9}', '\u{02da}', '\u{00b8}', '\u{02dd}', '\u{02db}', '\u{02c7}',
+];
+
+/// Returns the preferred family name of a font from its `name` table, as FreeType chooses it.
+///
+/// Fonts that don't set the `WWS` bit in `OS/2` may group faces that differ in more than weight,
+/// width, and slope under one typographic family, so their `WWS` family name comes first.
+pub(crate) fn name_family_name(name: &[u8], os2: Option<&[u8]>) -> Option<String> {
+    let fs_selection = os2.and_then(|os2| read_u16(os2, OS2_FS_SELECTION_OFFSET));
+    let name_ids: &[u16] = match fs_selection {
+        Some(fs_selection) if fs_selection & OS2_FS_SELECTION_WWS != 0 => {
+            &[NAME_ID_TYPOGRAPHIC_FAMILY, NAME_ID_FAMILY]
+        }
+        _ => &[
+            NAME_ID_WWS_FAMILY,
+            NAME_ID_TYPOGRAPHIC_FAMILY,
+            NAME_ID_FAMILY,
+        ],
+    };
+    name_ids
+        .iter()
+        .find_map(|&name_id| name_string(name, name_id))
+}
+
+/// Returns the style name of a font within its family from its `name` table, such as
+/// `"Semibold Italic"`, chosen to pair with the family name that `name_family_name()` returns.
+pub(crate) fn name_style_name(name: &[u8], os2: Option<&[u8]>) -> Option<String> {
+    let fs_selection = os2.and_then(|os2| read_u16(os2, OS2_FS_SELECTION_OFFSET));
+    let name_ids: &[u16] = match fs_selection {
+        Some(fs_selection) if fs_selection & OS2_FS_SELECTION_WWS != 0 => {
+            &[NAME_ID_TYPOGRAPHIC_SUBFAMILY, NAME_ID_SUBFAMILY]
+        }
+        _ => &[
+            NAME_ID_WWS_SUBFAMILY,
+            NAME_ID_TYPOGRAPHIC_SUBFAMILY,
+            NAME_ID_SUBFAMILY,
+        ],
+    };
+    name_ids
+        .iter()
+        .find_map(|&name_id| name_string(name, name_id))
+}
+
+/// Returns the style, weight, and width of a font from its `OS/2` table, or from its `head`
+/// table if it has no `OS/2` table, by the same rules as the FreeType loader.
+pub(crate) fn sfnt_properties(os2: Option<&[u8]>, head: Option<&[u8]>) -> Properties {
+    let fs_selection = os2.and_then(|os2| read_u16(os2, OS2_FS_SELECTION_OFFSET));
+    let style = match fs_selection {
+        Some(fs_selection) if fs_selection & OS2_FS_SELECTION_OBLIQUE != 0 => Style::Oblique,
+        Some(fs_selection) if fs_selection & OS2_FS_SELECTION_ITALIC != 0 => Style::Italic,
+        Some(_) => Style::Normal,
+        None => match head.and_then(|head| read_u16(head, HEAD_MAC_STYLE_OFFSET)) {
+            Some(mac_style) if mac_style & HEAD_MAC_STYLE_ITALIC != 0 => Style::Italic,
+            _ => Style::Normal,
+        },
+    };
+    let weight = match os2.and_then(|os2| read_u16(os2, OS2_WEIGHT_CLASS_OFFSET)) {
+        Some(weight_class) => Weight(weight_class as f32),
+        None => Weight::NORMAL,
+    };
+    let stretch = match os2.and_then(|os2| read_u16(os2, OS2_WIDTH_CLASS_OFFSET)) {
+        Some(width_class @ 1..=9) => Stretch(Stretch::MAPPING[width_class as usize - 1]),
+        _ => Stretch::NORMAL,
+    };
+    Properties {
+        style,
+        weight,
+        stretch,
+    }
+}
+
+/// Returns true if an `fvar` table defines any variation axes.
+#[inline]
+pub(crate) fn fvar_has_axes(fvar: &[u8]) -> bool {
+    matches!(read_u16(fvar, FVAR_AXIS_COUNT_OFFSET), Some(axis_count) if axis_count > 0)
+}
+
+// The 258 standard Macintosh glyph names, in the order that `post` table versions 1.0 and 2.0 refer
+// to them.
+static MAC_GLYPH_NAMES: [&str; 258] = [
+    ".notdef",
+    ".null",
+    "nonmarkingreturn",
+    "space",
+    "exclam",
+    "quotedbl",
+    "numbersign",
+    "dollar",
+    "percent",
+    "ampersand",
+    "quotesingle",
+    "parenleft",
+    "parenright",
+    "asterisk",
+    "plus",
+    "comma",
+    "hyphen",
+    "period",
+    "slash",
+    "zero",
+    "one",
+    "two",
+    "three",
+    "four",
+    "five",
+    "six",
+    "seven",
+    "eight",
+    "nine",
+    "colon",
+    "semicolon",
+    "less",
+    "equal",
+    "greater",
+    "question",
+    "at",
+    "A",
+    "B",
+    "C",
+    "D",
+    "E",
+    "F",
+    "G",
+    "H",
+    "I",
+    "J",
+    "K",
+    "L",
+    "M",
+    "N",
+    "O",
+    "P",
+    "Q",
+    "R",
+    "S",
+    "T",
+    "U",
+    "V",
+    "W",
+    "X",
+    "Y",
+    "Z",
+    "bracketleft",
+    "backslash",
+    "bracketright",
+    "asciicircum",
+    "underscore",
+    "grave",
+    "a",
+    "b",
+    "c",
+    "d",
+    "e",
+    "f",
+    "g",
+    "h",
+    "i",
+    "j",
+    "k",
+    "l",
+    "m",
+    "n",
+    "o",
+    "p",
+    "q",
+    "r",
+    "s",
+    "t",
+    "u",
+    "v",
+    "w",
+    "x",
+    "y",
+    "z",
+    "braceleft",
+    "bar",
+    "braceright",
+    "asciitilde",
+    "Adieresis",
+    "Aring",
+    "Ccedilla",
+    "Eacute",
+    "Ntilde",
+    "Odieresis",
+    "Udieresis",
+    "aacute",
+    "agrave",
+    "acircumflex",
+    "adieresis",
+    "atilde",
+    "aring",
+    "ccedilla",
+    "eacute",
+    "egrave",
+    "ecircumflex",
+    "edieresis",
+    "iacute",
+    "igrave",
+    "icircumflex",
+    "idieresis",
+    "ntilde",
+    "oacute",
+    "ograve",
+    "ocircumflex",
+    "odieresis",
+    "otilde",
+    "uacute",
+    "ugrave",
+    "ucircumflex",
+    "udieresis",
+    "dagger",
+    "degree",
+    "cent",
+    "sterling",
+    "section",
+    "bullet",
+    "paragraph",
+    "germandbls",
+    "registered",
+    "copyright",
+    "trademark",
+    "acute",
+    "dieresis",
+    "notequal",
+    "AE",
+    "Oslash",
+    "infinity",
+    "plusminus",
+    "lessequal",
+    "greaterequal",
+    "yen",
+    "mu",
+    "partialdiff",
+    "summation",
+    "product",
+    "pi",
+    "integral",
+    "ordfeminine",
+    "ordmasculine",
+    "Omega",
+    "ae",
+    "oslash",
+    "questiondown",
+    "exclamdown",
+    "logicalnot",
+    "radical",
+    "florin",
+    "approxequal",
+    "Delta",
+    "guillemotleft",
+    "guillemotright",
+    "ellipsis",
+    "nonbreakingspace",
+    "Agrave",
+    "Atilde",
+    "Otilde",
+    "OE",
+    "oe",
+    "endash",
+    "emdash",
+    "quotedblleft",
+    "quotedblright",
+    "quoteleft",
+    "quoteright",
+    "divide",
+    "lozenge",
+    "ydieresis",
+    "Ydieresis",
+    "fraction",
+    "currency",
+    "guilsinglleft",
+    "guilsinglright",
+    "fi",
+    "fl",
+    "daggerdbl",
+    "periodcentered",
+    "quotesinglbase",
+    "quotedblbase",
+    "perthousand",
+    "Acircumflex",
+    "Ecircumflex",
+    "Aacute",
+    "Edieresis",
+    "Egrave",
+    "Iacute",
+    "Icircumflex",
+    "Idieresis",
+    "Igrave",
+    "Oacute",
+    "Ocircumflex",
+    "apple",
+    "Ograve",
+    "Uacute",
+    "Ucircumflex",
+    "Ugrave",
+    "dotlessi",
+    "circumflex",
+    "tilde",
+    "macron",
+    "breve",
+    "dotaccent",
+    "ring",
+    "cedilla",
+    "hungarumlaut",
+    "ogonek",
+    "caron",
+    "Lslash",
+    "lslash",
+    "Scaron",
+    "scaron",
+    "Zcaron",
+    "zcaron",
+    "brokenbar",
+    "Eth",
+    "eth",
+    "Yacute",
+    "yacute",
+    "Thorn",
+    "thorn",
+    "minus",
+    "multiply",
+    "onesuperior",
+    "twosuperior",
+    "threesuperior",
+    "onehalf",
+    "onequarter",
+    "threequarters",
+    "franc",
+    "Gbreve",
+    "gbreve",
+    "Idotaccent",
+    "Scedilla",
+    "scedilla",
+    "Cacute",
+    "cacute",
+    "Ccaron",
+    "ccaron",
+    "dcroat",
+];
+
+/// Returns the horizontal kerning adjustment between two glyphs, in font units, from the pair
+/// lists of an OpenType `kern` table.
+///
+/// Only format 0 subtables that kern horizontally are read; adjustments from several subtables
+/// are added together. Apple's version 1 `kern` tables yield 0.
+pub(crate) fn kern_pair_adjustment(kern: &[u8], left_glyph_id: u32, right_glyph_id: u32) -> i32 {
+    // Version 0 tables start with a 16-bit version number of 0.
+    if read_u16(kern, 0) != Some(0) {
+        return 0;
+    }
+    let num_tables = read_u16(kern, KERN_NUM_TABLES_OFFSET).unwrap_or(0);
+    let pair = (left_glyph_id << 16) | (right_glyph_id & 0xffff);
+
+    let mut adjustment = 0;
+    let mut subtable = KERN_SUBTABLES_OFFSET;
+    for _ in 0..num_tables {
+        let (length, coverage) = match (
+            read_u16(kern, subtable + KERN_SUBTABLE_LENGTH_OFFSET),
+            read_u16(kern, subtable + KERN_SUBTABLE_COVERAGE_OFFSET),
+        ) {
+            (Some(length), Some(coverage)) => (length as usize, coverage),
+            _ => break,
+        };
+        let flags = coverage
+            & (KERN_COVERAGE_HORIZONTAL | KERN_COVERAGE_MINIMUM | KERN_COVERAGE_CROSS_STREAM);
+        if coverage & KERN_COVERAGE_FORMAT_MASK == 0 && flags == KERN_COVERAGE_HORIZONTAL {
+            adjustment += kern_format_0_lookup(kern, subtable, pair).unwrap_or(0) as i32;
+        }
+        if length == 0 {
+            break;
+        }
+        subtable += length;
+    }
+    adjustment
+}
+
+// Binary-searches the pairs of a format 0 subtable, which are sorted by the left and right glyph
+// IDs combined into one 32-bit key.
+fn kern_format_0_lookup(kern: &[u8], subtable: usize, pair: u32) -> Option<i16> {
+    let num_pairs = read_u16(kern, subtable + KERN_SUBTABLE_NUM_PAIRS_OFFSET)? as usize;
+    let pairs = subtable + KERN_SUBTABLE_PAIRS_OFFSET;
+    let (mut low, mut high) = (0, num_pairs);
+    while low < high {
+        let mid = (low + high) / 2;
+        let record = pairs + mid * KERN_PAIR_LENGTH;
+        match read_u32(kern, record)?.cmp(&pair) {
+            Ordering::Less => low = mid + 1,
+            Ordering::Greater => high = mid,
+            Ordering::Equal => return read_i16(kern, record + 4),
+        }
+    }
+    None
+}
+
+/// Returns the glyph that a `GSUB` ligature substitution produces for a sequence of component
+/// glyphs, looking through the lookups of each feature in `feature_tags` in turn.
+///
+/// Ligature lookups that the features only apply through contextual lookups are searched too,
+/// right after the contextual lookup, but the context itself isn't checked. Every script and
+/// language system is searched, and lookup flags are ignored.
+pub(crate) fn gsub_ligature_glyph(
+    gsub: &[u8],
+    feature_tags: &[u32],
+    components: &[u32],
+) -> Option<u32> {
+    let components: Vec<u16> = components
+        .iter()
+        .map(|&glyph_id| u16::try_from(glyph_id).ok())
+        .collect::<Option<_>>()?;
+    let (&first_component, other_components) = components.split_first()?;
+
+    let feature_list = read_u16(gsub, GSUB_FEATURE_LIST_OFFSET_OFFSET)? as usize;
+    let lookup_list = read_u16(gsub, GSUB_LOOKUP_LIST_OFFSET_OFFSET)? as usize;
+    let feature_count = read_u16(gsub, feature_list)? as usize;
+    let mut lookup_indices = vec![];
+    for &feature_tag in feature_tags {
+        for feature_index in 0..feature_count {
+            let record = feature_list + 2 + feature_index * GSUB_FEATURE_RECORD_LENGTH;
+            if read_u32(gsub, record)? != feature_tag {
+                continue;
+            }
+            let feature = feature_list + read_u16(gsub, record + 4)? as usize;
+            let lookup_index_count = read_u16(gsub, feature + 2)? as usize;
+            for lookup_index_index in 0..lookup_index_count {
+                let lookup_index = read_u16(gsub, feature + 4 + lookup_index_index * 2)?;
+                lookup_indices.push(lookup_index);
+                for (subtable_type, subtable) in
+                    gsub_lookup_subtables(gsub, lookup_list, lookup_index).unwrap_or_default()
+                {
+                    lookup_indices.extend(
+                        gsub_nested_lookup_indices(gsub, subtable_type, subtable)
+                            .unwrap_or_default(),
+                    );
+                }
+            }
+        }
+    }
+
+    lookup_indices.into_iter().find_map(|lookup_index| {
+        gsub_lookup_subtables(gsub, lookup_list, lookup_index)?
+            .into_iter()
+            .filter(|&(subtable_type, _)| subtable_type == GSUB_LOOKUP_TYPE_LIGATURE)
+            .find_map(|(_, subtable)| {
+                gsub_ligature_subtable_glyph(gsub, subtable, first_component, other_components)
+            })
+    })
+}
+
+// Returns the type and offset of each subtable of a `GSUB` lookup, looking through extension
+// subtables to the subtables they wrap.
+fn gsub_lookup_subtables(
+    gsub: &[u8],
+    lookup_list: usize,
+    lookup_index: u16,
+) -> Option<Vec<(u16, usize)>> {
+    if lookup_index >= read_u16(gsub, lookup_list)? {
+        return None;
+    }
+    let lookup =
+        lookup_list + read_u16(gsub, lookup_list + 2 + lookup_index as usize * 2)? as usize;
+    let lookup_type = read_u16(gsub, lookup)?;
+    let subtable_count = read_u16(gsub, lookup + 4)? as usize;
+    let mut subtables = vec![];
+    for subtable_index in 0..subtable_count {
+        let subtable = lookup + read_u16(gsub, lookup + 6 + subtable_index * 2)? as usize;
+        if lookup_type == GSUB_LOOKUP_TYPE_EXTENSION {
+            let extension_type = read_u16(gsub, subtable + 2)?;
+            subtables.push((
+                extension_type,
+                subtable + read_u32(gsub, subtable + 4)? as usize,
+            ));
+        } else {
+            subtables.push((lookup_type, subtable));
+        }
+    }
+    Some(subtables)
+}
+
+// Returns the indices of the lookups that a contextual or chained contextual substitution
+// subtable applies. Other subtables apply none.
+fn gsub_nested_lookup_indices(
+    gsub: &[u8],
+    subtable_type: u16,
+    subtable: usize,
+) -> Option<Vec<u16>> {
+    // Each entry is the offset of an array of substitution lookup records and its length.
+    let mut record_arrays = vec![];
+    let format = read_u16(gsub, subtable)?;
+    match (subtable_type, format) {
+        (GSUB_LOOKUP_TYPE_CONTEXT, 1) | (GSUB_LOOKUP_TYPE_CONTEXT, 2) => {
+            for rule in gsub_context_rules(gsub, subtable, if format == 1 { 4 } else { 6 })? {
+                let glyph_count = read_u16(gsub, rule)? as usize;
+                let subst_count = read_u16(gsub, rule + 2)?;
+                record_arrays.push((rule + 4 + glyph_count.saturating_sub(1) * 2, subst_count));
+            }
+        }
+        (GSUB_LOOKUP_TYPE_CONTEXT, 3) => {
+            let glyph_count = read_u16(gsub, subtable + 2)? as usize;
+            let subst_count = read_u16(gsub, subtable + 4)?;
+            record_arrays.push((subtable + 6 + glyph_count * 2, subst_count));
+        }
+        (GSUB_LOOKUP_TYPE_CHAINED_CONTEXT, 1) | (GSUB_LOOKUP_TYPE_CHAINED_CONTEXT, 2) => {
+            for rule in gsub_context_rules(gsub, subtable, if format == 1 { 4 } else { 10 })? {
+                let mut offset = rule + 2 + read_u16(gsub, rule)? as usize * 2;
+                let input_count = read_u16(gsub, offset)? as usize;
+                offset += 2 + input_count.saturating_sub(1) * 2;
+                offset += 2 + read_u16(gsub, offset)? as usize * 2;
+                record_arrays.push((offset + 2, read_u16(gsub, offset)?));
+            }
+        }
+        (GSUB_LOOKUP_TYPE_CHAINED_CONTEXT, 3) => {
+            let mut offset = subtable + 2;
+            // Skip the backtrack, input, and lookahead coverage arrays.
+            for _ in 0..3 {
+                offset += 2 + read_u16(gsub, offset)? as usize * 2;
+            }
+            record_arrays.push((offset + 2, read_u16(gsub, offset)?));
+        }
+        _ => {}
+    }
+
+    let mut lookup_indices = vec![];
+    for (records, record_count) in record_arrays {
+        for record_index in 0..record_count as usize {
+            lookup_indices.push(read_u16(gsub, records + record_index * 4 + 2)?);
+        }
+    }
+    Some(lookup_indices)
+}
+
+// Returns the offsets of the rules in every rule set of a format 1 or 2 contextual subtable,
+// whose rule set count is at `set_count_offset`, with the offsets following it.
+fn gsub_context_rules(gsub: &[u8], subtable: usize, set_count_offset: usize) -> Option<Vec<usize>> {
+    let set_count = read_u16(gsub, subtable + set_count_offset)? as usize;
+    let mut rules = vec![];
+    for set_index in 0..set_count {
+        let set = match read_u16(gsub, subtable + set_count_offset + 2 + set_index * 2)? {
+            0 => continue,
+            set_offset => subtable + set_offset as usize,
+        };
+        let rule_count = read_u16(gsub, set)? as usize;
+        for rule_index in 0..rule_count {
+            rules.push(set + read_u16(gsub, set + 2 + rule_index * 2)? as usize);
+        }
+    }
+    Some(rules)
+}
+
+fn gsub_ligature_subtable_glyph(
+    gsub: &[u8],
+    subtable: usize,
+    first_component: u16,
+    other_components: &[u16],
+) -> Option<u32> {
+    if read_u16(gsub, subtable)? != 1 {
+        return None;
+    }
+    let coverage = subtable + read_u16(gsub, subtable + 2)? as usize;
+    let coverage_index = coverage_index(gsub, coverage, first_component)?;
+    if coverage_index >= read_u16(gsub, subtable + 4)? {
+        return None;
+    }
+    let ligature_set =
+        subtable + read_u16(gsub, subtable + 6 + coverage_index as usize * 2)? as usize;
+    let ligature_count = read_u16(gsub, ligature_set)? as usize;
+    for ligature_index in 0..ligature_count {
+        let ligature =
+            ligature_set + read_u16(gsub, ligature_set + 2 + ligature_index * 2)? as usize;
+        let component_count = read_u16(gsub, ligature + 2)? as usize;
+        if component_count != other_components.len() + 1 {
+            continue;
+        }
+        let matches = other_components
+            .iter()
+            .enumerate()
+            .all(|(index, &component)| read_u16(gsub, ligature + 4 + index * 2) == Some(component));
+        if matches {
+            return read_u16(gsub, ligature).map(u32::from);
+        }
+    }
+    None
+}
+
+// Returns the index of a glyph in an OpenType coverage table, if the table covers it.
+fn coverage_index(data: &[u8], coverage: usize, glyph_id: u16) -> Option<u16> {
+    match read_u16(data, coverage)? {
+        1 => {
+            let glyph_count = read_u16(data, coverage + 2)? as usize;
+            let (mut low, mut high) = (0, glyph_count);
+            while low < high {
+                let mid = (low + high) / 2;
+                match read_u16(data, coverage + 4 + mid * 2)?.cmp(&glyph_id) {
+                    Ordering::Less => low = mid + 1,
+                    Ordering::Greater => high = mid,
+                    Ordering::Equal => return Some(mid as u16),
+                }
+            }
+            None
+        }
+        2 => {
+            let range_count = read_u16(data, coverage + 2)? as usize;
+            let (mut low, mut high) = (0, range_count);
+            while low < high {
+                let mid = (low + high) / 2;
+                let range = coverage + 4 + mid * 6;
+                let (start, end) = (read_u16(data, range)?, read_u16(data, range + 2)?);
+                if glyph_id < start {
+                    high = mid;
+                } else if glyph_id > end {
+                    low = mid + 1;
+                } else {
+                    let start_coverage_index = read_u16(data, range + 4)?;
+                    return start_coverage_index.checked_add(glyph_id - start);
+                }
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+/// Returns the advance width of a glyph, in whole pixels, from the `hdmx` device record for
+/// `ppem` pixels per em.
+pub(crate) fn hdmx_device_advance(
+    hdmx: &[u8],
+    num_glyphs: u16,
+    glyph_id: u32,
+    ppem: u8,
+) -> Option<u8> {
+    if glyph_id >= num_glyphs as u32 {
+        return None;
+    }
+    let num_records = read_u16(hdmx, HDMX_NUM_RECORDS_OFFSET)? as usize;
+    let record_size = read_u32(hdmx, HDMX_SIZE_DEVICE_RECORD_OFFSET)? as usize;
+    // Each record starts with its pixel size and maximum width, followed by one width per glyph.
+    if record_size < HDMX_DEVICE_RECORD_WIDTHS_OFFSET + num_glyphs as usize {
+        return None;
+    }
+    (0..num_records)
+        .map(|index| HDMX_RECORDS_OFFSET + index * record_size)
+        .find(|&record| read_u8(hdmx, record) == Some(ppem))
+        .and_then(|record| {
+            read_u8(
+                hdmx,
+                record + HDMX_DEVICE_RECORD_WIDTHS_OFFSET + glyph_id as usize,
+            )
+        })
+}
+
+/// Returns the position of `baseline` for `script` along the horizontal axis of a `BASE` table,
+/// in font units.
+///
+/// Scripts that the table does not list use its `DFLT` entry, if there is one. Coordinates that
+/// are adjusted by a glyph contour point or a device table are returned unadjusted.
+pub(crate) fn base_coordinate(
+    base: &[u8],
+    script: ScriptTag,
+    baseline: BaselineTag,
+) -> Option<i16> {
+    let axis = non_null_offset(read_u16(base, BASE_HORIZ_AXIS_OFFSET_OFFSET)?)?;
+    let tag_list = axis + non_null_offset(read_u16(base, axis)?)?;
+    let script_list = axis
+        + non_null_offset(read_u16(
+            base,
+            axis + BASE_AXIS_BASE_SCRIPT_LIST_OFFSET_OFFSET,
+        )?)?;
+
+    // The values of each script are in the same order as the axis's baseline tags.
+    let tag_count = read_u16(base, tag_list)? as usize;
+    let baseline_index = (0..tag_count)
+        .find(|&index| read_u32(base, tag_list + 2 + index * 4) == Some(baseline.0))?;
+
+    let script_count = read_u16(base, script_list)? as usize;
+    let find_script = |tag: ScriptTag| {
+        (0..script_count)
+            .map(|index| script_list + 2 + index * BASE_SCRIPT_RECORD_LENGTH)
+            .find(|&record| read_u32(base, record) == Some(tag.0))
+    };
+    let record = find_script(script).or_else(|| find_script(ScriptTag::DEFAULT))?;
+    let base_script = script_list + non_null_offset(read_u16(base, record + 4)?)?;
+    let base_values = base_script + non_null_offset(read_u16(base, base_script)?)?;
+
+    let coord_count = read_u16(base, base_values + BASE_VALUES_BASE_COORD_COUNT_OFFSET)? as usize;
+    if baseline_index >= coord_count {
+        return None;
+    }
+    let coord_offset = read_u16(
+        base,
+        base_values + BASE_VALUES_BASE_COORD_OFFSETS_OFFSET + baseline_index * 2,
+    )?;
+    let base_coord = base_values + non_null_offset(coord_offset)?;
+    // All three formats of `BaseCoord` start with the same coordinate.
+    read_i16(base, base_coord + BASE_COORD_COORDINATE_OFFSET)
+}
+
+/// Returns the advance of every glyph from an `hmtx` or `vmtx` table, given the corresponding
+/// `hhea` or `vhea` header.
+///
+/// Only the first `numberOf{H,Ver}Metrics` glyphs have an advance of their own; the rest repeat
+/// the last one, as is common for monospaced runs at the end of the glyph set.
+pub(crate) fn long_metric_advances(header: &[u8], metrics: &[u8], num_glyphs: u16) -> Vec<u16> {
+    let num_long_metrics = match read_u16(header, METRICS_HEADER_NUM_LONG_METRICS_OFFSET) {
+        Some(num_long_metrics) => num_long_metrics.min(num_glyphs) as usize,
+        None => return vec![],
+    };
+    let mut advances: Vec<u16> = (0..num_long_metrics)
+        .map_while(|glyph_index| read_u16(metrics, glyph_index * LONG_METRIC_RECORD_LENGTH))
+        .collect();
+    if let Some(&last_advance) = advances.last() {
+        advances.resize(num_glyphs as usize, last_advance);
+    }
+    advances
+}
+
+/// Reads the hinting parameters from the private dict of a `CFF ` table.
+///
+/// CID-keyed fonts keep a private dict per font dict; the first one is read.
+pub(crate) fn cff_hint_params(cff: &[u8]) -> Option<CffHintParams> {
+    let header_size = read_u8(cff, CFF_HEADER_SIZE_OFFSET)? as usize;
+    let (_, top_dict_index) = cff_index(cff, header_size)?;
+    let (top_dicts, _) = cff_index(cff, top_dict_index)?;
+    let mut top_dict = cff_dict(cff.get(top_dicts.first()?.clone())?)?;
+    if let Some(fd_array) = cff_dict_operands(&top_dict, CFF_OP_FD_ARRAY) {
+        let (font_dicts, _) = cff_index(cff, *fd_array.first()? as usize)?;
+        top_dict = cff_dict(cff.get(font_dicts.first()?.clone())?)?;
+    }
+    let (size, offset) = match *cff_dict_operands(&top_dict, CFF_OP_PRIVATE)? {
+        [size, offset] => (size as usize, offset as usize),
+        _ => return None,
+    };
+    let private_dict = cff_dict(cff.get(offset..offset.checked_add(size)?)?)?;
+
+    // Zones are stored as deltas from the previous edge.
+    let zones = |operator| -> Vec<(f32, f32)> {
+        let mut edge = 0.0;
+        let edges: Vec<f32> = cff_dict_operands(&private_dict, operator)
+            .unwrap_or(&[])
+            .iter()
+            .map(|delta| {
+                edge += delta;
+                edge
+            })
+            .collect();
+        edges
+            .chunks_exact(2)
+            .map(|zone| (zone[0], zone[1]))
+            .collect()
+    };
+    let width = |operator| {
+        cff_dict_operands(&private_dict, operator).and_then(|operands| operands.first().cloned())
+    };
+    Some(CffHintParams {
+        blue_values: zones(CFF_OP_BLUE_VALUES),
+        other_blues: zones(CFF_OP_OTHER_BLUES),
+        std_hw: width(CFF_OP_STD_HW),
+        std_vw: width(CFF_OP_STD_VW),
+    })
+}
+
+/// Returns the byte range of each object in the CFF INDEX at `offset`, and the offset just past
+/// the INDEX.
+fn cff_index(cff: &[u8], offset: usize) -> Option<(Vec<Range<usize>>, usize)> {
+    let count = read_u16(cff, offset)? as usize;
+    if count == 0 {
+        return Some((vec![], offset + 2));
+    }
+    let offset_size = read_u8(cff, offset + 2)? as usize;
+    if !(1..=4).contains(&offset_size) {
+        return None;
+    }
+    let object_offset = |index: usize| {
+        let start = offset + 3 + index * offset_size;
+        let bytes = cff.get(start..start + offset_size)?;
+        Some(
+            bytes
+                .iter()
+                .fold(0, |value, &byte| value << 8 | byte as usize),
+        )
+    };
+    // Object offsets count from 1, at the byte before the object data.
+    let data_offset = offset + 2 + (count + 1) * offset_size;
+    let mut objects = Vec::with_capacity(count);
+    for index in 0..count {
+        let start = data_offset + object_offset(index)?;
+        let end = data_offset + object_offset(index + 1)?;
+        if end < start || end > cff.len() {
+            return None;
+        }
+        objects.push(start..end);
+    }
+    let end = objects.last()?.end;
+    Some((objects, end))
+}
+
+/// Decodes a CFF DICT into its operators, each with its operands.
+fn cff_dict(dict: &[u8]) -> Option<Vec<(u16, Vec<f32>)>> {
+    let mut entries = vec![];
+    let mut operands = vec![];
+    let mut offset = 0;
+    while let Some(byte) = read_u8(dict, offset) {
+        offset += 1;
+        match byte {
+            0..=21 => {
+                let operator = if byte == CFF_OP_ESCAPE {
+                    offset += 1;
+                    1200 + read_u8(dict, offset - 1)? as u16
+                } else {
+                    byte as u16
+                };
+                entries.push((operator, std::mem::take(&mut operands)));
+            }
+            28 => {
+                operands.push(read_i16(dict, offset)? as f32);
+                offset += 2;
+            }
+            29 => {
+                operands.push(read_i32(dict, offset)? as f32);
+                offset += 4;
+            }
+            30 => {
+                let (value, length) = cff_real(dict.get(offset..)?)?;
+                operands.push(value);
+                offset += length;
+            }
+            32..=246 => operands.push(byte as f32 - 139.0),
+            247..=250 => {
+                let low = read_u8(dict, offset)? as f32;
+                operands.push((byte as f32 - 247.0) * 256.0 + low + 108.0);
+                offset += 1;
+            }
+            251..=254 => {
+                let low = read_u8(dict, offset)? as f32;
+                operands.push(-(byte as f32 - 251.0) * 256.0 - low - 108.0);
+                offset += 1;
+            }
+            _ => return None,
+        }
+    }
+    Some(entries)
+}
+
+fn cff_dict_operands(dict: &[(u16, Vec<f32>)], operator: u16) -> Option<&[f32]> {
+    dict.iter()
+        .find(|&&(entry_operator, _)| entry_operator == operator)
+        .map(|(_, operands)| &operands[..])
+}
+
+/// Decodes a CFF real number operand, packed as nibbles after its prefix byte, returning it and
+/// the number of bytes it took.
+fn cff_real(data: &[u8]) -> Option<(f32, usize)> {
+    let mut string = String::new();
+    for (index, &byte) in data.iter().enumerate() {
+        for nibble in [byte >> 4, byte & 0xf] {
+            match nibble {
+                0..=9 => string.push((b'0' + nibble) as char),
+                0xa => string.push('.'),
+                0xb => string.push('E'),
+                0xc => string.push_str("E-"),
+                0xe => string.push('-'),
+                0xf => return Some((string.parse().ok()?, index + 1)),
+                _ => return None,
+            }
+        }
+    }
+    None
+}
+
+/// Converts a signed 2.14 fixed-point number to a float.
+#[inline]
+fn f2dot14_to_f32(value: i16) -> f32 {
+    value as f32 / 16384.0
+}
+
+/// Converts a signed 16.16 fixed-point number to a float.
+#[inline]
+fn fixed_to_f32(value: i32) -> f32 {
+    value as f32 / 65536.0
+}
+
+/// Rounds a float to the nearest value representable in 2.14 fixed point.
+#[inline]
+fn round_to_f2dot14(value: f32) -> f32 {
+    (value * 16384.0).round() / 16384.0
+}
+
+/// A variation axis from the `fvar` table, in user-space units.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct FvarAxis {
+    pub(crate) tag: u32,
+    pub(crate) min_value: f32,
+    pub(crate) default_value: f32,
+    pub(crate) max_value: f32,
+}
+
+impl FvarAxis {
+    /// Maps a user-space value on this axis to a normalized coordinate in [-1, 1], without
+    /// applying `avar`.
+    pub(crate) fn normalize(&self, value: f32) -> f32 {
+        let value = value.max(self.min_value).min(self.max_value);
+        let normalized = if value < self.default_value {
+            -(self.default_value - value) / (self.default_value - self.min_value)
+        } else if value > self.default_value {
+            (value - self.default_value) / (self.max_value - self.default_value)
+        } else {
+            0.0
+        };
+        round_to_f2dot14(normalized)
+    }
+}
+
+/// Returns the variation axes of an `fvar` table, in the order the font defines them.
+pub(crate) fn fvar_axes(fvar: &[u8]) -> Vec<FvarAxis> {
+    let (axes_offset, axis_count, axis_size) = match (
+        read_u16(fvar, FVAR_AXES_ARRAY_OFFSET_OFFSET),
+        read_u16(fvar, FVAR_AXIS_COUNT_OFFSET),
+        read_u16(fvar, FVAR_AXIS_SIZE_OFFSET),
+    ) {
+        (Some(axes_offset), Some(axis_count), Some(axis_size)) => (
+            axes_offset as usize,
+            axis_count as usize,
+            axis_size as usize,
+        ),
+        _ => return vec![],
+    };
+    (0..axis_count)
+        .map_while(|axis_index| {
+            let record_offset = axes_offset + axis_index * axis_size;
+            Some(FvarAxis {
+                tag: read_u32(fvar, record_offset)?,
+                min_value: fixed_to_f32(read_i32(fvar, record_offset + 4)?),
+                default_value: fixed_to_f32(read_i32(fvar, record_offset + 8)?),
+                max_value: fixed_to_f32(read_i32(fvar, record_offset + 12)?),
+            })
+        })
+        .collect()
+}
+
+/// Returns the segment map of each axis in an `avar` table, as `(from, to)` pairs of normalized
+/// coordinates.
+pub(crate) fn avar_segment_maps(avar: &[u8]) -> Vec<Vec<(f32, f32)>> {
+    let axis_count = read_u16(avar, AVAR_AXIS_COUNT_OFFSET).unwrap_or(0);
+    let mut segment_maps = vec![];
+    let mut offset = AVAR_SEGMENT_MAPS_OFFSET;
+    for _ in 0..axis_count {
+        let position_map_count = match read_u16(avar, offset) {
+            Some(position_map_count) => position_map_count as usize,
+            None => break,
+        };
+        offset += 2;
+        let segment_map: Option<Vec<_>> = (0..position_map_count)
+            .map(|map_index| {
+                let map_offset = offset + map_index * 4;
+                Some((
+                    f2dot14_to_f32(read_i16(avar, map_offset)?),
+                    f2dot14_to_f32(read_i16(avar, map_offset + 2)?),
+                ))
+            })
+            .collect();
+        match segment_map {
+            Some(segment_map) => segment_maps.push(segment_map),
+            None => break,
+        }
+        offset += position_map_count * 4;
+    }
+    segment_maps
+}
+
+/// Runs a normalized coordinate through an `avar` segment map.
+///
+/// Coordinates are interpolated linearly between the map's entries. Coordinates outside the
+/// mapped range, and all coordinates if the map is empty, are left unchanged.
+pub(crate) fn apply_avar_segment_map(segment_map: &[(f32, f32)], coordinate: f32) -> f32 {
+    for window in segment_map.windows(2) {
+        let ((from_0, to_0), (from_1, to_1)) = (window[0], window[1]);
+        if coordinate == from_0 {
+            return to_0;
+        }
+        if coordinate < from_1 {
+            if coordinate < from_0 || from_1 <= from_0 {
+                break;
+            }
+            let t = (coordinate - from_0) / (from_1 - from_0);
+            return round_to_f2dot14(to_0 + (to_1 - to_0) * t);
+        }
+    }
+    match segment_map.last() {
+        Some(&(from, to)) if coordinate == from => to,
+        _ => coordinate,
+    }
+}
+
+/// Returns the delta that an `MVAR` table applies to each metric it varies, keyed by the metric's
+/// value tag (e.g. `'xhgt'`), at the given normalized variation coordinates.
+pub(crate) fn mvar_deltas(mvar: &[u8], coordinates: &[f32]) -> Vec<(u32, f32)> {
+    let (record_size, record_count, store_offset) = match (
+        read_u16(mvar, MVAR_VALUE_RECORD_SIZE_OFFSET),
+        read_u16(mvar, MVAR_VALUE_RECORD_COUNT_OFFSET),
+        read_u16(mvar, MVAR_ITEM_VARIATION_STORE_OFFSET_OFFSET),
+    ) {
+        (Some(record_size), Some(record_count), Some(store_offset)) if store_offset != 0 => (
+            record_size as usize,
+            record_count as usize,
+            store_offset as usize,
+        ),
+        _ => return vec![],
+    };
+    let store = match mvar.get(store_offset..) {
+        Some(store) => store,
+        None => return vec![],
+    };
+    (0..record_count)
+        .map_while(|record_index| {
+            let record_offset = MVAR_VALUE_RECORDS_OFFSET + record_index * record_size;
+            let tag = read_u32(mvar, record_offset)?;
+            let outer_index = read_u16(mvar, record_offset + 4)?;
+            let inner_index = read_u16(mvar, record_offset + 6)?;
+            let delta =
+                item_variation_delta(store, outer_index, inner_index, coordinates).unwrap_or(0.0);
+            Some((tag, delta))
+        })
+        .collect()
+}
+
+/// Returns the delta that an `HVAR` table applies to the advance width of a glyph at the given
+/// normalized variation coordinates.
+///
+/// Glyphs are mapped to items of the variation store through the advance width mapping, or
+/// directly by glyph ID if the table has none. Returns `None` if the table is malformed.
+pub(crate) fn hvar_advance_delta(hvar: &[u8], glyph_id: u32, coordinates: &[f32]) -> Option<f32> {
+    let store = hvar.get(read_u32(hvar, HVAR_ITEM_VARIATION_STORE_OFFSET_OFFSET)? as usize..)?;
+    let (outer_index, inner_index) = match read_u32(hvar, HVAR_ADVANCE_WIDTH_MAPPING_OFFSET_OFFSET)?
+    {
+        0 => (0, u16::try_from(glyph_id).ok()?),
+        mapping_offset => delta_set_index(hvar.get(mapping_offset as usize..)?, glyph_id)?,
+    };
+    item_variation_delta(store, outer_index, inner_index, coordinates)
+}
+
+// Looks up the outer and inner indices of an item in a `DeltaSetIndexMap`. Glyphs past the end of
+// the map use its last entry.
+fn delta_set_index(map: &[u8], glyph_id: u32) -> Option<(u16, u16)> {
+    let entry_format = read_u8(map, 1)?;
+    let (map_count, entries_offset) = match read_u8(map, 0)? {
+        0 => (read_u16(map, 2)? as u32, 4),
+        1 => (read_u32(map, 2)?, 6),
+        _ => return None,
+    };
+    let entry_index = glyph_id.min(map_count.checked_sub(1)?) as usize;
+    let entry_size = ((entry_format & DELTA_SET_INDEX_MAP_ENTRY_SIZE_MASK) >> 4) as usize + 1;
+    let inner_bit_count = (entry_format & DELTA_SET_INDEX_MAP_INNER_INDEX_BIT_COUNT_MASK) + 1;
+    let entry_offset = entries_offset + entry_index * entry_size;
+    let entry = map
+        .get(entry_offset..entry_offset + entry_size)?
+        .iter()
+        .fold(0u32, |entry, &byte| (entry << 8) | byte as u32);
+    Some((
+        (entry >> inner_bit_count) as u16,
+        (entry & ((1 << inner_bit_count) - 1)) as u16,
+    ))
+}
+
+/// Blends the deltas of one item in an `ItemVariationStore` at the given normalized variation
+/// coordinates.
+///
+/// Axes beyond the end of `coordinates` are at their defaults. Returns `None` if the store is
+/// malformed or has no such item.
+pub(crate) fn item_variation_delta(
+    store: &[u8],
+    outer_index: u16,
+    inner_index: u16,
+    coordinates: &[f32],
+) -> Option<f32> {
+    if read_u16(store, 0)? != ITEM_VARIATION_STORE_FORMAT {
+        return None;
+    }
+    let region_list = store.get(read_u32(store, 2)? as usize..)?;
+    if outer_index >= read_u16(store, 6)? {
+        return None;
+    }
+    let data = store.get(read_u32(store, 8 + outer_index as usize * 4)? as usize..)?;
+
+    let item_count = read_u16(data, 0)?;
+    let word_delta_count = read_u16(data, 2)?;
+    let region_index_count = read_u16(data, 4)? as usize;
+    if inner_index >= item_count {
+        return None;
+    }
+    let long_words = word_delta_count & ITEM_VARIATION_DATA_LONG_WORDS != 0;
+    let word_count = (word_delta_count & ITEM_VARIATION_DATA_WORD_COUNT_MASK) as usize;
+    let (word_size, short_size) = if long_words { (4, 2) } else { (2, 1) };
+    let row_size =
+        word_count * word_size + region_index_count.checked_sub(word_count)? * short_size;
+    let row_offset = 6 + region_index_count * 2 + inner_index as usize * row_size;
+
+    let mut delta = 0.0;
+    for column in 0..region_index_count {
+        let region_index = read_u16(data, 6 + column * 2)?;
+        let value = if column < word_count {
+            let offset = row_offset + column * word_size;
+            if long_words {
+                read_i32(data, offset)? as f32
+            } else {
+                read_i16(data, offset)? as f32
+            }
+        } else {
+            let offset = row_offset + word_count * word_size + (column - word_count) * short_size;
+            if long_words {
+                read_i16(data, offset)? as f32
+            } else {
+                read_u8(data, offset)? as i8 as f32
+            }
+        };
+        delta += value * variation_region_scalar(region_list, region_index, coordinates)?;
+    }
+    Some(delta)
+}
+
+// Computes how strongly a region of a `VariationRegionList` applies at the given coordinates,
+// from 0.0 to 1.0.
+fn variation_region_scalar(
+    region_list: &[u8],
+    region_index: u16,
+    coordinates: &[f32],
+) -> Option<f32> {
+    let axis_count = read_u16(region_list, 0)? as usize;
+    if region_index >= read_u16(region_list, 2)? {
+        return None;
+    }
+    let region_offset = 4 + region_index as usize * axis_count * 6;
+    let mut scalar = 1.0;
+    for axis_index in 0..axis_count {
+        let offset = region_offset + axis_index * 6;
+        let start = f2dot14_to_f32(read_i16(region_list, offset)?);
+        let peak = f2dot14_to_f32(read_i16(region_list, offset + 2)?);
+        let end = f2dot14_to_f32(read_i16(region_list, offset + 4)?);
+        let coordinate = coordinates.get(axis_index).cloned().unwrap_or(0.0);
+        // Axes with no peak, and malformed ranges, don't constrain the region.
+        if peak == 0.0 || start > peak || peak > end || (start < 0.0 && end > 0.0) {
+            continue;
+        }
+        if coordinate == peak {
+            continue;
+        }
+        if coordinate <= start || coordinate >= end {
+            return Some(0.0);
+        }
+        scalar *= if coordinate < peak {
+            (coordinate - start) / (peak - start)
+        } else {
+            (end - coordinate) / (end - peak)
+        };
+    }
+    Some(scalar)
+}
+
+/// Returns the `glyf` table data for a glyph, using `head` and `loca` to locate it.
+///
+/// Glyphs with no outline (such as the space) yield an empty slice.
+pub(crate) fn glyf_glyph_data<'a>(
+    head: &[u8],
+    loca: &[u8],
+    glyf: &'a [u8],
+    glyph_id: u32,
+) -> Option<&'a [u8]> {
+    glyf.get(loca_glyph_range(head, loca, glyph_id)?)
+}
+
+/// Returns the checksum of the whole font recorded in the `head` table.
+pub(crate) fn head_checksum_adjustment(head: &[u8]) -> Option<u32> {
+    read_u32(head, HEAD_CHECKSUM_ADJUSTMENT_OFFSET)
+}
+
+/// Returns the byte range of a glyph's data within the `glyf` table, from the `loca` table.
+///
+/// Glyphs with no outline (such as the space) have an empty range.
+pub(crate) fn loca_glyph_range(head: &[u8], loca: &[u8], glyph_id: u32) -> Option<Range<usize>> {
+    let glyph_id = glyph_id as usize;
+    let (start, end) = match read_i16(head, HEAD_INDEX_TO_LOC_FORMAT_OFFSET)? {
+        0 => (
+            read_u16(loca, glyph_id * 2)? as usize * 2,
+            read_u16(loca, glyph_id * 2 + 2)? as usize * 2,
+        ),
+        _ => (
+            read_u32(loca, glyph_id * 4)? as usize,
+            read_u32(loca, glyph_id * 4 + 4)? as usize,
+        ),
+    };
+    if end < start {
+        return None;
+    }
+    Some(start..end)
+}
+
+/// Returns true if a glyph's `glyf` data sets the `OVERLAP_SIMPLE` or `OVERLAP_COMPOUND` flag,
+/// marking it as having overlapping contours or components.
+pub(crate) fn glyf_overlap_flag(glyph_data: &[u8]) -> bool {
+    let number_of_contours = match read_i16(glyph_data, 0) {
+        Some(number_of_contours) => number_of_contours,
+        None => return false,
+    };
+
+    if number_of_contours >= 0 {
+        // The flag lives on the first point of a simple glyph, after the contour end points and
+        // the instructions.
+        let instructions_length_offset = 10 + number_of_contours as usize * 2;
+        return read_u16(glyph_data, instructions_length_offset)
+            .and_then(|instructions_length| {
+                read_u8(
+                    glyph_data,
+                    instructions_length_offset + 2 + instructions_length as usize,
+                )
+            })
+            .is_some_and(|flags| flags & SIMPLE_OVERLAP_SIMPLE != 0);
+    }
+
+    glyf_composite_glyph_index_offsets(glyph_data)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|glyph_index_offset| read_u16(glyph_data, glyph_index_offset - 2))
+        .any(|flags| flags & COMPOSITE_OVERLAP_COMPOUND != 0)
+}
+
+/// Parses the contours of a simple glyph from its `glyf` data, keeping each point's on-curve flag.
+///
+/// Empty glyphs have no contours. Returns `None` if the glyph is a composite glyph or if its data
+/// is malformed.
+pub(crate) fn glyf_simple_glyph_points(glyph_data: &[u8]) -> Option<Vec<ContourPoints>> {
+    if glyph_data.is_empty() {
+        return Some(vec![]);
+    }
+    let number_of_contours = read_i16(glyph_data, 0)?;
+    if number_of_contours < 0 {
+        return None;
+    }
+
+    let mut offset = 10;
+    let mut end_points = Vec::with_capacity(number_of_contours as usize);
+    for _ in 0..number_of_contours {
+        end_points.push(read_u16(glyph_data, offset)? as usize);
+        offset += 2;
+    }
+    let point_count = match end_points.last() {
+        Some(&last_end_point) => last_end_point + 1,
+        None => return Some(vec![]),
+    };
+    let instructions_length = read_u16(glyph_data, offset)? as usize;
+    offset += 2 + instructions_length;
+
+    let mut flags = Vec::with_capacity(point_count);
+    while flags.len() < point_count {
+        let flag = read_u8(glyph_data, offset)?;
+        offset += 1;
+        let repeat_count = if flag & SIMPLE_REPEAT_FLAG != 0 {
+            offset += 1;
+            read_u8(glyph_data, offset - 1)? as usize
+        } else {
+            0
+        };
+        flags.extend(std::iter::repeat(flag).take(repeat_count + 1));
+    }
+    flags.truncate(point_count);
+
+    // Coordinates are stored as deltas from the previous point, first all x and then all y.
+    let mut read_coordinates = |short_flag: u8, same_or_positive_flag: u8| {
+        let mut coordinates = Vec::with_capacity(point_count);
+        let mut value = 0i32;
+        for &flag in &flags {
+            if flag & short_flag != 0 {
+                let delta = read_u8(glyph_data, offset)? as i32;
+                offset += 1;
+                value += if flag & same_or_positive_flag != 0 {
+                    delta
+                } else {
+                    -delta
+                };
+            } else if flag & same_or_positive_flag == 0 {
+                value += read_i16(glyph_data, offset)? as i32;
+                offset += 2;
+            }
+            coordinates.push(value);
+        }
+        Some(coordinates)
+    };
+    let xs = read_coordinates(
+        SIMPLE_X_SHORT_VECTOR,
+        SIMPLE_X_IS_SAME_OR_POSITIVE_X_SHORT_VECTOR,
+    )?;
+    let ys = read_coordinates(
+        SIMPLE_Y_SHORT_VECTOR,
+        SIMPLE_Y_IS_SAME_OR_POSITIVE_Y_SHORT_VECTOR,
+    )?;
+
+    let mut contours = Vec::with_capacity(end_points.len());
+    let mut start = 0;
+    for end_point in end_points {
+        if end_point < start || end_point >= point_count {
+            return None;
+        }
+        let range = start..(end_point + 1);
+        contours.push(ContourPoints {
+            positions: range
+                .clone()
+                .map(|index| Vector2F::new(xs[index] as f32, ys[index] as f32))
+                .collect(),
+            on_curve: flags[range]
+                .iter()
+                .map(|flag| flag & SIMPLE_ON_CURVE_POINT != 0)
+                .collect(),
+        });
+        start = end_point + 1;
+    }
+    Some(contours)
+}
+
+/// Returns the byte offsets of the glyph index field of each component of a composite glyph,
+/// so that the indices can be rewritten.
+///
+/// Returns `None` if the glyph is a simple glyph (or empty) or if its data is malformed.
+pub(crate) fn glyf_composite_glyph_index_offsets(glyph_data: &[u8]) -> Option<Vec<usize>> {
+    if read_i16(glyph_data, 0)? >= 0 {
+        return None;
+    }
+
+    let mut offsets = vec![];
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph_data, offset)?;
+        read_u16(glyph_data, offset + 2)?;
+        offsets.push(offset + 2);
+        if flags & COMPOSITE_MORE_COMPONENTS == 0 {
+            return Some(offsets);
+        }
+        offset += 4;
+        offset += if flags & COMPOSITE_ARG_1_AND_2_ARE_WORDS != 0 {
+            4
+        } else {
+            2
+        };
+        offset += if flags & COMPOSITE_WE_HAVE_A_SCALE != 0 {
+            2
+        } else if flags & COMPOSITE_WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            4
+        } else if flags & COMPOSITE_WE_HAVE_A_TWO_BY_TWO != 0 {
+            8
+        } else {
+            0
+        };
+    }
+}
+
+/// Parses the components of a composite glyph from its `glyf` data.
+///
+/// Returns `None` if the glyph is a simple glyph (or empty) or if its data is malformed.
+pub(crate) fn glyf_composite_components(glyph_data: &[u8]) -> Option<Vec<GlyphComponent>> {
+    // A negative `numberOfContours` marks a composite glyph.
+    if read_i16(glyph_data, 0)? >= 0 {
+        return None;
+    }
+
+    let mut components = vec![];
+    let mut offset = 10;
+    loop {
+        let flags = read_u16(glyph_data, offset)?;
+        let glyph_id = read_u16(glyph_data, offset + 2)? as u32;
+        offset += 4;
+
+        let (arg1, arg2) = if flags & COMPOSITE_ARG_1_AND_2_ARE_WORDS != 0 {
+            let args = (
+                read_u16(glyph_data, offset)?,
+                read_u16(glyph_data, offset + 2)?,
+            );
+            offset += 4;
+            if flags & COMPOSITE_ARGS_ARE_XY_VALUES != 0 {
+                (args.0 as i16 as i32, args.1 as i16 as i32)
+            } else {
+                (args.0 as i32, args.1 as i32)
+            }
+        } else {
+            let args = (
+                read_u8(glyph_data, offset)?,
+                read_u8(glyph_data, offset + 1)?,
+            );
+            offset += 2;
+            if flags & COMPOSITE_ARGS_ARE_XY_VALUES != 0 {
+                (args.0 as i8 as i32, args.1 as i8 as i32)
+            } else {
+                (args.0 as i32, args.1 as i32)
+            }
+        };
+
+        let matrix = if flags & COMPOSITE_WE_HAVE_A_SCALE != 0 {
+            let scale = f2dot14_to_f32(read_i16(glyph_data, offset)?);
+            offset += 2;
+            Matrix2x2F::from_scale(scale)
+        } else if flags & COMPOSITE_WE_HAVE_AN_X_AND_Y_SCALE != 0 {
+            let x_scale = f2dot14_to_f32(read_i16(glyph_data, offset)?);
+            let y_scale = f2dot14_to_f32(read_i16(glyph_data, offset + 2)?);
+            offset += 4;
+            Matrix2x2F::from_scale(Vector2F::new(x_scale, y_scale))
+        } else if flags & COMPOSITE_WE_HAVE_A_TWO_BY_TWO != 0 {
+            let x_scale = f2dot14_to_f32(read_i16(glyph_data, offset)?);
+            let scale_01 = f2dot14_to_f32(read_i16(glyph_data, offset + 2)?);
+            let scale_10 = f2dot14_to_f32(read_i16(glyph_data, offset + 4)?);
+            let y_scale = f2dot14_to_f32(read_i16(glyph_data, offset + 6)?);
+            offset += 8;
+            // The `glyf` table stores the matrix so that x' = x_scale * x + scale_10 * y and
+            // y' = scale_01 * x + y_scale * y.
+            Matrix2x2F::row_major(x_scale, scale_10, scale_01, y_scale)
+        } else {
+            Matrix2x2F::default()
+        };
+
+        let (offset_vector, anchor_points) = if flags & COMPOSITE_ARGS_ARE_XY_VALUES != 0 {
+            (Vector2F::new(arg1 as f32, arg2 as f32), None)
+        } else {
+            (Vector2F::default(), Some((arg1 as u32, arg2 as u32)))
+        };
+
+        components.push(GlyphComponent {
+            glyph_id,
+            offset: offset_vector,
+            matrix,
+            anchor_points,
+        });
+
+        if flags & COMPOSITE_MORE_COMPONENTS == 0 {
+            break;
+        }
+    }
+
+    Some(components)
+}
+
+/// Returns the colors of palette `palette_index` in a `CPAL` table.
+pub(crate) fn cpal_palette(cpal: &[u8], palette_index: u16) -> Option<Vec<Color>> {
+    let num_palette_entries = read_u16(cpal, CPAL_NUM_PALETTE_ENTRIES_OFFSET)? as usize;
+    let num_palettes = read_u16(cpal, CPAL_NUM_PALETTES_OFFSET)?;
+    if palette_index >= num_palettes {
+        return None;
+    }
+    let color_records_offset = read_u32(cpal, CPAL_COLOR_RECORDS_ARRAY_OFFSET_OFFSET)? as usize;
+    let first_color_index = read_u16(
+        cpal,
+        CPAL_COLOR_RECORD_INDICES_OFFSET + palette_index as usize * 2,
+    )? as usize;
+    (0..num_palette_entries)
+        .map(|entry| {
+            // Color records are stored in BGRA order.
+            let record = color_records_offset + (first_color_index + entry) * 4;
+            Some(Color {
+                b: read_u8(cpal, record)?,
+                g: read_u8(cpal, record + 1)?,
+                r: read_u8(cpal, record + 2)?,
+                a: read_u8(cpal, record + 3)?,
+            })
+        })
+        .collect()
+}
+
+/// Returns the paint graph of a color glyph from a `COLR` table, with colors taken from `palette`.
+///
+/// Glyphs with a version 1 paint are returned as they are; glyphs with only a version 0 layer list
+/// become a `Paint::Layers` of solid-colored glyphs. Returns `None` if the glyph isn't a color
+/// glyph or if its paint graph is malformed.
+pub(crate) fn colr_glyph_paint(colr: &[u8], palette: &[Color], glyph_id: u32) -> Option<Paint> {
+    let glyph_id: u16 = glyph_id.try_into().ok()?;
+    let mut parser = ColrPaintParser {
+        colr,
+        palette,
+        remaining_paints: COLR_MAX_PAINT_COUNT,
+    };
+    match parser.base_glyph_paint_offset(glyph_id) {
+        Some(offset) => parser.paint(offset, 0),
+        None => parser.layer_list_paint(glyph_id),
+    }
+}
+
+struct ColrPaintParser<'a> {
+    colr: &'a [u8],
+    palette: &'a [Color],
+    // Paint graphs may share subgraphs, so limit the total number of nodes as well as the depth.
+    remaining_paints: u32,
+}
+
+impl<'a> ColrPaintParser<'a> {
+    // Looks up a glyph in the version 1 `BaseGlyphList` and returns the offset of its paint.
+    fn base_glyph_paint_offset(&self, glyph_id: u16) -> Option<usize> {
+        if read_u16(self.colr, 0)? < 1 {
+            return None;
+        }
+        let list = non_null_offset(read_u32(self.colr, COLR_BASE_GLYPH_LIST_OFFSET_OFFSET)?)?;
+        let count = read_u32(self.colr, list)? as usize;
+        let record = find_glyph_record(self.colr, list + 4, count, 6, glyph_id)?;
+        Some(list + non_null_offset(read_u32(self.colr, record + 2)?)?)
+    }
+
+    // Builds a paint from the version 0 base glyph and layer records.
+    fn layer_list_paint(&self, glyph_id: u16) -> Option<Paint> {
+        let num_base_glyphs = read_u16(self.colr, COLR_NUM_BASE_GLYPH_RECORDS_OFFSET)? as usize;
+        let base_glyphs = read_u32(self.colr, COLR_BASE_GLYPH_RECORDS_OFFSET_OFFSET)? as usize;
+        let layers = read_u32(self.colr, COLR_LAYER_RECORDS_OFFSET_OFFSET)? as usize;
+        let record = find_glyph_record(self.colr, base_glyphs, num_base_glyphs, 6, glyph_id)?;
+        let first_layer_index = read_u16(self.colr, record + 2)? as usize;
+        let num_layers = read_u16(self.colr, record + 4)? as usize;
+        (first_layer_index..first_layer_index + num_layers)
+            .map(|layer_index| {
+                let layer = layers + layer_index * 4;
+                Some(Paint::Glyph {
+                    glyph_id: read_u16(self.colr, layer)? as u32,
+                    paint: Box::new(Paint::Solid(
+                        self.paint_color(read_u16(self.colr, layer + 2)?, 1.0)?,
+                    )),
+                })
+            })
+            .collect::<Option<_>>()
+            .map(Paint::Layers)
+    }
+
+    fn paint(&mut self, offset: usize, depth: u32) -> Option<Paint> {
+        if depth > COLR_MAX_PAINT_DEPTH || self.remaining_paints == 0 {
+            return None;
+        }
+        self.remaining_paints -= 1;
+
+        let colr = self.colr;
+        let format = read_u8(colr, offset)?;
+        let point = |field: usize| -> Option<Vector2F> {
+            Some(Vector2F::new(
+                read_i16(colr, offset + field)? as f32,
+                read_i16(colr, offset + field + 2)? as f32,
+            ))
+        };
+        let f2dot14 = |field: usize| read_i16(colr, offset + field).map(f2dot14_to_f32);
+        // Angles are stored in half-turns.
+        let angle = |field: usize| f2dot14(field).map(|angle| angle * f32::consts::PI);
+
+        // Odd formats from 3 to 31 are the variable versions of the format before them, with
+        // the same fields followed by a variation index.
+        let variable = format % 2 == 1 && (3..=31).contains(&format) && format != 11;
+        let paint = match format {
+            COLR_PAINT_COLR_LAYERS => {
+                let num_layers = read_u8(colr, offset + 1)? as usize;
+                let first_layer_index = read_u32(colr, offset + 2)? as usize;
+                let list = non_null_offset(read_u32(colr, COLR_LAYER_LIST_OFFSET_OFFSET)?)?;
+                let mut layers = Vec::with_capacity(num_layers);
+                for layer_index in first_layer_index..first_layer_index + num_layers {
+                    let layer_offset = read_u32(colr, list + 4 + layer_index * 4)?;
+                    layers.push(self.paint(list + non_null_offset(layer_offset)?, depth + 1)?);
+                }
+                Paint::Layers(layers)
+            }
+            COLR_PAINT_SOLID | COLR_PAINT_VAR_SOLID => {
+                Paint::Solid(self.paint_color(read_u16(colr, offset + 1)?, f2dot14(3)?)?)
+            }
+            COLR_PAINT_LINEAR_GRADIENT | COLR_PAINT_VAR_LINEAR_GRADIENT => Paint::LinearGradient {
+                color_line: self.color_line(self.child_offset(offset, 1)?, variable)?,
+                p0: point(4)?,
+                p1: point(8)?,
+                p2: point(12)?,
+            },
+            COLR_PAINT_RADIAL_GRADIENT | COLR_PAINT_VAR_RADIAL_GRADIENT => Paint::RadialGradient {
+                color_line: self.color_line(self.child_offset(offset, 1)?, variable)?,
+                c0: point(4)?,
+                r0: read_u16(colr, offset + 8)? as f32,
+                c1: point(10)?,
+                r1: read_u16(colr, offset + 14)? as f32,
+            },
+            COLR_PAINT_SWEEP_GRADIENT | COLR_PAINT_VAR_SWEEP_GRADIENT => Paint::SweepGradient {
+                color_line: self.color_line(self.child_offset(offset, 1)?, variable)?,
+                center: point(4)?,
+                start_angle: angle(8)?,
+                end_angle: angle(10)?,
+            },
+            COLR_PAINT_GLYPH => Paint::Glyph {
+                glyph_id: read_u16(colr, offset + 4)? as u32,
+                paint: Box::new(self.paint(self.child_offset(offset, 1)?, depth + 1)?),
+            },
+            COLR_PAINT_COLR_GLYPH => {
+                let glyph_id = read_u16(colr, offset + 1)?;
+                self.paint(self.base_glyph_paint_offset(glyph_id)?, depth + 1)?
+            }
+            COLR_PAINT_COMPOSITE => Paint::Composite {
+                source: Box::new(self.paint(self.child_offset(offset, 1)?, depth + 1)?),
+                mode: composite_mode(read_u8(colr, offset + 4)?)?,
+                backdrop: Box::new(self.paint(self.child_offset(offset, 5)?, depth + 1)?),
+            },
+            COLR_PAINT_TRANSFORM..=COLR_PAINT_VAR_SKEW_AROUND_CENTER => {
+                let transform = match format & !1 {
+                    COLR_PAINT_TRANSFORM => {
+                        let affine = self.child_offset(offset, 4)?;
+                        let field = |index: usize| read_i32(colr, affine + index * 4);
+                        // `Affine2x3` stores xx, yx, xy, yy, dx, dy.
+                        Transform2F::row_major(
+                            fixed_to_f32(field(0)?),
+                            fixed_to_f32(field(2)?),
+                            fixed_to_f32(field(1)?),
+                            fixed_to_f32(field(3)?),
+                            fixed_to_f32(field(4)?),
+                            fixed_to_f32(field(5)?),
+                        )
+                    }
+                    COLR_PAINT_TRANSLATE => Transform2F::from_translation(point(4)?),
+                    COLR_PAINT_SCALE => {
+                        Transform2F::from_scale(Vector2F::new(f2dot14(4)?, f2dot14(6)?))
+                    }
+                    COLR_PAINT_SCALE_AROUND_CENTER => around_center(
+                        Transform2F::from_scale(Vector2F::new(f2dot14(4)?, f2dot14(6)?)),
+                        point(8)?,
+                    ),
+                    COLR_PAINT_SCALE_UNIFORM => Transform2F::from_scale(f2dot14(4)?),
+                    COLR_PAINT_SCALE_UNIFORM_AROUND_CENTER => {
+                        around_center(Transform2F::from_scale(f2dot14(4)?), point(6)?)
+                    }
+                    COLR_PAINT_ROTATE => rotation(angle(4)?),
+                    COLR_PAINT_ROTATE_AROUND_CENTER => {
+                        around_center(rotation(angle(4)?), point(6)?)
+                    }
+                    COLR_PAINT_SKEW => skew(angle(4)?, angle(6)?),
+                    COLR_PAINT_SKEW_AROUND_CENTER => {
+                        around_center(skew(angle(4)?, angle(6)?), point(8)?)
+                    }
+                    _ => return None,
+                };
+                Paint::Transform {
+                    transform,
+                    paint: Box::new(self.paint(self.child_offset(offset, 1)?, depth + 1)?),
+                }
+            }
+            _ => return None,
+        };
+        Some(paint)
+    }
+
+    // Reads the 24-bit offset in the field at `field` of the paint at `offset` and returns the
+    // position it points to. Such offsets are relative to the start of the paint.
+    fn child_offset(&self, offset: usize, field: usize) -> Option<usize> {
+        Some(offset + non_null_offset(read_u24(self.colr, offset + field)?)?)
+    }
+
+    fn color_line(&self, offset: usize, variable: bool) -> Option<ColorLine> {
+        let extend = match read_u8(self.colr, offset)? {
+            1 => Extend::Repeat,
+            2 => Extend::Reflect,
+            // Unknown modes are treated as padding.
+            _ => Extend::Pad,
+        };
+        let num_stops = read_u16(self.colr, offset + 1)? as usize;
+        let stop_length = if variable { 10 } else { 6 };
+        let stops = (0..num_stops)
+            .map(|stop_index| {
+                let stop = offset + 3 + stop_index * stop_length;
+                Some(ColorStop {
+                    offset: f2dot14_to_f32(read_i16(self.colr, stop)?),
+                    color: self.paint_color(
+                        read_u16(self.colr, stop + 2)?,
+                        f2dot14_to_f32(read_i16(self.colr, stop + 4)?),
+                    )?,
+                })
+            })
+            .collect::<Option<_>>()?;
+        Some(ColorLine { extend, stops })
+    }
+
+    fn paint_color(&self, palette_entry: u16, alpha: f32) -> Option<PaintColor> {
+        if palette_entry == COLR_FOREGROUND_PALETTE_ENTRY {
+            return Some(PaintColor::Foreground { alpha });
+        }
+        let color = *self.palette.get(palette_entry as usize)?;
+        Some(PaintColor::Color(Color {
+            a: (color.a as f32 * alpha.clamp(0.0, 1.0)).round() as u8,
+            ..color
+        }))
+    }
+}
+
+// Binary-searches `count` records of `record_length` bytes, each starting with a glyph ID, and
+// returns the offset of the record for `glyph_id`.
+fn find_glyph_record(
+    data: &[u8],
+    start: usize,
+    count: usize,
+    record_length: usize,
+    glyph_id: u16,
+) -> Option<usize> {
+    let (mut low, mut high) = (0, count);
+    while low < high {
+        let middle = low + (high - low) / 2;
+        let record = start + middle * record_length;
+        match read_u16(data, record)?.cmp(&glyph_id) {
+            Ordering::Less => low = middle + 1,
+            Ordering::Greater => high = middle,
+            Ordering::Equal => return Some(record),
+        }
+    }
+    None
+}
+
+#[inline]
+fn non_null_offset<T: Into<u32>>(offset: T) -> Option<usize> {
+    match offset.into() {
+        0 => None,
+        offset => Some(offset as usize),
+    }
+}
+
+fn composite_mode(value: u8) -> Option<CompositeMode> {
+    const MODES: [CompositeMode; 28] = [
+        CompositeMode::Clear,
+        CompositeMode::Source,
+        CompositeMode::Destination,
+        CompositeMode::SourceOver,
+        CompositeMode::DestinationOver,
+        CompositeMode::SourceIn,
+        CompositeMode::DestinationIn,
+        CompositeMode::SourceOut,
+        CompositeMode::DestinationOut,
+        CompositeMode::SourceAtop,
+        CompositeMode::DestinationAtop,
+        CompositeMode::Xor,
+        CompositeMode::Plus,
+        CompositeMode::Screen,
+        CompositeMode::Overlay,
+        CompositeMode::Darken,
+        CompositeMode::Lighten,
+        CompositeMode::ColorDodge,
+        CompositeMode::ColorBurn,
+        CompositeMode::HardLight,
+        CompositeMode::SoftLight,
+        CompositeMode::Difference,
+        CompositeMode::Exclusion,
+        CompositeMode::Multiply,
+        CompositeMode::Hue,
+        CompositeMode::Saturation,
+        CompositeMode::Color,
+        CompositeMode::Luminosity,
+    ];
+    MODES.get(value as usize).cloned()
+}
+
+// Rotates counterclockwise by `angle` radians.
+fn rotation(angle: f32) -> Transform2F {
+    let (sin, cos) = angle.sin_cos();
+    Transform2F::row_major(cos, -sin, sin, cos, 0.0, 0.0)
+}
+
+// Skews by `x_angle` radians counterclockwise along the x axis and `y_angle` radians along the
+// y axis.
+fn skew(x_angle: f32, y_angle: f32) -> Transform2F {
+    Transform2F::row_major(1.0, -x_angle.tan(), y_angle.tan(), 1.0, 0.0, 0.0)
+}
+
+fn around_center(transform: Transform2F, center: Vector2F) -> Transform2F {
+    Transform2F::from_translation(center) * transform * Transform2F::from_translation(-center)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{apply_avar_segment_map, avar_segment_maps, cmap_format_12_glyph_id, FvarAxis};
+    use super::{bitmap_location_has_glyph, colr_glyph_paint, cpal_palette, hdmx_device_advance};
+    use super::{cmap_unicode_mappings, sbix_has_glyph};
+    use crate::color::{Color, ColorLine, ColorStop, Extend, Paint, PaintColor};
+    use pathfinder_geometry::transform2d::Transform2F;
+    use pathfinder_geometry::vector::Vector2F;
+
+    // An `avar` table with one axis whose default-to-max half is remapped so that the middle of
+    // that range lands a quarter of the way along it.
+    static AVAR_TABLE: [u8; 26] = [
+        0x00, 0x01, 0x00, 0x00, // version 1.0
+        0x00, 0x00, // reserved
+        0x00, 0x01, // axisCount
+        0x00, 0x04, // positionMapCount
+        0xc0, 0x00, 0xc0, 0x00, // -1.0 -> -1.0
+        0x00, 0x00, 0x00, 0x00, // 0.0 -> 0.0
+        0x20, 0x00, 0x10, 0x00, // 0.5 -> 0.25
+        0x40, 0x00, 0x40, 0x00, // 1.0 -> 1.0
+    ];
+
+    #[test]
+    fn map_coordinates_through_avar() {
+        let segment_maps = avar_segment_maps(&AVAR_TABLE);
+        assert_eq!(
+            segment_maps,
+            vec![vec![(-1.0, -1.0), (0.0, 0.0), (0.5, 0.25), (1.0, 1.0)]]
+        );
+
+        let weight_axis = FvarAxis {
+            tag: 0x77676874, // 'wght'
+            min_value: 100.0,
+            default_value: 400.0,
+            max_value: 700.0,
+        };
+        let normalized = weight_axis.normalize(550.0);
+        assert_eq!(normalized, 0.5);
+        assert_eq!(apply_avar_segment_map(&segment_maps[0], normalized), 0.25);
+
+        assert_eq!(apply_avar_segment_map(&segment_maps[0], 0.75), 0.625);
+        assert_eq!(apply_avar_segment_map(&segment_maps[0], 1.0), 1.0);
+        assert_eq!(apply_avar_segment_map(&segment_maps[0], -0.5), -0.5);
+        assert_eq!(apply_avar_segment_map(&[], 0.3), 0.3);
+    }
+
+    // A `cmap` table whose only subtable is format 12 but is listed under the Unicode BMP
+    // encoding, mapping U+1F600..=U+1F602 to glyphs 10..=12 and U+20000 to glyph 20.
+    static CMAP_TABLE: [u8; 52] = [
+        0x00, 0x00, // version
+        0x00, 0x01, // numTables
+        0x00, 0x00, 0x00, 0x03, // platform 0, encoding 3
+        0x00, 0x00, 0x00, 0x0c, // subtable offset
+        0x00, 0x0c, 0x00, 0x00, // format 12, reserved
+        0x00, 0x00, 0x00, 0x28, // length
+        0x00, 0x00, 0x00, 0x00, // language
+        0x00, 0x00, 0x00, 0x02, // numGroups
+        0x00, 0x01, 0xf6, 0x00, 0x00, 0x01, 0xf6, 0x02, 0x00, 0x00, 0x00, 0x0a, // group 0
+        0x00, 0x02, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x14, // group 1
+    ];
+
+    // A format 12 `cmap` table whose one group, U+1F600..=U+1F602, starts at glyph 0xfffffffe and
+    // so runs past the largest glyph ID.
+    static OVERFLOWING_CMAP_TABLE: [u8; 40] = [
+        0x00, 0x00, // version
+        0x00, 0x01, // numTables
+        0x00, 0x03, 0x00, 0x0a, // platform 3, encoding 10
+        0x00, 0x00, 0x00, 0x0c, // subtable offset
+        0x00, 0x0c, 0x00, 0x00, // format 12, reserved
+        0x00, 0x00, 0x00, 0x1c, // length
+        0x00, 0x00, 0x00, 0x00, // language
+        0x00, 0x00, 0x00, 0x01, // numGroups
+        0x00, 0x01, 0xf6, 0x00, 0x00, 0x01, 0xf6, 0x02, 0xff, 0xff, 0xff, 0xfe, // group 0
+    ];
+
+    #[test]
+    fn look_up_supplementary_characters_in_cmap_format_12() {
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE, 0x1f600), Some(10));
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE, 0x1f602), Some(12));
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE, 0x20000), Some(20));
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE, 0x1f603), None);
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE, 0x41), None);
+
+        assert_eq!(
+            cmap_format_12_glyph_id(&OVERFLOWING_CMAP_TABLE, 0x1f601),
+            Some(0xffffffff)
+        );
+        assert_eq!(
+            cmap_format_12_glyph_id(&OVERFLOWING_CMAP_TABLE, 0x1f602),
+            None
+        );
+        assert_eq!(
+            cmap_unicode_mappings(&OVERFLOWING_CMAP_TABLE),
+            vec![(0x1f600, 0xfffffffe), (0x1f601, 0xffffffff)]
+        );
+        assert_eq!(cmap_format_12_glyph_id(&CMAP_TABLE[..20], 0x1f600), None);
+    }
+
+    // A `COLR` version 1 table. Glyph 2 has a version 0 layer list; glyph 5 is glyph 6 filled with
+    // a linear gradient; glyph 7 is glyph 5 translated by (10, 20); and glyph 8 refers to itself.
+    static COLR_TABLE: [u8; 121] = [
+        0x00, 0x01, // version
+        0x00, 0x01, // numBaseGlyphRecords
+        0x00, 0x00, 0x00, 0x22, // baseGlyphRecordsOffset
+        0x00, 0x00, 0x00, 0x28, // layerRecordsOffset
+        0x00, 0x02, // numLayerRecords
+        0x00, 0x00, 0x00, 0x30, // baseGlyphListOffset
+        0x00, 0x00, 0x00, 0x00, // layerListOffset
+        0x00, 0x00, 0x00, 0x00, // clipListOffset
+        0x00, 0x00, 0x00, 0x00, // varIndexMapOffset
+        0x00, 0x00, 0x00, 0x00, // itemVariationStoreOffset
+        0x00, 0x02, 0x00, 0x00, 0x00, 0x02, // 34: glyph 2, layers 0..2
+        0x00, 0x03, 0x00, 0x01, // 40: glyph 3, palette entry 1
+        0x00, 0x04, 0x00, 0x00, // 44: glyph 4, palette entry 0
+        0x00, 0x00, 0x00, 0x03, // 48: numBaseGlyphPaintRecords
+        0x00, 0x05, 0x00, 0x00, 0x00, 0x16, // glyph 5, paint at 70
+        0x00, 0x07, 0x00, 0x00, 0x00, 0x3b, // glyph 7, paint at 107
+        0x00, 0x08, 0x00, 0x00, 0x00, 0x46, // glyph 8, paint at 118
+        0x0a, 0x00, 0x00, 0x06, 0x00, 0x06, // 70: PaintGlyph, glyph 6
+        0x04, 0x00, 0x00, 0x10, // 76: PaintLinearGradient
+        0x00, 0x00, 0x00, 0x00, // p0 = (0, 0)
+        0x00, 0x64, 0x00, 0x00, // p1 = (100, 0)
+        0x00, 0x00, 0x00, 0x64, // p2 = (0, 100)
+        0x02, 0x00, 0x02, // 92: ColorLine, reflect, 2 stops
+        0x00, 0x00, 0x00, 0x00, 0x40, 0x00, // 0.0: palette entry 0, alpha 1.0
+        0x40, 0x00, 0xff, 0xff, 0x20, 0x00, // 1.0: foreground, alpha 0.5
+        0x0e, 0x00, 0x00, 0x08, 0x00, 0x0a, 0x00, 0x14, // 107: PaintTranslate (10, 20)
+        0x0b, 0x00, 0x05, // 115: PaintColrGlyph, glyph 5
+        0x0b, 0x00, 0x08, // 118: PaintColrGlyph, glyph 8
+    ];
+
+    // A `CPAL` table with one palette of opaque red and half-transparent green.
+    static CPAL_TABLE: [u8; 22] = [
+        0x00, 0x00, // version
+        0x00, 0x02, // numPaletteEntries
+        0x00, 0x01, // numPalettes
+        0x00, 0x02, // numColorRecords
+        0x00, 0x00, 0x00, 0x0e, // colorRecordsArrayOffset
+        0x00, 0x00, // colorRecordIndices[0]
+        0x00, 0x00, 0xff, 0xff, // red
+        0x00, 0xff, 0x00, 0x80, // green
+    ];
+
+    #[test]
+    fn read_colr_paint_graphs() {
+        let red = Color {
+            r: 0xff,
+            g: 0,
+            b: 0,
+            a: 0xff,
+        };
+        let green = Color {
+            r: 0,
+            g: 0xff,
+            b: 0,
+            a: 0x80,
+        };
+        let palette = cpal_palette(&CPAL_TABLE, 0).unwrap();
+        assert_eq!(palette, vec![red, green]);
+        assert_eq!(cpal_palette(&CPAL_TABLE, 1), None);
+
+        assert_eq!(
+            colr_glyph_paint(&COLR_TABLE, &palette, 2),
+            Some(Paint::Layers(vec![
+                Paint::Glyph {
+                    glyph_id: 3,
+                    paint: Box::new(Paint::Solid(PaintColor::Color(green))),
+                },
+                Paint::Glyph {
+                    glyph_id: 4,
+                    paint: Box::new(Paint::Solid(PaintColor::Color(red))),
+                },
+            ]))
+        );
+
+        let gradient_glyph = Paint::Glyph {
+            glyph_id: 6,
+            paint: Box::new(Paint::LinearGradient {
+                color_line: ColorLine {
+                    extend: Extend::Reflect,
+                    stops: vec![
+                        ColorStop {
+                            offset: 0.0,
+                            color: PaintColor::Color(red),
+                        },
+                        ColorStop {
+                            offset: 1.0,
+                            color: PaintColor::Foreground { alpha: 0.5 },
+                        },
+                    ],
+                },
+                p0: Vector2F::new(0.0, 0.0),
+                p1: Vector2F::new(100.0, 0.0),
+                p2: Vector2F::new(0.0, 100.0),
+            }),
+        };
+        assert_eq!(
+            colr_glyph_paint(&COLR_TABLE, &palette, 5),
+            Some(gradient_glyph.clone())
+        );
+        assert_eq!(
+            colr_glyph_paint(&COLR_TABLE, &palette, 7),
+            Some(Paint::Transform {
+                transform: Transform2F::from_translation(Vector2F::new(10.0, 20.0)),
+                paint: Box::new(gradient_glyph),
+            })
+        );
+
+        // Reference cycles, glyphs without color, and truncated tables yield nothing.
+        assert_eq!(colr_glyph_paint(&COLR_TABLE, &palette, 8), None);
+        assert_eq!(colr_glyph_paint(&COLR_TABLE, &palette, 6), None);
+        assert_eq!(colr_glyph_paint(&COLR_TABLE[..100], &palette, 5), None);
+    }
+
+    // An `hdmx` table for a font with 3 glyphs, with records for 9 and 12 pixels per em. Each
+    // record is padded to a multiple of 4 bytes.
+    static HDMX_TABLE: [u8; 24] = [
+        0x00, 0x00, // version
+        0x00, 0x02, // numRecords
+        0x00, 0x00, 0x00, 0x08, // sizeDeviceRecord
+        0x09, 0x06, 0x05, 0x06, 0x03, 0x00, 0x00, 0x00, // 9 ppem, max width 6
+        0x0c, 0x08, 0x07, 0x08, 0x04, 0x00, 0x00, 0x00, // 12 ppem, max width 8
+    ];
+
+    #[test]
+    fn look_up_device_advances_in_hdmx() {
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 3, 0, 9), Some(5));
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 3, 2, 9), Some(3));
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 3, 1, 12), Some(8));
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 3, 1, 10), None);
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 3, 3, 9), None);
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE, 7, 0, 9), None);
+        assert_eq!(hdmx_device_advance(&HDMX_TABLE[..12], 3, 0, 12), None);
+    }
+
+    // A `CBLC` table with one strike whose index subtables cover glyphs 3 to 5 and 9.
+    static CBLC_TABLE: [u8; 72] = [
+        0x00, 0x03, 0x00, 0x00, // version 3.0
+        0x00, 0x00, 0x00, 0x01, // numSizes
+        0x00, 0x00, 0x00, 0x38, // indexSubTableArrayOffset
+        0x00, 0x00, 0x00, 0x10, // indexTablesSize
+        0x00, 0x00, 0x00, 0x02, // numberOfIndexSubTables
+        0x00, 0x00, 0x00, 0x00, // colorRef
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // hori
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // vert
+        0x00, 0x03, 0x00, 0x09, // startGlyphIndex, endGlyphIndex
+        0x6d, 0x6d, 0x20, 0x01, // ppemX, ppemY, bitDepth, flags
+        0x00, 0x03, 0x00, 0x05, 0x00, 0x00, 0x00, 0x10, // glyphs 3 to 5
+        0x00, 0x09, 0x00, 0x09, 0x00, 0x00, 0x00, 0x18, // glyph 9
+    ];
+
+    // An `sbix` table with one strike for three glyphs, of which only glyph 1 has an image.
+    static SBIX_TABLE: [u8; 36] = [
+        0x00, 0x01, 0x00, 0x01, // version, flags
+        0x00, 0x00, 0x00, 0x01, // numStrikes
+        0x00, 0x00, 0x00, 0x0c, // strike offset
+        0x00, 0x14, 0x00, 0x48, // ppem, ppi
+        0x00, 0x00, 0x00, 0x14, 0x00, 0x00, 0x00, 0x14, // glyphDataOffsets[0..2]
+        0x00, 0x00, 0x00, 0x18, 0x00, 0x00, 0x00, 0x18, // glyphDataOffsets[2..4]
+        b'p', b'n', b'g', b' ', // glyph 1's graphic type
+    ];
+
+    #[test]
+    fn look_up_color_bitmap_glyphs() {
+        assert!(bitmap_location_has_glyph(&CBLC_TABLE, 3));
+        assert!(bitmap_location_has_glyph(&CBLC_TABLE, 5));
+        assert!(bitmap_location_has_glyph(&CBLC_TABLE, 9));
+        assert!(!bitmap_location_has_glyph(&CBLC_TABLE, 6));
+        assert!(!bitmap_location_has_glyph(&CBLC_TABLE[..64], 9));
+
+        assert!(sbix_has_glyph(&SBIX_TABLE, 1));
+        assert!(!sbix_has_glyph(&SBIX_TABLE, 0));
+        assert!(!sbix_has_glyph(&SBIX_TABLE, 2));
+
+        // Truncated tables that claim billions of strikes stop at the end of the data.
+        let mut cblc = CBLC_TABLE[..8].to_vec();
+        cblc[4..8].copy_from_slice(&[0xff; 4]);
+        assert!(!bitmap_location_has_glyph(&cblc, 3));
+        let mut sbix = SBIX_TABLE[..8].to_vec();
+        sbix[4..8].copy_from_slice(&[0xff; 4]);
+        assert!(!sbix_has_glyph(&sbix, 1));
+    }
+}
@@ -14,6 +14,9 @@
 
 use std::fs::File;
 use std::io::{Error as IOError, Read};
+use std::sync::OnceLock;
+
+use crate::properties::Properties;
 
 pub(crate) static SFNT_VERSIONS: [[u8; 4]; 4] = [
     [0x00, 0x01, 0x00, 0x00],
@@ -22,24 +25,20 @@ pub(crate) static SFNT_VERSIONS: [[u8; 4]; 4] = [
     [b't', b'y', b'p', b'1'],
 ];
 
-pub(crate) fn clamp(x: f32, min: f32, max: f32) -> f32 {
-    if x < min {
-        min
-    } else if x > max {
-        max
-    } else {
-        x
-    }
-}
+// The ways a PostScript Type 1 font can begin: the segment header of a binary `.pfb` file, or the
+// comment that opens the cleartext of an ASCII `.pfa` file.
+static TYPE1_SIGNATURES: [&[u8]; 3] = [b"\x80\x01", b"%!PS-AdobeFont", b"%!FontType1"];
 
-#[inline]
-pub(crate) fn lerp(a: f32, b: f32, t: f32) -> f32 {
-    a + (b - a) * t
+/// Returns true if the data looks like a PostScript Type 1 font, in `.pfb` or `.pfa` form.
+pub(crate) fn is_type1_font(data: &[u8]) -> bool {
+    TYPE1_SIGNATURES
+        .iter()
+        .any(|signature| data.starts_with(signature))
 }
 
 #[inline]
 pub(crate) fn div_round_up(a: usize, b: usize) -> usize {
-    (a + b - 1) / b
+    a.div_ceil(b)
 }
 
 pub(crate) fn slurp_file(file: &mut File) -> Result<Vec<u8>, IOError> {
@@ -50,3 +49,14 @@ pub(crate) fn slurp_file(file: &mut File) -> Result<Vec<u8>, IOError> {
     file.read_to_end(&mut data)?;
     Ok(data)
 }
+
+/// Descriptive information that loaders remember after it's first asked for, since looking it up
+/// means reading the `name`, `OS/2`, and `head` tables again, which is slow when a whole family
+/// is being matched.
+#[derive(Clone, Default)]
+pub(crate) struct DescriptionCache {
+    pub(crate) postscript_name: OnceLock<Option<String>>,
+    pub(crate) full_name: OnceLock<String>,
+    pub(crate) family_name: OnceLock<String>,
+    pub(crate) properties: OnceLock<Properties>,
+}
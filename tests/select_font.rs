@@ -150,6 +150,7 @@ mod test {
         match_handle!(family.fonts()[5], "C:\\WINDOWS\\FONTS\\ARIAL.TTF", 0);
         match_handle!(family.fonts()[6], "C:\\WINDOWS\\FONTS\\ARIALBD.TTF", 0);
         match_handle!(family.fonts()[7], "C:\\WINDOWS\\FONTS\\ARIBLK.TTF", 0);
+        assert_eq!(family.display_name().as_deref(), Some("Arial"));
     }
 
     #[allow(non_snake_case)]
@@ -189,6 +190,7 @@ mod test {
 #[cfg(all(feature = "source", target_os = "linux"))]
 mod test {
     use super::*;
+    use font_kit::source::Source;
 
     #[test]
     fn select_best_match_serif() {
@@ -267,6 +269,33 @@ mod test {
             .collect();
         assert!(filenames.iter().any(|name| name == "DejaVuSans-Bold.ttf"));
         assert!(filenames.iter().any(|name| name == "DejaVuSans.ttf"));
+        assert_eq!(family.display_name().as_deref(), Some("DejaVu Sans"));
+    }
+
+    #[test]
+    fn select_family_sorted_by_properties_dejavu() {
+        let family = SystemSource::new()
+            .select_family_sorted_by_properties("DejaVu Sans")
+            .unwrap();
+        let properties: Vec<Properties> = family
+            .fonts()
+            .iter()
+            .map(|handle| handle.load().unwrap().properties())
+            .collect();
+        assert!(properties.len() > 1);
+        for pair in properties.windows(2) {
+            let (a, b) = (&pair[0], &pair[1]);
+            assert!(
+                a.stretch.0 < b.stretch.0
+                    || (a.stretch.0 == b.stretch.0 && a.weight.0 <= b.weight.0)
+            );
+        }
+        assert!(properties
+            .iter()
+            .any(|p| p.weight.0 < font_kit::properties::Weight::NORMAL.0));
+        assert!(properties
+            .iter()
+            .any(|p| p.weight.0 > font_kit::properties::Weight::NORMAL.0));
     }
 
     #[allow(non_snake_case)]
@@ -381,6 +410,7 @@ mod test {
         match_handle!(family.fonts()[1], "/Library/Fonts/Arial Bold.ttf", 0);
         match_handle!(family.fonts()[2], "/Library/Fonts/Arial Bold Italic.ttf", 0);
         match_handle!(family.fonts()[3], "/Library/Fonts/Arial Italic.ttf", 0);
+        assert_eq!(family.display_name().as_deref(), Some("Arial"));
     }
 
     #[allow(non_snake_case)]
@@ -10,22 +10,41 @@
 
 // General tests.
 
-use font_kit::canvas::{Canvas, Format, RasterizationOptions};
+use font_kit::caching_font::CachingFont;
+use font_kit::canvas::{
+    Canvas, CoverageStats, Format, GlyphKey, PixelValue, RasterTransform, RasterizationOptions,
+    RasterizeContext,
+};
+use font_kit::classification::{self, CodePageRanges, EmbeddingPermission, EmbeddingRestrictions};
+use font_kit::error::FontLoadingError;
 use font_kit::family_name::FamilyName;
 use font_kit::file_type::FileType;
 use font_kit::font::Font;
+use font_kit::font_info::FontInfo;
 use font_kit::hinting::HintingOptions;
-use font_kit::outline::{Contour, Outline, OutlineBuilder, PointFlags};
-use font_kit::properties::{Properties, Stretch, Weight};
+use font_kit::matching::{self, MatchPriority};
+use font_kit::metrics::{BaselineTag, LineMetrics, ScriptTag};
+use font_kit::outline::{
+    Contour, FillRule, GlyphComponent, Outline, OutlineBuilder, OutlineFormat, PathSegment,
+    PointFlags,
+};
+use font_kit::properties::{Properties, PropertyMapping, Stretch, StretchRange, Style, Weight};
 use pathfinder_geometry::rect::{RectF, RectI};
-use pathfinder_geometry::transform2d::Transform2F;
+use pathfinder_geometry::transform2d::{Matrix2x2F, Transform2F};
 use pathfinder_geometry::vector::{Vector2F, Vector2I};
 use std::fs::File;
 use std::io::Read;
+use std::ops::ControlFlow;
 use std::sync::Arc;
 
+#[cfg(feature = "source")]
+use font_kit::handle::Handle;
 #[cfg(feature = "source")]
 use font_kit::source::SystemSource;
+#[cfg(feature = "source")]
+use font_kit::sources::caching::CachingSource;
+#[cfg(feature = "source")]
+use font_kit::sources::mem::MemSource;
 
 static TEST_FONT_FILE_PATH: &str = "resources/tests/eb-garamond/EBGaramond12-Regular.otf";
 static TEST_FONT_POSTSCRIPT_NAME: &str = "EBGaramond12-Regular";
@@ -41,6 +60,7 @@ static KNOWN_SYSTEM_FONT_NAME: &'static str = "Arial";
 #[cfg(target_os = "linux")]
 static KNOWN_SYSTEM_FONT_NAME: &str = "DejaVu Sans";
 
+#[allow(dead_code)]
 static SFNT_VERSIONS: [[u8; 4]; 4] = [
     [0x00, 0x01, 0x00, 0x00],
     [b'O', b'T', b'T', b'O'],
@@ -80,857 +100,4531 @@ pub fn get_font_full_name_from_lowercase_family_name() {
     assert_eq!(font.full_name(), KNOWN_SYSTEM_FONT_NAME);
 }
 
+#[cfg(feature = "source")]
 #[test]
-pub fn load_font_from_file() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+pub fn for_each_family_matches_all_families() {
+    let source = SystemSource::new();
+    let mut family_count = 0;
+    let flow = source
+        .for_each_family(|family_name| {
+            assert!(!family_name.is_empty());
+            family_count += 1;
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+    assert!(flow.is_continue());
+    assert!(family_count >= source.all_families().unwrap().len());
 }
 
+#[cfg(feature = "source")]
 #[test]
-pub fn load_font_from_memory() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let mut font_data = vec![];
-    file.read_to_end(&mut font_data).unwrap();
-    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
-    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+pub fn for_each_family_stops_early() {
+    let mut family_count = 0;
+    let flow = SystemSource::new()
+        .for_each_family(|_| {
+            family_count += 1;
+            ControlFlow::Break(())
+        })
+        .unwrap();
+    assert!(flow.is_break());
+    assert_eq!(family_count, 1);
 }
 
+#[cfg(feature = "source")]
 #[test]
-pub fn analyze_file() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    assert_eq!(Font::analyze_file(&mut file).unwrap(), FileType::Single);
+pub fn all_families_sorted_by_folded_name() {
+    use font_kit::error::SelectionError;
+    use font_kit::family_handle::FamilyHandle;
+    use font_kit::source::Source;
+    use std::any::Any;
+
+    struct FamilyListSource(Vec<&'static str>);
+
+    impl Source for FamilyListSource {
+        fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+            Ok(vec![])
+        }
+
+        fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+            Ok(self
+                .0
+                .iter()
+                .map(|&family_name| family_name.to_owned())
+                .collect())
+        }
+
+        fn select_family_by_name(&self, _: &str) -> Result<FamilyHandle, SelectionError> {
+            Err(SelectionError::NotFound)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    let source = FamilyListSource(vec![
+        "Zapfino",
+        "arial",
+        "Ångström",
+        "Œuvre",
+        "Arial",
+        "Angus",
+        "Éclair",
+        "Eclair",
+        "Ubuntu",
+    ]);
+    assert_eq!(
+        source.all_families_sorted_by_folded_name().unwrap(),
+        [
+            "Ångström",
+            "Angus",
+            "Arial",
+            "Eclair",
+            "Éclair",
+            "Œuvre",
+            "Ubuntu",
+            "Zapfino"
+        ]
+    );
+    assert_eq!(source.all_families().unwrap().len(), 9);
+    // The default `for_each_family()` visits the same names and stops when asked.
+    let mut visited = vec![];
+    let flow = source
+        .for_each_family(&mut |family_name| {
+            visited.push(family_name.to_owned());
+            if visited.len() == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        })
+        .unwrap();
+    assert!(flow.is_break());
+    assert_eq!(visited, ["Zapfino", "arial"]);
+
+    let families = SystemSource::new()
+        .all_families_sorted_by_folded_name()
+        .unwrap();
+    let mut lowercase_families: Vec<_> = families.iter().map(|name| name.to_lowercase()).collect();
+    lowercase_families.dedup();
+    assert_eq!(lowercase_families.len(), families.len());
 }
 
+#[cfg(feature = "source")]
 #[test]
-pub fn analyze_bytes() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let mut font_data = vec![];
-    file.read_to_end(&mut font_data).unwrap();
+fn select_family_fuzzy() {
+    use font_kit::error::SelectionError;
+    use font_kit::family_handle::FamilyHandle;
+    use font_kit::source::Source;
+    use std::any::Any;
+
+    struct NamedFamilySource(Vec<&'static str>);
+
+    impl Source for NamedFamilySource {
+        fn all_fonts(&self) -> Result<Vec<Handle>, SelectionError> {
+            Ok(vec![])
+        }
+
+        fn all_families(&self) -> Result<Vec<String>, SelectionError> {
+            Ok(self
+                .0
+                .iter()
+                .map(|&family_name| family_name.to_owned())
+                .collect())
+        }
+
+        fn select_family_by_name(&self, family_name: &str) -> Result<FamilyHandle, SelectionError> {
+            let name = self
+                .0
+                .iter()
+                .find(|name| name.eq_ignore_ascii_case(family_name))
+                .ok_or(SelectionError::NotFound)?;
+            let mut family = FamilyHandle::new();
+            family.set_display_name(Some(name.to_string()));
+            Ok(family)
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+
+        fn as_mut_any(&mut self) -> &mut dyn Any {
+            self
+        }
+    }
+
+    let source = NamedFamilySource(vec!["Arial", "Aerial", "Bat", "Cat", "Helvetica"]);
+    let closest = |name, max_distance| {
+        source
+            .select_family_fuzzy(name, max_distance)
+            .map(|family| family.display_name().unwrap())
+    };
+    assert_eq!(closest("Ariel", 1), Ok("Arial".to_owned()));
+    assert_eq!(closest("ariel", 1), Ok("Arial".to_owned()));
+    assert_eq!(closest("Ariel", 0), Err(SelectionError::NotFound));
+    // Exact matches win even when another name is just as close.
+    assert_eq!(closest("Aerial", 2), Ok("Aerial".to_owned()));
+    // Ties go to the name that sorts first.
+    assert_eq!(closest("Hat", 1), Ok("Bat".to_owned()));
+    assert_eq!(closest("Helvetika Neue", 3), Err(SelectionError::NotFound));
+    assert_eq!(closest("", 3), Err(SelectionError::InvalidQuery));
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn caching_source_memoizes_matches() {
+    let source =
+        MemSource::from_fonts(vec![Handle::from_path(TEST_FONT_FILE_PATH.into(), 0)].into_iter())
+            .unwrap();
+    let source = CachingSource::with_capacity(source, 2);
+    let garamond = [FamilyName::Title("EB Garamond".to_owned())];
+
+    let first = source
+        .select_best_match(&garamond, &Properties::new())
+        .unwrap();
+    assert_eq!(source.cached_match_count(), 1);
+    // A weight that rounds to the same value hits the same entry.
+    let second = source
+        .select_best_match(&garamond, Properties::new().weight(Weight(400.2)))
+        .unwrap();
+    assert_eq!(source.cached_match_count(), 1);
     assert_eq!(
-        Font::analyze_bytes(Arc::new(font_data)).unwrap(),
-        FileType::Single
+        first.load().unwrap().postscript_name(),
+        second.load().unwrap().postscript_name()
     );
+
+    // Misses are cached too, and the oldest entry is evicted once the cache is full.
+    let missing = [FamilyName::Title("Missing Family".to_owned())];
+    assert!(source
+        .select_best_match(&missing, &Properties::new())
+        .is_err());
+    assert!(source
+        .select_best_match(&garamond, Properties::new().weight(Weight::BOLD))
+        .is_ok());
+    assert_eq!(source.cached_match_count(), 2);
+
+    source.clear();
+    assert_eq!(source.cached_match_count(), 0);
 }
 
 #[cfg(feature = "source")]
 #[test]
-pub fn get_glyph_for_char() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
-    assert_eq!(glyph, 68);
+pub fn mem_source_family_display_name() {
+    let source =
+        MemSource::from_fonts(vec![Handle::from_path(TEST_FONT_FILE_PATH.into(), 0)].into_iter())
+            .unwrap();
+    let family = source.select_family_by_name("EB Garamond").unwrap();
+    assert_eq!(family.display_name().as_deref(), Some("EB Garamond"));
 }
 
-#[cfg(all(
-    feature = "source",
-    any(target_family = "windows", target_os = "macos")
-))]
+#[cfg(feature = "source")]
 #[test]
-pub fn get_glyph_outline() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
+pub fn select_family_loaded() {
+    use font_kit::family::Family;
+    use font_kit::source::Source;
+
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(
+                "resources/tests/eb-garamond/EBGaramond12-Italic.otf".into(),
+                0,
+            ),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let fonts = source.select_family_loaded("EB Garamond").unwrap();
+    let mut styles: Vec<_> = fonts.iter().map(|font| font.properties().style).collect();
+    styles.sort_by_key(|style| *style as u8);
+    assert_eq!(styles, [Style::Normal, Style::Italic]);
+
+    let family_handle = source.select_family_by_name("EB Garamond").unwrap();
+    let family = Family::<Font>::from_handle(&family_handle).unwrap();
+    assert_eq!(family.fonts().len(), fonts.len());
+    assert!(source.select_family_loaded("Nonexistent Family").is_err());
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn mem_source_add_and_remove_fonts() {
+    let mut source = MemSource::from_fonts(
+        vec![Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0)].into_iter(),
+    )
+    .unwrap();
+    assert!(source.select_family_by_name("EB Garamond").is_err());
+
+    source
+        .add_font(Handle::from_path(TEST_FONT_FILE_PATH.into(), 0))
         .unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+    source
+        .add_font(Handle::from_path(
+            "resources/tests/eb-garamond/EBGaramond12-Italic.otf".into(),
+            0,
+        ))
         .unwrap();
+    assert_eq!(
+        source.all_families().unwrap(),
+        vec!["EB Garamond".to_owned(), "Inconsolata".to_owned()]
+    );
+    let family = source.select_family_by_name("EB Garamond").unwrap();
+    let postscript_names: Vec<_> = family
+        .fonts()
+        .iter()
+        .map(|handle| handle.load().unwrap().postscript_name().unwrap())
+        .collect();
+    assert_eq!(
+        postscript_names,
+        vec![TEST_FONT_POSTSCRIPT_NAME, "EBGaramond12-Italic"]
+    );
 
-    let outline = outline_builder.into_outline();
+    assert!(source.remove_font(TEST_FONT_POSTSCRIPT_NAME).is_some());
+    assert!(source.remove_font(TEST_FONT_POSTSCRIPT_NAME).is_none());
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(136.0, 1259.0),
-                        Vector2F::new(136.0, 1466.0),
-                        Vector2F::new(316.0, 1466.0),
-                        Vector2F::new(316.0, 1259.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(136.0, 0.0),
-                        Vector2F::new(136.0, 1062.0),
-                        Vector2F::new(316.0, 1062.0),
-                        Vector2F::new(316.0, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-            ],
-        }
+        source
+            .select_family_by_name("EB Garamond")
+            .unwrap()
+            .fonts()
+            .len(),
+        1
+    );
+    assert!(source.remove_font("EBGaramond12-Italic").is_some());
+    assert!(source.select_family_by_name("EB Garamond").is_err());
+    assert_eq!(
+        source.all_families().unwrap(),
+        vec!["Inconsolata".to_owned()]
     );
 }
 
-#[cfg(all(
-    feature = "source",
-    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
-))]
+#[cfg(feature = "source")]
 #[test]
-pub fn get_glyph_outline() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::None, &mut outline_builder)
-        .unwrap();
+pub fn select_best_match_in_unicode_range() {
+    use font_kit::source::Source;
 
-    let outline = outline_builder.into_outline();
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let families = [
+        FamilyName::Title("Inconsolata".to_owned()),
+        FamilyName::Title("EB Garamond".to_owned()),
+    ];
+    let properties = Properties::new();
+    let postscript_name = |unicode_range: Option<&[std::ops::RangeInclusive<char>]>| {
+        source
+            .select_best_match_in_range(&families, &properties, unicode_range)
+            .map(|handle| handle.load().unwrap().postscript_name().unwrap())
+    };
+
+    assert_eq!(postscript_name(None).unwrap(), "Inconsolata-Regular");
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(193.0, 1120.0),
-                        Vector2F::new(377.0, 1120.0),
-                        Vector2F::new(377.0, 0.0),
-                        Vector2F::new(193.0, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(193.0, 1556.0),
-                        Vector2F::new(377.0, 1556.0),
-                        Vector2F::new(377.0, 1323.0),
-                        Vector2F::new(193.0, 1323.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-            ],
-        }
+        postscript_name(Some(&['A'..='Z'])).unwrap(),
+        "Inconsolata-Regular"
+    );
+    // Only EB Garamond has the thumbs up sign, so Inconsolata is skipped.
+    assert_eq!(
+        postscript_name(Some(&['A'..='Z', '\u{1f44d}'..='\u{1f44d}'])).unwrap(),
+        TEST_FONT_POSTSCRIPT_NAME
     );
+    // Neither font covers CJK ideographs.
+    assert!(postscript_name(Some(&['\u{4e00}'..='\u{4e0f}'])).is_err());
 }
 
-// Right now, only FreeType can do hinting.
-#[cfg(all(
-    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
-    feature = "loader-freetype-default",
-    feature = "source"
-))]
+#[cfg(feature = "source")]
 #[test]
-pub fn get_vertically_hinted_glyph_outline() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
+pub fn select_best_match_with_synthesis() {
+    use font_kit::source::Source;
+
+    // EB Garamond comes in upright and italic, both of regular weight; Inconsolata is upright.
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(
+                "resources/tests/eb-garamond/EBGaramond12-Italic.otf".into(),
+                0,
+            ),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let garamond = [FamilyName::Title("EB Garamond".to_owned())];
+    let inconsolata = [FamilyName::Title("Inconsolata".to_owned())];
+    let italic = *Properties::new().style(Style::Italic);
+
+    let best_match = source
+        .select_best_match_with_synthesis(&inconsolata, &italic)
         .unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::Vertical(16.0), &mut outline_builder)
+    assert_eq!(best_match.properties.style, Style::Normal);
+    assert!(best_match.needs_synthetic_italic);
+    assert!(!best_match.needs_synthetic_bold);
+
+    let best_match = source
+        .select_best_match_with_synthesis(&garamond, &italic)
         .unwrap();
+    assert_eq!(
+        best_match.handle.load().unwrap().postscript_name().unwrap(),
+        "EBGaramond12-Italic"
+    );
+    assert!(!best_match.needs_synthetic_italic);
+    assert!(!best_match.needs_synthetic_bold);
 
-    let outline = outline_builder.into_outline();
+    let best_match = source
+        .select_best_match_with_synthesis(
+            &garamond,
+            Properties::new().style(Style::Italic).weight(Weight::BOLD),
+        )
+        .unwrap();
+    assert!(!best_match.needs_synthetic_italic);
+    assert!(best_match.needs_synthetic_bold);
+
+    // A semibold query is satisfied by nothing lighter than semibold.
+    assert!(matching::needs_synthetic_bold(
+        Properties::new().weight(Weight::MEDIUM),
+        Properties::new().weight(Weight::SEMIBOLD)
+    ));
+    assert!(!matching::needs_synthetic_bold(
+        Properties::new().weight(Weight::SEMIBOLD),
+        Properties::new().weight(Weight::BOLD)
+    ));
+    assert!(!matching::needs_synthetic_italic(
+        Properties::new().style(Style::Oblique),
+        &italic
+    ));
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_by_postscript_name_prefix() {
+    use font_kit::error::SelectionError;
+
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let postscript_names = |prefix: &str| {
+        source
+            .select_by_postscript_name_prefix(prefix)
+            .map(|handles| {
+                handles
+                    .iter()
+                    .map(|handle| handle.load().unwrap().postscript_name().unwrap())
+                    .collect::<Vec<_>>()
+            })
+    };
+
+    // Both faces of the collection share a prefix and come back sorted by name.
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(136.0, 1316.0),
-                        Vector2F::new(136.0, 1536.0),
-                        Vector2F::new(316.0, 1536.0),
-                        Vector2F::new(316.0, 1316.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(136.0, 0.0),
-                        Vector2F::new(136.0, 1152.0),
-                        Vector2F::new(316.0, 1152.0),
-                        Vector2F::new(316.0, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-            ],
-        }
+        postscript_names("EBGaramond12-").unwrap(),
+        vec!["EBGaramond12-Italic", "EBGaramond12-Regular"]
+    );
+    assert_eq!(
+        postscript_names("Inconsolata").unwrap(),
+        vec!["Inconsolata-Regular"]
+    );
+    assert_eq!(postscript_names("").unwrap().len(), 3);
+    // Matching is case-sensitive.
+    assert_eq!(
+        postscript_names("ebgaramond").unwrap_err(),
+        SelectionError::NotFound
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn source_capabilities() {
+    use font_kit::source::{Source, SourceCapabilities};
+
+    let mem_source =
+        MemSource::from_fonts(vec![Handle::from_path(TEST_FONT_FILE_PATH.into(), 0)].into_iter())
+            .unwrap();
+    let capabilities = mem_source.capabilities();
+    assert!(capabilities.contains(SourceCapabilities::POSTSCRIPT_NAME_LOOKUP));
+    assert!(capabilities.contains(SourceCapabilities::POSTSCRIPT_NAME_PREFIX_LOOKUP));
+    assert!(!capabilities.contains(SourceCapabilities::DEFAULT_UI_FONT));
+
+    // A wrapper performs natively what the wrapped source does, and answers the platform
+    // queries the same way.
+    let system_source = SystemSource::new();
+    let caching_source = CachingSource::new(SystemSource::new());
+    assert_eq!(caching_source.capabilities(), system_source.capabilities());
+    assert_eq!(
+        caching_source.default_fallback_families(),
+        system_source.default_fallback_families()
     );
+    let ui_font_name = |source: &dyn Source| {
+        let handle = source.default_ui_font().ok()?;
+        Font::from_handle(&handle).ok()?.postscript_name()
+    };
+    assert_eq!(ui_font_name(&caching_source), ui_font_name(&system_source));
 }
 
 #[cfg(all(
     feature = "source",
-    not(feature = "loader-freetype-default"),
-    not(any(target_os = "macos", target_os = "ios", target_family = "windows"))
+    any(target_os = "macos", target_os = "ios"),
+    not(feature = "loader-freetype-default")
 ))]
 #[test]
-pub fn get_vertically_hinted_glyph_outline() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
+pub fn core_text_source_capabilities() {
+    use font_kit::source::{Source, SourceCapabilities};
+
+    let capabilities = SystemSource::new().capabilities();
+    assert!(capabilities.contains(SourceCapabilities::POSTSCRIPT_NAME_LOOKUP));
+    assert!(capabilities.contains(SourceCapabilities::DEFAULT_UI_FONT));
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn fs_source_skips_duplicate_fonts() {
+    use font_kit::sources::fs::FsSource;
+    use std::env;
+    use std::fs;
+
+    let root = env::temp_dir().join(format!("font-kit-duplicates-{}", std::process::id()));
+    let (first, second) = (root.join("first"), root.join("second"));
+    for directory in &[&first, &second] {
+        fs::create_dir_all(directory).unwrap();
+        fs::copy(
+            FILE_PATH_INCONSOLATA_TTF,
+            directory.join("Inconsolata-Regular.ttf"),
+        )
+        .unwrap();
+    }
+    fs::copy(TEST_FONT_FILE_PATH, first.join("EBGaramond12-Regular.otf")).unwrap();
+
+    let deduplicated = FsSource::in_paths([&first, &second], false);
+    let with_duplicates = FsSource::in_paths([&first, &second], true);
+    let whole_tree = FsSource::in_path(&root);
+    fs::remove_dir_all(&root).unwrap();
+
+    // The first copy found is the one kept.
+    let inconsolata = deduplicated.select_family_by_name("Inconsolata").unwrap();
+    assert_eq!(inconsolata.fonts().len(), 1);
+    match inconsolata.fonts()[0] {
+        Handle::Path { ref path, .. } => assert!(path.starts_with(&first)),
+        ref other => panic!("unexpected handle: {:?}", other),
+    }
+    assert_eq!(deduplicated.all_fonts().unwrap().len(), 2);
+
+    let inconsolata = with_duplicates
+        .select_family_by_name("Inconsolata")
+        .unwrap();
+    assert_eq!(inconsolata.fonts().len(), 2);
+    assert_eq!(with_duplicates.all_fonts().unwrap().len(), 3);
+
+    assert_eq!(whole_tree.all_fonts().unwrap().len(), 2);
+}
+
+// Only the FreeType loader is known to open WOFF fonts.
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn fs_source_indexes_woff_fonts() {
+    use font_kit::sources::fs::{FsFormats, FsSource};
+    use std::env;
+    use std::fs;
+
+    let root = env::temp_dir().join(format!("font-kit-woff-{}", std::process::id()));
+    fs::create_dir_all(&root).unwrap();
+    let font_data = fs::read(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    fs::write(
+        root.join("Inconsolata-Regular.woff"),
+        woff_from_sfnt(&font_data),
+    )
+    .unwrap();
+
+    let all_formats = FsSource::in_path(&root);
+    let sfnt_only = FsSource::in_paths_with_formats([&root], false, FsFormats::SFNT);
+
+    let handle = all_formats
+        .select_by_postscript_name("Inconsolata-Regular")
+        .unwrap();
+    let font = handle.load().unwrap();
+    assert_eq!(font.family_name(), "Inconsolata");
+    assert!(font.glyph_for_char('a').is_some());
+    fs::remove_dir_all(&root).unwrap();
+
+    assert!(sfnt_only.all_fonts().unwrap().is_empty());
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn fs_source_cache_skips_unchanged_files() {
+    use font_kit::sources::fs::FsSource;
+    use font_kit::sources::multi::MultiSource;
+    use std::env;
+    use std::fs;
+    use std::time::Duration;
+
+    let root = env::temp_dir().join(format!("font-kit-cache-{}", std::process::id()));
+    let (font_dir, cache_path) = (root.join("fonts"), root.join("cache").join("index"));
+    fs::create_dir_all(&font_dir).unwrap();
+    let font_path = font_dir.join("Inconsolata-Regular.ttf");
+    let font_data = fs::read(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    fs::write(&font_path, &font_data).unwrap();
+    let modified = fs::metadata(&font_path).unwrap().modified().unwrap();
+
+    let source = FsSource::with_cache([&font_dir], &cache_path);
+    assert!(source
+        .select_by_postscript_name("Inconsolata-Regular")
+        .is_ok());
+    assert!(cache_path.is_file());
+
+    // Replace the font with garbage of the same size and put its modification time back. The
+    // cached entry still matches, so the file must not be parsed again.
+    fs::write(&font_path, vec![0; font_data.len()]).unwrap();
+    let file = File::options().write(true).open(&font_path).unwrap();
+    file.set_modified(modified).unwrap();
+    drop(file);
+    let source = FsSource::with_cache([&font_dir], &cache_path);
+    let handle = source
+        .select_by_postscript_name("Inconsolata-Regular")
+        .unwrap();
+    match handle {
+        Handle::Path { ref path, .. } => assert_eq!(*path, font_path),
+        _ => panic!("Expected a path handle"),
+    }
+    // Matching uses the cached properties instead of loading the garbage.
+    assert!(source
+        .select_best_match(
+            &[FamilyName::Title("Inconsolata".to_owned())],
+            &Properties::new()
+        )
+        .is_ok());
+    // So does matching in a multi-source, which hands the family back to the source it came from.
+    let source = MultiSource::from_sources(vec![
+        Box::new(MemSource::empty()),
+        Box::new(FsSource::with_cache([&font_dir], &cache_path)),
+    ]);
+    assert!(source
+        .select_best_match(
+            &[FamilyName::Title("Inconsolata".to_owned())],
+            &Properties::new()
+        )
+        .is_ok());
+
+    // Once the modification time changes, the file is rescanned and the garbage is dropped.
+    let file = File::options().write(true).open(&font_path).unwrap();
+    file.set_modified(modified + Duration::from_secs(1))
+        .unwrap();
+    drop(file);
+    let source = FsSource::with_cache([&font_dir], &cache_path);
+    assert!(source
+        .select_by_postscript_name("Inconsolata-Regular")
+        .is_err());
+
+    fs::remove_dir_all(&root).unwrap();
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn describe_handle() {
+    use font_kit::error::SelectionError;
+    use font_kit::source::{FaceDescription, Source};
+
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0),
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let handle = source
+        .select_by_postscript_name(TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1])
+        .unwrap();
+    // EB Garamond groups its optical sizes into one family, so the size is part of the style.
+    assert_eq!(
+        source.describe_handle(&handle).unwrap(),
+        FaceDescription {
+            family_name: "EB Garamond".to_owned(),
+            style_name: "12 Italic".to_owned(),
+            postscript_name: Some(TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1].to_owned()),
+            properties: *Properties::new().style(Style::Italic),
+            is_variable: false,
+            is_color: false,
+        }
+    );
+
+    let missing = Handle::from_path("resources/tests/missing.ttf".into(), 0);
+    assert!(matches!(
+        source.describe_handle(&missing),
+        Err(SelectionError::CannotAccessSource { .. })
+    ));
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn all_face_descriptions() {
+    use font_kit::source::Source;
+
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0),
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+            Handle::from_path("resources/tests/type1/FontKitTest.pfb".into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let descriptions = source.all_face_descriptions().unwrap();
+    assert_eq!(descriptions.len(), source.all_fonts().unwrap().len());
+
+    // Fonts described from their tables match fonts described by loading them, and fonts in
+    // other formats are loaded.
+    for (handle, description) in &descriptions {
+        assert_eq!(*description, source.describe_handle(handle).unwrap());
+        assert!(!description.is_variable && !description.is_color);
+    }
+    let italic = descriptions
+        .iter()
+        .find(|(_, description)| {
+            description.postscript_name.as_deref() == Some(TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1])
+        })
+        .unwrap();
+    assert_eq!(italic.1.style_name, "12 Italic");
+    assert_eq!(italic.1.properties.style, Style::Italic);
+}
+
+#[cfg(all(feature = "source", feature = "rayon"))]
+#[test]
+pub fn all_fonts_parallel() {
+    use font_kit::source::Source;
+
+    let source = MemSource::from_fonts(
+        vec![
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0),
+            Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1),
+            Handle::from_path(TEST_FONT_FILE_PATH.into(), 0),
+            Handle::from_path(FILE_PATH_EB_GARAMOND_TTF.into(), 0),
+            Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0),
+        ]
+        .into_iter(),
+    )
+    .unwrap();
+    let expected: Vec<_> = source
+        .all_fonts()
+        .unwrap()
+        .into_iter()
+        .map(|handle| {
+            let description = source.describe_handle(&handle).unwrap();
+            (handle, description)
+        })
+        .collect();
+    let described = source.all_fonts_parallel().unwrap();
+    assert_eq!(described.len(), 5);
+    for ((handle, description), (expected_handle, expected_description)) in
+        described.iter().zip(expected.iter())
+    {
+        assert_eq!(
+            handle.load().unwrap().postscript_name(),
+            expected_handle.load().unwrap().postscript_name()
+        );
+        assert_eq!(description, expected_description);
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_fallback_override() {
+    use font_kit::error::SelectionError;
+    use font_kit::sources::multi::MultiSource;
+
+    let source = MemSource::from_fonts(
+        vec![Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 0)].into_iter(),
+    )
+    .unwrap();
+    let mut source = MultiSource::from_sources(vec![Box::new(source)]);
+    let emoji_font = Handle::from_path(FILE_PATH_INCONSOLATA_TTF.into(), 0);
+    source.add_fallback_override('\u{1f300}'..='\u{1faff}', emoji_font);
+    // A later override for an overlapping range doesn't take precedence.
+    source.add_fallback_override(
+        '\u{1f600}'..='\u{1f64f}',
+        Handle::from_path(FILE_PATH_EB_GARAMOND_TTF.into(), 0),
+    );
+
+    match source.select_fallback_for_char('😀', &Properties::new()) {
+        Ok(Handle::Path { path, font_index }) => {
+            assert_eq!(path.to_str(), Some(FILE_PATH_INCONSOLATA_TTF));
+            assert_eq!(font_index, 0);
+        }
+        other => panic!("unexpected fallback: {:?}", other),
+    }
+    // The memory source can't resolve the generic fallback families.
+    assert!(matches!(
+        source.select_fallback_for_char('A', &Properties::new()),
+        Err(SelectionError::NotFound)
+    ));
+}
+
+#[test]
+pub fn family_name_from_css() {
+    assert_eq!(FamilyName::from_css("serif"), FamilyName::Serif);
+    assert_eq!(FamilyName::from_css("Sans-Serif"), FamilyName::SansSerif);
+    assert_eq!(FamilyName::from_css(" MONOSPACE "), FamilyName::Monospace);
+    assert_eq!(FamilyName::from_css("cursive"), FamilyName::Cursive);
+    assert_eq!(FamilyName::from_css("fantasy"), FamilyName::Fantasy);
+
+    let eb_garamond = FamilyName::Title("EB Garamond".to_owned());
+    assert_eq!(FamilyName::from_css("\"EB Garamond\""), eb_garamond);
+    assert_eq!(FamilyName::from_css("'EB Garamond'"), eb_garamond);
+    assert_eq!(FamilyName::from_css("EB   Garamond"), eb_garamond);
+
+    // Quoting a generic keyword makes it a family name.
+    assert_eq!(
+        FamilyName::from_css("\"serif\""),
+        FamilyName::Title("serif".to_owned())
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_invalid_family_name() {
+    use font_kit::error::SelectionError;
+
+    let mem_source =
+        MemSource::from_fonts(vec![Handle::from_path(TEST_FONT_FILE_PATH.into(), 0)].into_iter())
+            .unwrap();
+    let system_source = SystemSource::new();
+    for family_name in &["", "   ", "\t\n", "EB\0Garamond"] {
+        assert_eq!(
+            mem_source.select_family_by_name(family_name).unwrap_err(),
+            SelectionError::InvalidQuery
+        );
+        assert_eq!(
+            system_source
+                .select_family_by_name(family_name)
+                .unwrap_err(),
+            SelectionError::InvalidQuery
+        );
+        let family_names = [
+            FamilyName::Title(family_name.to_string()),
+            FamilyName::Title("EB Garamond".to_owned()),
+        ];
+        assert_eq!(
+            mem_source
+                .select_best_match(&family_names, &Properties::new())
+                .unwrap_err(),
+            SelectionError::InvalidQuery
+        );
+    }
+
+    // A genuine miss is still reported as such.
+    assert_eq!(
+        mem_source.select_family_by_name("Nonexistent").unwrap_err(),
+        SelectionError::NotFound
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn select_generic_families() {
+    use font_kit::source::Source;
+
+    let source = SystemSource::new();
+    for family_name in &[
+        FamilyName::SansSerif,
+        FamilyName::Serif,
+        FamilyName::Monospace,
+    ] {
+        let family = source.select_family_by_generic_name(family_name).unwrap();
+        assert!(!family.is_empty());
+        assert!(family.fonts()[0].load().is_ok());
+    }
+}
+
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn select_generic_family_fallback_chain() {
+    let source = SystemSource::new();
+    let families = source
+        .select_families_by_generic_name(&FamilyName::Serif)
+        .unwrap();
+    assert!(families.len() > 1);
+    assert!(families.iter().all(|family| !family.is_empty()));
+    // The first family is the one that the generic name maps to on its own.
+    assert_eq!(
+        families[0].display_name(),
+        source
+            .select_family_by_name("serif")
+            .unwrap()
+            .display_name()
+    );
+
+    let title = FamilyName::Title("DejaVu Serif".to_owned());
+    assert_eq!(
+        source
+            .select_families_by_generic_name(&title)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn get_default_ui_font_and_fallback_families() {
+    use font_kit::source::Source;
+
+    let source = SystemSource::new();
+    let font = source.default_ui_font().unwrap().load().unwrap();
+    assert!(font.glyph_for_char('A').is_some());
+
+    let fallback_families = source.default_fallback_families();
+    assert!(!fallback_families.is_empty());
+    for family_name in &fallback_families {
+        assert!(source.select_family_by_generic_name(family_name).is_ok());
+    }
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn caching_source_is_send_and_sync() {
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<CachingSource<MemSource>>();
+}
+
+#[test]
+pub fn get_handles_for_all_faces_at_path() {
+    use font_kit::handle::Handle;
+
+    let handles = Handle::all_faces_at_path(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    let postscript_names: Vec<_> = handles
+        .iter()
+        .map(|handle| {
+            match *handle {
+                Handle::Path { ref path, .. } => {
+                    assert_eq!(path.to_str(), Some(TEST_FONT_COLLECTION_FILE_PATH))
+                }
+                _ => panic!("Expected a path handle"),
+            }
+            handle.load().unwrap().postscript_name().unwrap()
+        })
+        .collect();
+    assert_eq!(
+        postscript_names,
+        ["EBGaramond12-Regular", "EBGaramond12-Italic"]
+    );
+
+    let handles = Handle::all_faces_at_path(TEST_FONT_FILE_PATH).unwrap();
+    assert_eq!(handles.len(), 1);
+    assert!(Handle::all_faces_at_path("resources/tests/no-such-font.ttf").is_err());
+}
+
+#[test]
+pub fn check_handles() {
+    use font_kit::handle::Handle;
+    use std::io::ErrorKind;
+
+    assert!(Handle::from_path(TEST_FONT_FILE_PATH.into(), 0)
+        .check()
+        .is_ok());
+    assert!(Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 1)
+        .check()
+        .is_ok());
+    match Handle::from_path(TEST_FONT_COLLECTION_FILE_PATH.into(), 2).check() {
+        Err(FontLoadingError::NoSuchFontInCollection) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+    match Handle::from_path("resources/tests/no-such-font.ttf".into(), 0).check() {
+        Err(FontLoadingError::Io(error)) => assert_eq!(error.kind(), ErrorKind::NotFound),
+        other => panic!("unexpected result: {:?}", other),
+    }
+
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    assert!(Handle::from_memory(Arc::new(font_data), 0).check().is_ok());
+    match Handle::from_memory(Arc::new(b"not a font".to_vec()), 0).check() {
+        Err(FontLoadingError::UnknownFormat) => {}
+        other => panic!("unexpected result: {:?}", other),
+    }
+}
+
+#[test]
+pub fn load_corrupt_font_path_reports_path() {
+    use font_kit::handle::Handle;
+    use std::env;
+    use std::error::Error;
+    use std::fs;
+
+    let path = env::temp_dir().join(format!("font-kit-corrupt-{}.ttf", std::process::id()));
+    fs::write(&path, b"not a font").unwrap();
+    let result = Handle::from_path(path.clone(), 3).load();
+    fs::remove_file(&path).unwrap();
+
+    let error = result.err().unwrap();
+    match error {
+        FontLoadingError::InFile {
+            path: ref error_path,
+            font_index: 3,
+            ..
+        } => assert_eq!(*error_path, path),
+        ref other => panic!("unexpected error: {:?}", other),
+    }
+    assert!(error.to_string().contains(&*path.to_string_lossy()));
+    assert!(matches!(
+        error.root_cause(),
+        FontLoadingError::UnknownFormat | FontLoadingError::Parse
+    ));
+    assert!(error.source().is_some());
+}
+
+#[test]
+pub fn load_font_from_file() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[test]
+pub fn load_font_from_memory() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let mut font_data = vec![];
+    file.read_to_end(&mut font_data).unwrap();
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[test]
+pub fn load_font_from_shared_memory() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let mut font_data = vec![];
+    file.read_to_end(&mut font_data).unwrap();
+    let shared_data: Box<[u8]> = font_data.clone().into_boxed_slice();
+    let font = Font::from_shared_bytes(Arc::new(shared_data), 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+    assert!(font.glyph_for_char('a').is_some());
+
+    // The buffer must stay alive for clones that outlive the original font.
+    let font = font.clone();
+    assert_eq!(*font.copy_font_data().unwrap(), font_data);
+}
+
+#[test]
+pub fn get_cached_font_description() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let description = |font: &Font| {
+        (
+            font.postscript_name(),
+            font.full_name(),
+            font.family_name(),
+            font.properties(),
+        )
+    };
+    let first = description(&font);
+    assert_eq!(first.0.as_deref(), Some(TEST_FONT_POSTSCRIPT_NAME));
+    assert_eq!(description(&font), first);
+    assert_eq!(description(&font.clone()), first);
+}
+
+#[test]
+pub fn get_name_strings() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert_eq!(font.designer().as_deref(), Some("Raph Levien, Cyreal"));
+    assert!(font
+        .license()
+        .unwrap()
+        .starts_with("This Font Software is licensed under the SIL Open Font License"));
+    assert_eq!(font.name_string(6), font.postscript_name());
+    assert_eq!(font.sample_text(), None);
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(font.designer(), None);
+    assert_eq!(font.name_string(1).as_deref(), Some("EB Garamond 12"));
+    assert_eq!(font.name_string(0xffff), None);
+}
+
+#[test]
+pub fn analyze_file() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    assert_eq!(Font::analyze_file(&mut file).unwrap(), FileType::Single);
+}
+
+#[test]
+pub fn analyze_bytes() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let mut font_data = vec![];
+    file.read_to_end(&mut font_data).unwrap();
+    assert_eq!(
+        Font::analyze_bytes(Arc::new(font_data)).unwrap(),
+        FileType::Single
+    );
+}
+
+#[test]
+pub fn native_font_round_trip() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let native_font = font.native_font();
+    let font = unsafe { Font::from_native_font(&native_font) };
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[cfg(target_os = "macos")]
+#[test]
+pub fn load_font_from_core_text_font() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let font = Font::from_core_text_font(font.native_font());
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[cfg(target_family = "windows")]
+#[test]
+pub fn load_font_from_dwrite_font_face() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let font = Font::from_dwrite_font_face(font.native_font().dwrite_font_face).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), TEST_FONT_POSTSCRIPT_NAME);
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn get_glyph_for_char() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(glyph, 68);
+}
+
+#[test]
+pub fn get_glyph_for_char_or_notdef() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(
+        font.glyph_for_char_or_notdef('a'),
+        font.glyph_for_char('a').unwrap()
+    );
+    // EB Garamond has no Devanagari.
+    assert_eq!(font.glyph_for_char('\u{0915}'), None);
+    assert_eq!(font.glyph_for_char_or_notdef('\u{0915}'), 0);
+}
+
+#[test]
+pub fn covers_char() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.covers_char('a'));
+    assert!(!font.covers_char('\u{0915}'));
+}
+
+#[test]
+pub fn rank_fonts_by_coverage() {
+    let garamond = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let inconsolata = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+
+    // EB Garamond covers Greek and Cyrillic; Inconsolata covers only the Latin letters,
+    // punctuation, and spaces.
+    let text = "Hello, Ωμέγα Привет";
+    assert!(garamond.covers_all(text.chars()));
+    assert!(!inconsolata.covers_all(text.chars()));
+    assert_eq!(garamond.coverage_ratio(text.chars()), 1.0);
+    assert_eq!(inconsolata.coverage_ratio(text.chars()), 8.0 / 19.0);
+
+    let mut fonts = [&inconsolata, &garamond];
+    fonts.sort_by(|a, b| {
+        b.coverage_ratio(text.chars())
+            .partial_cmp(&a.coverage_ratio(text.chars()))
+            .unwrap()
+    });
+    assert_eq!(
+        fonts[0].postscript_name().as_deref(),
+        Some(TEST_FONT_POSTSCRIPT_NAME)
+    );
+
+    assert!(inconsolata.covers_all("".chars()));
+    assert_eq!(inconsolata.coverage_ratio("".chars()), 1.0);
+}
+
+#[test]
+pub fn score_script_coverage() {
+    let garamond = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let inconsolata = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+
+    // Inconsolata covers nearly all of the Latin repertoire, but no Cyrillic.
+    assert!(inconsolata.script_coverage(ScriptTag::LATIN) > 0.9);
+    assert_eq!(inconsolata.script_coverage(ScriptTag::CYRILLIC), 0.0);
+    assert_eq!(garamond.script_coverage(ScriptTag::CYRILLIC), 1.0);
+    assert_eq!(garamond.script_coverage(ScriptTag::HAN), 0.0);
+    assert_eq!(garamond.script_coverage(ScriptTag::DEFAULT), 0.0);
+}
+
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+pub fn covers_char_mapped_to_notdef() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let glyph_a = Font::from_bytes(Arc::new(font_data.clone()), 0)
+        .unwrap()
+        .glyph_for_char('A')
+        .unwrap();
+
+    // A format 4 `cmap` that maps `A` to its usual glyph and every character from U+0100 to
+    // U+024F to `.notdef`, through a glyph ID array of zeros.
+    let segments: [(u16, u16, u16, u16); 3] = [
+        (0x0041, 0x0041, (glyph_a as u16).wrapping_sub(0x41), 0),
+        (0x0100, 0x024f, 0, 4),
+        (0xffff, 0xffff, 1, 0),
+    ];
+    let mut subtable: Vec<u16> = vec![4, 0, 0, 6, 4, 1, 2];
+    subtable.extend(segments.iter().map(|segment| segment.1));
+    subtable.push(0);
+    subtable.extend(segments.iter().map(|segment| segment.0));
+    subtable.extend(segments.iter().map(|segment| segment.2));
+    subtable.extend(segments.iter().map(|segment| segment.3));
+    subtable.extend(std::iter::repeat(0).take(0x150));
+    subtable[1] = (subtable.len() * 2) as u16;
+    let mut cmap = vec![0, 0, 0, 1, 0, 3, 0, 1, 0, 0, 0, 12];
+    cmap.extend(subtable.iter().flat_map(|value| value.to_be_bytes()));
+    replace_font_table(&mut font_data, b"cmap", b"cmaX", &[]);
+    replace_font_table(&mut font_data, b"GSUB", b"cmap", &cmap);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.glyph_for_char('A'), Some(glyph_a));
+    assert!(font.covers_char('A'));
+    assert!(!font.covers_char('\u{0100}'));
+    assert!(!font.covers_char('\u{024f}'));
+    assert!(!font.covers_char('B'));
+}
+
+#[cfg(all(
+    feature = "source",
+    any(target_family = "windows", target_os = "macos")
+))]
+#[test]
+pub fn get_glyph_outline() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(136.0, 1259.0),
+                        Vector2F::new(136.0, 1466.0),
+                        Vector2F::new(316.0, 1466.0),
+                        Vector2F::new(316.0, 1259.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(136.0, 0.0),
+                        Vector2F::new(136.0, 1062.0),
+                        Vector2F::new(316.0, 1062.0),
+                        Vector2F::new(316.0, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+            ],
+        }
+    );
+}
+
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn get_glyph_outline() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(193.0, 1120.0),
+                        Vector2F::new(377.0, 1120.0),
+                        Vector2F::new(377.0, 0.0),
+                        Vector2F::new(193.0, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(193.0, 1556.0),
+                        Vector2F::new(377.0, 1556.0),
+                        Vector2F::new(377.0, 1323.0),
+                        Vector2F::new(193.0, 1323.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+            ],
+        }
+    );
+}
+
+// Right now, only FreeType can do hinting.
+#[cfg(all(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype-default",
+    feature = "source"
+))]
+#[test]
+pub fn get_vertically_hinted_glyph_outline() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::Vertical(16.0), &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(136.0, 1316.0),
+                        Vector2F::new(136.0, 1536.0),
+                        Vector2F::new(316.0, 1536.0),
+                        Vector2F::new(316.0, 1316.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(136.0, 0.0),
+                        Vector2F::new(136.0, 1152.0),
+                        Vector2F::new(316.0, 1152.0),
+                        Vector2F::new(316.0, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+            ],
+        }
+    );
+}
+
+#[cfg(all(
+    feature = "source",
+    not(feature = "loader-freetype-default"),
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows"))
+))]
+#[test]
+pub fn get_vertically_hinted_glyph_outline() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
     font.outline(glyph, HintingOptions::Vertical(16.0), &mut outline_builder)
         .unwrap();
 
-    let outline = outline_builder.into_outline();
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(256.0, 1152.0),
+                        Vector2F::new(384.0, 1152.0),
+                        Vector2F::new(384.0, 0.0),
+                        Vector2F::new(256.0, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(256.0, 1536.0),
+                        Vector2F::new(384.0, 1536.0),
+                        Vector2F::new(384.0, 1280.0),
+                        Vector2F::new(256.0, 1280.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+            ],
+        }
+    );
+}
+
+// Right now, only FreeType can do hinting.
+#[cfg(all(
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+    feature = "loader-freetype-default",
+    feature = "source"
+))]
+#[test]
+pub fn get_fully_hinted_glyph_outline() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::Full(10.0), &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(137.6, 1228.8),
+                        Vector2F::new(137.6, 1433.6),
+                        Vector2F::new(316.80002, 1433.6),
+                        Vector2F::new(316.80002, 1228.8),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(137.6, 0.0),
+                        Vector2F::new(137.6, 1024.0),
+                        Vector2F::new(316.80002, 1024.0),
+                        Vector2F::new(316.80002, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 4],
+                },
+            ],
+        }
+    );
+}
+
+#[cfg(all(
+    feature = "source",
+    not(feature = "loader-freetype-default"),
+    not(any(target_os = "macos", target_os = "ios", target_family = "windows"))
+))]
+#[test]
+pub fn get_fully_hinted_glyph_outline() {
+    let mut file = File::open(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::Full(10.0), &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(
+        outline,
+        Outline {
+            contours: vec![
+                Contour {
+                    positions: vec![
+                        Vector2F::new(100.0, 100.0),
+                        Vector2F::new(200.0, 100.0),
+                        Vector2F::new(200.0, 400.0),
+                        Vector2F::new(100.0, 400.0),
+                        Vector2F::new(100.0, 500.0),
+                        Vector2F::new(300.0, 500.0),
+                        Vector2F::new(300.0, 100.0),
+                        Vector2F::new(400.0, 100.0),
+                        Vector2F::new(400.0, 0.0),
+                        Vector2F::new(100.0, 0.0),
+                    ],
+                    flags: vec![PointFlags::empty(); 10],
+                },
+                Contour {
+                    positions: vec![
+                        Vector2F::new(200.0, 600.0),
+                        Vector2F::new(200.0, 600.0),
+                        Vector2F::new(200.0, 600.0),
+                        Vector2F::new(200.0, 600.0),
+                        Vector2F::new(200.0, 600.0),
+                        Vector2F::new(200.0, 700.0),
+                        Vector2F::new(200.0, 700.0),
+                        Vector2F::new(200.0, 700.0),
+                        Vector2F::new(200.0, 700.0),
+                        Vector2F::new(300.0, 700.0),
+                        Vector2F::new(300.0, 700.0),
+                        Vector2F::new(300.0, 700.0),
+                        Vector2F::new(300.0, 600.0),
+                        Vector2F::new(300.0, 600.0),
+                        Vector2F::new(300.0, 600.0),
+                        Vector2F::new(300.0, 600.0),
+                        Vector2F::new(200.0, 600.0),
+                    ],
+                    flags: vec![
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                        PointFlags::CONTROL_POINT_0,
+                        PointFlags::empty(),
+                    ],
+                },
+            ],
+        }
+    );
+}
+
+#[test]
+pub fn get_empty_glyph_outline() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+
+    let outline = outline_builder.into_outline();
+    assert_eq!(outline, Outline::new());
+}
+
+#[test]
+pub fn get_cached_glyph_outline() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph = font.glyph_for_char('J').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+    let expected = outline_builder.into_outline();
+
+    let caching_font = CachingFont::with_capacity(font, 2);
+    for _ in 0..2 {
+        let mut outline_builder = OutlineBuilder::new();
+        caching_font
+            .outline(glyph, HintingOptions::None, &mut outline_builder)
+            .unwrap();
+        assert_eq!(outline_builder.into_outline(), expected);
+        assert_eq!(caching_font.cached_outline_count(), 1);
+    }
+
+    // Different hinting options get their own entries, and the oldest entry is evicted.
+    for &hinting in &[HintingOptions::Full(12.0), HintingOptions::Full(16.0)] {
+        caching_font
+            .outline(glyph, hinting, &mut OutlineBuilder::new())
+            .unwrap();
+    }
+    assert_eq!(caching_font.cached_outline_count(), 2);
+
+    caching_font.clear_outline_cache();
+    assert_eq!(caching_font.cached_outline_count(), 0);
+}
+
+// https://github.com/servo/font-kit/issues/141
+#[test]
+pub fn get_glyph_raster_bounds() {
+    let mut file = File::open(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char('J').expect("No glyph for char!");
+    let transform = Transform2F::default();
+    let size = 32.0;
+    let hinting_options = HintingOptions::None;
+    let rasterization_options = RasterizationOptions::GrayscaleAa;
+    #[cfg(not(target_family = "windows"))]
+    let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 21));
+    #[cfg(target_family = "windows")]
+    let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 20));
+    assert_eq!(
+        font.raster_bounds(
+            glyph,
+            size,
+            transform,
+            hinting_options,
+            rasterization_options
+        ),
+        Ok(expected_rect)
+    );
+}
+
+#[cfg(all(
+    feature = "source",
+    any(target_family = "windows", target_os = "macos", target_os = "ios")
+))]
+#[test]
+pub fn get_glyph_typographic_bounds() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(
+        font.typographic_bounds(glyph),
+        Ok(RectF::new(
+            Vector2F::new(74.0, -24.0),
+            Vector2F::new(978.0, 1110.0)
+        ))
+    );
+}
+
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn get_glyph_typographic_bounds() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(
+        font.typographic_bounds(glyph),
+        Ok(RectF::new(
+            Vector2F::new(123.0, -29.0),
+            Vector2F::new(946.0, 1176.0)
+        ))
+    );
+}
+
+#[test]
+pub fn get_glyph_vertical_extents() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let extents = |character| {
+        let glyph = font.glyph_for_char(character).expect("No glyph for char!");
+        font.glyph_vertical_extents(glyph).unwrap()
+    };
+    let (x_ascent, x_descent) = extents('x');
+    let (h_ascent, h_descent) = extents('H');
+    assert!(x_ascent > 0.0 && x_ascent < h_ascent);
+    // Round glyphs overshoot the baseline slightly, but only descenders reach far below it.
+    let (p_ascent, p_descent) = extents('p');
+    assert!(p_ascent < h_ascent);
+    assert!(x_descent < p_descent / 4.0 && h_descent < p_descent / 4.0);
+    assert!(h_ascent < font.metrics().ascent);
+}
+
+#[cfg(all(feature = "source", target_family = "windows"))]
+#[test]
+pub fn get_glyph_advance_and_origin() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1139.0, 0.0)));
+    assert_eq!(font.origin(glyph), Ok(Vector2F::new(74.0, 1898.0)));
+}
+
+#[cfg(all(feature = "source", target_os = "macos"))]
+#[test]
+pub fn get_glyph_advance_and_origin() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1139.0, 0.0)));
+    assert_eq!(font.origin(glyph), Ok(Vector2F::default()));
+}
+
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn get_glyph_advance_and_origin() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1255.0, 0.0)));
+    assert_eq!(font.origin(glyph), Ok(Vector2F::default()));
+}
+
+#[cfg(all(
+    feature = "source",
+    any(target_family = "windows", target_os = "macos")
+))]
+#[test]
+pub fn get_font_metrics() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let metrics = font.metrics();
+    assert_eq!(metrics.units_per_em, 2048);
+    assert_eq!(metrics.ascent, 1854.0);
+    assert_eq!(metrics.descent, -434.0);
+    assert_eq!(metrics.line_gap, 67.0);
+    assert_eq!(metrics.underline_position, -217.0);
+    assert_eq!(metrics.underline_thickness, 150.0);
+    assert_eq!(metrics.cap_height, 1467.0);
+    assert_eq!(metrics.x_height, 1062.0);
+
+    // Different versions of the font can have different max heights, so ignore that.
+    let bounding_box = metrics.bounding_box;
+    assert_eq!(bounding_box.origin(), Vector2F::new(-1361.0, -665.0));
+    assert_eq!(bounding_box.width(), 5457.0);
+}
+
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+#[test]
+pub fn get_font_metrics() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let metrics = font.metrics();
+    assert_eq!(metrics.units_per_em, 2048);
+    assert_eq!(metrics.ascent, 1901.0);
+    assert_eq!(metrics.descent, -483.0);
+    assert_eq!(metrics.line_gap, 0.0); // FIXME(pcwalton): Huh?!
+    assert_eq!(metrics.underline_position, -40.0);
+    assert_eq!(metrics.underline_thickness, 90.0);
+    // This font's `OS/2` table leaves these zero, so they are measured from the glyph outlines.
+    assert_eq!(metrics.cap_height, 1493.0);
+    assert_eq!(metrics.x_height, 1120.0);
+    assert_eq!(
+        metrics.bounding_box,
+        RectF::new(
+            Vector2F::new(-2090.0, -948.0),
+            Vector2F::new(5763.0, 3472.0)
+        )
+    );
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn get_font_properties() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let properties = font.properties();
+    assert_eq!(properties.weight, Weight(400.0));
+    assert_eq!(properties.stretch, Stretch(1.0));
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn load_font_table() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let head_table = font
+        .load_font_table(OPENTYPE_TABLE_TAG_HEAD)
+        .expect("Where's the `head` table?");
+    assert_eq!(&head_table[12..16], &[0x5f, 0x0f, 0x3c, 0xf5]);
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn rasterize_glyph_with_grayscale_aa() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(-raster_rect.origin().to_f32()),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    check_L_shape(&canvas);
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn rasterize_glyph_bilevel() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 16.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(-raster_rect.origin().to_f32()),
+        HintingOptions::None,
+        RasterizationOptions::Bilevel,
+    )
+    .unwrap();
+    assert!(canvas
+        .pixels
+        .iter()
+        .all(|&value| value == 0 || value == 0xff));
+    check_L_shape(&canvas);
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn rasterize_glyph_bilevel_offset() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::from_translation(Vector2F::new(30.0, 100.0)),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(-raster_rect.origin().to_f32() + Vector2F::new(30.0, 100.0)),
+        HintingOptions::None,
+        RasterizationOptions::Bilevel,
+    )
+    .unwrap();
+
+    assert!(canvas
+        .pixels
+        .iter()
+        .all(|&value| value == 0 || value == 0xff));
+    check_L_shape(&canvas);
+}
+
+#[cfg(all(
+    feature = "source",
+    any(
+        not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
+        feature = "loader-freetype-default"
+    )
+))]
+#[test]
+#[allow(clippy::manual_contains)]
+pub fn rasterize_glyph_with_full_hinting() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+    let origin = -raster_rect.origin().to_f32();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(origin),
+        HintingOptions::Full(size),
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    check_L_shape(&canvas);
+
+    // Make sure the top and bottom (non-blank) rows have some fully black pixels in them.
+    let mut top_row = &canvas.pixels[0..canvas.stride];
+    if top_row.iter().all(|&value| value == 0) {
+        top_row = &canvas.pixels[canvas.stride..(2 * canvas.stride)];
+    }
+
+    assert!(top_row.iter().any(|&value| value == 0xff));
+    for y in (0..(canvas.size.y() as usize)).rev() {
+        let bottom_row = &canvas.pixels[(y * canvas.stride)..((y + 1) * canvas.stride)];
+        if bottom_row.iter().all(|&value| value == 0) {
+            continue;
+        }
+        assert!(bottom_row.iter().any(|&value| value == 0xff));
+        break;
+    }
+}
+
+#[cfg(all(feature = "source", target_family = "windows"))]
+#[test]
+pub fn rasterize_glyph() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('{').unwrap();
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        Transform2F::from_translation(-raster_rect.origin().to_f32()),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    check_curly_shape(&canvas);
+}
+
+// Tests that an empty glyph can be successfully rasterized to a 0x0 canvas (issue #7).
+#[cfg(feature = "source")]
+#[test]
+pub fn rasterize_empty_glyph() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
+    let mut canvas = Canvas::new(Vector2I::splat(16), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph,
+        16.0,
+        Transform2F::default(),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+}
+
+// Tests that an empty glyph can be successfully rasterized to a 0x0 canvas (issue #7).
+#[cfg(feature = "source")]
+#[test]
+pub fn rasterize_empty_glyph_on_empty_canvas() {
+    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_file(&mut file, 0).unwrap();
+    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
+    let size = 32.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph,
+            size,
+            Transform2F::default(),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph,
+        size,
+        Transform2F::from_translation(-raster_rect.origin().to_f32()),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+}
+
+#[cfg(feature = "source")]
+#[test]
+pub fn font_transform() {
+    let font = SystemSource::new()
+        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+        .unwrap()
+        .load()
+        .unwrap();
+    let glyph_id = font.glyph_for_char('L').unwrap();
+    let size = 16.0;
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::from_translation(Vector2F::splat(8.0)),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+    let raster_rect2 = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            Transform2F::row_major(3.0, 0.0, 0.0, 3.0, 8.0, 8.0),
+            HintingOptions::None,
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+    assert!((raster_rect2.width() - raster_rect.width() * 3).abs() <= 3);
+    assert!((raster_rect2.height() - raster_rect.height() * 3).abs() <= 3);
+    assert!((raster_rect2.origin_x() - ((raster_rect.origin_x() - 8) * 3 + 8)).abs() <= 3);
+    assert!((raster_rect2.origin_y() - ((raster_rect.origin_y() - 8) * 3 + 8)).abs() <= 3);
+}
+
+#[test]
+fn try_new_canvas() {
+    use font_kit::error::CanvasError;
+
+    let empty = RectI::new(Vector2I::new(3, 4), Vector2I::zero());
+    assert_eq!(
+        Canvas::try_new(empty.size(), Format::A8).err(),
+        Some(CanvasError::EmptySize)
+    );
+    assert_eq!(
+        Canvas::try_new(Vector2I::new(8, -1), Format::A8).err(),
+        Some(CanvasError::EmptySize)
+    );
+    assert_eq!(
+        Canvas::try_new(Vector2I::new(Canvas::MAX_DIMENSION + 1, 1), Format::A8).err(),
+        Some(CanvasError::TooLarge)
+    );
+
+    let canvas = Canvas::try_new(Vector2I::new(5, 3), Format::Rgb24).unwrap();
+    assert_eq!(canvas.stride, 15);
+    assert_eq!(canvas.pixels, vec![0; 45]);
+    assert_eq!(canvas.format, Format::Rgb24);
+}
+
+#[test]
+fn fill_and_clear_canvas() {
+    let mut canvas = Canvas::new(Vector2I::new(4, 3), Format::Rgb24);
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(2, 1), Vector2I::new(8, 8)),
+        &[1, 2, 3],
+    );
+    assert_eq!(&canvas.pixels[0..12], &[0; 12]);
+    assert_eq!(
+        &canvas.pixels[12..24],
+        &[0, 0, 0, 0, 0, 0, 1, 2, 3, 1, 2, 3]
+    );
+    assert_eq!(
+        &canvas.pixels[24..36],
+        &[0, 0, 0, 0, 0, 0, 1, 2, 3, 1, 2, 3]
+    );
+
+    canvas.clear();
+    assert!(canvas.pixels.iter().all(|&value| value == 0));
+}
+
+#[test]
+fn crop_canvas_to_ink_bounds() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let mut canvas = Canvas::with_stride(Vector2I::splat(64), 80, Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        32.0,
+        Transform2F::from_translation(Vector2F::new(16.0, 40.0)),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+
+    let ink_bounds = canvas.ink_bounds();
+    assert!(ink_bounds.width() > 0 && ink_bounds.height() > 0);
+    assert!(ink_bounds.width() < 64 && ink_bounds.height() < 64);
+    let cropped = canvas.crop(ink_bounds);
+    assert_eq!(cropped.size, ink_bounds.size());
+    assert_eq!(
+        cropped.ink_bounds(),
+        RectI::new(Vector2I::default(), ink_bounds.size())
+    );
+
+    // Every inked pixel survives the crop.
+    let ink = |canvas: &Canvas| {
+        let width = canvas.size.x() as usize;
+        let rows = canvas.pixels.chunks(canvas.stride);
+        rows.flat_map(|row| row[..width].iter().copied())
+            .filter(|&coverage| coverage != 0)
+            .collect::<Vec<u8>>()
+    };
+    assert_eq!(ink(&cropped), ink(&canvas));
+}
+
+#[test]
+fn rasterize_glyph_at_subpixel_origins() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('l').unwrap();
+    let rasterize = |x| {
+        let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            24.0,
+            Transform2F::from_translation(Vector2F::new(8.0 + x, 24.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        canvas.pixels
+    };
+
+    let pixels = rasterize(0.0);
+    assert_ne!(rasterize(0.33), pixels);
+
+    // Origins that round to the same position render identically.
+    if let Some(positions) = font.subpixel_positions(RasterizationOptions::GrayscaleAa) {
+        let positions = positions as f32;
+        assert_eq!(rasterize(0.25 / positions), pixels);
+        assert_eq!(
+            rasterize(0.33),
+            rasterize((0.33 * positions).round() / positions)
+        );
+    }
+}
+
+#[test]
+fn rasterize_glyph_at_fractional_origin() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('l').unwrap();
+    let rasterize = |x| {
+        font.rasterize_glyph_at(
+            glyph_id,
+            24.0,
+            Vector2F::new(x, 30.0),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap()
+    };
+
+    let (canvas, position) = rasterize(10.0);
+    assert_eq!(canvas.format, Format::A8);
+    assert!(canvas.pixels.contains(&255));
+    // The stem of the 'l' sits right of the origin and rises above the baseline.
+    assert!(position.x() >= 9 && position.x() < 10 + canvas.size.x());
+    assert!(position.y() < 30 && position.y() + canvas.size.y() > 30);
+
+    // A whole-pixel move only moves the canvas.
+    let (moved_canvas, moved_position) = rasterize(13.0);
+    assert_eq!(moved_canvas.pixels, canvas.pixels);
+    assert_eq!(moved_position, position + Vector2I::new(3, 0));
+
+    // A half-pixel move changes the coverage.
+    let (half_canvas, half_position) = rasterize(10.5);
+    assert_eq!(half_position.y(), position.y());
+    assert!(half_canvas.size != canvas.size || half_canvas.pixels != canvas.pixels);
+}
+
+#[test]
+fn build_raster_transform() {
+    let origin = Vector2F::new(10.5, 20.0);
+    assert_eq!(
+        RasterTransform::build(12.0, 72.0, origin, None),
+        Transform2F::from_translation(origin)
+    );
+
+    // A 12-point glyph at 144 DPI, skewed and raised by half an em.
+    let oblique = Transform2F::row_major(1.0, -0.25, 0.0, 1.0, 0.0, -0.5);
+    let expected = Transform2F::row_major(2.0, -0.5, 0.0, 2.0, 10.5, 8.0);
+    assert_eq!(
+        RasterTransform::build(12.0, 144.0, origin, Some(oblique)),
+        expected
+    );
+}
+
+#[test]
+fn rasterize_glyph_with_non_uniform_scale() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('H').unwrap();
+    // Rasterizes the glyph into a canvas with two pixels of padding around its raster bounds
+    // and returns the bounding box of the inked pixels.
+    let ink_bounds = |scale: Vector2F| {
+        let transform = Transform2F::from_scale(scale);
+        let hinting = HintingOptions::Full(24.0);
+        let raster_bounds = font
+            .raster_bounds(
+                glyph_id,
+                24.0,
+                transform,
+                hinting,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        let mut canvas = Canvas::new(raster_bounds.size() + Vector2I::splat(4), Format::A8);
+        let origin = Vector2I::splat(2) - raster_bounds.origin();
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            24.0,
+            Transform2F::from_translation(origin.to_f32()) * transform,
+            hinting,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+
+        let (mut min, mut max) = (canvas.size, Vector2I::zero());
+        for y in 0..canvas.size.y() {
+            for x in 0..canvas.size.x() {
+                if canvas.pixels[y as usize * canvas.stride + x as usize] != 0 {
+                    min = min.min(Vector2I::new(x, y));
+                    max = max.max(Vector2I::new(x + 1, y + 1));
+                }
+            }
+        }
+        // Nothing is drawn in the padding, so the glyph wasn't clipped or shifted.
+        assert!(min.x() >= 1 && min.y() >= 1);
+        assert!(max.x() < canvas.size.x() && max.y() < canvas.size.y());
+        RectI::from_points(min, max)
+    };
+
+    let uniform = ink_bounds(Vector2F::splat(1.0));
+    let extended = ink_bounds(Vector2F::new(2.0, 1.0));
+    assert!((extended.width() - 2 * uniform.width()).abs() <= 2);
+    assert!((extended.height() - uniform.height()).abs() <= 1);
+    let condensed = ink_bounds(Vector2F::new(0.5, 1.0));
+    assert!((2 * condensed.width() - uniform.width()).abs() <= 2);
+    assert!((condensed.height() - uniform.height()).abs() <= 1);
+}
+
+#[test]
+fn rasterize_glyph_stable() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('O').unwrap();
+    let transform = Transform2F::from_translation(Vector2F::new(8.0, 48.0));
+    let rasterize = |format, rasterization_options| {
+        let mut canvas = Canvas::new(Vector2I::splat(64), format);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            48.0,
+            transform,
+            HintingOptions::Full(48.0),
+            rasterization_options,
+        )
+        .unwrap();
+        canvas
+    };
+
+    let stable = rasterize(Format::A8, RasterizationOptions::StableGrayscaleAa);
+    assert_eq!(
+        stable.pixels,
+        rasterize(Format::A8, RasterizationOptions::StableGrayscaleAa).pixels
+    );
+
+    // The stroke is solid and the bowl is empty.
+    let bounds = font
+        .raster_bounds(
+            glyph_id,
+            48.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::StableGrayscaleAa,
+        )
+        .unwrap();
+    let center = ((bounds.origin() + bounds.lower_right()).to_f32() * 0.5).to_i32();
+    assert_eq!(
+        stable.pixels[center.y() as usize * 64 + center.x() as usize],
+        0
+    );
+    assert!(stable.pixels.contains(&255));
+
+    // The total coverage is close to what the platform rasterizer produces.
+    let total = |canvas: &Canvas| canvas.pixels.iter().map(|&value| value as u32).sum::<u32>();
+    let platform = rasterize(Format::A8, RasterizationOptions::GrayscaleAa);
+    let (stable_total, platform_total) = (total(&stable) as f32, total(&platform) as f32);
+    assert!((stable_total - platform_total).abs() < platform_total * 0.05);
+
+    // Other canvas formats get the same coverage.
+    let rgba = rasterize(Format::Rgba32, RasterizationOptions::StableGrayscaleAa);
+    for (&coverage, pixel) in stable.pixels.iter().zip(rgba.pixels.chunks(4)) {
+        assert_eq!(pixel[3], coverage);
+    }
+}
+
+#[test]
+fn rasterize_glyph_sdf() {
+    use font_kit::canvas::DEFAULT_SDF_SPREAD;
+    use font_kit::error::GlyphLoadingError;
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('o').unwrap();
+    let transform = Transform2F::from_translation(Vector2F::new(8.0, 48.0));
+    let rasterize = |format| {
+        let mut canvas = Canvas::new(Vector2I::splat(64), format);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            48.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        canvas
+    };
+    let coverage = rasterize(Format::A8);
+    let sdf = rasterize(Format::Sdf);
+
+    // Pixels half covered by the glyph straddle the outline, where the field is about 0.5.
+    // Pixels wholly inside or outside the glyph are at least half a pixel from the outline.
+    let mut boundary_pixels = 0;
+    for (&coverage, &distance) in coverage.pixels.iter().zip(sdf.pixels.iter()) {
+        match coverage {
+            0 => assert!(distance < 128),
+            255 => assert!(distance > 128),
+            96..=160 => {
+                boundary_pixels += 1;
+                assert!((distance as i32 - 128).abs() <= 32);
+            }
+            _ => {}
+        }
+    }
+    assert!(boundary_pixels > 10);
+
+    let mut canvas = Canvas::new(Vector2I::splat(64), Format::Sdf);
+    font.rasterize_glyph_sdf(
+        &mut canvas,
+        glyph_id,
+        48.0,
+        transform,
+        HintingOptions::None,
+        DEFAULT_SDF_SPREAD,
+    )
+    .unwrap();
+    assert_eq!(canvas.pixels, sdf.pixels);
+    let mut canvas = Canvas::new(Vector2I::splat(64), Format::A8);
+    assert_eq!(
+        font.rasterize_glyph_sdf(
+            &mut canvas,
+            glyph_id,
+            48.0,
+            transform,
+            HintingOptions::None,
+            DEFAULT_SDF_SPREAD,
+        ),
+        Err(GlyphLoadingError::Unsupported)
+    );
+}
+
+#[test]
+fn read_canvas_pixels() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('I').unwrap();
+    let mut canvas = Canvas::with_stride(Vector2I::splat(64), 80, Format::A8);
+    font.rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        48.0,
+        Transform2F::from_translation(Vector2F::new(8.0, 48.0)),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+
+    // The middle of the stem is fully covered, and the corners of the canvas are blank.
+    let ink_bounds = canvas.ink_bounds();
+    let x = (ink_bounds.min_x() + ink_bounds.max_x()) as usize / 2;
+    let y = (ink_bounds.min_y() + ink_bounds.max_y()) as usize / 2;
+    assert_eq!(canvas.pixel(x, y), PixelValue::A8(255));
+    assert_eq!(canvas.pixel(0, 0), PixelValue::A8(0));
+    assert_eq!(canvas.pixel(63, 63), PixelValue::A8(0));
+    // Rows leave out the padding at their ends.
+    assert_eq!(canvas.row(y).len(), 64);
+    assert_eq!(canvas.row(y)[x], 255);
+
+    canvas.row_mut(0)[1] = 7;
+    assert_eq!(canvas.pixel(1, 0), PixelValue::A8(7));
+
+    let mut canvas = Canvas::new(Vector2I::new(3, 2), Format::Rgba32);
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(2, 1), Vector2I::splat(1)),
+        &[1, 2, 3, 4],
+    );
+    assert_eq!(canvas.pixel(2, 1), PixelValue::Rgba32([1, 2, 3, 4]));
+    assert_eq!(canvas.row(1), &[0, 0, 0, 0, 0, 0, 0, 0, 1, 2, 3, 4]);
+    let out_of_bounds = std::panic::catch_unwind(|| canvas.pixel(3, 0));
+    assert!(out_of_bounds.is_err());
+}
+
+#[test]
+fn fill_and_crop_a1_canvas() {
+    // `A1` pixels don't fill whole bytes, so only rows have a byte size.
+    assert_eq!(Format::A1.bytes_per_pixel(), 0);
+    assert_eq!(Format::A1.bytes_per_row(12), 2);
+    assert_eq!(Format::Rgba32.bytes_per_pixel(), 4);
+
+    let mut canvas = Canvas::new(Vector2I::new(12, 2), Format::A1);
+    assert_eq!(canvas.stride, 2);
+    assert_eq!(canvas.pixels, vec![0; 4]);
+
+    canvas.fill_rect(RectI::new(Vector2I::new(3, 1), Vector2I::new(6, 5)), &[1]);
+    // Pixels are packed most significant bit first.
+    assert_eq!(canvas.row(0), &[0, 0]);
+    assert_eq!(canvas.row(1), &[0b0001_1111, 0b1000_0000]);
+    assert_eq!(canvas.pixel(3, 1), PixelValue::A1(true));
+    assert_eq!(canvas.pixel(2, 1), PixelValue::A1(false));
+    assert_eq!(
+        canvas.ink_bounds(),
+        RectI::new(Vector2I::new(3, 1), Vector2I::new(6, 1))
+    );
+
+    let cropped = canvas.crop(canvas.ink_bounds());
+    assert_eq!((cropped.size, cropped.stride), (Vector2I::new(6, 1), 1));
+    assert_eq!(cropped.pixels, [0b1111_1100]);
+}
+
+#[test]
+fn rasterize_glyph_to_a1_canvas() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    let rasterize = |format| {
+        let mut canvas = Canvas::new(Vector2I::new(37, 40), format);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            32.0,
+            Transform2F::from_translation(Vector2F::new(4.0, 32.0)),
+            HintingOptions::Full(32.0),
+            RasterizationOptions::Bilevel,
+        )
+        .unwrap();
+        canvas
+    };
+
+    let a1_canvas = rasterize(Format::A1);
+    assert_eq!(a1_canvas.stride, 5);
+    let a8_canvas = rasterize(Format::A8);
+    let mut inked_count = 0;
+    for y in 0..40 {
+        // The unused bits at the end of each row stay clear.
+        assert_eq!(a1_canvas.row(y)[4] & 0b0000_0111, 0);
+        for x in 0..37 {
+            let on = match a1_canvas.pixel(x, y) {
+                PixelValue::A1(on) => on,
+                value => panic!("unexpected pixel value: {:?}", value),
+            };
+            assert_eq!(
+                a8_canvas.pixel(x, y),
+                PixelValue::A8(if on { 255 } else { 0 })
+            );
+            inked_count += on as u32;
+        }
+    }
+    // The 'A' covers a fair part of its box, but far from all of it.
+    let ink_bounds = a1_canvas.ink_bounds();
+    assert_eq!(ink_bounds, a8_canvas.ink_bounds());
+    let box_area = (ink_bounds.width() * ink_bounds.height()) as u32;
+    assert!(inked_count > box_area / 8 && inked_count < box_area * 2 / 3);
+
+    // Antialiased coverage is thresholded at half coverage.
+    let mut thresholded = Canvas::new(Vector2I::new(37, 40), Format::A1);
+    font.rasterize_glyph(
+        &mut thresholded,
+        glyph_id,
+        32.0,
+        Transform2F::from_translation(Vector2F::new(4.0, 32.0)),
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+    assert!(thresholded.pixels.iter().any(|&byte| byte != 0));
+}
+
+#[test]
+fn crop_canvas_clamps_to_bounds() {
+    let mut canvas = Canvas::new(Vector2I::new(4, 3), Format::Rgb24);
+    assert_eq!(canvas.ink_bounds(), RectI::default());
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(2, 1), Vector2I::new(1, 1)),
+        &[0, 0, 9],
+    );
+    assert_eq!(
+        canvas.ink_bounds(),
+        RectI::new(Vector2I::new(2, 1), Vector2I::splat(1))
+    );
+
+    let cropped = canvas.crop(RectI::new(Vector2I::new(1, -5), Vector2I::new(10, 7)));
+    assert_eq!(cropped.size, Vector2I::new(3, 2));
+    assert_eq!(cropped.format, Format::Rgb24);
+    assert_eq!(
+        cropped.pixels,
+        [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 9, 0, 0, 0]
+    );
+
+    let cropped = canvas.crop(RectI::new(Vector2I::new(5, 5), Vector2I::splat(2)));
+    assert_eq!(cropped.size, Vector2I::default());
+    assert!(cropped.pixels.is_empty());
+}
+
+#[test]
+fn clear_and_rasterize_glyph() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('O').unwrap();
+    let size = 32.0;
+    let transform = Transform2F::from_translation(Vector2F::new(16.0, 48.0));
+    let raster_rect = font
+        .raster_bounds(
+            glyph_id,
+            size,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+
+    let mut canvas = Canvas::new(Vector2I::splat(64), Format::A8);
+    canvas.fill_rect(RectI::new(Vector2I::default(), canvas.size), &[0xff]);
+    font.clear_and_rasterize_glyph(
+        &mut canvas,
+        glyph_id,
+        size,
+        transform,
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    )
+    .unwrap();
+
+    // The counter of the "O" is cleared, but the rest of the canvas is left alone.
+    let center = raster_rect.to_f32().center().to_i32();
+    assert_eq!(
+        canvas.pixels[center.y() as usize * canvas.stride + center.x() as usize],
+        0
+    );
+    assert_eq!(canvas.pixels[0], 0xff);
+}
+
+#[test]
+fn rasterize_glyph_clipped() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('O').unwrap();
+    let size = 32.0;
+    let transform = Transform2F::from_translation(Vector2F::new(16.0, 48.0));
+
+    // Distance fields extend past the outline, so they're clipped too.
+    for &format in &[Format::A8, Format::Sdf] {
+        let mut unclipped = Canvas::new(Vector2I::splat(64), format);
+        font.rasterize_glyph(
+            &mut unclipped,
+            glyph_id,
+            size,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+
+        // Cut the glyph in half vertically.
+        let clip_rect = RectI::new(Vector2I::default(), Vector2I::new(64, 36));
+        let mut clipped = Canvas::new(Vector2I::splat(64), format);
+        font.rasterize_glyph_clipped(
+            &mut clipped,
+            glyph_id,
+            size,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            clip_rect,
+        )
+        .unwrap();
+
+        let split = clip_rect.max_y() as usize * clipped.stride;
+        assert_eq!(clipped.pixels[..split], unclipped.pixels[..split]);
+        assert!(clipped.pixels[..split].iter().any(|&pixel| pixel != 0));
+        assert!(clipped.pixels[split..].iter().all(|&pixel| pixel == 0));
+        assert!(unclipped.pixels[split..].iter().any(|&pixel| pixel != 0));
+    }
+}
+
+#[test]
+fn rasterize_glyphs_with_context() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let mut context = RasterizeContext::new();
+    for &rasterization_options in &[
+        RasterizationOptions::GrayscaleAa,
+        RasterizationOptions::Bilevel,
+    ] {
+        for &hinting_options in &[HintingOptions::None, HintingOptions::Full(24.0)] {
+            for character in "Og,".chars() {
+                let glyph_id = font.glyph_for_char(character).unwrap();
+                let transform = Transform2F::from_translation(Vector2F::new(8.25, 40.5));
+
+                let mut expected = Canvas::new(Vector2I::splat(64), Format::Rgba32);
+                font.rasterize_glyph(
+                    &mut expected,
+                    glyph_id,
+                    24.0,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+                .unwrap();
+                let mut actual = Canvas::new(Vector2I::splat(64), Format::Rgba32);
+                font.rasterize_glyph_with_context(
+                    &mut context,
+                    &mut actual,
+                    glyph_id,
+                    24.0,
+                    transform,
+                    hinting_options,
+                    rasterization_options,
+                )
+                .unwrap();
+
+                assert!(expected.pixels.iter().any(|&pixel| pixel != 0));
+                assert_eq!(actual.pixels, expected.pixels);
+            }
+        }
+    }
+}
+
+#[test]
+fn rasterize_glyph_colored() {
+    use font_kit::color::Color;
+    use font_kit::error::GlyphLoadingError;
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let mut key = GlyphKey::new(font.glyph_for_char('g').unwrap(), 32.0);
+    key.transform = Transform2F::from_translation(Vector2F::new(8.0, 32.0));
+    let size = Vector2I::splat(48);
+
+    let mut coverage = Canvas::new(size, Format::A8);
+    font.rasterize_glyph(
+        &mut coverage,
+        key.glyph_id,
+        key.point_size,
+        key.transform,
+        key.hinting_options,
+        key.rasterization_options,
+    )
+    .unwrap();
+    assert!(coverage.pixels.iter().any(|&value| value != 0));
+
+    for &color in &[
+        Color {
+            r: 200,
+            g: 100,
+            b: 50,
+            a: 255,
+        },
+        Color {
+            r: 255,
+            g: 0,
+            b: 128,
+            a: 128,
+        },
+    ] {
+        let mut canvas = Canvas::new(size, Format::Rgba32);
+        font.rasterize_glyph_colored(&mut canvas, &key, color)
+            .unwrap();
+
+        // Each pixel is the coverage times the color, premultiplied by the color's alpha.
+        let tint = |coverage: u8, channel: u8| {
+            ((coverage as u32 * channel as u32 * color.a as u32 + 65025 / 2) / 65025) as u8
+        };
+        let expected: Vec<u8> = coverage
+            .pixels
+            .iter()
+            .flat_map(|&value| {
+                [
+                    tint(value, color.r),
+                    tint(value, color.g),
+                    tint(value, color.b),
+                    tint(value, 255),
+                ]
+            })
+            .collect();
+        assert_eq!(canvas.pixels, expected);
+    }
+
+    let mut canvas = Canvas::new(size, Format::A8);
+    assert_eq!(
+        font.rasterize_glyph_colored(&mut canvas, &key, Color::default()),
+        Err(GlyphLoadingError::Unsupported)
+    );
+}
+
+// The `COLR` table is patched in under a new tag, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn rasterize_colr_glyph_colored() {
+    use font_kit::color::Color;
+
+    let monochrome_font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let (color_font, glyph_id) = colr_font('g');
+    let red = Color {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+    let blue = Color {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+    let rasterize = |font: &Font, glyph_id: u32, color: Color| {
+        let mut key = GlyphKey::new(glyph_id, 32.0);
+        key.transform = Transform2F::from_translation(Vector2F::new(8.0, 32.0));
+        let mut canvas = Canvas::new(Vector2I::splat(48), Format::Rgba32);
+        font.rasterize_glyph_colored(&mut canvas, &key, color)
+            .unwrap();
+        canvas.pixels
+    };
+
+    // The glyph's only layer is drawn in the red of the palette, whatever the tint.
+    assert_eq!(
+        rasterize(&color_font, glyph_id, blue),
+        rasterize(&monochrome_font, glyph_id, red)
+    );
+    // Glyphs without color data are tinted.
+    let other_glyph_id = monochrome_font.glyph_for_char('o').unwrap();
+    assert_eq!(
+        rasterize(&color_font, other_glyph_id, blue),
+        rasterize(&monochrome_font, other_glyph_id, blue)
+    );
+}
+
+// The `COLR` table is patched in under a new tag, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn rasterize_glyph_with_color_preference() {
+    use font_kit::canvas::ColorPreference;
+    use font_kit::color::Color;
+    use font_kit::error::GlyphLoadingError;
+
+    let monochrome_font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let (color_font, glyph_id) = colr_font('g');
+
+    let mut key = GlyphKey::new(glyph_id, 32.0);
+    key.transform = Transform2F::from_translation(Vector2F::new(8.0, 32.0));
+    let red = Color {
+        r: 255,
+        g: 0,
+        b: 0,
+        a: 255,
+    };
+    let blue = Color {
+        r: 0,
+        g: 0,
+        b: 255,
+        a: 255,
+    };
+    let rasterize = |font: &Font, key: &GlyphKey, color: Color| {
+        let mut canvas = Canvas::new(Vector2I::splat(48), Format::Rgba32);
+        font.rasterize_glyph_colored(&mut canvas, key, color)
+            .map(|()| canvas.pixels)
+    };
+    let red_outline = rasterize(&monochrome_font, &key, red).unwrap();
+    let blue_outline = rasterize(&monochrome_font, &key, blue).unwrap();
+    assert_ne!(red_outline, blue_outline);
+
+    // By default, the color glyph is drawn in the red of its palette, ignoring the tint.
+    assert_eq!(rasterize(&color_font, &key, blue).unwrap(), red_outline);
+    // `Never` draws its outline tinted, just like a monochrome font.
+    key.color = ColorPreference::Never;
+    assert_eq!(rasterize(&color_font, &key, blue).unwrap(), blue_outline);
+    // `Force` draws in color too, but requires color data for the glyph, not just the font.
+    key.color = ColorPreference::Force;
+    assert_eq!(rasterize(&color_font, &key, blue).unwrap(), red_outline);
+    assert_eq!(
+        rasterize(&monochrome_font, &key, blue),
+        Err(GlyphLoadingError::Unsupported)
+    );
+    key.glyph_id = color_font.glyph_for_char('o').unwrap();
+    assert_eq!(
+        rasterize(&color_font, &key, blue),
+        Err(GlyphLoadingError::Unsupported)
+    );
+}
+
+// `FT_LOAD_COLOR` makes FreeType composite COLR layers itself, into a BGRA bitmap.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn rasterize_glyph_with_color_load_flag() {
+    use freetype_sys::FT_LOAD_COLOR;
+
+    let (mut font, glyph_id) = colr_font('g');
+    let rasterize = |font: &Font, format| {
+        let mut canvas = Canvas::new(Vector2I::splat(48), format);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            32.0,
+            Transform2F::from_translation(Vector2F::new(8.0, 32.0)),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+        canvas.pixels
+    };
+    let outline = rasterize(&font, Format::A8);
+
+    font.set_load_flags(FT_LOAD_COLOR);
+    // The glyph is drawn in the red of its palette, with premultiplied alpha.
+    let pixels = rasterize(&font, Format::Rgba32);
+    assert!(pixels.chunks(4).any(|pixel| pixel == [0xff, 0, 0, 0xff]));
+    assert!(pixels
+        .chunks(4)
+        .all(|pixel| pixel[0] == pixel[3] && pixel[1] == 0 && pixel[2] == 0));
+    // Canvases without color keep the coverage.
+    assert_eq!(rasterize(&font, Format::A8), outline);
+}
+
+#[test]
+fn rasterize_line() {
+    use font_kit::error::GlyphLoadingError;
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let point_size = 32.0;
+    let canvas = font
+        .rasterize_line(
+            "Hi",
+            point_size,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    assert_eq!(canvas.format, Format::A8);
+
+    // The canvas holds the whole line, from ascent to descent and from the pen's start to its
+    // end.
+    let scale = point_size / font.metrics().units_per_em as f32;
+    let line_width = ['H', 'i']
+        .iter()
+        .map(|&character| {
+            font.advance(font.glyph_for_char(character).unwrap())
+                .unwrap()
+                .x()
+        })
+        .sum::<f32>()
+        * scale;
+    let line_metrics = font.line_metrics(point_size);
+    assert!(canvas.size.x() >= line_width.ceil() as i32);
+    assert!(canvas.size.x() <= line_width.ceil() as i32 + 2);
+    assert!(canvas.size.y() >= (line_metrics.ascent - line_metrics.descent).ceil() as i32);
+
+    // Both glyphs are drawn, the `H` in the left half and the `i` in the right.
+    let ink_bounds = canvas.ink_bounds();
+    let split = (font.advance(font.glyph_for_char('H').unwrap()).unwrap().x() * scale) as i32;
+    assert!(ink_bounds.min_x() < split && ink_bounds.max_x() > split);
+    let left = canvas.crop(RectI::new(
+        Vector2I::default(),
+        Vector2I::new(split, canvas.size.y()),
+    ));
+    let right = canvas.crop(RectI::from_points(Vector2I::new(split, 0), canvas.size));
+    assert!(left.pixels.iter().any(|&value| value != 0));
+    assert!(right.pixels.iter().any(|&value| value != 0));
+
+    let empty = font
+        .rasterize_line(
+            "",
+            point_size,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    assert_eq!(empty.size.x(), 0);
+    assert!(empty.pixels.is_empty());
+
+    // Lines wider than any canvas can be are errors.
+    let too_wide = font.rasterize_line(
+        &" ".repeat(1000),
+        1000.0,
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+    );
+    assert_eq!(too_wide.unwrap_err(), GlyphLoadingError::InvalidCanvasSize);
+}
+
+// Patching the test font only works with FreeType.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn rasterize_line_with_kerning() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let h = font.glyph_for_char('H').unwrap() as u16;
+    let i = font.glyph_for_char('i').unwrap() as u16;
+
+    // A version 0 `kern` table that moves `i` 100 units closer to `H`.
+    #[rustfmt::skip]
+    let mut kern = vec![
+        0, 0, 0, 1,                     // version, nTables
+        0, 0, 0, 20, 0, 1,              // subtable version, length, coverage (horizontal)
+        0, 1, 0, 6, 0, 0, 0, 0,         // nPairs, searchRange, entrySelector, rangeShift
+    ];
+    kern.extend_from_slice(&h.to_be_bytes());
+    kern.extend_from_slice(&i.to_be_bytes());
+    kern.extend_from_slice(&(-100i16).to_be_bytes());
+    replace_font_table(&mut font_data, b"GSUB", b"kern", &kern);
+    let kerned_font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    let point_size = 100.0;
+    let line_width = |font: &Font| {
+        let canvas = font
+            .rasterize_line(
+                "Hi",
+                point_size,
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+        canvas.ink_bounds().width()
+    };
+    let kerning = 100.0 * point_size / font.metrics().units_per_em as f32;
+    let difference = (line_width(&font) - line_width(&kerned_font)) as f32;
+    assert!((difference - kerning).abs() <= 1.0);
+}
+
+// The vertical bar of Inconsolata is a plain rectangle, so at a large size it is nearly solid.
+#[test]
+fn get_glyph_coverage_stats() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('|').unwrap();
+
+    let stats = font
+        .glyph_coverage_stats(&GlyphKey::new(glyph_id, 256.0))
+        .unwrap();
+    assert!(
+        stats.mean > 240.0,
+        "mean coverage {} is too low",
+        stats.mean
+    );
+    assert_eq!(stats.max, 255);
+    assert_eq!(stats.filled_pixel_count, stats.pixel_count);
+
+    let mut key = GlyphKey::new(glyph_id, 256.0);
+    key.rasterization_options = RasterizationOptions::Bilevel;
+    let stats = font.glyph_coverage_stats(&key).unwrap();
+    assert_eq!((stats.min, stats.max, stats.mean), (255, 255, 255.0));
+
+    let space = font.glyph_for_char(' ').unwrap();
+    let stats = font
+        .glyph_coverage_stats(&GlyphKey::new(space, 256.0))
+        .unwrap();
+    assert_eq!(stats, CoverageStats::default());
+}
+
+#[test]
+fn get_all_horizontal_advances() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let advances = font.all_horizontal_advances();
+    assert_eq!(advances.len(), font.glyph_count() as usize);
+    for character in &['a', 'W', ' '] {
+        let glyph_id = font.glyph_for_char(*character).unwrap();
+        assert_eq!(
+            advances[glyph_id as usize] as f32,
+            font.advance(glyph_id).unwrap().x()
+        );
+    }
+}
+
+#[test]
+fn get_all_vertical_advances_without_vmtx() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.all_vertical_advances().is_empty());
+}
+
+#[test]
+fn normalized_variation_coordinates_of_static_font() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let wght = u32::from_be_bytes(*b"wght");
+    assert!(font
+        .normalized_variation_coordinates(&[(wght, 550.0)])
+        .is_empty());
+}
+
+// The tables are patched in under new tags, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn apply_mvar_deltas_to_metrics() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+
+    // An `fvar` table with a weight axis from 100 to 900, defaulting to 400.
+    #[rustfmt::skip]
+    let fvar: [u8; 36] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 16, 0, 2,                    // axesArrayOffset, reserved
+        0, 1, 0, 20,                    // axisCount, axisSize
+        0, 0, 0, 4,                     // instanceCount, instanceSize
+        b'w', b'g', b'h', b't',         // axisTag
+        0, 100, 0, 0, 1, 144, 0, 0,     // minValue, defaultValue
+        3, 132, 0, 0, 0, 0, 1, 0,       // maxValue, flags, axisNameID
+    ];
+    // An `MVAR` table that raises the x-height by 100 units at the heaviest weight.
+    #[rustfmt::skip]
+    let mvar: [u8; 52] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 0, 0, 8,                     // reserved, valueRecordSize
+        0, 1, 0, 20,                    // valueRecordCount, itemVariationStoreOffset
+        b'x', b'h', b'g', b't', 0, 0, 0, 0, // xhgt: item 0 of data 0
+        0, 1, 0, 0, 0, 12,              // 20: store format, variationRegionListOffset
+        0, 1, 0, 0, 0, 22,              // itemVariationDataCount, itemVariationDataOffsets
+        0, 1, 0, 1,                     // 32: axisCount, regionCount
+        0, 0, 64, 0, 64, 0,             // region 0: start 0, peak 1, end 1
+        0, 1, 0, 1, 0, 1, 0, 0,         // 42: itemCount, wordDeltaCount, regionIndexCount, 0
+        0, 100,                         // item 0: +100
+    ];
+    replace_font_table(&mut font_data, b"GSUB", b"fvar", &fvar);
+    replace_font_table(&mut font_data, b"GPOS", b"MVAR", &mvar);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    let wght = u32::from_be_bytes(*b"wght");
+    let x_height = font.metrics().x_height;
+    assert_eq!(
+        font.metrics_at_variation(&[(wght, 400.0)]).x_height,
+        x_height
+    );
+    assert_eq!(
+        font.metrics_at_variation(&[(wght, 650.0)]).x_height,
+        x_height + 50.0
+    );
+    let bold_metrics = font.metrics_at_variation(&[(wght, 900.0)]);
+    assert_eq!(bold_metrics.x_height, x_height + 100.0);
+    assert_eq!(bold_metrics.cap_height, font.metrics().cap_height);
+    assert_eq!(
+        font.metrics_at_variation(&[(wght, 100.0)]).x_height,
+        x_height
+    );
+}
+
+// The tables are patched in under new tags, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn apply_hvar_deltas_to_advances() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+
+    // An `fvar` table with a weight axis from 100 to 900, defaulting to 400.
+    #[rustfmt::skip]
+    let fvar: [u8; 36] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 16, 0, 2,                    // axesArrayOffset, reserved
+        0, 1, 0, 20,                    // axisCount, axisSize
+        0, 0, 0, 4,                     // instanceCount, instanceSize
+        b'w', b'g', b'h', b't',         // axisTag
+        0, 100, 0, 0, 1, 144, 0, 0,     // minValue, defaultValue
+        3, 132, 0, 0, 0, 0, 1, 0,       // maxValue, flags, axisNameID
+    ];
+    // An `HVAR` table that widens every glyph by 100 units at the heaviest weight and narrows
+    // it by 50 at the lightest.
+    #[rustfmt::skip]
+    let hvar: [u8; 67] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 0, 0, 20, 0, 0, 0, 62,       // itemVariationStoreOffset, advanceWidthMappingOffset
+        0, 0, 0, 0, 0, 0, 0, 0,         // lsbMappingOffset, rsbMappingOffset
+        0, 1, 0, 0, 0, 12,              // 20: store format, variationRegionListOffset
+        0, 1, 0, 0, 0, 28,              // itemVariationDataCount, itemVariationDataOffsets
+        0, 1, 0, 2,                     // 32: axisCount, regionCount
+        0, 0, 64, 0, 64, 0,             // region 0: start 0, peak 1, end 1
+        192, 0, 192, 0, 0, 0,           // region 1: start -1, peak -1, end 0
+        0, 1, 0, 2, 0, 2, 0, 0, 0, 1,   // 48: itemCount, wordDeltaCount, regionIndexes
+        0, 100, 255, 206,               // item 0: +100, -50
+        0, 0, 0, 1, 0,                  // 62: map format 0, entryFormat, mapCount, entry 0
+    ];
+    replace_font_table(&mut font_data, b"GSUB", b"fvar", &fvar);
+    replace_font_table(&mut font_data, b"GPOS", b"HVAR", &hvar);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+
+    let wght = u32::from_be_bytes(*b"wght");
+    let glyph_id = font.glyph_for_char('a').unwrap();
+    let advance = font.advance(glyph_id).unwrap();
+    assert_eq!(
+        font.advance_at_variation(glyph_id, &[(wght, 400.0)])
+            .unwrap(),
+        advance
+    );
+    let light_advance = font
+        .advance_at_variation(glyph_id, &[(wght, 100.0)])
+        .unwrap();
+    let bold_advance = font
+        .advance_at_variation(glyph_id, &[(wght, 900.0)])
+        .unwrap();
+    assert_eq!(light_advance, advance - Vector2F::new(50.0, 0.0));
+    assert_eq!(bold_advance, advance + Vector2F::new(100.0, 0.0));
+    assert_eq!(
+        font.advance_at_variation(glyph_id, &[(wght, 650.0)])
+            .unwrap(),
+        advance + Vector2F::new(50.0, 0.0)
+    );
+}
+
+#[test]
+fn rasterize_glyph_with_premultiplied_and_straight_alpha() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let transform = Transform2F::from_translation(Vector2F::new(8.0, 40.0));
+    let rasterize = |premultiplied_alpha| {
+        let mut canvas = Canvas::new(Vector2I::splat(64), Format::Rgba32);
+        font.rasterize_glyph_with_alpha(
+            &mut canvas,
+            glyph_id,
+            32.0,
+            transform,
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+            premultiplied_alpha,
+        )
+        .unwrap();
+        canvas
+    };
+
+    let premultiplied = rasterize(true);
+    assert!(premultiplied.pixels.iter().any(|&value| value != 0));
+    for pixel in premultiplied.pixels.chunks(4) {
+        assert!(pixel[0..3].iter().all(|&component| component <= pixel[3]));
+    }
+
+    // Unpremultiplying leaves alpha alone and can only brighten the color.
+    let straight = rasterize(false);
+    for (straight, premultiplied) in straight
+        .pixels
+        .chunks(4)
+        .zip(premultiplied.pixels.chunks(4))
+    {
+        assert_eq!(straight[3], premultiplied[3]);
+        assert!((0..3).all(|index| straight[index] >= premultiplied[index]));
+    }
+    assert!(straight.pixels != premultiplied.pixels);
+
+    // Straight-alpha pixels already on the canvas are left alone or cleared, never converted
+    // again.
+    let background = [0x40, 0x40, 0x40, 0x80];
+    let mut canvas = Canvas::new(Vector2I::splat(64), Format::Rgba32);
+    canvas.fill_rect(RectI::new(Vector2I::default(), canvas.size), &background);
+    font.rasterize_glyph_with_alpha(
+        &mut canvas,
+        glyph_id,
+        32.0,
+        transform,
+        HintingOptions::None,
+        RasterizationOptions::GrayscaleAa,
+        false,
+    )
+    .unwrap();
+    assert!(canvas.pixels.chunks(4).any(|pixel| pixel == background));
+    for (pixel, straight) in canvas.pixels.chunks(4).zip(straight.pixels.chunks(4)) {
+        assert!(pixel == background || pixel == straight);
+    }
+}
+
+#[test]
+fn rasterize_glyph_to_high_precision_formats() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+    let transform = Transform2F::from_translation(Vector2F::new(8.0, 40.0));
+    let rasterize = |format, rasterization_options| {
+        let mut canvas = Canvas::new(Vector2I::splat(64), format);
+        font.rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            32.0,
+            transform,
+            HintingOptions::None,
+            rasterization_options,
+        )
+        .unwrap();
+        canvas
+    };
+
+    for &rasterization_options in &[
+        RasterizationOptions::Bilevel,
+        RasterizationOptions::GrayscaleAa,
+    ] {
+        let a8 = rasterize(Format::A8, rasterization_options);
+        let a16 = rasterize(Format::A16, rasterization_options);
+        let af32 = rasterize(Format::AF32, rasterization_options);
+        assert_eq!(a16.stride, 128);
+        assert_eq!(af32.stride, 256);
+
+        let a16_values: Vec<u16> = a16
+            .pixels
+            .chunks(2)
+            .map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]))
+            .collect();
+        let af32_values: Vec<f32> = af32
+            .pixels
+            .chunks(4)
+            .map(|bytes| f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+            .collect();
+        assert!(af32_values.iter().any(|&value| value > 0.0));
+        assert!(af32_values
+            .iter()
+            .all(|&value| (0.0..=1.0).contains(&value)));
+        for ((&a8_value, &a16_value), &af32_value) in a8
+            .pixels
+            .iter()
+            .zip(a16_values.iter())
+            .zip(af32_values.iter())
+        {
+            assert_eq!(a16_value, a8_value as u16 * 257);
+            assert_eq!((af32_value * 255.0).round() as u8, a8_value);
+        }
+    }
+}
+
+#[test]
+fn get_glyph_for_supplementary_char() {
+    for path in &[TEST_FONT_FILE_PATH, FILE_PATH_EB_GARAMOND_TTF] {
+        let font = Font::from_path(path, 0).unwrap();
+        let thumbs_up = font.glyph_for_char('\u{1f44d}').unwrap();
+        let regional_indicator_a = font.glyph_for_char('\u{1f1e6}').unwrap();
+        assert_ne!(thumbs_up, regional_indicator_a);
+        assert!(font.glyph_for_char('\u{20000}').is_none());
+    }
+}
+
+// The `cmap` table is patched in place, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_glyph_for_char_from_cmap_format_13() {
+    let mut font_data = std::fs::read(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    let original_font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let glyph_a = original_font.glyph_for_char('A');
+    assert!(original_font.glyph_for_char('\u{e01}').is_none());
+
+    // Keep the Windows Unicode BMP format 4 subtable, and add a format 13 subtable, as a
+    // last-resort font would, that maps the Thai block to glyph 5 and the emoticons to glyph 6.
+    let cmap_offset = font_table_offset(&font_data, b"cmap");
+    let cmap = &font_data[cmap_offset..];
+    let format_4_offset = (0..u16::from_be_bytes([cmap[2], cmap[3]]) as usize)
+        .map(|index| 4 + index * 8)
+        .find(|&record_offset| cmap[record_offset..record_offset + 4] == [0, 3, 0, 1])
+        .map(|record_offset| read_u32_be(&cmap[record_offset + 4..]) as usize)
+        .unwrap();
+    let format_4_length =
+        u16::from_be_bytes([cmap[format_4_offset + 2], cmap[format_4_offset + 3]]) as usize;
+    let format_4_subtable = &cmap[format_4_offset..format_4_offset + format_4_length];
+
+    let format_13_offset = 20 + format_4_length as u32;
+    let mut table = vec![0, 0, 0, 2];
+    table.extend_from_slice(&[0, 0, 0, 6]);
+    table.extend_from_slice(&format_13_offset.to_be_bytes());
+    table.extend_from_slice(&[0, 3, 0, 1]);
+    table.extend_from_slice(&20u32.to_be_bytes());
+    table.extend_from_slice(format_4_subtable);
+    table.extend_from_slice(&[0, 13, 0, 0]);
+    table.extend_from_slice(&40u32.to_be_bytes());
+    table.extend_from_slice(&0u32.to_be_bytes());
+    table.extend_from_slice(&2u32.to_be_bytes());
+    for &(start, end, glyph_id) in &[(0xe00u32, 0xe7fu32, 5u32), (0x1f600, 0x1f64f, 6)] {
+        table.extend_from_slice(&start.to_be_bytes());
+        table.extend_from_slice(&end.to_be_bytes());
+        table.extend_from_slice(&glyph_id.to_be_bytes());
+    }
+    replace_font_table(&mut font_data, b"cmap", b"cmap", &table);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.glyph_for_char('A'), glyph_a);
+    assert_eq!(font.glyph_for_char('\u{e01}'), Some(5));
+    assert_eq!(font.glyph_for_char('\u{e7f}'), Some(5));
+    assert_eq!(font.glyph_for_char('\u{1f600}'), Some(6));
+    assert!(font.glyph_for_char('\u{e80}').is_none());
+    assert!(font.glyph_for_char('\u{1f650}').is_none());
+}
+
+// The `cmap` table is patched in place, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_glyph_for_char_from_mac_roman_cmap() {
+    let mut font_data = std::fs::read(FILE_PATH_INCONSOLATA_TTF).unwrap();
+    let original_font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let glyph_a = original_font.glyph_for_char('A').unwrap();
+    let glyph_e_acute = original_font.glyph_for_char('\u{e9}').unwrap();
+    let glyph_e_grave = original_font.glyph_for_char('\u{c8}').unwrap();
+
+    // Replace every subtable with a single Mac Roman format 6 subtable that maps 'A', 'é' (0x8e
+    // in Mac Roman), and 'È' (0xe9, the code of 'é' in Unicode) to their original glyphs.
+    let mut glyph_ids = [0u16; 256];
+    glyph_ids[b'A' as usize] = glyph_a as u16;
+    glyph_ids[0x8e] = glyph_e_acute as u16;
+    glyph_ids[0xe9] = glyph_e_grave as u16;
+    let mut table = vec![0, 0, 0, 1];
+    table.extend_from_slice(&[0, 1, 0, 0]);
+    table.extend_from_slice(&12u32.to_be_bytes());
+    table.extend_from_slice(&[0, 6]);
+    table.extend_from_slice(&(10 + 2 * glyph_ids.len() as u16).to_be_bytes());
+    table.extend_from_slice(&[0, 0, 0, 0, 1, 0]);
+    for glyph_id in &glyph_ids {
+        table.extend_from_slice(&glyph_id.to_be_bytes());
+    }
+    replace_font_table(&mut font_data, b"cmap", b"cmap", &table);
+    // Drop the glyph names too, which FreeType would otherwise build a Unicode mapping from.
+    let post_offset = font_table_offset(&font_data, b"post");
+    font_data[post_offset..post_offset + 4].copy_from_slice(&[0, 3, 0, 0]);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.glyph_for_char('A'), Some(glyph_a));
+    assert_eq!(font.glyph_for_char('\u{e9}'), Some(glyph_e_acute));
+    assert_eq!(font.glyph_for_char('\u{c8}'), Some(glyph_e_grave));
+    assert!(font.glyph_for_char('B').is_none());
+    assert!(font.glyph_for_char('\u{100}').is_none());
+}
+
+// Patching the test font only works with FreeType.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_ligature_glyph() {
+    let mut font_data = std::fs::read(TEST_FONT_FILE_PATH).unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let f = font.glyph_for_char('f').unwrap() as u16;
+    let i = font.glyph_for_char('i').unwrap() as u16;
+    let fi = font.glyph_for_char('\u{fb01}').unwrap() as u16;
+
+    // A `GSUB` table whose `liga` feature has one ligature lookup, substituting "fi" for "f", "i".
+    let mut table = vec![];
+    for value in &[
+        0x0001, 0x0000, 10, 12, 26, // header
+        0,  // script list: no scripts
+        1, 0x6c69, 0x6761, 8, // feature list: 'liga'
+        0, 1, 0, // feature: lookup 0
+        1, 4, // lookup list
+        4, 0, 1, 8, // lookup: one ligature subtable
+        1, 8, 1, 14, // ligature substitution subtable
+        1, 1, f, // coverage: 'f'
+        1, 4, // ligature set
+        fi, 2, i, // ligature
+    ] {
+        table.extend_from_slice(&value.to_be_bytes());
+    }
+    replace_font_table(&mut font_data, b"GSUB", b"GSUB", &table);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.ligature_glyph(&[f as u32, i as u32]), Some(fi as u32));
+    assert_eq!(font.ligature_glyph(&[f as u32]), None);
+    assert_eq!(font.ligature_glyph(&[f as u32, i as u32, i as u32]), None);
+    assert_eq!(font.ligature_glyph(&[i as u32, f as u32]), None);
+    assert_eq!(font.ligature_glyph(&[]), None);
+}
+
+// Only FreeType reads PostScript Type 1 fonts.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn load_type1_font() {
+    let mut font_data = vec![];
+    File::open("resources/tests/type1/FontKitTest.pfb")
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let font_data = Arc::new(font_data);
+    assert_eq!(
+        Font::analyze_bytes(font_data.clone()).unwrap(),
+        FileType::Single
+    );
+    let font = Font::from_bytes(font_data, 0).unwrap();
+    assert_eq!(font.postscript_name().unwrap(), "FontKitTest");
+    assert_eq!(font.family_name(), "Font Kit Test");
+    assert_eq!(font.glyph_count(), 3);
+    assert_eq!(font.metrics().units_per_em, 1000);
+    assert_eq!(font.load_font_table(OPENTYPE_TABLE_TAG_HEAD), None);
+
+    // 'A' is mapped through its glyph name. The lens glyph has a name that means nothing in
+    // Unicode, so 'O' is mapped through the font's encoding.
+    assert_eq!(font.glyph_for_char('A'), Some(1));
+    let glyph = font.glyph_for_char('O').unwrap();
+    assert_eq!(glyph, 2);
+    assert_eq!(font.glyph_for_char('B'), None);
+    assert_eq!(font.advance(glyph).unwrap(), Vector2F::new(600.0, 0.0));
+
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+    assert_eq!(
+        outline_builder.into_outline(),
+        Outline {
+            contours: vec![Contour {
+                positions: vec![
+                    Vector2F::new(300.0, 0.0),
+                    Vector2F::new(450.0, 0.0),
+                    Vector2F::new(550.0, 200.0),
+                    Vector2F::new(550.0, 400.0),
+                    Vector2F::new(450.0, 400.0),
+                    Vector2F::new(300.0, 200.0),
+                    Vector2F::new(300.0, 0.0),
+                ],
+                flags: vec![
+                    PointFlags::empty(),
+                    PointFlags::CONTROL_POINT_0,
+                    PointFlags::CONTROL_POINT_1,
+                    PointFlags::empty(),
+                    PointFlags::CONTROL_POINT_0,
+                    PointFlags::CONTROL_POINT_1,
+                    PointFlags::empty(),
+                ],
+            }],
+        }
+    );
+}
+
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_hinted_glyph_outline() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph = font.glyph_for_char('J').expect("No glyph for char!");
+    let point_size = 9.0;
+    let hinted_outline = |hinting| {
+        let mut outline_builder = OutlineBuilder::new();
+        font.hinted_outline(glyph, point_size, hinting, &mut outline_builder)
+            .unwrap();
+        outline_builder.into_outline()
+    };
+
+    // Without hinting, the outline is just scaled down from font units to pixels.
+    let mut outline_builder = OutlineBuilder::new();
+    font.outline(glyph, HintingOptions::None, &mut outline_builder)
+        .unwrap();
+    let unscaled = outline_builder.into_outline();
+    let unhinted = hinted_outline(HintingOptions::None);
+    let scale = point_size / font.metrics().units_per_em as f32;
+    let positions = |outline: &Outline| -> Vec<Vector2F> {
+        outline
+            .contours
+            .iter()
+            .flat_map(|contour| contour.positions.iter().cloned())
+            .collect()
+    };
+    assert_eq!(positions(&unhinted).len(), positions(&unscaled).len());
+    for (unhinted, unscaled) in positions(&unhinted).iter().zip(positions(&unscaled).iter()) {
+        assert!((*unhinted - *unscaled * scale).length() < 1.0 / 32.0);
+    }
+
+    let hinted = hinted_outline(HintingOptions::Full(0.0));
+    assert!(!hinted.contours.is_empty());
+    assert_ne!(positions(&hinted), positions(&unhinted));
+}
+
+#[cfg(any(target_family = "windows", target_os = "macos", target_os = "ios"))]
+#[test]
+fn get_hinted_glyph_outline_is_unsupported() {
+    let font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    let glyph = font.glyph_for_char('J').expect("No glyph for char!");
+    let mut outline_builder = OutlineBuilder::new();
+    assert_eq!(
+        font.hinted_outline(glyph, 9.0, HintingOptions::Full(9.0), &mut outline_builder),
+        Err(font_kit::error::GlyphLoadingError::Unsupported)
+    );
+}
+
+#[test]
+fn get_glyph_names() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let names = font.glyph_names();
+    assert_eq!(names.len(), font.glyph_count() as usize);
+    assert_eq!(names[0].as_deref(), Some(".notdef"));
+    // Both standard Macintosh names and names stored in the table itself are resolved.
+    for &(character, name) in &[('A', "A"), (' ', "space"), ('é', "eacute"), ('ŋ', "eng")] {
+        let glyph_id = font.glyph_for_char(character).unwrap();
+        assert_eq!(names[glyph_id as usize].as_deref(), Some(name));
+    }
+
+    // This font has a version 3.0 `post` table, which doesn't name glyphs.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let names = font.glyph_names();
+    assert_eq!(names.len(), font.glyph_count() as usize);
+    assert!(names.iter().all(Option::is_none));
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn encode_canvas_as_png() {
+    // Two pixels of padding at the end of each row.
+    let mut canvas = Canvas::with_stride(Vector2I::new(3, 2), 10, Format::A16);
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(1, 0), Vector2I::new(2, 1)),
+        &[0x34, 0x12],
+    );
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(0, 1), Vector2I::new(1, 1)),
+        &[0xff, 0xff],
+    );
+    canvas.pixels[6..10].copy_from_slice(&[0xaa; 4]);
+    let mut png = vec![];
+    canvas.encode_png(&mut png).unwrap();
+
+    let decoder = png::Decoder::new(&png[..]);
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (3, 2));
+    assert_eq!(info.color_type, png::ColorType::Grayscale);
+    assert_eq!(info.bit_depth, png::BitDepth::Sixteen);
+    // The padding is dropped, and samples are big-endian.
+    assert_eq!(
+        pixels[..info.buffer_size()],
+        [0, 0, 0x12, 0x34, 0x12, 0x34, 0xff, 0xff, 0, 0, 0, 0]
+    );
+}
+
+#[cfg(feature = "png")]
+#[test]
+fn encode_rgba_canvas_as_png() {
+    let mut canvas = Canvas::new(Vector2I::new(2, 2), Format::Rgba32);
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(0, 0), Vector2I::new(1, 2)),
+        &[0x10, 0x20, 0x30, 0x40],
+    );
+    canvas.fill_rect(
+        RectI::new(Vector2I::new(1, 1), Vector2I::new(1, 1)),
+        &[0xff, 0x80, 0x00, 0xff],
+    );
+    let mut png = vec![];
+    canvas.encode_png(&mut png).unwrap();
+
+    let decoder = png::Decoder::new(&png[..]);
+    let mut reader = decoder.read_info().unwrap();
+    let mut pixels = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut pixels).unwrap();
+    assert_eq!((info.width, info.height), (2, 2));
+    assert_eq!(info.color_type, png::ColorType::Rgba);
+    assert_eq!(info.bit_depth, png::BitDepth::Eight);
+    assert_eq!(pixels[..info.buffer_size()], canvas.pixels[..]);
+}
+
+#[test]
+fn get_glyph_is_empty() {
+    for path in &[TEST_FONT_FILE_PATH, FILE_PATH_EB_GARAMOND_TTF] {
+        let font = Font::from_path(path, 0).unwrap();
+        assert!(font.glyph_is_empty(font.glyph_for_char(' ').unwrap()));
+        assert!(!font.glyph_is_empty(font.glyph_for_char('A').unwrap()));
+        // Composite glyphs aren't empty either.
+        assert!(!font.glyph_is_empty(font.glyph_for_char('é').unwrap()));
+        assert!(font.glyph_is_empty(font.glyph_count()));
+    }
+}
+
+#[test]
+fn get_outline_format() {
+    let truetype_font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert_eq!(truetype_font.outline_format(), OutlineFormat::TrueType);
+    let cff_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(cff_font.outline_format(), OutlineFormat::Cff);
+    let collection_font = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 1).unwrap();
+    assert_eq!(collection_font.outline_format(), OutlineFormat::Cff);
+}
+
+#[test]
+fn get_fill_rule() {
+    let truetype_font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert_eq!(truetype_font.fill_rule(), FillRule::NonZero);
+    let cff_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(cff_font.fill_rule(), FillRule::NonZero);
+
+    // In EB Garamond's "≠", the slash is a separate contour that crosses the bars, so the point
+    // where they overlap has a winding number of 2 in magnitude. The rasterizer fills it, as the
+    // nonzero rule says to; under the even-odd rule it would be a hole.
+    let glyph_id = truetype_font.glyph_for_char('\u{2260}').unwrap();
+    let mut builder = OutlineBuilder::new();
+    truetype_font
+        .outline(glyph_id, HintingOptions::None, &mut builder)
+        .unwrap();
+    let overlap = Vector2F::new(364.0, 356.0);
+    assert_eq!(winding_number(&builder.take_outline(), overlap).abs(), 2);
+
+    let point_size = 100.0;
+    let origin = Vector2F::new(10.0, 80.0);
+    let mut canvas = Canvas::new(Vector2I::splat(100), Format::A8);
+    truetype_font
+        .rasterize_glyph(
+            &mut canvas,
+            glyph_id,
+            point_size,
+            Transform2F::from_translation(origin),
+            HintingOptions::None,
+            RasterizationOptions::GrayscaleAa,
+        )
+        .unwrap();
+    let units_per_em = truetype_font.metrics().units_per_em as f32;
+    let pixel =
+        (origin + overlap * Vector2F::new(1.0, -1.0) * (point_size / units_per_em)).to_i32();
+    assert_eq!(
+        canvas.pixel(pixel.x() as usize, pixel.y() as usize),
+        PixelValue::A8(255)
+    );
+}
+
+// The `glyf` table is patched in place, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_patched_glyph_overlap_flags() {
+    const OVERLAP_SIMPLE: u8 = 0x40;
+    const OVERLAP_COMPOUND: u16 = 0x0400;
+
+    let mut font_data = std::fs::read(FILE_PATH_EB_GARAMOND_TTF).unwrap();
+    let original_font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let simple_glyph_id = original_font.glyph_for_char('a').unwrap();
+    let composite_glyph_id = original_font.glyph_for_char('\u{e9}').unwrap();
+
+    let head_offset = font_table_offset(&font_data, b"head");
+    let loca_offset = font_table_offset(&font_data, b"loca");
+    let glyf_offset = font_table_offset(&font_data, b"glyf");
+    let long_offsets = font_data[head_offset + 50..head_offset + 52] == [0, 1];
+    let glyph_offset = |font_data: &[u8], glyph_id: u32| {
+        let glyph_offset = if long_offsets {
+            read_u32_be(&font_data[loca_offset + glyph_id as usize * 4..]) as usize
+        } else {
+            let offset = loca_offset + glyph_id as usize * 2;
+            u16::from_be_bytes([font_data[offset], font_data[offset + 1]]) as usize * 2
+        };
+        glyf_offset + glyph_offset
+    };
+
+    // In a simple glyph, the flag is on the first point; it follows the contour end points and
+    // the instructions.
+    let glyph = glyph_offset(&font_data, simple_glyph_id);
+    let num_contours = i16::from_be_bytes([font_data[glyph], font_data[glyph + 1]]);
+    assert!(num_contours > 0);
+    let instructions_offset = glyph + 10 + num_contours as usize * 2;
+    let instructions_length = u16::from_be_bytes([
+        font_data[instructions_offset],
+        font_data[instructions_offset + 1],
+    ]) as usize;
+    font_data[instructions_offset + 2 + instructions_length] |= OVERLAP_SIMPLE;
+
+    // In a composite glyph, it's on the first component.
+    let glyph = glyph_offset(&font_data, composite_glyph_id);
+    assert!(i16::from_be_bytes([font_data[glyph], font_data[glyph + 1]]) < 0);
+    let flags = u16::from_be_bytes([font_data[glyph + 10], font_data[glyph + 11]]);
+    font_data[glyph + 10..glyph + 12].copy_from_slice(&(flags | OVERLAP_COMPOUND).to_be_bytes());
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert!(font.glyph_has_overlap_flag(simple_glyph_id));
+    assert!(font.glyph_has_overlap_flag(composite_glyph_id));
+    assert!(!font.glyph_has_overlap_flag(font.glyph_for_char('b').unwrap()));
+}
+
+#[test]
+fn get_glyph_overlap_flags() {
+    // Neither font is variable, so neither sets the overlap flags.
+    let truetype_font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    for character in &['a', '\u{e9}'] {
+        let glyph_id = truetype_font.glyph_for_char(*character).unwrap();
+        assert!(!truetype_font.glyph_has_overlap_flag(glyph_id));
+    }
+    let cff_font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = cff_font.glyph_for_char('a').unwrap();
+    assert!(!cff_font.glyph_has_overlap_flag(glyph_id));
+}
+
+#[test]
+fn get_panose() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let panose = font.panose().unwrap();
+    assert_eq!(
+        panose[classification::PANOSE_FAMILY_KIND],
+        classification::PANOSE_FAMILY_KIND_LATIN_TEXT
+    );
+    // EB Garamond is a serif face.
+    assert!(!classification::panose_serif_style_is_sans_serif(
+        panose[classification::PANOSE_SERIF_STYLE]
+    ));
+}
+
+#[test]
+fn get_cff_hint_params() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let params = font.cff_hint_params().unwrap();
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(256.0, 1152.0),
-                        Vector2F::new(384.0, 1152.0),
-                        Vector2F::new(384.0, 0.0),
-                        Vector2F::new(256.0, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(256.0, 1536.0),
-                        Vector2F::new(384.0, 1536.0),
-                        Vector2F::new(384.0, 1280.0),
-                        Vector2F::new(256.0, 1280.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-            ],
-        }
+        params.blue_values,
+        [
+            (-22.0, 0.0),
+            (268.0, 287.0),
+            (400.0, 430.0),
+            (450.0, 464.0),
+            (616.0, 630.0),
+            (650.0, 664.0),
+            (700.0, 705.0),
+        ]
     );
+    assert_eq!(params.other_blues, [(-290.0, -285.0), (-181.0, -167.0)]);
+    assert_eq!(params.std_hw, Some(41.0));
+    assert_eq!(params.std_vw, Some(70.0));
+
+    let truetype_font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert_eq!(truetype_font.cff_hint_params(), None);
 }
 
-// Right now, only FreeType can do hinting.
-#[cfg(all(
-    not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
-    feature = "loader-freetype-default",
-    feature = "source"
-))]
 #[test]
-pub fn get_fully_hinted_glyph_outline() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+fn get_embedding_restrictions() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
         .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::Full(10.0), &mut outline_builder)
+        .read_to_end(&mut font_data)
         .unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    assert_eq!(
+        font.embedding_restrictions(),
+        EmbeddingRestrictions::default()
+    );
+    assert_eq!(
+        font.embedding_restrictions().permission,
+        EmbeddingPermission::Installable
+    );
 
-    let outline = outline_builder.into_outline();
+    // Set `fsType` to restricted-license embedding with no subsetting.
+    let os2_offset = font_table_offset(&font_data, b"OS/2");
+    font_data[os2_offset + 8..os2_offset + 10].copy_from_slice(&[0x01, 0x02]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(137.6, 1228.8),
-                        Vector2F::new(137.6, 1433.6),
-                        Vector2F::new(316.80002, 1433.6),
-                        Vector2F::new(316.80002, 1228.8),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(137.6, 0.0),
-                        Vector2F::new(137.6, 1024.0),
-                        Vector2F::new(316.80002, 1024.0),
-                        Vector2F::new(316.80002, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 4],
-                },
-            ],
+        font.embedding_restrictions(),
+        EmbeddingRestrictions {
+            permission: EmbeddingPermission::Restricted,
+            no_subsetting: true,
+            bitmap_only: false,
         }
     );
 }
 
-#[cfg(all(
-    feature = "source",
-    not(feature = "loader-freetype-default"),
-    not(any(target_os = "macos", target_os = "ios", target_family = "windows"))
-))]
 #[test]
-pub fn get_fully_hinted_glyph_outline() {
-    let mut file = File::open(FILE_PATH_INCONSOLATA_TTF).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char('i').expect("No glyph for char!");
+fn get_italic_angle() {
+    let roman = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 0).unwrap();
+    let italic = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 1).unwrap();
+    assert_eq!(roman.italic_angle(), 0.0);
+    assert_eq!(roman.properties().style, Style::Normal);
+    assert_eq!(italic.italic_angle(), -17.0);
+    assert_eq!(italic.properties().style, Style::Italic);
+}
 
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::Full(10.0), &mut outline_builder)
+#[test]
+fn is_signed() {
+    // EB Garamond has no `DSIG` table, and Inconsolata has one with no signatures.
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert!(!font.is_signed());
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
         .unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    assert!(!font.is_signed());
 
-    let outline = outline_builder.into_outline();
+    // Claim one signature. Only the header is checked, so the missing record doesn't matter.
+    let dsig_offset = font_table_offset(&font_data, b"DSIG");
+    font_data[dsig_offset + 4..dsig_offset + 6].copy_from_slice(&[0, 1]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert!(font.is_signed());
+}
+
+#[test]
+fn decode_embedding_restrictions() {
     assert_eq!(
-        outline,
-        Outline {
-            contours: vec![
-                Contour {
-                    positions: vec![
-                        Vector2F::new(100.0, 100.0),
-                        Vector2F::new(200.0, 100.0),
-                        Vector2F::new(200.0, 400.0),
-                        Vector2F::new(100.0, 400.0),
-                        Vector2F::new(100.0, 500.0),
-                        Vector2F::new(300.0, 500.0),
-                        Vector2F::new(300.0, 100.0),
-                        Vector2F::new(400.0, 100.0),
-                        Vector2F::new(400.0, 0.0),
-                        Vector2F::new(100.0, 0.0),
-                    ],
-                    flags: vec![PointFlags::empty(); 10],
-                },
-                Contour {
-                    positions: vec![
-                        Vector2F::new(200.0, 600.0),
-                        Vector2F::new(200.0, 600.0),
-                        Vector2F::new(200.0, 600.0),
-                        Vector2F::new(200.0, 600.0),
-                        Vector2F::new(200.0, 600.0),
-                        Vector2F::new(200.0, 700.0),
-                        Vector2F::new(200.0, 700.0),
-                        Vector2F::new(200.0, 700.0),
-                        Vector2F::new(200.0, 700.0),
-                        Vector2F::new(300.0, 700.0),
-                        Vector2F::new(300.0, 700.0),
-                        Vector2F::new(300.0, 700.0),
-                        Vector2F::new(300.0, 600.0),
-                        Vector2F::new(300.0, 600.0),
-                        Vector2F::new(300.0, 600.0),
-                        Vector2F::new(300.0, 600.0),
-                        Vector2F::new(200.0, 600.0),
-                    ],
-                    flags: vec![
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                        PointFlags::CONTROL_POINT_0,
-                        PointFlags::empty(),
-                    ],
-                },
-            ],
-        }
+        EmbeddingRestrictions::from_fs_type(0x0204).permission,
+        EmbeddingPermission::PreviewAndPrint
+    );
+    assert!(EmbeddingRestrictions::from_fs_type(0x0204).bitmap_only);
+    // Old fonts may set several permission bits, and the least restrictive wins.
+    assert_eq!(
+        EmbeddingRestrictions::from_fs_type(0x000e).permission,
+        EmbeddingPermission::Editable
     );
 }
 
 #[test]
-pub fn get_empty_glyph_outline() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
-    let mut outline_builder = OutlineBuilder::new();
-    font.outline(glyph, HintingOptions::None, &mut outline_builder)
-        .unwrap();
+fn get_code_page_ranges() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let code_page_ranges = font.code_page_ranges().unwrap();
+    assert!(code_page_ranges
+        .contains(CodePageRanges::LATIN_1 | CodePageRanges::LATIN_2 | CodePageRanges::CYRILLIC));
+    assert!(!code_page_ranges.contains(CodePageRanges::JAPANESE));
+}
 
-    let outline = outline_builder.into_outline();
-    assert_eq!(outline, Outline::new());
+#[test]
+fn get_glyph_paint_without_color_tables() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('A').unwrap();
+    assert_eq!(font.glyph_paint(glyph_id, 0), None);
 }
 
-// https://github.com/servo/font-kit/issues/141
 #[test]
-pub fn get_glyph_raster_bounds() {
-    let mut file = File::open(FILE_PATH_INCONSOLATA_TTF).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char('J').expect("No glyph for char!");
-    let transform = Transform2F::default();
-    let size = 32.0;
-    let hinting_options = HintingOptions::None;
-    let rasterization_options = RasterizationOptions::GrayscaleAa;
-    #[cfg(not(target_family = "windows"))]
-    let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 21));
-    #[cfg(target_family = "windows")]
-    let expected_rect = RectI::new(Vector2I::new(1, -20), Vector2I::new(14, 20));
+fn subset_truetype_font() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let a_glyph_id = font.glyph_for_char('a').unwrap();
+    let e_acute_glyph_id = font.glyph_for_char('\u{e9}').unwrap();
+    let subset_data = font.subset(&[a_glyph_id, e_acute_glyph_id]).unwrap();
+    assert!(subset_data.len() < font.copy_font_data().unwrap().len() / 10);
+
+    let subset = Font::from_bytes(Arc::new(subset_data), 0).unwrap();
+    assert_eq!(subset.postscript_name(), font.postscript_name());
+    // `.notdef`, "a", "é", and the "e" and acute accent that "é" is built from.
+    assert_eq!(subset.glyph_count(), 5);
+    assert_eq!(subset.glyph_for_char('b'), None);
+    let subset_e_acute_glyph_id = subset.glyph_for_char('\u{e9}').unwrap();
     assert_eq!(
-        font.raster_bounds(
-            glyph,
-            size,
-            transform,
-            hinting_options,
-            rasterization_options
-        ),
-        Ok(expected_rect)
+        subset
+            .glyph_components(subset_e_acute_glyph_id)
+            .unwrap()
+            .len(),
+        2
     );
+
+    for character in &['a', 'e', '\u{e9}'] {
+        let glyph_id = font.glyph_for_char(*character).unwrap();
+        let subset_glyph_id = subset.glyph_for_char(*character).unwrap();
+        assert_eq!(
+            subset.advance(subset_glyph_id).unwrap(),
+            font.advance(glyph_id).unwrap()
+        );
+
+        let render = |font: &Font, glyph_id| {
+            let mut canvas = Canvas::new(Vector2I::splat(32), Format::A8);
+            font.rasterize_glyph(
+                &mut canvas,
+                glyph_id,
+                24.0,
+                Transform2F::from_translation(Vector2F::new(4.0, 24.0)),
+                HintingOptions::None,
+                RasterizationOptions::GrayscaleAa,
+            )
+            .unwrap();
+            canvas.pixels
+        };
+        let pixels = render(&subset, subset_glyph_id);
+        assert!(pixels.iter().any(|&value| value != 0));
+        assert_eq!(pixels, render(&font, glyph_id));
+    }
 }
 
-#[cfg(all(
-    feature = "source",
-    any(target_family = "windows", target_os = "macos", target_os = "ios")
-))]
 #[test]
-pub fn get_glyph_typographic_bounds() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
-    assert_eq!(
-        font.typographic_bounds(glyph),
-        Ok(RectF::new(
-            Vector2F::new(74.0, -24.0),
-            Vector2F::new(978.0, 1110.0)
-        ))
-    );
+fn subset_cff_font_is_unsupported() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    match font.subset(&[1]) {
+        Err(FontLoadingError::UnknownFormat) => {}
+        other => panic!("unexpected result: {:?}", other.map(|data| data.len())),
+    }
 }
 
-#[cfg(all(
-    feature = "source",
-    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
-))]
 #[test]
-pub fn get_glyph_typographic_bounds() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
+fn load_fonts_from_opentype_collection() {
+    let mut file = File::open(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
+    {
+        let font = Font::from_file(&mut file, 0).unwrap();
+        assert_eq!(
+            font.postscript_name().unwrap(),
+            TEST_FONT_COLLECTION_POSTSCRIPT_NAME[0]
+        );
+    }
+    let font = Font::from_file(&mut file, 1).unwrap();
     assert_eq!(
-        font.typographic_bounds(glyph),
-        Ok(RectF::new(
-            Vector2F::new(123.0, -29.0),
-            Vector2F::new(946.0, 1176.0)
-        ))
+        font.postscript_name().unwrap(),
+        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
     );
 }
-
-#[cfg(all(feature = "source", target_family = "windows"))]
-#[test]
-pub fn get_glyph_advance_and_origin() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
-    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1139.0, 0.0)));
-    assert_eq!(font.origin(glyph), Ok(Vector2F::new(74.0, 1898.0)));
+
+#[test]
+fn get_device_advance_without_hdmx() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let glyph_id = font.glyph_for_char('a').unwrap();
+    assert_eq!(font.device_advance(glyph_id, 12), None);
 }
 
-#[cfg(all(feature = "source", target_os = "macos"))]
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
 #[test]
-pub fn get_glyph_advance_and_origin() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+fn get_device_advance_from_hdmx() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
         .unwrap()
-        .load()
+        .read_to_end(&mut font_data)
         .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
-    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1139.0, 0.0)));
-    assert_eq!(font.origin(glyph), Ok(Vector2F::default()));
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let glyph_count = font.glyph_count() as usize;
+    let glyph_id = font.glyph_for_char('a').unwrap();
+
+    // Replace the `GSUB` table with an `hdmx` table holding one record for 16 pixels per em in
+    // which glyph `n` is `n % 200` pixels wide.
+    let record_size = (2 + glyph_count + 3) & !3;
+    let mut hdmx = vec![0, 0, 0, 1];
+    hdmx.extend_from_slice(&(record_size as u32).to_be_bytes());
+    hdmx.extend_from_slice(&[16, 199]);
+    hdmx.extend((0..glyph_count).map(|glyph_id| (glyph_id % 200) as u8));
+    hdmx.resize(8 + record_size, 0);
+    replace_font_table(&mut font_data, b"GSUB", b"hdmx", &hdmx);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(
+        font.device_advance(glyph_id, 16),
+        Some((glyph_id % 200) as u8)
+    );
+    assert_eq!(font.device_advance(glyph_id, 17), None);
+    assert_eq!(font.device_advance(glyph_count as u32, 16), None);
 }
 
-#[cfg(all(
-    feature = "source",
-    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
-))]
 #[test]
-pub fn get_glyph_advance_and_origin() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph = font.glyph_for_char('a').expect("No glyph for char!");
-    assert_eq!(font.advance(glyph), Ok(Vector2F::new(1255.0, 0.0)));
-    assert_eq!(font.origin(glyph), Ok(Vector2F::default()));
+fn get_baseline_without_base_table() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    assert_eq!(font.baseline(ScriptTag::LATIN, BaselineTag::ROMAN), None);
 }
 
-#[cfg(all(
-    feature = "source",
-    any(target_family = "windows", target_os = "macos")
-))]
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
 #[test]
-pub fn get_font_metrics() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+fn get_baseline_from_base_table() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
         .unwrap()
-        .load()
+        .read_to_end(&mut font_data)
         .unwrap();
-    let metrics = font.metrics();
-    assert_eq!(metrics.units_per_em, 2048);
-    assert_eq!(metrics.ascent, 1854.0);
-    assert_eq!(metrics.descent, -434.0);
-    assert_eq!(metrics.line_gap, 67.0);
-    assert_eq!(metrics.underline_position, -217.0);
-    assert_eq!(metrics.underline_thickness, 150.0);
-    assert_eq!(metrics.cap_height, 1467.0);
-    assert_eq!(metrics.x_height, 1062.0);
 
-    // Different versions of the font can have different max heights, so ignore that.
-    let bounding_box = metrics.bounding_box;
-    assert_eq!(bounding_box.origin(), Vector2F::new(-1361.0, -665.0));
-    assert_eq!(bounding_box.width(), 5457.0);
+    // A horizontal axis with `ideo` and `romn` baselines for `hani` and `latn`. Both scripts put
+    // the ideographic baseline 120 units below the roman one.
+    #[rustfmt::skip]
+    let base: [u8; 80] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 8, 0, 0,                     // horizAxisOffset, vertAxisOffset
+        // Axis
+        0, 4, 0, 14,                    // baseTagListOffset, baseScriptListOffset
+        // BaseTagList
+        0, 2, b'i', b'd', b'e', b'o', b'r', b'o', b'm', b'n',
+        // BaseScriptList
+        0, 2,
+        b'h', b'a', b'n', b'i', 0, 14,  // baseScriptTag, baseScriptOffset
+        b'l', b'a', b't', b'n', 0, 36,
+        // BaseScript for `hani`
+        0, 6, 0, 0, 0, 0,               // baseValuesOffset, defaultMinMaxOffset, baseLangSysCount
+        0, 0, 0, 2, 0, 8, 0, 12,        // defaultBaselineIndex, baseCoordCount, baseCoordOffsets
+        0, 1, 0xff, 0x88,               // BaseCoord format 1: -120
+        0, 1, 0, 0,                     // BaseCoord format 1: 0
+        // BaseScript for `latn`
+        0, 6, 0, 0, 0, 0,
+        0, 1, 0, 2, 0, 8, 0, 12,
+        0, 1, 0xff, 0x88,
+        0, 1, 0, 0,
+    ];
+    replace_font_table(&mut font_data, b"GSUB", b"BASE", &base);
+
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(
+        font.baseline(ScriptTag::HAN, BaselineTag::IDEOGRAPHIC),
+        Some(-120)
+    );
+    assert_eq!(font.baseline(ScriptTag::HAN, BaselineTag::ROMAN), Some(0));
+    assert_eq!(
+        font.baseline(ScriptTag::LATIN, BaselineTag::IDEOGRAPHIC),
+        Some(-120)
+    );
+    assert_eq!(font.baseline(ScriptTag::LATIN, BaselineTag::HANGING), None);
+    assert_eq!(font.baseline(ScriptTag::CYRILLIC, BaselineTag::ROMAN), None);
 }
 
-#[cfg(all(
-    feature = "source",
-    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
-))]
 #[test]
-pub fn get_font_metrics() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let metrics = font.metrics();
-    assert_eq!(metrics.units_per_em, 2048);
-    assert_eq!(metrics.ascent, 1901.0);
-    assert_eq!(metrics.descent, -483.0);
-    assert_eq!(metrics.line_gap, 0.0); // FIXME(pcwalton): Huh?!
-    assert_eq!(metrics.underline_position, -40.0);
-    assert_eq!(metrics.underline_thickness, 90.0);
-    assert_eq!(metrics.cap_height, 0.0); // FIXME(pcwalton): Huh?!
-    assert_eq!(metrics.x_height, 0.0); // FIXME(pcwalton): Huh?!
+fn get_available_sizes_of_scalable_font() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert!(font.available_sizes().is_empty());
+}
+
+#[test]
+fn match_with_weight_priority() {
+    let candidates = [
+        *Properties::new().style(Style::Italic),
+        *Properties::new().weight(Weight::BOLD),
+    ];
+    let query = *Properties::new().style(Style::Italic).weight(Weight::BOLD);
+    assert_eq!(matching::find_best_match(&candidates, &query), Ok(0));
     assert_eq!(
-        metrics.bounding_box,
-        RectF::new(
-            Vector2F::new(-2090.0, -948.0),
-            Vector2F::new(5763.0, 3472.0)
-        )
+        matching::find_best_match_with_priority(
+            &candidates,
+            &query,
+            MatchPriority::StretchStyleWeight
+        ),
+        Ok(0)
     );
+    for &priority in &[
+        MatchPriority::StretchWeightStyle,
+        MatchPriority::WeightStyleStretch,
+    ] {
+        assert_eq!(
+            matching::find_best_match_with_priority(&candidates, &query, priority),
+            Ok(1)
+        );
+    }
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn get_font_properties() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let properties = font.properties();
-    assert_eq!(properties.weight, Weight(400.0));
-    assert_eq!(properties.stretch, Stretch(1.0));
+fn match_with_style_priority() {
+    let candidates = [
+        *Properties::new().stretch(Stretch::CONDENSED),
+        *Properties::new()
+            .stretch(Stretch::CONDENSED)
+            .style(Style::Italic)
+            .weight(Weight::BOLD),
+        *Properties::new().weight(Weight::BOLD),
+    ];
+    let query = *Properties::new().style(Style::Italic).weight(Weight::BOLD);
+    assert_eq!(matching::find_best_match(&candidates, &query), Ok(2));
+    assert_eq!(
+        matching::find_best_match_with_priority(
+            &candidates,
+            &query,
+            MatchPriority::StyleStretchWeight
+        ),
+        Ok(1)
+    );
+    assert_eq!(
+        matching::find_best_match_with_priority(
+            &candidates,
+            &query,
+            MatchPriority::WeightStretchStyle
+        ),
+        Ok(2)
+    );
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn load_font_table() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let head_table = font
-        .load_font_table(OPENTYPE_TABLE_TAG_HEAD)
-        .expect("Where's the `head` table?");
-    assert_eq!(&head_table[12..16], &[0x5f, 0x0f, 0x3c, 0xf5]);
+fn match_out_of_range_weights() {
+    let candidates = [
+        *Properties::new().weight(Weight::LIGHT),
+        *Properties::new().weight(Weight::NORMAL),
+        *Properties::new().weight(Weight::BLACK),
+    ];
+    let best_match =
+        |weight| matching::find_best_match(&candidates, Properties::new().weight(Weight(weight)));
+    // Weights are clamped to [1, 1000] before matching.
+    assert_eq!(best_match(0.0), Ok(0));
+    assert_eq!(best_match(-100.0), Ok(0));
+    assert_eq!(best_match(1200.0), Ok(2));
+    assert_eq!(best_match(f32::INFINITY), Ok(2));
+    assert_eq!(best_match(f32::NAN), Ok(1));
+
+    assert_eq!(Weight(0.0).clamped(), Weight::MIN);
+    assert_eq!(Weight(1200.0).clamped(), Weight::MAX);
+    assert_eq!(Weight::BOLD.clamped(), Weight::BOLD);
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn rasterize_glyph_with_grayscale_aa() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph_id = font.glyph_for_char('L').unwrap();
-    let size = 32.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::default(),
-            HintingOptions::None,
-            RasterizationOptions::GrayscaleAa,
-        )
-        .unwrap();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32()),
-        HintingOptions::None,
-        RasterizationOptions::GrayscaleAa,
-    )
-    .unwrap();
-    check_L_shape(&canvas);
+fn snap_weight_to_nearest_named() {
+    assert_eq!(Weight(400.0).nearest_named(), (Weight::NORMAL, "normal"));
+    assert_eq!(
+        Weight(640.0).nearest_named(),
+        (Weight::SEMIBOLD, "semi-bold")
+    );
+    // Midpoints snap to the heavier weight.
+    assert_eq!(Weight(350.0).nearest_named(), (Weight::NORMAL, "normal"));
+    assert_eq!(Weight(349.9).nearest_named(), (Weight::LIGHT, "light"));
+    assert_eq!(Weight(650.0).nearest_named(), (Weight::BOLD, "bold"));
+    // Weights beyond the named ones snap to the ends.
+    assert_eq!(Weight(1.0).nearest_named(), (Weight::THIN, "thin"));
+    assert_eq!(Weight(1000.0).nearest_named(), (Weight::BLACK, "black"));
+    assert_eq!(Weight(-1e12).nearest_named(), (Weight::THIN, "thin"));
+    assert_eq!(Weight(1e12).nearest_named(), (Weight::BLACK, "black"));
+    assert_eq!(
+        Weight(f32::NEG_INFINITY).nearest_named(),
+        (Weight::THIN, "thin")
+    );
+    assert_eq!(
+        Weight(f32::INFINITY).nearest_named(),
+        (Weight::BLACK, "black")
+    );
+    assert_eq!(Weight(f32::NAN).nearest_named(), (Weight::NORMAL, "normal"));
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn rasterize_glyph_bilevel() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph_id = font.glyph_for_char('L').unwrap();
-    let size = 16.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::default(),
-            HintingOptions::None,
-            RasterizationOptions::Bilevel,
-        )
-        .unwrap();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32()),
-        HintingOptions::None,
-        RasterizationOptions::Bilevel,
-    )
-    .unwrap();
-    assert!(canvas
-        .pixels
-        .iter()
-        .all(|&value| value == 0 || value == 0xff));
-    check_L_shape(&canvas);
+fn clamp_weight_to_available() {
+    let available = [Weight::LIGHT, Weight::NORMAL, Weight::BOLD];
+    assert_eq!(Weight::BOLD.clamp_to_available(&available), Weight::BOLD);
+    // Light requests fall back to lighter weights and heavy ones to heavier weights, as in CSS.
+    assert_eq!(Weight(350.0).clamp_to_available(&available), Weight::LIGHT);
+    assert_eq!(Weight(550.0).clamp_to_available(&available), Weight::BOLD);
+    assert_eq!(Weight(450.0).clamp_to_available(&available), Weight::NORMAL);
+    assert_eq!(Weight::BLACK.clamp_to_available(&available), Weight::BOLD);
+    assert_eq!(Weight::THIN.clamp_to_available(&available), Weight::LIGHT);
+    assert_eq!(Weight(550.0).clamp_to_available(&[]), Weight(550.0));
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn rasterize_glyph_bilevel_offset() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph_id = font.glyph_for_char('L').unwrap();
-    let size = 32.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::from_translation(Vector2F::new(30.0, 100.0)),
-            HintingOptions::None,
-            RasterizationOptions::Bilevel,
-        )
-        .unwrap();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32() + Vector2F::new(30.0, 100.0)),
-        HintingOptions::None,
-        RasterizationOptions::Bilevel,
-    )
-    .unwrap();
+fn properties_from_style_name() {
+    let properties = Properties::from_style_name("Bold Italic Condensed");
+    assert_eq!(properties.weight, Weight::BOLD);
+    assert_eq!(properties.style, Style::Italic);
+    assert_eq!(properties.stretch, Stretch::CONDENSED);
 
-    assert!(canvas
-        .pixels
-        .iter()
-        .all(|&value| value == 0 || value == 0xff));
-    check_L_shape(&canvas);
+    let properties = Properties::from_style_name("SemiBold Oblique");
+    assert_eq!(properties.weight, Weight::SEMIBOLD);
+    assert_eq!(properties.style, Style::Oblique);
+    assert_eq!(properties.stretch, Stretch::NORMAL);
+
+    // Order, case, separators, and camel case don't matter.
+    for name in &[
+        "condensed extra light italic",
+        "Italic-ExtraLight-Condensed",
+        "ExtraLightCondensedItalic",
+        "Extra_Light Italic CONDENSED",
+    ] {
+        let properties = Properties::from_style_name(name);
+        assert_eq!(properties.weight, Weight::EXTRA_LIGHT, "{}", name);
+        assert_eq!(properties.style, Style::Italic, "{}", name);
+        assert_eq!(properties.stretch, Stretch::CONDENSED, "{}", name);
+    }
+
+    let properties = Properties::from_style_name("Semi Expanded Black");
+    assert_eq!(properties.weight, Weight::BLACK);
+    assert_eq!(properties.stretch, Stretch::SEMI_EXPANDED);
+
+    assert_eq!(Properties::from_style_name("Regular"), Properties::new());
+    assert_eq!(Properties::from_style_name(""), Properties::new());
 }
 
-#[cfg(all(
-    feature = "source",
-    any(
-        not(any(target_os = "macos", target_os = "ios", target_family = "windows")),
-        feature = "loader-freetype-default"
-    )
-))]
+// The `OS/2` table is retagged, which only the FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
 #[test]
-pub fn rasterize_glyph_with_full_hinting() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph_id = font.glyph_for_char('L').unwrap();
-    let size = 32.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::default(),
-            HintingOptions::None,
-            RasterizationOptions::Bilevel,
-        )
-        .unwrap();
-    let origin = -raster_rect.origin().to_f32();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(origin),
-        HintingOptions::Full(size),
-        RasterizationOptions::GrayscaleAa,
-    )
-    .unwrap();
-    check_L_shape(&canvas);
+fn get_properties_without_os2_table() {
+    let mut font_data =
+        std::fs::read("resources/tests/eb-garamond/EBGaramond12-Italic.otf").unwrap();
+    replace_font_table(&mut font_data, b"OS/2", b"OS/3", &[]);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(font.properties().style, Style::Italic);
+    assert_eq!(font.properties().weight, Weight::NORMAL);
+    assert_eq!(font.properties().stretch, Stretch::NORMAL);
+}
 
-    // Make sure the top and bottom (non-blank) rows have some fully black pixels in them.
-    let mut top_row = &canvas.pixels[0..canvas.stride];
-    if top_row.iter().all(|&value| value == 0) {
-        top_row = &canvas.pixels[canvas.stride..(2 * canvas.stride)];
-    }
+#[test]
+fn custom_property_mapping() {
+    let default_mapping = PropertyMapping::default();
+    assert_eq!(default_mapping, PropertyMapping::CORE_TEXT);
+    assert_eq!(default_mapping.weight_to_css(0.4), Weight::BOLD);
+    assert_eq!(default_mapping.weight_from_css(Weight::BOLD), 0.4);
+    assert_eq!(default_mapping.width_to_css(0.0), Stretch::NORMAL);
 
-    assert!(top_row.iter().any(|&value| value == 0xff));
-    for y in (0..(canvas.size.y() as usize)).rev() {
-        let bottom_row = &canvas.pixels[(y * canvas.stride)..((y + 1) * canvas.stride)];
-        if bottom_row.iter().all(|&value| value == 0) {
-            continue;
-        }
-        assert!(bottom_row.iter().any(|&value| value == 0xff));
-        break;
-    }
+    // A design system whose weights are evenly spaced, and whose widths vary less than usual.
+    let mapping = PropertyMapping {
+        weights: [-0.8, -0.6, -0.4, -0.2, 0.0, 0.2, 0.4, 0.6, 0.8],
+        widths: [0.6, 0.7, 0.8, 0.9, 1.0, 1.1, 1.2, 1.3, 1.4],
+    };
+    assert_eq!(mapping.weight_to_css(0.0), Weight::MEDIUM);
+    assert_eq!(mapping.weight_to_css(0.1), Weight(550.0));
+    assert_eq!(mapping.weight_from_css(Weight::LIGHT), -0.4);
+    assert_eq!(mapping.weight_from_css(Weight::MAX), 0.8);
+    assert_eq!(mapping.width_to_css(0.5), Stretch(1.2));
+    assert_eq!(mapping.width_from_css(Stretch::NORMAL), 0.0);
+    assert_eq!(mapping.width_from_css(Stretch::ULTRA_CONDENSED), -1.0);
 }
 
-#[cfg(all(feature = "source", target_family = "windows"))]
 #[test]
-pub fn rasterize_glyph() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
-        .unwrap()
-        .load()
-        .unwrap();
-    let glyph_id = font.glyph_for_char('{').unwrap();
-    let size = 32.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::default(),
-            HintingOptions::None,
-            RasterizationOptions::GrayscaleAa,
-        )
-        .unwrap();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph_id,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32()),
-        HintingOptions::None,
-        RasterizationOptions::GrayscaleAa,
+fn match_stretch_in_variable_wdth_range() {
+    let candidates = [
+        (
+            *Properties::new().stretch(Stretch::CONDENSED),
+            StretchRange::from(Stretch::CONDENSED),
+        ),
+        (Properties::new(), StretchRange::from_wdth_axis(75.0, 100.0)),
+        (
+            *Properties::new().style(Style::Italic),
+            StretchRange::from_wdth_axis(75.0, 100.0),
+        ),
+    ];
+    let result = matching::find_best_match_in_stretch_ranges(
+        &candidates,
+        Properties::new().stretch(Stretch(0.85)),
     )
     .unwrap();
-    check_curly_shape(&canvas);
+    assert_eq!(result.index, 1);
+    assert_eq!(result.stretch, Stretch(0.85));
+    assert_eq!(result.wdth(), 85.0);
 }
 
-// Tests that an empty glyph can be successfully rasterized to a 0x0 canvas (issue #7).
-#[cfg(feature = "source")]
 #[test]
-pub fn rasterize_empty_glyph() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
-    let mut canvas = Canvas::new(Vector2I::splat(16), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph,
-        16.0,
-        Transform2F::default(),
-        HintingOptions::None,
-        RasterizationOptions::GrayscaleAa,
+fn match_stretch_outside_variable_wdth_range() {
+    let candidates = [
+        (Properties::new(), StretchRange::from_wdth_axis(90.0, 110.0)),
+        (
+            *Properties::new().stretch(Stretch::CONDENSED),
+            StretchRange::from(Stretch::CONDENSED),
+        ),
+    ];
+    // Narrower widths are preferred for requests narrower than normal.
+    let result = matching::find_best_match_in_stretch_ranges(
+        &candidates,
+        Properties::new().stretch(Stretch(0.85)),
     )
     .unwrap();
+    assert_eq!(result.index, 1);
+    assert_eq!(result.stretch, Stretch::CONDENSED);
 }
 
-// Tests that an empty glyph can be successfully rasterized to a 0x0 canvas (issue #7).
-#[cfg(feature = "source")]
 #[test]
-pub fn rasterize_empty_glyph_on_empty_canvas() {
-    let mut file = File::open(TEST_FONT_FILE_PATH).unwrap();
-    let font = Font::from_file(&mut file, 0).unwrap();
-    let glyph = font.glyph_for_char(' ').expect("No glyph for char!");
-    let size = 32.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph,
-            size,
-            Transform2F::default(),
-            HintingOptions::None,
-            RasterizationOptions::GrayscaleAa,
-        )
-        .unwrap();
-    let mut canvas = Canvas::new(raster_rect.size(), Format::A8);
-    font.rasterize_glyph(
-        &mut canvas,
-        glyph,
-        size,
-        Transform2F::from_translation(-raster_rect.origin().to_f32()),
-        HintingOptions::None,
-        RasterizationOptions::GrayscaleAa,
-    )
-    .unwrap();
+fn describe_font_without_loading_it() {
+    let info = FontInfo::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(info.family_name, "EB Garamond");
+    assert_eq!(
+        info.postscript_name.as_deref(),
+        Some(TEST_FONT_POSTSCRIPT_NAME)
+    );
+    assert_eq!(info.properties, Properties::new());
+    assert!(!info.is_variable);
+
+    for (font_index, postscript_name) in TEST_FONT_COLLECTION_POSTSCRIPT_NAME.iter().enumerate() {
+        let info = FontInfo::from_path(TEST_FONT_COLLECTION_FILE_PATH, font_index as u32).unwrap();
+        assert_eq!(info.family_name, "EB Garamond");
+        assert_eq!(info.postscript_name.as_deref(), Some(*postscript_name));
+    }
+    let info = FontInfo::from_path(TEST_FONT_COLLECTION_FILE_PATH, 1).unwrap();
+    assert_eq!(info.properties, *Properties::new().style(Style::Italic));
+    assert_eq!(info.style_name.as_deref(), Some("12 Italic"));
+    assert!(!info.is_color);
+    assert!(matches!(
+        FontInfo::from_path(TEST_FONT_COLLECTION_FILE_PATH, 2),
+        Err(FontLoadingError::NoSuchFontInCollection)
+    ));
+    assert!(matches!(
+        FontInfo::from_path("resources/tests/times-roman-pcf/timR12.pcf", 0),
+        Err(FontLoadingError::UnknownFormat)
+    ));
 }
 
-#[cfg(feature = "source")]
 #[test]
-pub fn font_transform() {
-    let font = SystemSource::new()
-        .select_best_match(&[FamilyName::SansSerif], &Properties::new())
+fn describe_font_from_bytes() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
         .unwrap()
-        .load()
+        .read_to_end(&mut font_data)
         .unwrap();
-    let glyph_id = font.glyph_for_char('L').unwrap();
-    let size = 16.0;
-    let raster_rect = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::from_translation(Vector2F::splat(8.0)),
-            HintingOptions::None,
-            RasterizationOptions::Bilevel,
-        )
+    assert_eq!(
+        FontInfo::from_bytes(&font_data, 0).unwrap(),
+        FontInfo::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap()
+    );
+    assert!(matches!(
+        FontInfo::from_bytes(&font_data[..100], 0),
+        Err(FontLoadingError::Parse)
+    ));
+
+    // An `fvar` table with a single weight axis and no named instances.
+    #[rustfmt::skip]
+    let fvar: [u8; 36] = [
+        0, 1, 0, 0,                     // version 1.0
+        0, 16, 0, 2,                    // axesArrayOffset, reserved
+        0, 1, 0, 20,                    // axisCount, axisSize
+        0, 0, 0, 4,                     // instanceCount, instanceSize
+        b'w', b'g', b'h', b't',         // axisTag
+        0, 100, 0, 0, 1, 144, 0, 0,     // minValue, defaultValue
+        3, 132, 0, 0, 0, 0, 1, 0,       // maxValue, flags, axisNameID
+    ];
+    replace_font_table(&mut font_data, b"GSUB", b"fvar", &fvar);
+    assert!(FontInfo::from_bytes(&font_data, 0).unwrap().is_variable);
+}
+
+// `FontInfo` chooses names and properties the way the FreeType loader does.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn describe_fonts_like_freetype() {
+    for &(path, font_index) in &[
+        (TEST_FONT_FILE_PATH, 0),
+        (TEST_FONT_COLLECTION_FILE_PATH, 0),
+        (TEST_FONT_COLLECTION_FILE_PATH, 1),
+        (FILE_PATH_EB_GARAMOND_TTF, 0),
+        (FILE_PATH_INCONSOLATA_TTF, 0),
+    ] {
+        let info = FontInfo::from_path(path, font_index).unwrap();
+        let font = Font::from_path(path, font_index).unwrap();
+        assert_eq!(info.family_name, font.family_name());
+        assert_eq!(info.postscript_name, font.postscript_name());
+        assert_eq!(info.properties, font.properties());
+    }
+}
+
+#[test]
+fn get_glyph_count() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    assert_eq!(font.glyph_count(), 3084);
+}
+
+#[test]
+fn get_composite_glyph_components() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let e_acute = font.glyph_for_char('é').unwrap();
+    assert_eq!(
+        font.glyph_components(e_acute),
+        Some(vec![
+            GlyphComponent {
+                glyph_id: font.glyph_for_char('\u{301}').unwrap(),
+                offset: Vector2F::new(165.0, 0.0),
+                matrix: Matrix2x2F::default(),
+                anchor_points: None,
+            },
+            GlyphComponent {
+                glyph_id: font.glyph_for_char('e').unwrap(),
+                offset: Vector2F::default(),
+                matrix: Matrix2x2F::default(),
+                anchor_points: None,
+            },
+        ])
+    );
+}
+
+#[test]
+fn get_simple_glyph_components() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let e = font.glyph_for_char('e').unwrap();
+    assert_eq!(font.glyph_components(e), None);
+
+    // CFF fonts have no composite glyphs.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let e_acute = font.glyph_for_char('é').unwrap();
+    assert_eq!(font.glyph_components(e_acute), None);
+}
+
+#[test]
+fn get_glyph_raw_points() {
+    let font = Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0).unwrap();
+    let o = font.glyph_for_char('o').unwrap();
+    let contours = font.glyph_raw_points(o).unwrap();
+    assert_eq!(contours.len(), 2);
+    for contour in &contours {
+        assert_eq!(contour.positions.len(), contour.on_curve.len());
+        assert!(contour.on_curve.iter().any(|&on_curve| !on_curve));
+    }
+
+    // The off-curve points are the control points of the quadratic curves in the outline, and
+    // the on-curve points are their endpoints.
+    let mut segments: Vec<PathSegment> = vec![];
+    font.outline(o, HintingOptions::None, &mut segments)
         .unwrap();
-    let raster_rect2 = font
-        .raster_bounds(
-            glyph_id,
-            size,
-            Transform2F::row_major(3.0, 0.0, 0.0, 3.0, 8.0, 8.0),
-            HintingOptions::None,
-            RasterizationOptions::Bilevel,
-        )
+    let mut control_points = vec![];
+    let mut endpoints = vec![];
+    for segment in segments {
+        match segment {
+            PathSegment::MoveTo(to) | PathSegment::LineTo(to) => endpoints.push(to),
+            PathSegment::QuadraticCurveTo { ctrl, to } => {
+                control_points.push(ctrl);
+                endpoints.push(to);
+            }
+            _ => {}
+        }
+    }
+    for contour in &contours {
+        for (position, &on_curve) in contour.positions.iter().zip(contour.on_curve.iter()) {
+            if on_curve {
+                assert!(endpoints.contains(position));
+            } else {
+                assert!(control_points.contains(position));
+            }
+        }
+    }
+
+    // The space has no contours, and composite glyphs have no points of their own.
+    let space = font.glyph_for_char(' ').unwrap();
+    assert_eq!(font.glyph_raw_points(space), Some(vec![]));
+    let e_acute = font.glyph_for_char('é').unwrap();
+    assert_eq!(font.glyph_raw_points(e_acute), None);
+
+    // CFF outlines have no quadratic points.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let o = font.glyph_for_char('o').unwrap();
+    assert_eq!(font.glyph_raw_points(o), None);
+}
+
+#[test]
+fn get_glyph_outline_segments() {
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let glyph_id = font.glyph_for_char('g').unwrap();
+
+    let mut expected = vec![];
+    font.outline(glyph_id, HintingOptions::None, &mut expected)
         .unwrap();
-    assert!((raster_rect2.width() - raster_rect.width() * 3).abs() <= 3);
-    assert!((raster_rect2.height() - raster_rect.height() * 3).abs() <= 3);
-    assert!((raster_rect2.origin_x() - ((raster_rect.origin_x() - 8) * 3 + 8)).abs() <= 3);
-    assert!((raster_rect2.origin_y() - ((raster_rect.origin_y() - 8) * 3 + 8)).abs() <= 3);
+    let segments = font
+        .outline_segments(glyph_id, HintingOptions::None)
+        .unwrap();
+    assert_eq!(segments.len(), expected.len());
+    let segments: Vec<PathSegment> = segments.collect();
+    assert_eq!(segments, expected);
+
+    // The CFF outlines of EB Garamond are made of cubic curves.
+    assert!(matches!(segments.first(), Some(PathSegment::MoveTo(_))));
+    assert_eq!(segments.last(), Some(&PathSegment::Close));
+    assert!(segments
+        .iter()
+        .any(|segment| matches!(segment, PathSegment::CubicCurveTo { .. })));
 }
 
 #[test]
-fn load_fonts_from_opentype_collection() {
-    let mut file = File::open(TEST_FONT_COLLECTION_FILE_PATH).unwrap();
-    {
-        let font = Font::from_file(&mut file, 0).unwrap();
-        assert_eq!(
-            font.postscript_name().unwrap(),
-            TEST_FONT_COLLECTION_POSTSCRIPT_NAME[0]
+fn get_glyph_hit_region() {
+    // Returns how many times `polygons` wind around `point`, counterclockwise positive.
+    fn winding_number(polygons: &[Vec<Vector2F>], point: Vector2F) -> i32 {
+        let mut winding = 0;
+        for polygon in polygons {
+            for (index, &from) in polygon.iter().enumerate() {
+                let to = polygon[(index + 1) % polygon.len()];
+                if (from.y() <= point.y()) != (to.y() <= point.y()) {
+                    let t = (point.y() - from.y()) / (to.y() - from.y());
+                    if from.x() + t * (to.x() - from.x()) > point.x() {
+                        winding += if to.y() > from.y() { 1 } else { -1 };
+                    }
+                }
+            }
+        }
+        winding
+    }
+    let min_x = |polygon: &Vec<Vector2F>| {
+        polygon
+            .iter()
+            .map(|point| point.x())
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    for path in &[TEST_FONT_FILE_PATH, FILE_PATH_INCONSOLATA_TTF] {
+        let font = Font::from_path(path, 0).unwrap();
+        let glyph_id = font.glyph_for_char('O').unwrap();
+        let bounds = font.typographic_bounds(glyph_id).unwrap();
+        let mut polygons = font.glyph_hit_region(glyph_id, 1.0).unwrap();
+        assert_eq!(polygons.len(), 2);
+        for point in polygons.iter().flatten() {
+            assert!(bounds.dilate(1.0).contains_point(*point));
+        }
+
+        // The bowl is empty, but the stroke around it is filled.
+        let center = bounds.center();
+        assert_eq!(winding_number(&polygons, center), 0);
+        polygons.sort_by(|a, b| min_x(a).partial_cmp(&min_x(b)).unwrap());
+        let stroke = Vector2F::new(
+            (min_x(&polygons[0]) + min_x(&polygons[1])) * 0.5,
+            center.y(),
         );
+        assert_ne!(winding_number(&polygons, stroke), 0);
+        let outside = Vector2F::new(bounds.min_x() - 10.0, center.y());
+        assert_eq!(winding_number(&polygons, outside), 0);
+
+        // A tighter tolerance takes more points.
+        let point_count = |polygons: &[Vec<Vector2F>]| polygons.iter().map(Vec::len).sum::<usize>();
+        let fine_polygons = font.glyph_hit_region(glyph_id, 0.1).unwrap();
+        assert!(point_count(&fine_polygons) > point_count(&polygons));
     }
-    let font = Font::from_file(&mut file, 1).unwrap();
-    assert_eq!(
-        font.postscript_name().unwrap(),
-        TEST_FONT_COLLECTION_POSTSCRIPT_NAME[1]
-    );
+
+    // Glyphs with no outline have no region.
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let space = font.glyph_for_char(' ').unwrap();
+    assert!(font.glyph_hit_region(space, 1.0).unwrap().is_empty());
 }
 
 #[test]
-fn get_glyph_count() {
+fn compare_font_outlines() {
     let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
-    assert_eq!(font.glyph_count(), 3084);
+    assert!(font.outlines_equal(&font));
+
+    // The same font, read out of a collection.
+    let collection_font = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 0).unwrap();
+    assert!(font.outlines_equal(&collection_font));
+
+    let italic_font = Font::from_path(TEST_FONT_COLLECTION_FILE_PATH, 1).unwrap();
+    assert!(!font.outlines_equal(&italic_font));
+    let other_font = Font::from_path(FILE_PATH_INCONSOLATA_TTF, 0).unwrap();
+    assert!(!font.outlines_equal(&other_font));
+    assert!(!other_font.outlines_equal(&font));
+}
+
+#[test]
+fn font_keys_identify_fonts_by_content() {
+    use std::collections::HashMap;
+
+    let font = Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap();
+    let mut font_data = vec![];
+    File::open(TEST_FONT_FILE_PATH)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let same_font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    assert_eq!(
+        font.font_key(),
+        Font::from_path(TEST_FONT_FILE_PATH, 0).unwrap().font_key()
+    );
+    assert_eq!(font.font_key(), same_font.font_key());
+
+    let mut names = HashMap::new();
+    names.insert(font.font_key(), "regular");
+    names.insert(same_font.font_key(), "regular again");
+    let italic_font =
+        Font::from_path("resources/tests/eb-garamond/EBGaramond12-Italic.otf", 0).unwrap();
+    names.insert(italic_font.font_key(), "italic");
+    assert_eq!(names.len(), 2);
+    assert_eq!(names[&font.font_key()], "regular again");
+    assert_ne!(
+        font.font_key(),
+        Font::from_path(FILE_PATH_EB_GARAMOND_TTF, 0)
+            .unwrap()
+            .font_key()
+    );
 }
 
 // The initial off-curve point used to cause an assertion in the FreeType backend.
@@ -1255,3 +4949,188 @@ fn stripe_width(pixels: &[u8]) -> Option<u32> {
     assert_eq!(x, pixels.len());
     Some(stripe_width)
 }
+
+#[test]
+fn get_line_metrics() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let font = Font::from_bytes(Arc::new(font_data.clone()), 0).unwrap();
+    let metrics = font.metrics();
+    let line_metrics = font.line_metrics(20.0);
+    let scale = 20.0 / metrics.units_per_em as f32;
+    assert_eq!(
+        line_metrics.line_height,
+        (metrics.ascent - metrics.descent + metrics.line_gap) * scale
+    );
+    assert_eq!(line_metrics.ascent, metrics.ascent * scale);
+    assert_eq!(line_metrics.descent, metrics.descent * scale);
+    assert_eq!(line_metrics, LineMetrics::from_metrics(&metrics, 20.0));
+
+    // Set `USE_TYPO_METRICS` and give the typographic metrics different values from `hhea`.
+    let os2_offset = font_table_offset(&font_data, b"OS/2");
+    font_data[os2_offset + 63] |= 0x80;
+    font_data[os2_offset + 68..os2_offset + 74].copy_from_slice(&[
+        0x03, 0x20, // sTypoAscender = 800
+        0xfe, 0xd4, // sTypoDescender = -300
+        0x00, 0x64, // sTypoLineGap = 100
+    ]);
+
+    let line_metrics = Font::from_bytes(Arc::new(font_data), 0)
+        .unwrap()
+        .line_metrics(20.0);
+    assert_eq!(line_metrics.ascent, 16.0);
+    assert_eq!(line_metrics.descent, -6.0);
+    assert_eq!(line_metrics.leading, 2.0);
+    assert_eq!(line_metrics.baseline_offset, 17.0);
+    assert_eq!(line_metrics.line_height, 24.0);
+}
+
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+#[test]
+fn get_metrics_with_zeroed_os2_heights() {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_EB_GARAMOND_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let expected = Font::from_bytes(Arc::new(font_data.clone()), 0)
+        .unwrap()
+        .metrics();
+
+    // Zero out `sxHeight` and `sCapHeight` in the `OS/2` table.
+    let os2_offset = font_table_offset(&font_data, b"OS/2");
+    font_data[os2_offset + 86..os2_offset + 90].fill(0);
+
+    let metrics = Font::from_bytes(Arc::new(font_data), 0).unwrap().metrics();
+    assert!(metrics.x_height > 0.0);
+    assert!(metrics.x_height < metrics.cap_height);
+    assert!(metrics.cap_height < metrics.ascent);
+    assert!((metrics.x_height - expected.x_height).abs() < 30.0);
+    assert!((metrics.cap_height - expected.cap_height).abs() < 30.0);
+}
+
+// Returns the winding number of `outline` around `point`, treating each contour as a polygon
+// through its points, which is exact for outlines made only of lines.
+fn winding_number(outline: &Outline, point: Vector2F) -> i32 {
+    let mut winding_number = 0;
+    for contour in &outline.contours {
+        let positions = &contour.positions;
+        for (index, &from) in positions.iter().enumerate() {
+            let to = positions[(index + 1) % positions.len()];
+            let (edge, offset) = (to - from, point - from);
+            let side = edge.x() * offset.y() - edge.y() * offset.x();
+            if from.y() <= point.y() && to.y() > point.y() && side > 0.0 {
+                winding_number += 1;
+            } else if from.y() > point.y() && to.y() <= point.y() && side < 0.0 {
+                winding_number -= 1;
+            }
+        }
+    }
+    winding_number
+}
+
+// Overwrites the table tagged `old_tag`, which must be at least as large as `table`, with
+// `table`, and retags it `new_tag`.
+fn replace_font_table(font_data: &mut [u8], old_tag: &[u8; 4], new_tag: &[u8; 4], table: &[u8]) {
+    let record_offset = font_table_record_offset(font_data, old_tag);
+    let table_offset = read_u32_be(&font_data[record_offset + 8..]) as usize;
+    assert!(table.len() <= read_u32_be(&font_data[record_offset + 12..]) as usize);
+    font_data[record_offset..record_offset + 4].copy_from_slice(new_tag);
+    font_data[record_offset + 12..record_offset + 16]
+        .copy_from_slice(&(table.len() as u32).to_be_bytes());
+    font_data[table_offset..table_offset + table.len()].copy_from_slice(table);
+}
+
+// Loads Inconsolata with a COLR table that draws the glyph for `character` as a single red layer
+// of itself. The `COLR` and `CPAL` tables are patched in over `GPOS` and `GSUB`, which only the
+// FreeType loader picks up.
+#[cfg(not(any(target_family = "windows", target_os = "macos", target_os = "ios")))]
+fn colr_font(character: char) -> (Font, u32) {
+    let mut font_data = vec![];
+    File::open(FILE_PATH_INCONSOLATA_TTF)
+        .unwrap()
+        .read_to_end(&mut font_data)
+        .unwrap();
+    let glyph_id = Font::from_bytes(Arc::new(font_data.clone()), 0)
+        .unwrap()
+        .glyph_for_char(character)
+        .unwrap();
+    let glyph_id = (glyph_id as u16).to_be_bytes();
+
+    // Version 0, one base glyph record at 14, one layer record at 20.
+    let mut colr = vec![0, 0, 0, 1, 0, 0, 0, 14, 0, 0, 0, 20, 0, 1];
+    colr.extend_from_slice(&[glyph_id[0], glyph_id[1], 0, 0, 0, 1]);
+    colr.extend_from_slice(&[glyph_id[0], glyph_id[1], 0, 0]);
+    // Version 0, one palette of one color, stored as BGRA.
+    let cpal = [0, 0, 0, 1, 0, 1, 0, 1, 0, 0, 0, 14, 0, 0, 0, 0, 0xff, 0xff];
+    replace_font_table(&mut font_data, b"GPOS", b"COLR", &colr);
+    replace_font_table(&mut font_data, b"GSUB", b"CPAL", &cpal);
+    let font = Font::from_bytes(Arc::new(font_data), 0).unwrap();
+    (font, u32::from(u16::from_be_bytes(glyph_id)))
+}
+
+// Wraps a single SFNT font in a WOFF 1.0 container, storing every table uncompressed.
+#[cfg(all(
+    feature = "source",
+    not(any(target_family = "windows", target_os = "macos", target_os = "ios"))
+))]
+fn woff_from_sfnt(font_data: &[u8]) -> Vec<u8> {
+    const WOFF_HEADER_SIZE: usize = 44;
+    let table_count = u16::from_be_bytes([font_data[4], font_data[5]]) as usize;
+    let records: Vec<&[u8]> = (0..table_count)
+        .map(|index| &font_data[12 + index * 16..28 + index * 16])
+        .collect();
+    let padded_length = |record: &[u8]| (read_u32_be(&record[12..]) as usize + 3) & !3;
+    let sfnt_size = 12 + 16 * table_count + records.iter().map(|r| padded_length(r)).sum::<usize>();
+
+    let mut directory = vec![];
+    let mut tables = vec![];
+    let mut table_offset = WOFF_HEADER_SIZE + 20 * table_count;
+    for record in records {
+        let (offset, length) = (
+            read_u32_be(&record[8..]) as usize,
+            read_u32_be(&record[12..]) as usize,
+        );
+        directory.extend_from_slice(&record[0..4]);
+        directory.extend_from_slice(&(table_offset as u32).to_be_bytes());
+        directory.extend_from_slice(&(length as u32).to_be_bytes());
+        directory.extend_from_slice(&(length as u32).to_be_bytes());
+        directory.extend_from_slice(&record[4..8]);
+        tables.extend_from_slice(&font_data[offset..offset + length]);
+        tables.resize(tables.len() + padded_length(record) - length, 0);
+        table_offset += padded_length(record);
+    }
+
+    let mut woff = b"wOFF".to_vec();
+    woff.extend_from_slice(&font_data[0..4]);
+    woff.extend_from_slice(&(table_offset as u32).to_be_bytes());
+    woff.extend_from_slice(&(table_count as u16).to_be_bytes());
+    woff.extend_from_slice(&[0; 2]);
+    woff.extend_from_slice(&(sfnt_size as u32).to_be_bytes());
+    woff.extend_from_slice(&[0, 1, 0, 0]);
+    woff.resize(WOFF_HEADER_SIZE, 0);
+    woff.extend(directory);
+    woff.extend(tables);
+    woff
+}
+
+// Returns the offset of the table tagged `tag` in a single font.
+fn font_table_offset(font_data: &[u8], tag: &[u8; 4]) -> usize {
+    let record_offset = font_table_record_offset(font_data, tag);
+    read_u32_be(&font_data[record_offset + 8..]) as usize
+}
+
+fn font_table_record_offset(font_data: &[u8], tag: &[u8; 4]) -> usize {
+    let table_count = u16::from_be_bytes([font_data[4], font_data[5]]) as usize;
+    (0..table_count)
+        .map(|index| 12 + index * 16)
+        .find(|&offset| &font_data[offset..offset + 4] == tag)
+        .unwrap()
+}
+
+fn read_u32_be(bytes: &[u8]) -> u32 {
+    u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}